@@ -1,29 +1,641 @@
-use std::collections::HashSet;
-use std::path::Path;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
 use std::process::Command;
-use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Condvar, Mutex};
 
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
-use smctl_workspace::{RepoConfig, WorkspaceManifest};
+use smctl_workspace::{RemoteConfig, RepoConfig, WorkspaceManifest};
 
 /// Build result for a single repo.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct BuildResult {
     pub repo_name: String,
     pub success: bool,
     pub output: String,
     pub duration_ms: u64,
+    /// Dependency level the repo was built in (parallel builds only).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub level: Option<usize>,
+    /// Per-attempt durations in ms when tests were retried.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attempts: Vec<u64>,
+    /// Not built because a dependency failed (keep-going mode).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub skipped: bool,
+    /// Artifacts copied to `.smctl/artifacts/<build-id>/<repo>/`, relative to the workspace root.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub artifacts: Vec<PathBuf>,
+    /// Individual steps when the build ran more than one command.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub steps: Vec<StepResult>,
+    /// Where the commands ran, for remote builds (e.g. `ssh:gpu-box`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub executor: Option<String>,
+    /// Full stdout/stderr log under `.smctl/logs/<build-id>/`, relative to the workspace root.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log: Option<PathBuf>,
+    /// Interrupted while running (e.g. Ctrl-C).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cancelled: bool,
+    /// Hash of the repo's inputs when it was built (see [`smctl_workspace::input_hash`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_hash: Option<String>,
+}
+
+/// Outcome of one command in a multi-step build.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepResult {
+    pub command: String,
+    pub success: bool,
+    pub duration_ms: u64,
 }
 
 /// Overall build report.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BuildReport {
+    /// Id under `.smctl/builds/` and `.smctl/artifacts/` (a UTC timestamp).
+    #[serde(default)]
+    pub build_id: String,
     pub results: Vec<BuildResult>,
     pub total_duration_ms: u64,
     pub all_passed: bool,
 }
 
+/// Machine-readable report format for `smctl build --report`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Junit,
+    Json,
+}
+
+/// A `<format>:<path>` report destination (e.g. `junit:target/smctl.xml`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportSpec {
+    pub format: ReportFormat,
+    pub path: PathBuf,
+}
+
+impl std::str::FromStr for ReportSpec {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (format, path) = s
+            .split_once(':')
+            .with_context(|| format!("expected <format>:<path>, got '{s}'"))?;
+        let format = match format {
+            "junit" => ReportFormat::Junit,
+            "json" => ReportFormat::Json,
+            other => anyhow::bail!("unknown report format '{other}' (expected junit or json)"),
+        };
+        if path.is_empty() {
+            anyhow::bail!("missing report path in '{s}'");
+        }
+        Ok(Self {
+            format,
+            path: PathBuf::from(path),
+        })
+    }
+}
+
+impl BuildReport {
+    /// Whether the build was interrupted before it finished.
+    pub fn was_cancelled(&self) -> bool {
+        self.results.iter().any(|r| r.cancelled)
+    }
+
+    /// Render the report as JUnit XML, one `<testsuite>` per repo.
+    pub fn to_junit_xml(&self) -> String {
+        // Group results by repo, preserving first-seen order.
+        let mut suites: Vec<(&str, Vec<(&str, &BuildResult)>)> = Vec::new();
+        for result in &self.results {
+            let (suite, case) = match result.repo_name.strip_suffix(" (test)") {
+                Some(repo) => (repo, "test"),
+                None => (result.repo_name.as_str(), "build"),
+            };
+            match suites.iter_mut().find(|(name, _)| *name == suite) {
+                Some((_, cases)) => cases.push((case, result)),
+                None => suites.push((suite, vec![(case, result)])),
+            }
+        }
+
+        let total_failures = self
+            .results
+            .iter()
+            .filter(|r| !r.success && !r.skipped)
+            .count();
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        xml.push_str(&format!(
+            "<testsuites name=\"smctl build\" tests=\"{}\" failures=\"{}\" time=\"{}\">\n",
+            self.results.len(),
+            total_failures,
+            secs(self.total_duration_ms)
+        ));
+        for (suite, cases) in &suites {
+            let failures = cases
+                .iter()
+                .filter(|(_, r)| !r.success && !r.skipped)
+                .count();
+            let time: u64 = cases.iter().map(|(_, r)| r.duration_ms).sum();
+            xml.push_str(&format!(
+                "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" time=\"{}\">\n",
+                xml_escape(suite),
+                cases.len(),
+                failures,
+                secs(time)
+            ));
+            for (case, result) in cases {
+                let attrs = format!(
+                    "name=\"{}\" classname=\"{}\" time=\"{}\"",
+                    case,
+                    xml_escape(suite),
+                    secs(result.duration_ms)
+                );
+                if result.skipped {
+                    xml.push_str(&format!(
+                        "    <testcase {attrs}>\n      <skipped message=\"{}\"/>\n    </testcase>\n",
+                        xml_escape(&result.output)
+                    ));
+                } else if result.success {
+                    xml.push_str(&format!("    <testcase {attrs}/>\n"));
+                } else {
+                    xml.push_str(&format!(
+                        "    <testcase {attrs}>\n      <failure message=\"{case} failed\">{}</failure>\n    </testcase>\n",
+                        xml_escape(&result.output)
+                    ));
+                }
+            }
+            xml.push_str("  </testsuite>\n");
+        }
+        xml.push_str("</testsuites>\n");
+        xml
+    }
+
+    /// Write the report to `spec.path` in `spec.format`.
+    pub fn write_report(&self, spec: &ReportSpec) -> Result<()> {
+        let content = match spec.format {
+            ReportFormat::Junit => self.to_junit_xml(),
+            ReportFormat::Json => {
+                serde_json::to_string_pretty(self).context("failed to serialize build report")?
+            }
+        };
+        if let Some(parent) = spec.path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        std::fs::write(&spec.path, content)
+            .with_context(|| format!("failed to write {}", spec.path.display()))
+    }
+}
+
+fn secs(ms: u64) -> String {
+    format!("{:.3}", ms as f64 / 1000.0)
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Summary of a build recorded under `.smctl/builds/`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Build id (the report's file stem, a UTC timestamp).
+    pub id: String,
+    pub all_passed: bool,
+    pub total_duration_ms: u64,
+    pub results: usize,
+    pub failures: usize,
+}
+
+/// Per-step duration change between two recorded builds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DurationDelta {
+    pub repo_name: String,
+    pub before_ms: Option<u64>,
+    pub after_ms: Option<u64>,
+    /// `after - before` when the step ran in both builds.
+    pub delta_ms: Option<i64>,
+}
+
+/// Comparison of two recorded builds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildComparison {
+    pub before: String,
+    pub after: String,
+    pub total_delta_ms: i64,
+    pub steps: Vec<DurationDelta>,
+}
+
+/// Covered/total counts for one coverage metric.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CoverageCount {
+    pub count: u64,
+    pub covered: u64,
+}
+
+impl CoverageCount {
+    /// Covered percentage (100% when there is nothing to cover).
+    pub fn percent(&self) -> f64 {
+        if self.count == 0 {
+            100.0
+        } else {
+            self.covered as f64 * 100.0 / self.count as f64
+        }
+    }
+
+    fn add(&mut self, other: CoverageCount) {
+        self.count += other.count;
+        self.covered += other.covered;
+    }
+}
+
+/// Coverage summary for a single repo.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoCoverage {
+    pub repo_name: String,
+    pub success: bool,
+    pub message: String,
+    pub lines: CoverageCount,
+    pub functions: CoverageCount,
+    pub regions: CoverageCount,
+}
+
+/// Workspace-wide coverage report.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoverageReport {
+    pub repos: Vec<RepoCoverage>,
+    pub lines: CoverageCount,
+    pub functions: CoverageCount,
+    pub regions: CoverageCount,
+    pub all_passed: bool,
+}
+
+/// Output format for `smctl build graph`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    Dot,
+    Mermaid,
+    Json,
+}
+
+impl std::str::FromStr for GraphFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "dot" => Ok(Self::Dot),
+            "mermaid" => Ok(Self::Mermaid),
+            "json" => Ok(Self::Json),
+            other => {
+                anyhow::bail!("unknown graph format '{other}' (expected dot, mermaid or json)")
+            }
+        }
+    }
+}
+
+/// A repo in the dependency graph.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphNode {
+    pub name: String,
+    /// Build level (see [`resolve_build_levels`]).
+    pub level: usize,
+    pub depends_on: Vec<String>,
+    /// Build duration from a recorded build, if requested and available.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_duration_ms: Option<u64>,
+}
+
+/// Repo dependency graph in build order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DependencyGraph {
+    pub nodes: Vec<GraphNode>,
+}
+
+impl DependencyGraph {
+    fn label(node: &GraphNode) -> String {
+        match node.last_duration_ms {
+            Some(ms) => format!("{}\\nlevel {} · {}ms", node.name, node.level, ms),
+            None => format!("{}\\nlevel {}", node.name, node.level),
+        }
+    }
+
+    /// Graphviz DOT, with repos of the same level ranked together.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph workspace {\n    rankdir=LR;\n    node [shape=box];\n");
+        for node in &self.nodes {
+            out.push_str(&format!(
+                "    \"{}\" [label=\"{}\"];\n",
+                node.name,
+                Self::label(node)
+            ));
+        }
+        let max_level = self.nodes.iter().map(|n| n.level).max().unwrap_or(0);
+        for level in 0..=max_level {
+            let names: Vec<_> = self
+                .nodes
+                .iter()
+                .filter(|n| n.level == level)
+                .map(|n| format!("\"{}\"", n.name))
+                .collect();
+            if !names.is_empty() {
+                out.push_str(&format!("    {{ rank=same; {} }}\n", names.join("; ")));
+            }
+        }
+        for node in &self.nodes {
+            for dep in &node.depends_on {
+                out.push_str(&format!("    \"{}\" -> \"{}\";\n", dep, node.name));
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Mermaid flowchart (edges point from dependency to dependent).
+    pub fn to_mermaid(&self) -> String {
+        let id = |name: &str| name.replace(|c: char| !c.is_ascii_alphanumeric(), "_");
+        let mut out = String::from("flowchart LR\n");
+        for node in &self.nodes {
+            out.push_str(&format!(
+                "    {}[\"{}\"]\n",
+                id(&node.name),
+                Self::label(node).replace("\\n", "<br/>")
+            ));
+        }
+        for node in &self.nodes {
+            for dep in &node.depends_on {
+                out.push_str(&format!("    {} --> {}\n", id(dep), id(&node.name)));
+            }
+        }
+        out
+    }
+}
+
+/// Why `smctl build explain` expects a repo to be rebuilt or not.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RebuildReason {
+    /// Files in the repo changed since the base ref.
+    ChangedFiles { files: Vec<String> },
+    /// A (transitive) dependency changed since the base ref.
+    ChangedDependency { dependency: String },
+    /// The base ref can't be resolved in the repo or a dependency, so it is rebuilt.
+    UnresolvedBase { repo: String },
+    /// Nothing in the repo or its dependencies changed since the base ref.
+    Unchanged,
+    /// Inputs differ from the last successful build (or it was never built).
+    CacheMiss { previous: Option<String> },
+    /// Inputs match the last successful build, but plain builds always run.
+    Forced,
+}
+
+impl RebuildReason {
+    /// Whether the reason leads to a rebuild.
+    pub fn rebuilds(&self) -> bool {
+        *self != RebuildReason::Unchanged
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            RebuildReason::ChangedFiles { files } => match files.as_slice() {
+                [file] => format!("changed file {file}"),
+                [file, rest @ ..] => format!("changed file {file} (+{} more)", rest.len()),
+                [] => "changed files".to_string(),
+            },
+            RebuildReason::ChangedDependency { dependency } => {
+                format!("dependency '{dependency}' changed")
+            }
+            RebuildReason::UnresolvedBase { repo } => {
+                format!("base ref cannot be resolved in '{repo}'")
+            }
+            RebuildReason::Unchanged => "no changes in the repo or its dependencies".to_string(),
+            RebuildReason::CacheMiss { previous: None } => {
+                "cache miss: no successful build recorded".to_string()
+            }
+            RebuildReason::CacheMiss { previous: Some(_) } => {
+                "cache miss: inputs changed since the last successful build".to_string()
+            }
+            RebuildReason::Forced => {
+                "forced: inputs match the last successful build, but builds are not cached"
+                    .to_string()
+            }
+        }
+    }
+}
+
+/// One repo in an explained dependency chain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExplainedRepo {
+    pub name: String,
+    /// Current input hash (`None` outside a git checkout).
+    pub input_hash: Option<String>,
+    /// Input hash recorded by the last successful build.
+    pub last_built_hash: Option<String>,
+    /// Files changed since the base ref (`None` if it can't be resolved or no base was given).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub changed_files: Option<Vec<String>>,
+}
+
+/// Output of `smctl build explain`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Explanation {
+    pub repo_name: String,
+    /// Base ref of the `--changed` build being explained.
+    pub base: Option<String>,
+    /// Dependencies in build order, ending with the repo itself.
+    pub chain: Vec<ExplainedRepo>,
+    pub rebuild: bool,
+    pub reason: RebuildReason,
+}
+
+/// Clean result for a single repo.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CleanResult {
+    pub repo_name: String,
+    pub command: String,
+    pub success: bool,
+    pub message: String,
+    /// Bytes freed (estimated from `target/` in dry-run mode).
+    pub reclaimed_bytes: u64,
+}
+
+/// Overall clean report.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CleanReport {
+    pub results: Vec<CleanResult>,
+    /// smctl-managed temp dirs that were (or would be) removed.
+    pub removed_dirs: Vec<PathBuf>,
+    pub total_reclaimed_bytes: u64,
+    pub dry_run: bool,
+}
+
+/// Options controlling a build run.
+#[derive(Debug, Clone, Default)]
+pub struct BuildOptions<'a> {
+    /// Build a specific repo (and its dependencies).
+    pub repo_name: Option<&'a str>,
+    pub run_tests: bool,
+    pub clean_first: bool,
+    /// Build independent repos concurrently, starting each one as soon as
+    /// its dependencies have finished.
+    pub parallel: bool,
+    /// Maximum total `build_weight` of repos built at once in parallel mode
+    /// (overrides `[build] max_parallel`).
+    pub jobs: Option<usize>,
+    /// Named profile selecting per-repo build/test commands.
+    pub profile: Option<&'a str>,
+    /// Times a failed test command is rerun (overrides `[build] test_retries`).
+    pub retries: Option<u32>,
+    /// Build exactly these repos, without pulling in dependencies
+    /// (takes precedence over `repo_name`).
+    pub only: Option<&'a [String]>,
+    /// Keep building repos whose dependencies succeeded after a failure,
+    /// marking dependents of failed repos as skipped.
+    pub keep_going: bool,
+    /// Build only repos changed since this ref plus their dependents
+    /// (takes precedence over `only` and `repo_name`).
+    pub changed_since: Option<&'a str>,
+    /// Ask build tools for colored output even though it is captured
+    /// (kept verbatim in the per-repo logs).
+    pub color: bool,
+    /// Extra arguments appended to every repo's test command.
+    pub test_args: &'a [String],
+    /// Receives per-repo state changes as the build progresses.
+    pub progress: Option<ProgressFn<'a>>,
+    /// Stops the build and kills running commands when cancelled.
+    pub cancel: Option<&'a CancelToken>,
+}
+
+/// Per-repo state reported to a [`ProgressFn`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepoState {
+    Queued,
+    Building,
+    Testing,
+    Done,
+    Failed,
+    /// Not built because a dependency failed (keep-going mode).
+    Skipped,
+}
+
+/// Progress callback, called from build worker threads.
+#[derive(Clone, Copy)]
+pub struct ProgressFn<'a>(pub &'a (dyn Fn(&str, RepoState) + Sync));
+
+impl std::fmt::Debug for ProgressFn<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ProgressFn(..)")
+    }
+}
+
+/// Environment that makes common build tools emit ANSI colors when not on a TTY.
+const FORCE_COLOR_ENV: &[(&str, &str)] = &[
+    ("CARGO_TERM_COLOR", "always"),
+    ("CLICOLOR_FORCE", "1"),
+    ("FORCE_COLOR", "1"),
+];
+
+/// State shared by every repo command in one build run.
+struct RunContext<'a> {
+    root: &'a Path,
+    profile: Option<&'a str>,
+    retries: u32,
+    test_args: &'a [String],
+    progress: Option<ProgressFn<'a>>,
+    cancel: Option<&'a CancelToken>,
+    env: Vec<(String, String)>,
+    /// `.smctl/logs/<build-id>/`, relative to the workspace root.
+    log_dir: PathBuf,
+}
+
+impl RunContext<'_> {
+    fn new<'a>(
+        root: &'a Path,
+        manifest: &WorkspaceManifest,
+        opts: &BuildOptions<'a>,
+        build_id: &str,
+    ) -> RunContext<'a> {
+        let env = if opts.color {
+            FORCE_COLOR_ENV
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        RunContext {
+            root,
+            profile: opts.profile,
+            retries: opts.retries.or(manifest.build.test_retries).unwrap_or(0),
+            test_args: opts.test_args,
+            progress: opts.progress,
+            cancel: opts.cancel,
+            env,
+            log_dir: Path::new(".smctl").join("logs").join(build_id),
+        }
+    }
+
+    fn cancelled(&self) -> bool {
+        self.cancel.is_some_and(CancelToken::is_cancelled)
+    }
+
+    fn report(&self, repo: &RepoConfig, state: RepoState) {
+        if let Some(ProgressFn(progress)) = self.progress {
+            progress(&repo.name, state);
+        }
+    }
+
+    /// Log path for `repo`, relative to the workspace root.
+    fn log_path(&self, repo: &RepoConfig) -> PathBuf {
+        self.log_dir.join(format!("{}.log", repo.name))
+    }
+
+    /// Run `cmd` for `repo`, appending the command and both output streams to its log.
+    fn run(&self, repo: &RepoConfig, cmd: &str) -> Result<String> {
+        let executor = executor_for(self.root, repo);
+        let run_opts = RunOptions {
+            env: &self.env,
+            cancel: self.cancel,
+        };
+        let output = executor.output(repo, cmd, run_opts)?;
+        if let Err(e) = self.append_log(repo, cmd, &output) {
+            tracing::warn!("{}: could not write build log: {e:#}", repo.name);
+        }
+        let label = match repo.remote {
+            Some(_) => format!("{} ({})", repo.name, executor.name()),
+            None => repo.name.clone(),
+        };
+        output.into_result(&label, cmd)
+    }
+
+    fn append_log(&self, repo: &RepoConfig, cmd: &str, output: &CommandOutput) -> Result<()> {
+        use std::io::Write;
+
+        let path = self.root.join(self.log_path(repo));
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).context("failed to create log directory")?;
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("failed to open {}", path.display()))?;
+        writeln!(file, "$ {cmd}")?;
+        file.write_all(output.stdout.as_bytes())?;
+        file.write_all(output.stderr.as_bytes())?;
+        if !output.success {
+            writeln!(file, "(command failed)")?;
+        }
+        Ok(())
+    }
+}
+
 /// Resolve build order from dependency graph (topological sort).
 pub fn resolve_build_order(manifest: &WorkspaceManifest) -> Result<Vec<&RepoConfig>> {
     let repos = &manifest.repos;
@@ -98,6 +710,118 @@ pub fn resolve_build_levels(manifest: &WorkspaceManifest) -> Result<Vec<Vec<&Rep
     Ok(levels)
 }
 
+/// Dependency graph with level assignments; `last_build` annotates nodes
+/// with their build durations.
+pub fn dependency_graph(
+    manifest: &WorkspaceManifest,
+    last_build: Option<&BuildReport>,
+) -> Result<DependencyGraph> {
+    let levels = resolve_build_levels(manifest)?;
+    let nodes = resolve_build_order(manifest)?
+        .into_iter()
+        .map(|repo| GraphNode {
+            name: repo.name.clone(),
+            level: levels
+                .iter()
+                .position(|l| l.iter().any(|r| r.name == repo.name))
+                .unwrap_or(0),
+            depends_on: repo.depends_on.clone(),
+            last_duration_ms: last_build.and_then(|b| {
+                b.results
+                    .iter()
+                    .find(|r| r.repo_name == repo.name && !r.skipped)
+                    .map(|r| r.duration_ms)
+            }),
+        })
+        .collect();
+    Ok(DependencyGraph { nodes })
+}
+
+/// Explain whether `repo_name` would be rebuilt, and why.
+///
+/// With `base`, this answers for `smctl build --changed <base>`; without it,
+/// for a plain build, comparing input hashes against recorded history.
+pub fn explain(
+    root: &Path,
+    manifest: &WorkspaceManifest,
+    repo_name: &str,
+    base: Option<&str>,
+) -> Result<Explanation> {
+    if manifest.find_repo(repo_name).is_none() {
+        anyhow::bail!("repo '{repo_name}' not found");
+    }
+    let deps = collect_deps(manifest, repo_name);
+    let last_built = last_input_hashes(root)?;
+
+    let mut chain = Vec::new();
+    for repo in resolve_build_order(manifest)? {
+        if repo.name != repo_name && !deps.contains(&repo.name) {
+            continue;
+        }
+        let changed_files = match base {
+            Some(base) => smctl_workspace::changed_files_since(root, repo, base)?,
+            None => None,
+        };
+        chain.push(ExplainedRepo {
+            name: repo.name.clone(),
+            input_hash: smctl_workspace::input_hash(root, repo).ok(),
+            last_built_hash: last_built.get(&repo.name).cloned(),
+            changed_files,
+        });
+    }
+
+    let target = chain.last().context("repo missing from build order")?;
+    let reason = if base.is_some() {
+        let (deps, _) = chain.split_at(chain.len() - 1);
+        if let Some(files) = target.changed_files.as_ref().filter(|f| !f.is_empty()) {
+            RebuildReason::ChangedFiles {
+                files: files.clone(),
+            }
+        } else if let Some(dep) = deps
+            .iter()
+            .find(|d| d.changed_files.as_ref().is_some_and(|f| !f.is_empty()))
+        {
+            RebuildReason::ChangedDependency {
+                dependency: dep.name.clone(),
+            }
+        } else if let Some(unresolved) = chain.iter().find(|r| r.changed_files.is_none()) {
+            RebuildReason::UnresolvedBase {
+                repo: unresolved.name.clone(),
+            }
+        } else {
+            RebuildReason::Unchanged
+        }
+    } else if target.input_hash.is_some() && target.input_hash == target.last_built_hash {
+        RebuildReason::Forced
+    } else {
+        RebuildReason::CacheMiss {
+            previous: target.last_built_hash.clone(),
+        }
+    };
+
+    Ok(Explanation {
+        repo_name: repo_name.to_string(),
+        base: base.map(str::to_string),
+        chain,
+        rebuild: reason.rebuilds(),
+        reason,
+    })
+}
+
+/// Input hash of each repo's most recent successful recorded build.
+fn last_input_hashes(root: &Path) -> Result<HashMap<String, String>> {
+    let mut hashes = HashMap::new();
+    for id in json_ids(&history_dir(root))? {
+        let report = load_build(root, &id)?;
+        for result in report.results.into_iter().filter(|r| r.success) {
+            if let Some(hash) = result.input_hash {
+                hashes.entry(result.repo_name).or_insert(hash);
+            }
+        }
+    }
+    Ok(hashes)
+}
+
 /// Build repos in dependency order (sequential).
 pub fn build(
     root: &Path,
@@ -106,7 +830,16 @@ pub fn build(
     run_tests: bool,
     clean_first: bool,
 ) -> Result<BuildReport> {
-    build_inner(root, manifest, repo_name, run_tests, clean_first, false)
+    build_with_options(
+        root,
+        manifest,
+        &BuildOptions {
+            repo_name,
+            run_tests,
+            clean_first,
+            ..Default::default()
+        },
+    )
 }
 
 /// Build repos with optional parallelism.
@@ -117,244 +850,2324 @@ pub fn build_parallel(
     run_tests: bool,
     clean_first: bool,
 ) -> Result<BuildReport> {
-    build_inner(root, manifest, repo_name, run_tests, clean_first, true)
+    build_with_options(
+        root,
+        manifest,
+        &BuildOptions {
+            repo_name,
+            run_tests,
+            clean_first,
+            parallel: true,
+            ..Default::default()
+        },
+    )
 }
 
-fn build_inner(
+/// Build repos according to `opts`.
+pub fn build_with_options(
     root: &Path,
     manifest: &WorkspaceManifest,
-    repo_name: Option<&str>,
-    run_tests: bool,
-    clean_first: bool,
-    parallel: bool,
+    opts: &BuildOptions,
 ) -> Result<BuildReport> {
     let start = std::time::Instant::now();
-
-    if parallel {
-        return build_parallel_impl(root, manifest, repo_name, run_tests, clean_first, start);
+    if let Some(profile) = opts.profile
+        && !manifest
+            .repos
+            .iter()
+            .any(|r| r.profiles.contains_key(profile))
+    {
+        anyhow::bail!("profile '{profile}' is not defined by any repo");
     }
 
-    let build_order = resolve_build_order(manifest)?;
-
-    let repos_to_build: Vec<_> = match repo_name {
-        Some(name) => {
-            let _target = manifest
-                .find_repo(name)
-                .with_context(|| format!("repo '{name}' not found"))?;
-            let deps = collect_deps(manifest, name);
-            build_order
-                .into_iter()
-                .filter(|r| r.name == name || deps.contains(&r.name))
-                .collect()
+    // Narrow the build to changed repos and report the rest as unchanged.
+    let mut unchanged = Vec::new();
+    let changed;
+    let opts = match opts.changed_since {
+        Some(base) => {
+            changed = with_dependents(manifest, &changed_repos(root, manifest, base)?);
+            unchanged.extend(
+                manifest
+                    .repos
+                    .iter()
+                    .filter(|r| !changed.contains(&r.name))
+                    .map(unchanged_result),
+            );
+            &BuildOptions {
+                only: Some(&changed),
+                ..opts.clone()
+            }
         }
-        None => build_order,
+        None => opts,
     };
 
+    let hooks = &manifest.build.hooks;
     let mut results = Vec::new();
-    for repo in &repos_to_build {
-        if clean_first && let Some(cmd) = &repo.clean_cmd {
-            run_cmd(root, repo, cmd)?;
+
+    let pre_ok = match &hooks.pre_build {
+        Some(cmd) => {
+            let result = run_hook(root, "pre-build", cmd);
+            let ok = result.success;
+            results.push(result);
+            ok
         }
+        None => true,
+    };
 
-        let build_result = build_one_repo(root, repo);
-        let build_ok = build_result.success;
-        results.push(build_result);
+    let build_id = new_build_id(root);
+    let ctx = RunContext::new(root, manifest, opts, &build_id);
 
-        if !build_ok {
-            break;
+    if pre_ok {
+        if opts.parallel {
+            results.extend(build_parallel_impl(&ctx, manifest, opts)?);
+        } else {
+            results.extend(build_sequential_impl(&ctx, manifest, opts)?);
         }
 
-        if run_tests {
-            let test_result = test_one_repo(root, repo);
-            let test_ok = test_result.success;
-            results.push(test_result);
-            if !test_ok {
-                break;
+        // Collect artifacts before the post-build hook so it can publish them.
+        for result in results.iter_mut().filter(|r| r.success) {
+            let Some(repo) = manifest.find_repo(&result.repo_name) else {
+                continue;
+            };
+            if repo.artifacts.is_empty() {
+                continue;
             }
+            match collect_artifacts(root, repo, &build_id) {
+                Ok(artifacts) => result.artifacts = artifacts,
+                Err(e) => {
+                    result.success = false;
+                    result
+                        .output
+                        .push_str(&format!("\nartifact collection failed: {e:#}"));
+                }
+            }
+        }
+
+        results.extend(unchanged);
+
+        if let Some(cmd) = &hooks.post_build
+            && results.iter().all(|r| r.success)
+        {
+            results.push(run_hook(root, "post-build", cmd));
         }
     }
 
     let all_passed = results.iter().all(|r| r.success);
     Ok(BuildReport {
+        build_id,
         results,
         total_duration_ms: start.elapsed().as_millis() as u64,
         all_passed,
     })
 }
 
-fn build_parallel_impl(
-    root: &Path,
+fn build_sequential_impl(
+    ctx: &RunContext,
     manifest: &WorkspaceManifest,
-    repo_name: Option<&str>,
-    run_tests: bool,
-    clean_first: bool,
-    start: std::time::Instant,
-) -> Result<BuildReport> {
-    let levels = resolve_build_levels(manifest)?;
-
-    // Filter levels if building a specific repo
-    let target_repos: Option<HashSet<String>> = repo_name.map(|name| {
-        let mut set: HashSet<String> = collect_deps(manifest, name).into_iter().collect();
-        set.insert(name.to_string());
-        set
-    });
-
-    let results = Mutex::new(Vec::new());
-    let failed = Mutex::new(false);
+    opts: &BuildOptions,
+) -> Result<Vec<BuildResult>> {
+    let BuildOptions {
+        run_tests,
+        clean_first,
+        ..
+    } = *opts;
+
+    let targets = target_repos(manifest, opts)?;
+    let repos_to_build: Vec<_> = resolve_build_order(manifest)?
+        .into_iter()
+        .filter(|r| targets.as_ref().is_none_or(|t| t.contains(&r.name)))
+        .collect();
+    for repo in &repos_to_build {
+        ctx.report(repo, RepoState::Queued);
+    }
 
-    for level in &levels {
-        // Skip if already failed
-        if *failed.lock().unwrap() {
+    let mut results = Vec::new();
+    // Repos that failed or were skipped (only tracked with keep_going).
+    let mut failed_repos: HashSet<String> = HashSet::new();
+    for repo in &repos_to_build {
+        if ctx.cancelled() {
             break;
         }
-
-        let repos_in_level: Vec<_> = level
-            .iter()
-            .filter(|r| {
-                target_repos
-                    .as_ref()
-                    .is_none_or(|targets| targets.contains(&r.name))
-            })
-            .collect();
-
-        if repos_in_level.is_empty() {
+        if let Some(dep) = repo.depends_on.iter().find(|d| failed_repos.contains(*d)) {
+            results.push(skipped_result(repo, dep));
+            ctx.report(repo, RepoState::Skipped);
+            failed_repos.insert(repo.name.clone());
             continue;
         }
 
-        std::thread::scope(|s| {
-            let handles: Vec<_> = repos_in_level
-                .iter()
-                .map(|repo| {
-                    s.spawn(|| {
-                        if *failed.lock().unwrap() {
-                            return;
-                        }
-
-                        if clean_first && let Some(cmd) = &repo.clean_cmd {
-                            let _ = run_cmd(root, repo, cmd);
-                        }
-
-                        let build_result = build_one_repo(root, repo);
-                        let build_ok = build_result.success;
-                        results.lock().unwrap().push(build_result);
+        ctx.report(repo, RepoState::Building);
+        if clean_first && let Some(cmd) = &repo.clean_cmd {
+            ctx.run(repo, cmd)?;
+        }
 
-                        if !build_ok {
-                            *failed.lock().unwrap() = true;
-                            return;
-                        }
+        let build_result = build_one_repo(ctx, repo);
+        let mut ok = build_result.success;
+        results.push(build_result);
 
-                        if run_tests {
-                            let test_result = test_one_repo(root, repo);
-                            let test_ok = test_result.success;
-                            results.lock().unwrap().push(test_result);
-                            if !test_ok {
-                                *failed.lock().unwrap() = true;
-                            }
-                        }
-                    })
-                })
-                .collect();
+        if ok && run_tests {
+            ctx.report(repo, RepoState::Testing);
+            let test_result = test_one_repo(ctx, repo);
+            ok = test_result.success;
+            results.push(test_result);
+        }
+        ctx.report(
+            repo,
+            if ok {
+                RepoState::Done
+            } else {
+                RepoState::Failed
+            },
+        );
 
-            for handle in handles {
-                handle.join().unwrap();
+        if !ok {
+            if !opts.keep_going {
+                break;
             }
-        });
+            failed_repos.insert(repo.name.clone());
+        }
     }
 
-    let results = results.into_inner().unwrap();
-    let all_passed = results.iter().all(|r| r.success);
+    Ok(results)
+}
+
+/// Scheduler state shared by parallel build workers.
+struct Schedule<'m> {
+    /// Repos whose dependencies have all finished, in level order.
+    ready: VecDeque<&'m RepoConfig>,
+    /// Unfinished in-build dependencies per waiting repo.
+    waiting_on: HashMap<&'m str, usize>,
+    in_flight: usize,
+    /// Total `build_weight` of the repos in flight.
+    in_weight: usize,
+    /// Set after a failure when not keeping going.
+    stop: bool,
+    /// Repos that failed or were skipped (only tracked with keep_going).
+    failed_repos: HashSet<String>,
+}
+
+fn build_parallel_impl(
+    ctx: &RunContext,
+    manifest: &WorkspaceManifest,
+    opts: &BuildOptions,
+) -> Result<Vec<BuildResult>> {
+    let BuildOptions {
+        run_tests,
+        clean_first,
+        ..
+    } = *opts;
+    let jobs = opts
+        .jobs
+        .or(manifest.build.max_parallel)
+        .unwrap_or(usize::MAX)
+        .max(1);
+    let levels = resolve_build_levels(manifest)?;
+
+    let target_repos = target_repos(manifest, opts)?;
+    let repos: Vec<(usize, &RepoConfig)> = levels
+        .iter()
+        .enumerate()
+        .flat_map(|(level_idx, level)| level.iter().map(move |r| (level_idx, *r)))
+        .filter(|(_, r)| target_repos.as_ref().is_none_or(|t| t.contains(&r.name)))
+        .collect();
+    let level_of: HashMap<&str, usize> = repos
+        .iter()
+        .map(|(level_idx, r)| (r.name.as_str(), *level_idx))
+        .collect();
+    for (_, repo) in &repos {
+        ctx.report(repo, RepoState::Queued);
+    }
+
+    // Each repo starts as soon as its own dependencies finish, rather than
+    // waiting for the whole previous level.
+    let mut schedule = Schedule {
+        ready: VecDeque::new(),
+        waiting_on: HashMap::new(),
+        in_flight: 0,
+        in_weight: 0,
+        stop: false,
+        failed_repos: HashSet::new(),
+    };
+    for (_, repo) in &repos {
+        let pending = repo
+            .depends_on
+            .iter()
+            .filter(|d| level_of.contains_key(d.as_str()))
+            .count();
+        if pending == 0 {
+            schedule.ready.push_back(repo);
+        } else {
+            schedule.waiting_on.insert(&repo.name, pending);
+        }
+    }
+    let schedule = Mutex::new(schedule);
+    let wakeup = Condvar::new();
+    let results = Mutex::new(Vec::new());
+    let workers = jobs.min(repos.len());
+
+    std::thread::scope(|s| {
+        for _ in 0..workers {
+            s.spawn(|| {
+                loop {
+                    let (repo, failed_dep) = {
+                        let mut state = schedule.lock().unwrap();
+                        let repo = loop {
+                            if state.stop || ctx.cancelled() {
+                                return;
+                            }
+                            // Take the first ready repo that fits the weight
+                            // budget; anything fits when nothing else runs.
+                            let fits = state.ready.iter().position(|r| {
+                                state.in_flight == 0 || state.in_weight + r.build_weight() <= jobs
+                            });
+                            if let Some(repo) = fits.and_then(|i| state.ready.remove(i)) {
+                                break repo;
+                            }
+                            if state.in_flight == 0 {
+                                return;
+                            }
+                            state = wakeup.wait(state).unwrap();
+                        };
+                        state.in_flight += 1;
+                        state.in_weight += repo.build_weight();
+                        let failed_dep = repo
+                            .depends_on
+                            .iter()
+                            .find(|d| state.failed_repos.contains(*d))
+                            .cloned();
+                        (repo, failed_dep)
+                    };
+                    let level = level_of.get(repo.name.as_str()).copied();
+
+                    let ok = match failed_dep {
+                        Some(dep) => {
+                            let mut skipped = skipped_result(repo, &dep);
+                            skipped.level = level;
+                            results.lock().unwrap().push(skipped);
+                            ctx.report(repo, RepoState::Skipped);
+                            false
+                        }
+                        None => {
+                            ctx.report(repo, RepoState::Building);
+                            if clean_first && let Some(cmd) = &repo.clean_cmd {
+                                let _ = ctx.run(repo, cmd);
+                            }
+
+                            let mut build_result = build_one_repo(ctx, repo);
+                            build_result.level = level;
+                            let mut ok = build_result.success;
+                            results.lock().unwrap().push(build_result);
+
+                            if ok && run_tests {
+                                ctx.report(repo, RepoState::Testing);
+                                let mut test_result = test_one_repo(ctx, repo);
+                                test_result.level = level;
+                                ok = test_result.success;
+                                results.lock().unwrap().push(test_result);
+                            }
+                            ctx.report(
+                                repo,
+                                if ok {
+                                    RepoState::Done
+                                } else {
+                                    RepoState::Failed
+                                },
+                            );
+                            ok
+                        }
+                    };
+
+                    let mut state = schedule.lock().unwrap();
+                    state.in_flight -= 1;
+                    state.in_weight -= repo.build_weight();
+                    if !ok {
+                        if opts.keep_going {
+                            state.failed_repos.insert(repo.name.clone());
+                        } else {
+                            state.stop = true;
+                        }
+                    }
+                    for (_, dependent) in &repos {
+                        if !dependent.depends_on.contains(&repo.name) {
+                            continue;
+                        }
+                        if let Some(pending) = state.waiting_on.get_mut(dependent.name.as_str()) {
+                            *pending -= 1;
+                            if *pending == 0 {
+                                state.waiting_on.remove(dependent.name.as_str());
+                                state.ready.push_back(dependent);
+                            }
+                        }
+                    }
+                    wakeup.notify_all();
+                }
+            });
+        }
+    });
+
+    Ok(results.into_inner().unwrap())
+}
+
+/// Directory holding recorded build reports.
+pub fn history_dir(root: &Path) -> PathBuf {
+    root.join(".smctl").join("builds")
+}
+
+/// Directory holding collected build artifacts.
+pub fn artifacts_dir(root: &Path) -> PathBuf {
+    root.join(".smctl").join("artifacts")
+}
+
+/// Allocate a new build id: a UTC timestamp, suffixed if already taken.
+pub fn new_build_id(root: &Path) -> String {
+    timestamp_id(|id| {
+        history_dir(root).join(format!("{id}.json")).exists()
+            || artifacts_dir(root).join(id).exists()
+    })
+}
+
+/// A UTC timestamp id, with a numeric suffix while `taken` reports a clash.
+fn timestamp_id(taken: impl Fn(&str) -> bool) -> String {
+    let stamp = chrono::Utc::now().format("%Y%m%dT%H%M%S%.3fZ").to_string();
+    let mut id = stamp.clone();
+    let mut n = 1;
+    while taken(&id) {
+        id = format!("{stamp}-{n}");
+        n += 1;
+    }
+    id
+}
+
+/// Ids of `<id>.json` files in `dir`, newest first.
+fn json_ids(dir: &Path) -> Result<Vec<String>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut ids: Vec<String> = std::fs::read_dir(dir)
+        .with_context(|| format!("failed to read {}", dir.display()))?
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            name.strip_suffix(".json").map(|s| s.to_string())
+        })
+        .collect();
+    ids.sort_unstable_by(|a, b| b.cmp(a));
+    Ok(ids)
+}
+
+/// Record a build report as `.smctl/builds/<build-id>.json`, returning its id.
+pub fn record_build(root: &Path, report: &BuildReport) -> Result<String> {
+    let dir = history_dir(root);
+    std::fs::create_dir_all(&dir).context("failed to create build history directory")?;
+
+    let id = if report.build_id.is_empty() {
+        new_build_id(root)
+    } else {
+        report.build_id.clone()
+    };
+
+    let content =
+        serde_json::to_string_pretty(report).context("failed to serialize build report")?;
+    std::fs::write(dir.join(format!("{id}.json")), content)
+        .context("failed to write build history")?;
+    Ok(id)
+}
+
+/// Copy files matching a repo's `artifacts` globs into
+/// `.smctl/artifacts/<build-id>/<repo>/`, returning their workspace-relative paths.
+pub fn collect_artifacts(root: &Path, repo: &RepoConfig, build_id: &str) -> Result<Vec<PathBuf>> {
+    let repo_path = root.join(repo.local_path());
+    let dest = artifacts_dir(root).join(build_id).join(&repo.name);
+    let mut collected = Vec::new();
+
+    for pattern in &repo.artifacts {
+        let full = repo_path.join(pattern);
+        let matches = glob::glob(&full.to_string_lossy())
+            .with_context(|| format!("invalid artifact pattern '{pattern}'"))?;
+        let mut matched = false;
+        for entry in matches {
+            let path = entry.context("failed to read artifact path")?;
+            if !path.is_file() {
+                continue;
+            }
+            matched = true;
+            std::fs::create_dir_all(&dest)
+                .with_context(|| format!("failed to create {}", dest.display()))?;
+            let target = dest.join(path.file_name().context("artifact has no file name")?);
+            std::fs::copy(&path, &target)
+                .with_context(|| format!("failed to copy {}", path.display()))?;
+            collected.push(target.strip_prefix(root).unwrap_or(&target).to_path_buf());
+        }
+        if !matched {
+            tracing::warn!(
+                "{}: artifact pattern '{pattern}' matched no files",
+                repo.name
+            );
+        }
+    }
+
+    Ok(collected)
+}
+
+/// List recorded builds, newest first.
+pub fn list_history(root: &Path, limit: Option<usize>) -> Result<Vec<HistoryEntry>> {
+    let mut ids = json_ids(&history_dir(root))?;
+    ids.truncate(limit.unwrap_or(usize::MAX));
+
+    ids.into_iter()
+        .map(|id| {
+            let report = load_build(root, &id)?;
+            Ok(HistoryEntry {
+                id,
+                all_passed: report.all_passed,
+                total_duration_ms: report.total_duration_ms,
+                results: report.results.len(),
+                failures: report.results.iter().filter(|r| !r.success).count(),
+            })
+        })
+        .collect()
+}
+
+/// Load a recorded build by id (`latest` selects the most recent).
+pub fn load_build(root: &Path, id: &str) -> Result<BuildReport> {
+    let id = if id == "latest" {
+        list_history(root, Some(1))?
+            .pop()
+            .map(|e| e.id)
+            .context("no recorded builds")?
+    } else {
+        id.to_string()
+    };
+    let path = history_dir(root).join(format!("{id}.json"));
+    let content = std::fs::read_to_string(&path)
+        .with_context(|| format!("build '{id}' not found in history"))?;
+    serde_json::from_str(&content).with_context(|| format!("failed to parse {}", path.display()))
+}
+
+/// Compare step durations between two recorded builds.
+pub fn compare_builds(root: &Path, before: &str, after: &str) -> Result<BuildComparison> {
+    let a = load_build(root, before)?;
+    let b = load_build(root, after)?;
+
+    let mut steps: Vec<DurationDelta> = a
+        .results
+        .iter()
+        .map(|ra| {
+            let after_ms = b
+                .results
+                .iter()
+                .find(|rb| rb.repo_name == ra.repo_name)
+                .map(|rb| rb.duration_ms);
+            DurationDelta {
+                repo_name: ra.repo_name.clone(),
+                before_ms: Some(ra.duration_ms),
+                after_ms,
+                delta_ms: after_ms.map(|ms| ms as i64 - ra.duration_ms as i64),
+            }
+        })
+        .collect();
+    for rb in &b.results {
+        if !a.results.iter().any(|ra| ra.repo_name == rb.repo_name) {
+            steps.push(DurationDelta {
+                repo_name: rb.repo_name.clone(),
+                before_ms: None,
+                after_ms: Some(rb.duration_ms),
+                delta_ms: None,
+            });
+        }
+    }
+
+    Ok(BuildComparison {
+        before: before.to_string(),
+        after: after.to_string(),
+        total_delta_ms: b.total_duration_ms as i64 - a.total_duration_ms as i64,
+        steps,
+    })
+}
+
+/// Repos selected by `opts.only` / `opts.repo_name` (`None` means all).
+fn target_repos(
+    manifest: &WorkspaceManifest,
+    opts: &BuildOptions,
+) -> Result<Option<HashSet<String>>> {
+    if let Some(only) = opts.only {
+        return Ok(Some(only.iter().cloned().collect()));
+    }
+    match opts.repo_name {
+        Some(name) => {
+            manifest
+                .find_repo(name)
+                .with_context(|| format!("repo '{name}' not found"))?;
+            let mut set: HashSet<String> = collect_deps(manifest, name).into_iter().collect();
+            set.insert(name.to_string());
+            Ok(Some(set))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Repos with changes relative to `base` (see [`smctl_workspace::repo_changed_since`]).
+pub fn changed_repos(root: &Path, manifest: &WorkspaceManifest, base: &str) -> Result<Vec<String>> {
+    let mut changed = Vec::new();
+    for repo in &manifest.repos {
+        if smctl_workspace::repo_changed_since(root, repo, base)? {
+            changed.push(repo.name.clone());
+        }
+    }
+    Ok(changed)
+}
+
+/// Repos that transitively depend on any of `names`, including `names` themselves.
+pub fn with_dependents(manifest: &WorkspaceManifest, names: &[String]) -> Vec<String> {
+    let mut result: Vec<String> = names.to_vec();
+    let mut stack = names.to_vec();
+    while let Some(current) = stack.pop() {
+        for repo in &manifest.repos {
+            if repo.depends_on.contains(&current) && !result.contains(&repo.name) {
+                result.push(repo.name.clone());
+                stack.push(repo.name.clone());
+            }
+        }
+    }
+    result
+}
+
+/// Map a changed file to the repo whose checkout contains it (longest path wins).
+pub fn repo_for_path<'a>(
+    root: &Path,
+    manifest: &'a WorkspaceManifest,
+    path: &Path,
+) -> Option<&'a RepoConfig> {
+    manifest
+        .repos
+        .iter()
+        .filter(|r| path.starts_with(root.join(r.local_path())))
+        .max_by_key(|r| r.local_path().len())
+}
+
+/// Whether a changed path is build output or VCS metadata that shouldn't trigger a rebuild.
+fn is_ignored_change(repo_root: &Path, path: &Path) -> bool {
+    path.strip_prefix(repo_root).is_ok_and(|rel| {
+        rel.components().any(|c| {
+            matches!(
+                c.as_os_str().to_str(),
+                Some("target" | ".git" | ".smctl" | "node_modules")
+            )
+        })
+    })
+}
+
+/// Watch repo source trees and rebuild changed repos plus their dependents.
+///
+/// Changes are debounced: a rebuild starts once no event has arrived for
+/// `debounce`. `on_build` is called with the rebuilt repo names and report
+/// after every rebuild. Runs until the watcher channel closes.
+pub fn watch(
+    root: &Path,
+    manifest: &WorkspaceManifest,
+    opts: &BuildOptions,
+    debounce: std::time::Duration,
+    mut on_build: impl FnMut(&[String], &BuildReport),
+) -> Result<()> {
+    use notify::{RecursiveMode, Watcher};
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).context("failed to start file watcher")?;
+    for repo in &manifest.repos {
+        let path = root.join(repo.local_path());
+        if path.exists() {
+            watcher
+                .watch(&path, RecursiveMode::Recursive)
+                .with_context(|| format!("failed to watch {}", path.display()))?;
+        }
+    }
+
+    // How often an idle watcher checks for cancellation.
+    const CANCEL_POLL: std::time::Duration = std::time::Duration::from_millis(200);
+
+    let mut changed: Vec<String> = Vec::new();
+    loop {
+        if opts.cancel.is_some_and(CancelToken::is_cancelled) {
+            return Ok(());
+        }
+        let event = if changed.is_empty() {
+            match rx.recv_timeout(CANCEL_POLL) {
+                Ok(event) => event,
+                Err(std::sync::mpsc::RecvTimeoutError::Timeout) => continue,
+                Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => return Ok(()),
+            }
+        } else {
+            match rx.recv_timeout(debounce) {
+                Ok(event) => event,
+                Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {
+                    let names = with_dependents(manifest, &changed);
+                    let rebuild = BuildOptions {
+                        only: Some(&names),
+                        repo_name: None,
+                        ..opts.clone()
+                    };
+                    let report = build_with_options(root, manifest, &rebuild)?;
+                    let built: Vec<String> = resolve_build_order(manifest)?
+                        .into_iter()
+                        .filter(|r| names.contains(&r.name))
+                        .map(|r| r.name.clone())
+                        .collect();
+                    on_build(&built, &report);
+                    changed.clear();
+                    continue;
+                }
+                Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => return Ok(()),
+            }
+        };
+
+        let event = event.context("file watcher error")?;
+        for path in &event.paths {
+            if let Some(repo) = repo_for_path(root, manifest, path)
+                && !is_ignored_change(&root.join(repo.local_path()), path)
+                && !changed.contains(&repo.name)
+            {
+                changed.push(repo.name.clone());
+            }
+        }
+    }
+}
+
+/// Validate Cedar policies without building (`smctl build --cedar`).
+///
+/// Each repo with `[repos.cedar]` gets one result with a step per policy file:
+/// `cedar validate` against the schema when one is configured, otherwise
+/// `cedar check-parse`. Repos without Cedar policies are left out.
+pub fn verify_cedar(
+    root: &Path,
+    manifest: &WorkspaceManifest,
+    repo_name: Option<&str>,
+) -> Result<BuildReport> {
+    let start = std::time::Instant::now();
+    let repos: Vec<_> = match repo_name {
+        Some(name) => vec![
+            manifest
+                .find_repo(name)
+                .with_context(|| format!("repo '{name}' not found"))?,
+        ],
+        None => resolve_build_order(manifest)?,
+    };
+    let cedar = manifest.build.cedar_cmd.as_deref().unwrap_or("cedar");
+
+    let mut results = Vec::new();
+    for repo in repos {
+        let Some(config) = &repo.cedar else {
+            continue;
+        };
+        let repo_start = std::time::Instant::now();
+        let repo_path = root.join(repo.local_path());
+
+        let mut policies = Vec::new();
+        for dir in &config.policies {
+            let pattern = repo_path.join(dir).join("**").join("*.cedar");
+            let matches = glob::glob(&pattern.to_string_lossy())
+                .with_context(|| format!("invalid policy directory '{dir}'"))?;
+            for entry in matches {
+                let path = entry.context("failed to read policy path")?;
+                policies.push(path.strip_prefix(&repo_path).unwrap_or(&path).to_path_buf());
+            }
+        }
+        policies.sort();
+
+        let mut steps = Vec::new();
+        let mut output = String::new();
+        for policy in &policies {
+            let policy = shell_words::quote(&policy.to_string_lossy()).into_owned();
+            let cmd = match &config.schema {
+                Some(schema) => format!(
+                    "{cedar} validate --schema {} --policies {policy}",
+                    shell_words::quote(schema)
+                ),
+                None => format!("{cedar} check-parse --policies {policy}"),
+            };
+            let step_start = std::time::Instant::now();
+            let result = run_in(&repo_path, &repo.name, &cmd, false);
+            steps.push(StepResult {
+                command: cmd,
+                success: result.is_ok(),
+                duration_ms: step_start.elapsed().as_millis() as u64,
+            });
+            match result {
+                Ok(out) => output.push_str(&out),
+                Err(e) => {
+                    output.push_str(&e.to_string());
+                    output.push('\n');
+                }
+            }
+        }
+        if policies.is_empty() {
+            output = "no Cedar policies found".to_string();
+        }
+
+        results.push(BuildResult {
+            repo_name: format!("{} (cedar)", repo.name),
+            success: steps.iter().all(|s| s.success),
+            output,
+            duration_ms: repo_start.elapsed().as_millis() as u64,
+            steps,
+            ..Default::default()
+        });
+    }
+
+    let all_passed = results.iter().all(|r| r.success);
     Ok(BuildReport {
+        build_id: String::new(),
         results,
         total_duration_ms: start.elapsed().as_millis() as u64,
         all_passed,
     })
 }
 
-fn build_one_repo(root: &Path, repo: &RepoConfig) -> BuildResult {
-    let build_cmd = repo.build_cmd.as_deref().unwrap_or("cargo build");
-    let repo_start = std::time::Instant::now();
-    match run_cmd(root, repo, build_cmd) {
-        Ok(output) => BuildResult {
-            repo_name: repo.name.clone(),
-            success: true,
-            output,
-            duration_ms: repo_start.elapsed().as_millis() as u64,
-        },
-        Err(e) => BuildResult {
-            repo_name: repo.name.clone(),
-            success: false,
-            output: e.to_string(),
-            duration_ms: repo_start.elapsed().as_millis() as u64,
-        },
+/// Run each repo's coverage command (default `cargo llvm-cov --json --summary-only`)
+/// in dependency order and merge the per-repo summaries.
+pub fn coverage(
+    root: &Path,
+    manifest: &WorkspaceManifest,
+    repo_name: Option<&str>,
+) -> Result<CoverageReport> {
+    let repos: Vec<_> = match repo_name {
+        Some(name) => vec![
+            manifest
+                .find_repo(name)
+                .with_context(|| format!("repo '{name}' not found"))?,
+        ],
+        None => resolve_build_order(manifest)?,
+    };
+
+    let mut report = CoverageReport {
+        repos: Vec::new(),
+        lines: CoverageCount::default(),
+        functions: CoverageCount::default(),
+        regions: CoverageCount::default(),
+        all_passed: true,
+    };
+    for repo in repos {
+        let cmd = repo
+            .coverage_cmd
+            .as_deref()
+            .unwrap_or("cargo llvm-cov --json --summary-only");
+        let parsed = run_cmd(root, repo, cmd).and_then(|out| parse_llvm_cov_summary(&out));
+        let entry = match parsed {
+            Ok((lines, functions, regions)) => {
+                report.lines.add(lines);
+                report.functions.add(functions);
+                report.regions.add(regions);
+                RepoCoverage {
+                    repo_name: repo.name.clone(),
+                    success: true,
+                    message: String::new(),
+                    lines,
+                    functions,
+                    regions,
+                }
+            }
+            Err(e) => {
+                report.all_passed = false;
+                RepoCoverage {
+                    repo_name: repo.name.clone(),
+                    success: false,
+                    message: format!("{e:#}"),
+                    lines: CoverageCount::default(),
+                    functions: CoverageCount::default(),
+                    regions: CoverageCount::default(),
+                }
+            }
+        };
+        report.repos.push(entry);
+    }
+
+    Ok(report)
+}
+
+/// Extract `(lines, functions, regions)` totals from `llvm-cov export` JSON.
+fn parse_llvm_cov_summary(output: &str) -> Result<(CoverageCount, CoverageCount, CoverageCount)> {
+    let json: serde_json::Value =
+        serde_json::from_str(output).context("coverage output is not JSON")?;
+    let totals = json["data"][0]
+        .get("totals")
+        .context("coverage output has no data[0].totals")?;
+    let metric = |name: &str| CoverageCount {
+        count: totals[name]["count"].as_u64().unwrap_or(0),
+        covered: totals[name]["covered"].as_u64().unwrap_or(0),
+    };
+    Ok((metric("lines"), metric("functions"), metric("regions")))
+}
+
+/// Run each repo's clean command (default `cargo clean`) and remove
+/// smctl-managed temp dirs (`.smctl/tmp`).
+pub fn clean(
+    root: &Path,
+    manifest: &WorkspaceManifest,
+    repo_name: Option<&str>,
+    dry_run: bool,
+) -> Result<CleanReport> {
+    let repos: Vec<_> = match repo_name {
+        Some(name) => vec![
+            manifest
+                .find_repo(name)
+                .with_context(|| format!("repo '{name}' not found"))?,
+        ],
+        None => manifest.repos.iter().collect(),
+    };
+
+    let mut results = Vec::new();
+    for repo in repos {
+        let repo_path = root.join(repo.local_path());
+        let cmd = repo.clean_cmd.as_deref().unwrap_or("cargo clean");
+
+        if !repo_path.exists() {
+            results.push(CleanResult {
+                repo_name: repo.name.clone(),
+                command: cmd.to_string(),
+                success: false,
+                message: "not cloned".to_string(),
+                reclaimed_bytes: 0,
+            });
+            continue;
+        }
+
+        if dry_run {
+            results.push(CleanResult {
+                repo_name: repo.name.clone(),
+                command: cmd.to_string(),
+                success: true,
+                message: format!("would run '{cmd}'"),
+                reclaimed_bytes: smctl_workspace::dir_size(&repo_path.join("target")),
+            });
+            continue;
+        }
+
+        let before = smctl_workspace::dir_size(&repo_path);
+        let result = run_cmd(root, repo, cmd);
+        let after = smctl_workspace::dir_size(&repo_path);
+        results.push(CleanResult {
+            repo_name: repo.name.clone(),
+            command: cmd.to_string(),
+            success: result.is_ok(),
+            message: match result {
+                Ok(_) => "cleaned".to_string(),
+                Err(e) => e.to_string(),
+            },
+            reclaimed_bytes: before.saturating_sub(after),
+        });
+    }
+
+    let mut removed_dirs = Vec::new();
+    let mut temp_bytes = 0;
+    let tmp_dir = root.join(".smctl").join("tmp");
+    if tmp_dir.exists() {
+        temp_bytes = smctl_workspace::dir_size(&tmp_dir);
+        if !dry_run {
+            std::fs::remove_dir_all(&tmp_dir)
+                .with_context(|| format!("failed to remove {}", tmp_dir.display()))?;
+        }
+        removed_dirs.push(tmp_dir);
+    }
+
+    let total_reclaimed_bytes = results.iter().map(|r| r.reclaimed_bytes).sum::<u64>() + temp_bytes;
+    Ok(CleanReport {
+        results,
+        removed_dirs,
+        total_reclaimed_bytes,
+        dry_run,
+    })
+}
+
+fn build_one_repo(ctx: &RunContext, repo: &RepoConfig) -> BuildResult {
+    // Hash before building so the record reflects the inputs that were built.
+    let input_hash = smctl_workspace::input_hash(ctx.root, repo).ok();
+    let mut steps: Vec<&str> = Vec::new();
+    steps.extend(repo.pre_build_cmd.as_deref());
+    steps.extend(repo.build_cmd_for(ctx.profile));
+    steps.extend(repo.post_build_cmd.as_deref());
+
+    let repo_start = std::time::Instant::now();
+    let mut step_results = Vec::new();
+    let mut result = Ok(String::new());
+    for cmd in &steps {
+        let step_start = std::time::Instant::now();
+        let step = ctx.run(repo, cmd);
+        step_results.push(StepResult {
+            command: cmd.to_string(),
+            success: step.is_ok(),
+            duration_ms: step_start.elapsed().as_millis() as u64,
+        });
+        match step {
+            Ok(out) => {
+                if let Ok(output) = &mut result {
+                    output.push_str(&out);
+                }
+            }
+            Err(e) => {
+                result = Err(e);
+                break;
+            }
+        }
+    }
+    // Single-command builds keep the report compact.
+    if steps.len() == 1 {
+        step_results.clear();
+    }
+
+    let cancelled = result.is_err() && ctx.cancelled();
+    let (success, output) = match result {
+        Ok(output) => (true, output),
+        Err(e) => (false, e.to_string()),
+    };
+    BuildResult {
+        repo_name: repo.name.clone(),
+        success,
+        output,
+        duration_ms: repo_start.elapsed().as_millis() as u64,
+        steps: step_results,
+        cancelled,
+        input_hash,
+        executor: remote_executor(ctx.root, repo),
+        log: Some(ctx.log_path(repo)),
+        ..Default::default()
+    }
+}
+
+/// Executor name for repos built remotely (`None` for local builds).
+fn remote_executor(root: &Path, repo: &RepoConfig) -> Option<String> {
+    repo.remote
+        .as_ref()
+        .map(|_| executor_for(root, repo).name())
+}
+
+/// Result for a repo left out of a `--changed` build.
+fn unchanged_result(repo: &RepoConfig) -> BuildResult {
+    BuildResult {
+        repo_name: repo.name.clone(),
+        success: true,
+        output: "unchanged (skipped)".to_string(),
+        duration_ms: 0,
+        skipped: true,
+        ..Default::default()
+    }
+}
+
+/// Result for a repo not built because a dependency failed (keep-going mode).
+fn skipped_result(repo: &RepoConfig, failed_dep: &str) -> BuildResult {
+    BuildResult {
+        repo_name: repo.name.clone(),
+        success: false,
+        output: format!("skipped: dependency '{failed_dep}' failed"),
+        duration_ms: 0,
+        skipped: true,
+        ..Default::default()
+    }
+}
+
+/// Run a workspace-level hook from the workspace root.
+fn run_hook(root: &Path, name: &str, cmd: &str) -> BuildResult {
+    let hook_start = std::time::Instant::now();
+    let (success, output) = match run_in(root, name, cmd, false) {
+        Ok(output) => (true, output),
+        Err(e) => (false, e.to_string()),
+    };
+    BuildResult {
+        repo_name: format!("({name} hook)"),
+        success,
+        output,
+        duration_ms: hook_start.elapsed().as_millis() as u64,
+        ..Default::default()
+    }
+}
+
+fn test_one_repo(ctx: &RunContext, repo: &RepoConfig) -> BuildResult {
+    let test_cmd = with_args(repo.test_cmd_for(ctx.profile), ctx.test_args);
+    let repo_start = std::time::Instant::now();
+    let mut attempts = Vec::new();
+    let mut result = Err(anyhow::anyhow!("test never ran"));
+    for attempt in 0..=ctx.retries {
+        if attempt > 0 {
+            tracing::warn!("{}: retrying tests (attempt {})", repo.name, attempt + 1);
+        }
+        let attempt_start = std::time::Instant::now();
+        result = ctx.run(repo, &test_cmd);
+        attempts.push(attempt_start.elapsed().as_millis() as u64);
+        if result.is_ok() || ctx.cancelled() {
+            break;
+        }
+    }
+    // Only record attempts when a retry actually happened.
+    if attempts.len() == 1 {
+        attempts.clear();
+    }
+
+    let cancelled = result.is_err() && ctx.cancelled();
+    let (success, output) = match result {
+        Ok(output) => (true, output),
+        Err(e) => (false, e.to_string()),
+    };
+    BuildResult {
+        repo_name: format!("{} (test)", repo.name),
+        success,
+        output,
+        duration_ms: repo_start.elapsed().as_millis() as u64,
+        attempts,
+        cancelled,
+        executor: remote_executor(ctx.root, repo),
+        log: Some(ctx.log_path(repo)),
+        ..Default::default()
+    }
+}
+
+// ── Executors ────────────────────────────────────────────────────────
+
+/// Captured result of a command that was started successfully.
+#[derive(Debug, Clone, Default)]
+pub struct CommandOutput {
+    pub success: bool,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl CommandOutput {
+    fn from_output(output: std::process::Output) -> Self {
+        Self {
+            success: output.status.success(),
+            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        }
+    }
+
+    /// Stdout on success; an error carrying stderr otherwise.
+    pub fn into_result(self, label: &str, cmd: &str) -> Result<String> {
+        if self.success {
+            Ok(self.stdout)
+        } else {
+            anyhow::bail!("{}: command '{}' failed:\n{}", label, cmd, self.stderr);
+        }
+    }
+}
+
+/// Stops a build and kills the commands it is running.
+///
+/// Commands started with a token run in their own process group (on Unix) so
+/// that cancelling reaches every process they spawned, not just the direct child.
+#[derive(Debug, Default)]
+pub struct CancelToken {
+    cancelled: AtomicBool,
+    /// Process group ids of running commands.
+    running: Mutex<HashSet<u32>>,
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mark the build cancelled and terminate every running command.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        for pid in self.running.lock().unwrap().iter() {
+            terminate_group(*pid);
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Run `command` to completion unless cancelled first, capturing its output.
+    fn output(&self, command: &mut Command) -> std::io::Result<std::process::Output> {
+        if self.is_cancelled() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Interrupted,
+                "build cancelled",
+            ));
+        }
+        command
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped());
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            command.process_group(0);
+        }
+        let child = command.spawn()?;
+        let pid = child.id();
+        self.running.lock().unwrap().insert(pid);
+        // Cancelled between the check above and registration.
+        if self.is_cancelled() {
+            terminate_group(pid);
+        }
+        let output = child.wait_with_output();
+        self.running.lock().unwrap().remove(&pid);
+        output
+    }
+}
+
+#[cfg(unix)]
+fn terminate_group(pgid: u32) {
+    // SAFETY: kill(2) has no memory-safety preconditions; a negative pid
+    // addresses the process group created for the command.
+    unsafe {
+        libc::kill(-(pgid as libc::pid_t), libc::SIGTERM);
+    }
+}
+
+#[cfg(not(unix))]
+fn terminate_group(_pgid: u32) {}
+
+/// Per-command settings for [`Executor::output`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RunOptions<'a> {
+    /// Extra environment variables for the command.
+    pub env: &'a [(String, String)],
+    /// Kills the command when cancelled.
+    pub cancel: Option<&'a CancelToken>,
+}
+
+/// Runs a repo's commands and returns their stdout.
+pub trait Executor: Send + Sync {
+    /// Short description for reports (`local`, `ssh:<host>`).
+    fn name(&self) -> String;
+
+    /// Run `cmd` for `repo`, capturing both output streams. Errors only when
+    /// the command could not be started or was cancelled.
+    fn output(&self, repo: &RepoConfig, cmd: &str, opts: RunOptions) -> Result<CommandOutput>;
+
+    /// Run `cmd` for `repo`; failures carry the command's stderr.
+    fn run(&self, repo: &RepoConfig, cmd: &str) -> Result<String> {
+        self.output(repo, cmd, RunOptions::default())?
+            .into_result(&format!("{} ({})", repo.name, self.name()), cmd)
+    }
+}
+
+/// Run `command`, through `cancel` when given.
+fn spawn_output(
+    command: &mut Command,
+    cancel: Option<&CancelToken>,
+) -> std::io::Result<std::process::Output> {
+    match cancel {
+        Some(cancel) => cancel.output(command),
+        None => command.output(),
+    }
+}
+
+/// Runs commands in the repo's checkout under the workspace root.
+pub struct LocalExecutor {
+    root: PathBuf,
+}
+
+impl LocalExecutor {
+    pub fn new(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+        }
+    }
+}
+
+impl Executor for LocalExecutor {
+    fn name(&self) -> String {
+        "local".to_string()
+    }
+
+    fn output(&self, repo: &RepoConfig, cmd: &str, opts: RunOptions) -> Result<CommandOutput> {
+        let mut command = command_for(cmd, repo.shell)?;
+        command
+            .current_dir(self.root.join(repo.local_path()))
+            .envs(opts.env.iter().map(|(k, v)| (k, v)));
+        let output = spawn_output(&mut command, opts.cancel)
+            .with_context(|| format!("failed to run '{cmd}' in {}", repo.name))?;
+        Ok(CommandOutput::from_output(output))
+    }
+
+    fn run(&self, repo: &RepoConfig, cmd: &str) -> Result<String> {
+        self.output(repo, cmd, RunOptions::default())?
+            .into_result(&repo.name, cmd)
+    }
+}
+
+/// Runs commands on a remote host over ssh, in the repo's remote checkout.
+///
+/// The remote directory must already contain the sources; smctl does not
+/// sync them.
+pub struct SshExecutor {
+    remote: RemoteConfig,
+}
+
+impl SshExecutor {
+    pub fn new(remote: &RemoteConfig) -> Self {
+        Self {
+            remote: remote.clone(),
+        }
+    }
+}
+
+impl Executor for SshExecutor {
+    fn name(&self) -> String {
+        format!("ssh:{}", self.remote.host)
+    }
+
+    fn output(&self, repo: &RepoConfig, cmd: &str, opts: RunOptions) -> Result<CommandOutput> {
+        // The remote side always runs a shell, so re-quote lexed commands to
+        // keep the same argument boundaries as a local run.
+        let remote_cmd = if repo.shell {
+            cmd.to_string()
+        } else {
+            let words = shell_words::split(cmd)
+                .with_context(|| format!("failed to parse command '{cmd}'"))?;
+            shell_words::join(words)
+        };
+        // ssh does not forward the local environment, so export it remotely.
+        let exports: String = opts
+            .env
+            .iter()
+            .map(|(k, v)| format!("export {k}={}; ", shell_words::quote(v)))
+            .collect();
+        let script = format!(
+            "{}cd {} && {}",
+            exports,
+            shell_words::quote(&self.remote.path),
+            remote_cmd
+        );
+
+        let ssh = self.remote.ssh.as_deref().unwrap_or("ssh");
+        let mut command = command_for(ssh, false)?;
+        command.arg(&self.remote.host).arg(&script);
+        let output = spawn_output(&mut command, opts.cancel)
+            .with_context(|| format!("failed to run '{ssh}' for {}", repo.name))?;
+        Ok(CommandOutput::from_output(output))
+    }
+}
+
+/// Executor for a repo: ssh when `[repos.remote]` is set, local otherwise.
+pub fn executor_for(root: &Path, repo: &RepoConfig) -> Box<dyn Executor> {
+    match &repo.remote {
+        Some(remote) => Box::new(SshExecutor::new(remote)),
+        None => Box::new(LocalExecutor::new(root)),
+    }
+}
+
+fn run_cmd(root: &Path, repo: &RepoConfig, cmd: &str) -> Result<String> {
+    executor_for(root, repo).run(repo, cmd)
+}
+
+/// Append `args` to `cmd`, quoted so both shell and lexed commands see them verbatim.
+fn with_args(cmd: &str, args: &[String]) -> String {
+    if args.is_empty() {
+        cmd.to_string()
+    } else {
+        format!("{cmd} {}", shell_words::join(args))
+    }
+}
+
+/// Build the process for `cmd`: via the platform shell when `shell` is set,
+/// otherwise lexed with POSIX shell-word rules (quotes and escapes, no expansion).
+fn command_for(cmd: &str, shell: bool) -> Result<Command> {
+    if shell {
+        let mut command = if cfg!(windows) {
+            let mut c = Command::new("cmd");
+            c.arg("/C");
+            c
+        } else {
+            let mut c = Command::new("sh");
+            c.arg("-c");
+            c
+        };
+        command.arg(cmd);
+        return Ok(command);
+    }
+
+    let parts =
+        shell_words::split(cmd).with_context(|| format!("failed to parse command '{cmd}'"))?;
+    let Some((program, args)) = parts.split_first() else {
+        anyhow::bail!("empty command");
+    };
+    let mut command = Command::new(program);
+    command.args(args);
+    Ok(command)
+}
+
+fn run_in(dir: &Path, label: &str, cmd: &str, shell: bool) -> Result<String> {
+    let output = command_for(cmd, shell)?
+        .current_dir(dir)
+        .output()
+        .with_context(|| format!("failed to run '{cmd}' in {label}"))?;
+    CommandOutput::from_output(output).into_result(label, cmd)
+}
+
+fn collect_deps(manifest: &WorkspaceManifest, name: &str) -> Vec<String> {
+    let mut deps = Vec::new();
+    let mut stack = vec![name.to_string()];
+    while let Some(current) = stack.pop() {
+        if let Some(repo) = manifest.find_repo(&current) {
+            for dep in &repo.depends_on {
+                if !deps.contains(dep) {
+                    deps.push(dep.clone());
+                    stack.push(dep.clone());
+                }
+            }
+        }
+    }
+    deps
+}
+
+// ── Benchmarks ───────────────────────────────────────────────────────
+
+pub mod bench {
+    use std::path::{Path, PathBuf};
+
+    use anyhow::{Context, Result};
+    use serde::{Deserialize, Serialize};
+    use smctl_workspace::WorkspaceManifest;
+
+    use crate::{json_ids, resolve_build_order, run_cmd, timestamp_id};
+
+    /// A single benchmark measurement.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct Benchmark {
+        pub name: String,
+        /// Point estimate in nanoseconds.
+        pub nanos: f64,
+    }
+
+    /// Benchmarks from one repo's bench command.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct RepoBench {
+        pub repo_name: String,
+        pub success: bool,
+        pub message: String,
+        pub duration_ms: u64,
+        pub benchmarks: Vec<Benchmark>,
+    }
+
+    /// A recorded benchmark run (`.smctl/bench/<id>.json`).
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct BenchRun {
+        pub id: String,
+        pub repos: Vec<RepoBench>,
+    }
+
+    /// Change of one benchmark between two runs.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct BenchDelta {
+        pub repo_name: String,
+        pub name: String,
+        pub baseline_nanos: f64,
+        pub current_nanos: f64,
+        /// Relative change in percent (positive is slower).
+        pub change_pct: f64,
+        pub regression: bool,
+    }
+
+    /// Comparison of a run against a baseline run.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct BenchComparison {
+        pub baseline: String,
+        pub current: String,
+        pub threshold_pct: f64,
+        pub deltas: Vec<BenchDelta>,
+        pub regressions: usize,
+    }
+
+    /// Directory holding recorded benchmark runs.
+    pub fn bench_dir(root: &Path) -> PathBuf {
+        root.join(".smctl").join("bench")
+    }
+
+    /// Run each repo's bench command (default `cargo bench`) in dependency order.
+    pub fn run(
+        root: &Path,
+        manifest: &WorkspaceManifest,
+        repo_name: Option<&str>,
+    ) -> Result<BenchRun> {
+        let repos: Vec<_> = match repo_name {
+            Some(name) => vec![
+                manifest
+                    .find_repo(name)
+                    .with_context(|| format!("repo '{name}' not found"))?,
+            ],
+            None => resolve_build_order(manifest)?,
+        };
+
+        let mut results = Vec::new();
+        for repo in repos {
+            let cmd = repo.bench_cmd.as_deref().unwrap_or("cargo bench");
+            let start = std::time::Instant::now();
+            let output = run_cmd(root, repo, cmd);
+            let duration_ms = start.elapsed().as_millis() as u64;
+            results.push(match output {
+                Ok(out) => RepoBench {
+                    repo_name: repo.name.clone(),
+                    success: true,
+                    message: String::new(),
+                    duration_ms,
+                    benchmarks: parse_criterion(&out),
+                },
+                Err(e) => RepoBench {
+                    repo_name: repo.name.clone(),
+                    success: false,
+                    message: e.to_string(),
+                    duration_ms,
+                    benchmarks: Vec::new(),
+                },
+            });
+        }
+
+        let dir = bench_dir(root);
+        Ok(BenchRun {
+            id: timestamp_id(|id| dir.join(format!("{id}.json")).exists()),
+            repos: results,
+        })
+    }
+
+    /// Store a run as `.smctl/bench/<id>.json`.
+    pub fn record(root: &Path, run: &BenchRun) -> Result<()> {
+        let dir = bench_dir(root);
+        std::fs::create_dir_all(&dir).context("failed to create bench directory")?;
+        let content = serde_json::to_string_pretty(run).context("failed to serialize bench run")?;
+        std::fs::write(dir.join(format!("{}.json", run.id)), content)
+            .context("failed to write bench run")
+    }
+
+    /// Load a recorded run by id (`latest` selects the most recent).
+    pub fn load(root: &Path, id: &str) -> Result<BenchRun> {
+        let dir = bench_dir(root);
+        let id = if id == "latest" {
+            json_ids(&dir)?
+                .into_iter()
+                .next()
+                .context("no recorded bench runs")?
+        } else {
+            id.to_string()
+        };
+        let content = std::fs::read_to_string(dir.join(format!("{id}.json")))
+            .with_context(|| format!("bench run '{id}' not found"))?;
+        serde_json::from_str(&content).with_context(|| format!("failed to parse bench run '{id}'"))
+    }
+
+    /// Compare benchmarks present in both runs; a slowdown above
+    /// `threshold_pct` percent counts as a regression.
+    pub fn compare(baseline: &BenchRun, current: &BenchRun, threshold_pct: f64) -> BenchComparison {
+        let mut deltas = Vec::new();
+        for repo in &current.repos {
+            let Some(base_repo) = baseline
+                .repos
+                .iter()
+                .find(|r| r.repo_name == repo.repo_name)
+            else {
+                continue;
+            };
+            for bench in &repo.benchmarks {
+                let Some(base) = base_repo.benchmarks.iter().find(|b| b.name == bench.name) else {
+                    continue;
+                };
+                if base.nanos <= 0.0 {
+                    continue;
+                }
+                let change_pct = (bench.nanos - base.nanos) / base.nanos * 100.0;
+                deltas.push(BenchDelta {
+                    repo_name: repo.repo_name.clone(),
+                    name: bench.name.clone(),
+                    baseline_nanos: base.nanos,
+                    current_nanos: bench.nanos,
+                    change_pct,
+                    regression: change_pct > threshold_pct,
+                });
+            }
+        }
+
+        BenchComparison {
+            baseline: baseline.id.clone(),
+            current: current.id.clone(),
+            threshold_pct,
+            regressions: deltas.iter().filter(|d| d.regression).count(),
+            deltas,
+        }
+    }
+
+    /// Extract point estimates from criterion output:
+    /// `name  time:   [low estimate high]`, where long names sit on the previous line.
+    pub fn parse_criterion(output: &str) -> Vec<Benchmark> {
+        let mut benches = Vec::new();
+        let mut prev = "";
+        for line in output.lines() {
+            if let Some(idx) = line.find("time:") {
+                let name = match line[..idx].trim() {
+                    "" => prev.trim(),
+                    name => name,
+                };
+                let values = line[idx + 5..]
+                    .trim()
+                    .trim_start_matches('[')
+                    .trim_end_matches(']');
+                let words: Vec<&str> = values.split_whitespace().collect();
+                // [low unit estimate unit high unit]
+                if words.len() == 6
+                    && !name.is_empty()
+                    && let Some(nanos) = to_nanos(words[2], words[3])
+                {
+                    benches.push(Benchmark {
+                        name: name.to_string(),
+                        nanos,
+                    });
+                }
+            }
+            if !line.trim().is_empty() {
+                prev = line;
+            }
+        }
+        benches
+    }
+
+    fn to_nanos(value: &str, unit: &str) -> Option<f64> {
+        let value: f64 = value.parse().ok()?;
+        let scale = match unit {
+            "ps" => 1e-3,
+            "ns" => 1.0,
+            "us" | "\u{b5}s" => 1e3,
+            "ms" => 1e6,
+            "s" => 1e9,
+            _ => return None,
+        };
+        Some(value * scale)
+    }
+
+    /// Human-readable duration for a nanosecond estimate.
+    pub fn format_nanos(nanos: f64) -> String {
+        if nanos >= 1e9 {
+            format!("{:.2} s", nanos / 1e9)
+        } else if nanos >= 1e6 {
+            format!("{:.2} ms", nanos / 1e6)
+        } else if nanos >= 1e3 {
+            format!("{:.2} \u{b5}s", nanos / 1e3)
+        } else {
+            format!("{nanos:.2} ns")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_manifest() -> WorkspaceManifest {
+        WorkspaceManifest::parse(
+            r#"
+            [workspace]
+            name = "test"
+
+            [[repos]]
+            name = "A"
+            url = "https://example.com/a"
+            depends_on = []
+
+            [[repos]]
+            name = "B"
+            url = "https://example.com/b"
+            depends_on = ["A"]
+
+            [[repos]]
+            name = "C"
+            url = "https://example.com/c"
+            depends_on = ["A", "B"]
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_resolve_build_order() {
+        let manifest = make_manifest();
+        let order = resolve_build_order(&manifest).unwrap();
+        let names: Vec<_> = order.iter().map(|r| r.name.as_str()).collect();
+        // A must come before B and C; B must come before C
+        let a_pos = names.iter().position(|n| *n == "A").unwrap();
+        let b_pos = names.iter().position(|n| *n == "B").unwrap();
+        let c_pos = names.iter().position(|n| *n == "C").unwrap();
+        assert!(a_pos < b_pos);
+        assert!(b_pos < c_pos);
+    }
+
+    #[test]
+    fn test_circular_dependency() {
+        let manifest = WorkspaceManifest::parse(
+            r#"
+            [workspace]
+            name = "test"
+
+            [[repos]]
+            name = "A"
+            url = "https://example.com/a"
+            depends_on = ["B"]
+
+            [[repos]]
+            name = "B"
+            url = "https://example.com/b"
+            depends_on = ["A"]
+            "#,
+        )
+        .unwrap();
+        assert!(resolve_build_order(&manifest).is_err());
+    }
+
+    #[test]
+    fn test_clean_runs_clean_cmd_and_removes_tmp() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("A/out");
+        std::fs::create_dir_all(&out).unwrap();
+        std::fs::write(out.join("artifact.bin"), vec![0u8; 1024]).unwrap();
+        std::fs::create_dir_all(dir.path().join(".smctl/tmp")).unwrap();
+        std::fs::write(dir.path().join(".smctl/tmp/scratch"), "x").unwrap();
+
+        let manifest = WorkspaceManifest::parse(
+            r#"
+            [workspace]
+            name = "test"
+
+            [[repos]]
+            name = "A"
+            url = "https://example.com/a"
+            clean_cmd = "rm -rf out"
+            "#,
+        )
+        .unwrap();
+
+        // Dry run leaves everything in place
+        let report = clean(dir.path(), &manifest, None, true).unwrap();
+        assert!(report.dry_run);
+        assert_eq!(report.removed_dirs.len(), 1);
+        assert!(out.exists());
+        assert!(dir.path().join(".smctl/tmp").exists());
+
+        let report = clean(dir.path(), &manifest, None, false).unwrap();
+        assert!(report.results[0].success);
+        assert_eq!(report.results[0].reclaimed_bytes, 1024);
+        assert_eq!(report.total_reclaimed_bytes, 1025);
+        assert!(!out.exists());
+        assert!(!dir.path().join(".smctl/tmp").exists());
+    }
+
+    #[test]
+    fn test_parallel_build_reports_levels() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["A", "B", "C"] {
+            std::fs::create_dir_all(dir.path().join(name)).unwrap();
+        }
+        let manifest = WorkspaceManifest::parse(
+            r#"
+            [workspace]
+            name = "test"
+
+            [[repos]]
+            name = "A"
+            url = "https://example.com/a"
+            build_cmd = "true"
+
+            [[repos]]
+            name = "B"
+            url = "https://example.com/b"
+            build_cmd = "true"
+
+            [[repos]]
+            name = "C"
+            url = "https://example.com/c"
+            build_cmd = "true"
+            depends_on = ["A", "B"]
+            "#,
+        )
+        .unwrap();
+
+        let opts = BuildOptions {
+            parallel: true,
+            jobs: Some(1),
+            ..Default::default()
+        };
+        let report = build_with_options(dir.path(), &manifest, &opts).unwrap();
+        assert!(report.all_passed);
+        let level_of = |name: &str| {
+            report
+                .results
+                .iter()
+                .find(|r| r.repo_name == name)
+                .and_then(|r| r.level)
+        };
+        assert_eq!(level_of("A"), Some(0));
+        assert_eq!(level_of("B"), Some(0));
+        assert_eq!(level_of("C"), Some(1));
+
+        let report = build(dir.path(), &manifest, None, false, false).unwrap();
+        assert!(report.results.iter().all(|r| r.level.is_none()));
+    }
+
+    #[test]
+    fn test_parallel_build_starts_repos_when_their_deps_finish() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["A", "B", "C"] {
+            std::fs::create_dir_all(dir.path().join(name)).unwrap();
+        }
+        // A (level 0) only finishes once C (level 1) has been built, which
+        // deadlocks a level-by-level schedule: C's only dependency is B.
+        let manifest = WorkspaceManifest::parse(
+            r#"
+            [workspace]
+            name = "test"
+
+            [[repos]]
+            name = "A"
+            url = "https://example.com/a"
+            shell = true
+            build_cmd = "for i in $(seq 50); do [ -e ../C/built ] && exit 0; sleep 0.1; done; exit 1"
+
+            [[repos]]
+            name = "B"
+            url = "https://example.com/b"
+            build_cmd = "true"
+
+            [[repos]]
+            name = "C"
+            url = "https://example.com/c"
+            build_cmd = "touch built"
+            depends_on = ["B"]
+            "#,
+        )
+        .unwrap();
+
+        let report = build_parallel(dir.path(), &manifest, None, false, false).unwrap();
+        assert!(report.all_passed, "{:?}", report.results);
+        let c = report.results.iter().find(|r| r.repo_name == "C").unwrap();
+        assert_eq!(c.level, Some(1));
+    }
+
+    #[test]
+    fn test_parallel_build_respects_max_parallel() {
+        let dir = tempfile::tempdir().unwrap();
+        // Each build takes a lock dir; a failed mkdir means two builds overlapped.
+        std::fs::write(
+            dir.path().join("probe.sh"),
+            "mkdir ../lock 2>/dev/null || touch ../overlap\nsleep 0.1\nrmdir ../lock 2>/dev/null\n",
+        )
+        .unwrap();
+        let mut toml = String::from("[workspace]\nname = \"test\"\n\n[build]\nmax_parallel = 1\n");
+        for name in ["A", "B", "C", "D"] {
+            std::fs::create_dir_all(dir.path().join(name)).unwrap();
+            toml.push_str(&format!(
+                "\n[[repos]]\nname = \"{name}\"\nurl = \"https://example.com/{name}\"\nbuild_cmd = \"sh ../probe.sh\"\n"
+            ));
+        }
+        let manifest = WorkspaceManifest::parse(&toml).unwrap();
+        assert_eq!(manifest.build.max_parallel, Some(1));
+
+        let report = build_parallel(dir.path(), &manifest, None, false, false).unwrap();
+        assert!(report.all_passed);
+        assert_eq!(report.results.len(), 4);
+        assert!(!dir.path().join("overlap").exists());
+    }
+
+    #[test]
+    fn test_parallel_build_respects_build_weight() {
+        let dir = tempfile::tempdir().unwrap();
+        // Each build records which builds were running alongside it.
+        std::fs::create_dir_all(dir.path().join("running")).unwrap();
+        std::fs::write(
+            dir.path().join("probe.sh"),
+            "mkdir ../running/$1\nsleep 0.2\nls ../running > ../seen-$1\nrmdir ../running/$1\n",
+        )
+        .unwrap();
+        let mut toml = String::from("[workspace]\nname = \"test\"\n\n[build]\nmax_parallel = 2\n");
+        for (name, weight) in [("H", 2), ("L1", 1), ("L2", 1), ("L3", 1)] {
+            std::fs::create_dir_all(dir.path().join(name)).unwrap();
+            toml.push_str(&format!(
+                "\n[[repos]]\nname = \"{name}\"\nurl = \"https://example.com/{name}\"\nbuild_cmd = \"sh ../probe.sh {name}\"\nbuild_weight = {weight}\n"
+            ));
+        }
+        let manifest = WorkspaceManifest::parse(&toml).unwrap();
+        assert_eq!(manifest.repos[0].build_weight(), 2);
+
+        let report = build_parallel(dir.path(), &manifest, None, false, false).unwrap();
+        assert!(report.all_passed);
+        let seen =
+            |name: &str| std::fs::read_to_string(dir.path().join(format!("seen-{name}"))).unwrap();
+        assert_eq!(seen("H").trim(), "H");
+        for light in ["L1", "L2", "L3"] {
+            assert!(
+                !seen(light).contains('H'),
+                "{light} overlapped the heavy build"
+            );
+        }
+    }
+
+    #[test]
+    fn test_verify_cedar_runs_per_policy() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("A/policies/admin")).unwrap();
+        std::fs::create_dir_all(dir.path().join("B")).unwrap();
+        std::fs::write(
+            dir.path().join("A/policies/base.cedar"),
+            "permit(principal, action, resource);",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("A/policies/admin/bad.cedar"), "oops").unwrap();
+        // Stand-in for the cedar CLI: fails on policies containing "oops".
+        std::fs::write(
+            dir.path().join("fake-cedar.sh"),
+            "for f; do :; done\necho \"$1 $f\"\n! grep -q oops \"$f\"\n",
+        )
+        .unwrap();
+        let manifest = WorkspaceManifest::parse(&format!(
+            r#"
+            [workspace]
+            name = "test"
+
+            [build]
+            cedar_cmd = "sh {}"
+
+            [[repos]]
+            name = "A"
+            url = "https://example.com/a"
+
+            [repos.cedar]
+            policies = ["policies"]
+            schema = "policies/app.cedarschema"
+
+            [[repos]]
+            name = "B"
+            url = "https://example.com/b"
+            "#,
+            dir.path().join("fake-cedar.sh").display()
+        ))
+        .unwrap();
+
+        let report = verify_cedar(dir.path(), &manifest, None).unwrap();
+        assert!(!report.all_passed);
+        assert_eq!(report.results.len(), 1);
+        let result = &report.results[0];
+        assert_eq!(result.repo_name, "A (cedar)");
+        assert_eq!(result.steps.len(), 2);
+        assert!(
+            result.steps[0]
+                .command
+                .contains("validate --schema policies/app.cedarschema")
+        );
+        assert!(
+            result.steps[0]
+                .command
+                .ends_with("policies/admin/bad.cedar")
+        );
+        assert!(!result.steps[0].success);
+        assert!(result.steps[1].success);
+    }
+
+    #[test]
+    fn test_build_hooks() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("A")).unwrap();
+        let toml = r#"
+            [workspace]
+            name = "test"
+
+            [build.hooks]
+            pre_build = "touch codegen"
+            post_build = "touch published"
+
+            [[repos]]
+            name = "A"
+            url = "https://example.com/a"
+            pre_build_cmd = "touch pre"
+            build_cmd = "true"
+            post_build_cmd = "touch post"
+            "#;
+        let manifest = WorkspaceManifest::parse(toml).unwrap();
+
+        let report = build(dir.path(), &manifest, None, false, false).unwrap();
+        assert!(report.all_passed);
+        let names: Vec<_> = report
+            .results
+            .iter()
+            .map(|r| r.repo_name.as_str())
+            .collect();
+        assert_eq!(names, ["(pre-build hook)", "A", "(post-build hook)"]);
+        for file in ["codegen", "published", "A/pre", "A/post"] {
+            assert!(dir.path().join(file).exists(), "{file} missing");
+        }
+
+        // A failing pre-build hook stops the build before any repo runs
+        let manifest = WorkspaceManifest::parse(&toml.replace("touch codegen", "false")).unwrap();
+        std::fs::remove_file(dir.path().join("A/pre")).unwrap();
+        let report = build_parallel(dir.path(), &manifest, None, false, false).unwrap();
+        assert!(!report.all_passed);
+        assert_eq!(report.results.len(), 1);
+        assert!(!dir.path().join("A/pre").exists());
+    }
+
+    #[test]
+    fn test_build_profile() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("A")).unwrap();
+        let manifest = WorkspaceManifest::parse(
+            r#"
+            [workspace]
+            name = "test"
+
+            [[repos]]
+            name = "A"
+            url = "https://example.com/a"
+            build_cmd = "touch debug"
+
+            [repos.profiles.release]
+            build_cmd = "touch release"
+            "#,
+        )
+        .unwrap();
+
+        let opts = BuildOptions {
+            profile: Some("release"),
+            ..Default::default()
+        };
+        let report = build_with_options(dir.path(), &manifest, &opts).unwrap();
+        assert!(report.all_passed);
+        assert!(dir.path().join("A/release").exists());
+        assert!(!dir.path().join("A/debug").exists());
+
+        let opts = BuildOptions {
+            profile: Some("relase"),
+            ..Default::default()
+        };
+        assert!(build_with_options(dir.path(), &manifest, &opts).is_err());
+    }
+
+    #[test]
+    fn test_test_retries_record_attempts() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("A")).unwrap();
+        // Fails on the first run, passes afterwards.
+        std::fs::write(
+            dir.path().join("A/flaky.sh"),
+            "[ -e ran ] && exit 0\ntouch ran\nexit 1\n",
+        )
+        .unwrap();
+        let manifest = WorkspaceManifest::parse(
+            r#"
+            [workspace]
+            name = "test"
+
+            [build]
+            test_retries = 2
+
+            [[repos]]
+            name = "A"
+            url = "https://example.com/a"
+            build_cmd = "true"
+            test_cmd = "sh flaky.sh"
+            "#,
+        )
+        .unwrap();
+
+        let report = build(dir.path(), &manifest, None, true, false).unwrap();
+        assert!(report.all_passed);
+        assert_eq!(report.results[1].attempts.len(), 2);
+
+        // --retries 0 overrides the manifest
+        std::fs::remove_file(dir.path().join("A/ran")).unwrap();
+        let opts = BuildOptions {
+            run_tests: true,
+            retries: Some(0),
+            ..Default::default()
+        };
+        let report = build_with_options(dir.path(), &manifest, &opts).unwrap();
+        assert!(!report.all_passed);
+        assert!(report.results[1].attempts.is_empty());
+    }
+
+    #[test]
+    fn test_progress_reports_repo_states() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["A", "B", "C"] {
+            std::fs::create_dir_all(dir.path().join(name)).unwrap();
+        }
+        let manifest = WorkspaceManifest::parse(
+            r#"
+            [workspace]
+            name = "test"
+
+            [[repos]]
+            name = "A"
+            url = "https://example.com/a"
+            build_cmd = "true"
+            test_cmd = "true"
+
+            [[repos]]
+            name = "B"
+            url = "https://example.com/b"
+            build_cmd = "false"
+            depends_on = ["A"]
+
+            [[repos]]
+            name = "C"
+            url = "https://example.com/c"
+            build_cmd = "true"
+            depends_on = ["B"]
+            "#,
+        )
+        .unwrap();
+
+        let events = Mutex::new(Vec::new());
+        let record = |repo: &str, state: RepoState| {
+            events.lock().unwrap().push((repo.to_string(), state));
+        };
+        let opts = BuildOptions {
+            run_tests: true,
+            keep_going: true,
+            progress: Some(ProgressFn(&record)),
+            ..Default::default()
+        };
+        build_with_options(dir.path(), &manifest, &opts).unwrap();
+
+        let events = events.into_inner().unwrap();
+        let states = |repo: &str| -> Vec<RepoState> {
+            events
+                .iter()
+                .filter(|(r, _)| r == repo)
+                .map(|(_, s)| *s)
+                .collect()
+        };
+        use RepoState::*;
+        assert_eq!(states("A"), [Queued, Building, Testing, Done]);
+        assert_eq!(states("B"), [Queued, Building, Failed]);
+        assert_eq!(states("C"), [Queued, Skipped]);
+        // Everything is queued before the first build starts.
+        assert!(events[..3].iter().all(|(_, s)| *s == Queued));
     }
-}
 
-fn test_one_repo(root: &Path, repo: &RepoConfig) -> BuildResult {
-    let test_cmd = repo.test_cmd.as_deref().unwrap_or("cargo test");
-    let repo_start = std::time::Instant::now();
-    match run_cmd(root, repo, test_cmd) {
-        Ok(output) => BuildResult {
-            repo_name: format!("{} (test)", repo.name),
-            success: true,
-            output,
-            duration_ms: repo_start.elapsed().as_millis() as u64,
-        },
-        Err(e) => BuildResult {
-            repo_name: format!("{} (test)", repo.name),
-            success: false,
-            output: e.to_string(),
-            duration_ms: repo_start.elapsed().as_millis() as u64,
-        },
+    #[test]
+    fn test_cancel_kills_running_commands() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["A", "B"] {
+            std::fs::create_dir_all(dir.path().join(name)).unwrap();
+        }
+        // The background subshell only dies if the whole process group is killed.
+        let manifest = WorkspaceManifest::parse(
+            r#"
+            [workspace]
+            name = "test"
+
+            [[repos]]
+            name = "A"
+            url = "https://example.com/a"
+            shell = true
+            build_cmd = "(sleep 1; touch late) & wait"
+
+            [[repos]]
+            name = "B"
+            url = "https://example.com/b"
+            build_cmd = "touch built"
+            depends_on = ["A"]
+            "#,
+        )
+        .unwrap();
+
+        let cancel = CancelToken::new();
+        let opts = BuildOptions {
+            cancel: Some(&cancel),
+            ..Default::default()
+        };
+        let report = std::thread::scope(|s| {
+            s.spawn(|| {
+                std::thread::sleep(std::time::Duration::from_millis(300));
+                cancel.cancel();
+            });
+            build_with_options(dir.path(), &manifest, &opts).unwrap()
+        });
+
+        assert!(report.was_cancelled());
+        assert!(!report.all_passed);
+        assert_eq!(report.results.len(), 1);
+        assert!(report.results[0].cancelled);
+        std::thread::sleep(std::time::Duration::from_millis(1500));
+        assert!(!dir.path().join("A/late").exists());
+        assert!(!dir.path().join("B/built").exists());
     }
-}
 
-fn run_cmd(root: &Path, repo: &RepoConfig, cmd: &str) -> Result<String> {
-    let repo_path = root.join(repo.local_path());
-    let parts: Vec<&str> = cmd.split_whitespace().collect();
-    if parts.is_empty() {
-        anyhow::bail!("empty command");
+    #[test]
+    fn test_test_args_appended_to_test_cmd() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("A")).unwrap();
+        std::fs::write(
+            dir.path().join("A/args.sh"),
+            "printf '%s\\n' \"$@\" > args.txt\n",
+        )
+        .unwrap();
+        let manifest = WorkspaceManifest::parse(
+            r#"
+            [workspace]
+            name = "test"
+
+            [[repos]]
+            name = "A"
+            url = "https://example.com/a"
+            build_cmd = "true"
+            test_cmd = "sh args.sh --lib"
+            "#,
+        )
+        .unwrap();
+
+        let test_args = vec!["my test".to_string(), "--nocapture".to_string()];
+        let opts = BuildOptions {
+            run_tests: true,
+            test_args: &test_args,
+            ..Default::default()
+        };
+        let report = build_with_options(dir.path(), &manifest, &opts).unwrap();
+        assert!(report.all_passed);
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("A/args.txt")).unwrap(),
+            "--lib\nmy test\n--nocapture\n"
+        );
     }
 
-    let output = Command::new(parts[0])
-        .args(&parts[1..])
-        .current_dir(&repo_path)
-        .output()
-        .with_context(|| format!("failed to run '{cmd}' in {}", repo.name))?;
+    #[test]
+    fn test_report_spec_and_junit() {
+        let spec: ReportSpec = "junit:out/report.xml".parse().unwrap();
+        assert_eq!(spec.format, ReportFormat::Junit);
+        assert_eq!(spec.path, PathBuf::from("out/report.xml"));
+        assert!("xml:out.xml".parse::<ReportSpec>().is_err());
+        assert!("junit:".parse::<ReportSpec>().is_err());
+
+        let result = |name: &str, success: bool, output: &str| BuildResult {
+            repo_name: name.to_string(),
+            success,
+            output: output.to_string(),
+            duration_ms: 1500,
+            ..Default::default()
+        };
+        let report = BuildReport {
+            build_id: String::new(),
+            results: vec![
+                result("A", true, ""),
+                result("A (test)", false, "assert <a> & b"),
+                result("B", true, ""),
+            ],
+            total_duration_ms: 4500,
+            all_passed: false,
+        };
+        let xml = report.to_junit_xml();
+        assert!(xml.contains(r#"<testsuites name="smctl build" tests="3" failures="1""#));
+        assert!(xml.contains(r#"<testsuite name="A" tests="2" failures="1" time="3.000">"#));
+        assert!(xml.contains(r#"<testsuite name="B" tests="1" failures="0""#));
+        assert!(xml.contains("assert &lt;a&gt; &amp; b"));
+
+        let dir = tempfile::tempdir().unwrap();
+        let spec = ReportSpec {
+            format: ReportFormat::Json,
+            path: dir.path().join("reports/build.json"),
+        };
+        report.write_report(&spec).unwrap();
+        let parsed: BuildReport =
+            serde_json::from_str(&std::fs::read_to_string(&spec.path).unwrap()).unwrap();
+        assert_eq!(parsed.results.len(), 3);
+    }
 
-    if output.status.success() {
-        Ok(String::from_utf8_lossy(&output.stdout).to_string())
-    } else {
-        anyhow::bail!(
-            "{}: command '{}' failed:\n{}",
-            repo.name,
-            cmd,
-            String::from_utf8_lossy(&output.stderr)
+    #[test]
+    fn test_build_history() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(list_history(dir.path(), None).unwrap().is_empty());
+        assert!(load_build(dir.path(), "latest").is_err());
+
+        let result = |name: &str, ms: u64| BuildResult {
+            repo_name: name.to_string(),
+            success: true,
+            output: String::new(),
+            duration_ms: ms,
+            ..Default::default()
+        };
+        let first = BuildReport {
+            build_id: String::new(),
+            results: vec![result("A", 100), result("B", 200)],
+            total_duration_ms: 300,
+            all_passed: true,
+        };
+        let second = BuildReport {
+            build_id: String::new(),
+            results: vec![result("A", 150), result("C", 50)],
+            total_duration_ms: 200,
+            all_passed: true,
+        };
+        let id1 = record_build(dir.path(), &first).unwrap();
+        let id2 = record_build(dir.path(), &second).unwrap();
+        assert_ne!(id1, id2);
+
+        let history = list_history(dir.path(), None).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].id, id2);
+        assert_eq!(list_history(dir.path(), Some(1)).unwrap().len(), 1);
+        assert_eq!(
+            load_build(dir.path(), "latest").unwrap().total_duration_ms,
+            200
         );
+
+        let cmp = compare_builds(dir.path(), &id1, &id2).unwrap();
+        assert_eq!(cmp.total_delta_ms, -100);
+        let step = |name: &str| cmp.steps.iter().find(|s| s.repo_name == name).unwrap();
+        assert_eq!(step("A").delta_ms, Some(50));
+        assert_eq!(step("B").after_ms, None);
+        assert_eq!(step("C").before_ms, None);
     }
-}
 
-fn collect_deps(manifest: &WorkspaceManifest, name: &str) -> Vec<String> {
-    let mut deps = Vec::new();
-    let mut stack = vec![name.to_string()];
-    while let Some(current) = stack.pop() {
-        if let Some(repo) = manifest.find_repo(&current) {
-            for dep in &repo.depends_on {
-                if !deps.contains(dep) {
-                    deps.push(dep.clone());
-                    stack.push(dep.clone());
-                }
-            }
-        }
+    #[test]
+    fn test_with_dependents_and_repo_for_path() {
+        let manifest = make_manifest();
+        let mut names = with_dependents(&manifest, &["B".to_string()]);
+        names.sort();
+        assert_eq!(names, ["B", "C"]);
+        assert_eq!(with_dependents(&manifest, &["A".to_string()]).len(), 3);
+
+        let root = Path::new("/ws");
+        let repo = repo_for_path(root, &manifest, Path::new("/ws/B/src/lib.rs")).unwrap();
+        assert_eq!(repo.name, "B");
+        assert!(repo_for_path(root, &manifest, Path::new("/ws/README.md")).is_none());
+        assert!(is_ignored_change(
+            Path::new("/ws/B"),
+            Path::new("/ws/B/target/debug/b")
+        ));
+        assert!(!is_ignored_change(
+            Path::new("/ws/B"),
+            Path::new("/ws/B/src/lib.rs")
+        ));
     }
-    deps
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_build_only_skips_dependencies() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["A", "B", "C"] {
+            std::fs::create_dir_all(dir.path().join(name)).unwrap();
+        }
+        let mut manifest = make_manifest();
+        for repo in &mut manifest.repos {
+            repo.build_cmd = Some("true".into());
+        }
+        let only = with_dependents(&manifest, &["B".to_string()]);
+        for parallel in [false, true] {
+            let opts = BuildOptions {
+                only: Some(&only),
+                parallel,
+                ..Default::default()
+            };
+            let report = build_with_options(dir.path(), &manifest, &opts).unwrap();
+            let names: Vec<_> = report
+                .results
+                .iter()
+                .map(|r| r.repo_name.as_str())
+                .collect();
+            assert_eq!(names, ["B", "C"]);
+        }
+    }
 
-    fn make_manifest() -> WorkspaceManifest {
-        WorkspaceManifest::parse(
+    #[test]
+    fn test_keep_going_skips_dependents() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["A", "B", "C", "D"] {
+            std::fs::create_dir_all(dir.path().join(name)).unwrap();
+        }
+        // A fails; B depends on A, C on B; D is independent.
+        let manifest = WorkspaceManifest::parse(
             r#"
             [workspace]
             name = "test"
@@ -362,37 +3175,95 @@ mod tests {
             [[repos]]
             name = "A"
             url = "https://example.com/a"
-            depends_on = []
+            build_cmd = "false"
 
             [[repos]]
             name = "B"
             url = "https://example.com/b"
+            build_cmd = "true"
             depends_on = ["A"]
 
             [[repos]]
             name = "C"
             url = "https://example.com/c"
-            depends_on = ["A", "B"]
+            build_cmd = "true"
+            depends_on = ["B"]
+
+            [[repos]]
+            name = "D"
+            url = "https://example.com/d"
+            build_cmd = "true"
             "#,
         )
-        .unwrap()
+        .unwrap();
+
+        for parallel in [false, true] {
+            let opts = BuildOptions {
+                parallel,
+                keep_going: true,
+                ..Default::default()
+            };
+            let report = build_with_options(dir.path(), &manifest, &opts).unwrap();
+            assert!(!report.all_passed);
+            let get = |name: &str| report.results.iter().find(|r| r.repo_name == name).unwrap();
+            assert!(!get("A").success && !get("A").skipped);
+            assert!(get("B").skipped);
+            assert!(get("C").skipped);
+            assert!(get("D").success);
+        }
+
+        // Fail-fast (sequential) stops at the first failure.
+        let report = build(dir.path(), &manifest, None, false, false).unwrap();
+        assert_eq!(report.results.len(), 1);
     }
 
     #[test]
-    fn test_resolve_build_order() {
-        let manifest = make_manifest();
-        let order = resolve_build_order(&manifest).unwrap();
-        let names: Vec<_> = order.iter().map(|r| r.name.as_str()).collect();
-        // A must come before B and C; B must come before C
-        let a_pos = names.iter().position(|n| *n == "A").unwrap();
-        let b_pos = names.iter().position(|n| *n == "B").unwrap();
-        let c_pos = names.iter().position(|n| *n == "C").unwrap();
-        assert!(a_pos < b_pos);
-        assert!(b_pos < c_pos);
+    fn test_artifacts_collected_into_build_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("A/dist")).unwrap();
+        let manifest = WorkspaceManifest::parse(
+            r#"
+            [workspace]
+            name = "test"
+
+            [[repos]]
+            name = "A"
+            url = "https://example.com/a"
+            build_cmd = "touch dist/app.wasm dist/lib.wasm dist/notes.txt"
+            artifacts = ["dist/*.wasm"]
+            "#,
+        )
+        .unwrap();
+
+        let report = build(dir.path(), &manifest, None, false, false).unwrap();
+        assert!(report.all_passed);
+        assert!(!report.build_id.is_empty());
+        let mut artifacts = report.results[0].artifacts.clone();
+        artifacts.sort();
+        let base = PathBuf::from(".smctl/artifacts")
+            .join(&report.build_id)
+            .join("A");
+        assert_eq!(artifacts, [base.join("app.wasm"), base.join("lib.wasm")]);
+        assert!(dir.path().join(base.join("app.wasm")).exists());
+        assert!(!dir.path().join(base.join("notes.txt")).exists());
+
+        // The recorded history entry uses the same id.
+        assert_eq!(record_build(dir.path(), &report).unwrap(), report.build_id);
     }
 
     #[test]
-    fn test_circular_dependency() {
+    fn test_coverage_merges_repo_summaries() {
+        let dir = tempfile::tempdir().unwrap();
+        let summary = |covered: u64| {
+            format!(
+                r#"{{"data":[{{"totals":{{"lines":{{"count":100,"covered":{covered}}},"functions":{{"count":10,"covered":5}},"regions":{{"count":0,"covered":0}}}}}}]}}"#
+            )
+        };
+        for (name, covered) in [("A", 80), ("B", 60)] {
+            std::fs::create_dir_all(dir.path().join(name)).unwrap();
+            std::fs::write(dir.path().join(name).join("cov.json"), summary(covered)).unwrap();
+        }
+        std::fs::create_dir_all(dir.path().join("C")).unwrap();
         let manifest = WorkspaceManifest::parse(
             r#"
             [workspace]
@@ -401,16 +3272,363 @@ mod tests {
             [[repos]]
             name = "A"
             url = "https://example.com/a"
-            depends_on = ["B"]
+            coverage_cmd = "cat cov.json"
 
             [[repos]]
             name = "B"
             url = "https://example.com/b"
-            depends_on = ["A"]
+            coverage_cmd = "cat cov.json"
+
+            [[repos]]
+            name = "C"
+            url = "https://example.com/c"
+            coverage_cmd = "echo not-json"
             "#,
         )
         .unwrap();
-        assert!(resolve_build_order(&manifest).is_err());
+
+        let report = coverage(dir.path(), &manifest, None).unwrap();
+        assert!(!report.all_passed);
+        assert_eq!(
+            report.lines,
+            CoverageCount {
+                count: 200,
+                covered: 140
+            }
+        );
+        assert_eq!(report.lines.percent(), 70.0);
+        assert_eq!(report.functions.covered, 10);
+        assert_eq!(report.regions.percent(), 100.0);
+        assert!(!report.repos[2].success);
+    }
+
+    #[test]
+    fn test_multi_step_build_reports_steps() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("A")).unwrap();
+        let manifest = WorkspaceManifest::parse(
+            r#"
+            [workspace]
+            name = "test"
+
+            [[repos]]
+            name = "A"
+            url = "https://example.com/a"
+            build_cmd = ["touch one", "false", "touch three"]
+            "#,
+        )
+        .unwrap();
+
+        let report = build(dir.path(), &manifest, None, false, false).unwrap();
+        assert!(!report.all_passed);
+        let steps = &report.results[0].steps;
+        assert_eq!(steps.len(), 2);
+        assert!(steps[0].success);
+        assert_eq!(steps[1].command, "false");
+        assert!(!steps[1].success);
+        assert!(dir.path().join("A/one").exists());
+        assert!(!dir.path().join("A/three").exists());
+    }
+
+    #[test]
+    fn test_build_logs_capture_both_streams() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("A")).unwrap();
+        let manifest = WorkspaceManifest::parse(
+            r#"
+            [workspace]
+            name = "test"
+
+            [[repos]]
+            name = "A"
+            url = "https://example.com/a"
+            shell = true
+            build_cmd = "echo out; echo err >&2; echo color=$CLICOLOR_FORCE"
+            test_cmd = "echo failing >&2; exit 1"
+            "#,
+        )
+        .unwrap();
+
+        let opts = BuildOptions {
+            run_tests: true,
+            color: true,
+            ..Default::default()
+        };
+        let report = build_with_options(dir.path(), &manifest, &opts).unwrap();
+        assert!(!report.all_passed);
+        let log = report.results[0].log.clone().unwrap();
+        assert_eq!(report.results[1].log.as_ref(), Some(&log));
+        assert!(log.starts_with(Path::new(".smctl/logs").join(&report.build_id)));
+
+        let content = std::fs::read_to_string(dir.path().join(&log)).unwrap();
+        assert!(content.contains("$ echo out;"));
+        assert!(content.contains("out\ncolor=1\nerr\n"));
+        assert!(content.contains("failing\n(command failed)"));
+    }
+
+    /// Create a git repo with one commit in each `<root>/<name>`.
+    fn init_git_repos(root: &Path, names: &[&str]) {
+        for name in names {
+            let path = root.join(name);
+            std::fs::create_dir_all(&path).unwrap();
+            std::fs::write(path.join("README.md"), name).unwrap();
+            let cmds: &[&[&str]] = &[
+                &["git", "init", "-q", "-b", "main"],
+                &["git", "add", "."],
+                &[
+                    "git",
+                    "-c",
+                    "user.name=Test",
+                    "-c",
+                    "user.email=test@test.com",
+                    "commit",
+                    "-qm",
+                    "init",
+                ],
+            ];
+            for cmd in cmds {
+                let status = Command::new(cmd[0])
+                    .args(&cmd[1..])
+                    .current_dir(&path)
+                    .status()
+                    .unwrap();
+                assert!(status.success(), "{} failed", cmd.join(" "));
+            }
+        }
+    }
+
+    #[test]
+    fn test_build_changed_since() {
+        let dir = tempfile::tempdir().unwrap();
+        init_git_repos(dir.path(), &["A", "B", "C"]);
+        std::fs::write(dir.path().join("A/new.rs"), "").unwrap();
+
+        // B depends on A; C is independent.
+        let mut manifest = make_manifest();
+        manifest.repos[2].depends_on.clear();
+        for repo in &mut manifest.repos {
+            repo.build_cmd = Some("true".into());
+        }
+
+        let opts = BuildOptions {
+            changed_since: Some("main"),
+            ..Default::default()
+        };
+        let report = build_with_options(dir.path(), &manifest, &opts).unwrap();
+        assert!(report.all_passed);
+        let get = |name: &str| report.results.iter().find(|r| r.repo_name == name).unwrap();
+        assert!(!get("A").skipped);
+        assert!(!get("B").skipped);
+        assert!(get("C").skipped);
+        assert_eq!(get("C").output, "unchanged (skipped)");
+    }
+
+    #[test]
+    fn test_explain_rebuild_reasons() {
+        let dir = tempfile::tempdir().unwrap();
+        init_git_repos(dir.path(), &["A", "B", "C"]);
+        // C depends on A and B.
+        let mut manifest = make_manifest();
+        for repo in &mut manifest.repos {
+            repo.build_cmd = Some("true".into());
+        }
+
+        // Plain builds: never built, then built with identical inputs.
+        let e = explain(dir.path(), &manifest, "C", None).unwrap();
+        let names: Vec<_> = e.chain.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, ["A", "B", "C"]);
+        assert_eq!(e.reason, RebuildReason::CacheMiss { previous: None });
+        let report = build(dir.path(), &manifest, None, false, false).unwrap();
+        record_build(dir.path(), &report).unwrap();
+        let e = explain(dir.path(), &manifest, "C", None).unwrap();
+        assert_eq!(e.reason, RebuildReason::Forced);
+        assert!(e.chain[2].input_hash.is_some());
+
+        std::fs::write(dir.path().join("A/lib.rs"), "fn main() {}").unwrap();
+        let e = explain(dir.path(), &manifest, "A", None).unwrap();
+        assert!(matches!(
+            e.reason,
+            RebuildReason::CacheMiss { previous: Some(_) }
+        ));
+
+        // --changed: A changed a file, C only through its dependency on A.
+        let e = explain(dir.path(), &manifest, "A", Some("main")).unwrap();
+        assert_eq!(
+            e.reason,
+            RebuildReason::ChangedFiles {
+                files: vec!["lib.rs".to_string()]
+            }
+        );
+        let e = explain(dir.path(), &manifest, "C", Some("main")).unwrap();
+        assert_eq!(
+            e.reason,
+            RebuildReason::ChangedDependency {
+                dependency: "A".to_string()
+            }
+        );
+        std::fs::remove_file(dir.path().join("A/lib.rs")).unwrap();
+        let e = explain(dir.path(), &manifest, "B", Some("main")).unwrap();
+        assert_eq!(e.reason, RebuildReason::Unchanged);
+        assert!(!e.rebuild);
+        let e = explain(dir.path(), &manifest, "B", Some("no-such-ref")).unwrap();
+        assert!(matches!(e.reason, RebuildReason::UnresolvedBase { .. }));
+    }
+
+    #[test]
+    fn test_command_lexing_and_shell_mode() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("A")).unwrap();
+        let manifest = WorkspaceManifest::parse(
+            r#"
+            [workspace]
+            name = "test"
+
+            [[repos]]
+            name = "A"
+            url = "https://example.com/a"
+            build_cmd = ["touch 'with space'", "sh -c \"touch one && touch two\""]
+
+            [[repos]]
+            name = "B"
+            url = "https://example.com/b"
+            path = "A"
+            shell = true
+            build_cmd = "touch three && touch four"
+            "#,
+        )
+        .unwrap();
+
+        let report = build(dir.path(), &manifest, None, false, false).unwrap();
+        assert!(report.all_passed, "{:?}", report.results);
+        for file in ["with space", "one", "two", "three", "four"] {
+            assert!(dir.path().join("A").join(file).exists(), "{file} missing");
+        }
+        assert!(command_for("echo 'unterminated", false).is_err());
+    }
+
+    #[test]
+    fn test_dependency_graph_formats() {
+        let manifest = make_manifest();
+        let graph = dependency_graph(&manifest, None).unwrap();
+        let level = |name: &str| graph.nodes.iter().find(|n| n.name == name).unwrap().level;
+        assert_eq!((level("A"), level("B"), level("C")), (0, 1, 2));
+
+        let dot = graph.to_dot();
+        assert!(dot.starts_with("digraph workspace {"));
+        assert!(dot.contains("\"A\" -> \"B\";"));
+        assert!(dot.contains("{ rank=same; \"C\" }"));
+
+        let mermaid = graph.to_mermaid();
+        assert!(mermaid.contains("A --> C"));
+        assert!(mermaid.contains("B[\"B<br/>level 1\"]"));
+
+        let last = BuildReport {
+            build_id: String::new(),
+            results: vec![BuildResult {
+                repo_name: "B".to_string(),
+                success: true,
+                output: String::new(),
+                duration_ms: 42,
+                ..Default::default()
+            }],
+            total_duration_ms: 42,
+            all_passed: true,
+        };
+        let graph = dependency_graph(&manifest, Some(&last)).unwrap();
+        let b = graph.nodes.iter().find(|n| n.name == "B").unwrap();
+        assert_eq!(b.last_duration_ms, Some(42));
+        assert!(graph.to_dot().contains("level 1 · 42ms"));
+        assert_eq!(
+            "mermaid".parse::<GraphFormat>().unwrap(),
+            GraphFormat::Mermaid
+        );
+    }
+
+    #[test]
+    fn test_ssh_executor_runs_remote_command() {
+        let dir = tempfile::tempdir().unwrap();
+        let remote = dir.path().join("remote checkout");
+        std::fs::create_dir_all(&remote).unwrap();
+        std::fs::create_dir_all(dir.path().join("gpu")).unwrap();
+        // Stand-in for ssh: drop the host and run the script locally.
+        let fake_ssh = dir.path().join("fake-ssh.sh");
+        std::fs::write(&fake_ssh, "shift\nexec sh -c \"$1\"\n").unwrap();
+
+        let manifest = WorkspaceManifest::parse(&format!(
+            r#"
+            [workspace]
+            name = "test"
+
+            [[repos]]
+            name = "gpu"
+            url = "https://example.com/gpu"
+            build_cmd = "touch 'built here'"
+
+            [repos.remote]
+            host = "gpu-box"
+            path = "{}"
+            ssh = "sh {}"
+            "#,
+            remote.display(),
+            fake_ssh.display()
+        ))
+        .unwrap();
+
+        let repo = manifest.find_repo("gpu").unwrap();
+        assert_eq!(executor_for(dir.path(), repo).name(), "ssh:gpu-box");
+        let report = build(dir.path(), &manifest, None, false, false).unwrap();
+        assert!(report.all_passed, "{:?}", report.results);
+        assert_eq!(report.results[0].executor.as_deref(), Some("ssh:gpu-box"));
+        assert!(remote.join("built here").exists());
+        assert!(!dir.path().join("gpu/built here").exists());
+    }
+
+    #[test]
+    fn test_bench_parse_record_and_compare() {
+        let output = "\
+fib 20                  time:   [26.029 us 26.251 us 26.505 us]
+                        change: [-1.2% +0.3% +1.8%] (p = 0.70 > 0.05)
+a_really_long_benchmark_name_that_wraps
+                        time:   [1.5000 ms 1.6000 ms 1.7000 ms]
+noise line
+";
+        let benches = bench::parse_criterion(output);
+        assert_eq!(benches.len(), 2);
+        assert_eq!(benches[0].name, "fib 20");
+        assert!((benches[0].nanos - 26_251.0).abs() < 1e-6);
+        assert_eq!(benches[1].name, "a_really_long_benchmark_name_that_wraps");
+        assert!((benches[1].nanos - 1_600_000.0).abs() < 1e-6);
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("A")).unwrap();
+        std::fs::write(dir.path().join("A/bench.txt"), output).unwrap();
+        let manifest = WorkspaceManifest::parse(
+            r#"
+            [workspace]
+            name = "test"
+
+            [[repos]]
+            name = "A"
+            url = "https://example.com/a"
+            bench_cmd = "cat bench.txt"
+            "#,
+        )
+        .unwrap();
+
+        let baseline = bench::run(dir.path(), &manifest, None).unwrap();
+        assert_eq!(baseline.repos[0].benchmarks.len(), 2);
+        bench::record(dir.path(), &baseline).unwrap();
+        assert_eq!(bench::load(dir.path(), "latest").unwrap().id, baseline.id);
+
+        let mut current = baseline.clone();
+        current.id = "next".to_string();
+        current.repos[0].benchmarks[0].nanos *= 1.10;
+        current.repos[0].benchmarks[1].nanos *= 1.02;
+        let cmp = bench::compare(&baseline, &current, 5.0);
+        assert_eq!(cmp.deltas.len(), 2);
+        assert_eq!(cmp.regressions, 1);
+        assert!(cmp.deltas[0].regression);
+        assert_eq!(bench::format_nanos(26_251.0), "26.25 \u{b5}s");
     }
 
     #[test]
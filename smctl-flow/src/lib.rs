@@ -1,8 +1,9 @@
-use std::path::Path;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
-use smctl_workspace::{FlowConfig, WorkspaceManifest};
+use smctl_workspace::{CommandSpec, FlowConfig, MergeStrategy, RepoConfig, WorkspaceManifest};
 
 /// Result of a flow operation across repos.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -10,6 +11,20 @@ pub struct FlowResult {
     pub operation: String,
     pub branch_name: String,
     pub repos: Vec<FlowRepoResult>,
+    /// Hook steps run around the operation (`[flow.hooks]`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hooks: Vec<HookResult>,
+}
+
+/// One step of a flow hook.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookResult {
+    /// `pre_start`, `pre_finish` or `post_finish`.
+    pub hook: String,
+    pub command: String,
+    pub success: bool,
+    /// Combined stdout and stderr.
+    pub output: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,6 +32,323 @@ pub struct FlowRepoResult {
     pub repo_name: String,
     pub success: bool,
     pub message: String,
+    /// Pull request opened for the repo, when finishing via pull requests.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pull_request: Option<String>,
+    /// Merge strategy used, for finish operations.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strategy: Option<MergeStrategy>,
+}
+
+/// Options for finishing feature, release and hotfix branches.
+#[derive(Debug, Clone, Default)]
+pub struct FinishOptions {
+    /// Merge strategy; `flow.merge_strategy` when unset.
+    pub strategy: Option<MergeStrategy>,
+    /// Sign the release tag (`release finish` only).
+    pub sign: bool,
+    /// Keep the repos already merged when a merge fails in another repo,
+    /// instead of rolling them back.
+    pub keep_partial: bool,
+    /// What to do with uncommitted changes.
+    pub dirty: DirtyTree,
+    /// Delete the branch on this remote once it merged in every repo.
+    pub delete_remote: Option<String>,
+}
+
+/// What to do with uncommitted changes in a repo a flow operation is about
+/// to check out branches in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DirtyTree {
+    /// Refuse to run, naming the dirty repos.
+    #[default]
+    Refuse,
+    /// Run anyway.
+    Force,
+    /// Stash the changes first and pop them afterwards.
+    Stash,
+}
+
+/// A finish operation stopped by a merge conflict, saved in
+/// `.smctl/state/flow.json` until `flow continue` or `flow abort`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlowState {
+    pub operation: String,
+    pub branch: String,
+    pub target: String,
+    pub strategy: MergeStrategy,
+    /// Repos merged before the conflict.
+    pub merged: Vec<MergedRepo>,
+    /// Repo whose merge waits for the conflicts to be resolved.
+    pub conflicted: MergedRepo,
+    pub conflicting_files: Vec<String>,
+    /// Repos not merged yet.
+    pub pending: Vec<String>,
+    /// Targets to merge into after this one, in order; the branch is kept
+    /// until the last (`release finish` and `hotfix finish` go on to
+    /// develop after main).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub then: Vec<String>,
+    /// Tag to create on `target` once it is merged.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<PendingTag>,
+    /// The finish's [`FinishOptions::keep_partial`].
+    #[serde(default)]
+    pub keep_partial: bool,
+    /// The finish's [`FinishOptions::delete_remote`], applied once
+    /// `flow continue` completes it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delete_remote: Option<String>,
+}
+
+/// The tag `release finish` creates on main once the release merged.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingTag {
+    pub name: String,
+    pub message: String,
+    pub sign: bool,
+}
+
+/// What `flow abort` needs to undo a merge in one repo.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergedRepo {
+    pub repo_name: String,
+    /// Target branch commit before the merge.
+    pub target_before: String,
+    /// Tip of the merged branch.
+    pub branch_tip: String,
+}
+
+impl FlowState {
+    pub fn path(root: &Path) -> PathBuf {
+        root.join(".smctl").join("state").join("flow.json")
+    }
+
+    /// The operation in progress, if any.
+    pub fn load(root: &Path) -> Result<Option<Self>> {
+        let path = Self::path(root);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(&path).context("failed to read flow state")?;
+        serde_json::from_str(&content)
+            .map(Some)
+            .context("failed to parse flow state")
+    }
+
+    fn save(&self, root: &Path) -> Result<()> {
+        let path = Self::path(root);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)
+            .context("failed to write flow state")
+    }
+
+    fn clear(root: &Path) -> Result<()> {
+        let path = Self::path(root);
+        if path.exists() {
+            std::fs::remove_file(&path).context("failed to remove flow state")?;
+        }
+        Ok(())
+    }
+}
+
+/// Journal of one `release finish`, saved in `.smctl/ops/<id>.json` as it
+/// runs: every repo's refs before it started, then each step it took.
+/// `flow undo` restores the refs, whether the operation completed or not.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpJournal {
+    pub id: String,
+    pub operation: String,
+    pub branch: String,
+    /// Start time, in seconds since the Unix epoch.
+    pub started: i64,
+    pub status: OpStatus,
+    pub repos: Vec<RepoSnapshot>,
+    pub steps: Vec<OpStep>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum OpStatus {
+    /// Still running, or interrupted by an error.
+    Running,
+    Completed,
+    /// Waiting on a merge conflict (`flow continue` / `flow abort`).
+    Stopped,
+    /// A merge failed; see the steps.
+    Failed,
+    Undone,
+}
+
+impl std::fmt::Display for OpStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Running => "running",
+            Self::Completed => "completed",
+            Self::Stopped => "stopped",
+            Self::Failed => "failed",
+            Self::Undone => "undone",
+        })
+    }
+}
+
+/// A repo's state before an operation touched it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoSnapshot {
+    pub repo_name: String,
+    /// Checked-out ref (`refs/heads/...`), or the commit if HEAD was detached.
+    pub head: String,
+    /// Every ref the operation may change, with its target before, or
+    /// `None` if it did not exist.
+    pub refs: BTreeMap<String, Option<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpStep {
+    pub repo_name: String,
+    pub step: String,
+    pub success: bool,
+    pub message: String,
+}
+
+impl OpJournal {
+    pub fn dir(root: &Path) -> PathBuf {
+        root.join(".smctl").join("ops")
+    }
+
+    /// Snapshot `refs` in `repos` and save a new running journal.
+    fn begin(
+        root: &Path,
+        repos: &[&RepoConfig],
+        operation: &str,
+        branch: &str,
+        refs: &[String],
+    ) -> Result<Self> {
+        let started = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or_default();
+        let mut snapshots = Vec::new();
+        for repo in repos {
+            let git_repo = git2::Repository::open(root.join(repo.local_path()))
+                .with_context(|| format!("failed to open repo {}", repo.name))?;
+            let head = git_repo.head()?;
+            let head = match head.name() {
+                Some(name) if head.is_branch() => name.to_string(),
+                _ => head.peel_to_commit()?.id().to_string(),
+            };
+            let refs = refs
+                .iter()
+                .map(|name| {
+                    let target = git_repo.refname_to_id(name).ok();
+                    (name.clone(), target.map(|oid| oid.to_string()))
+                })
+                .collect();
+            snapshots.push(RepoSnapshot {
+                repo_name: repo.name.clone(),
+                head,
+                refs,
+            });
+        }
+        let base = format!("{started}-{}", branch.replace('/', "-"));
+        let mut id = base.clone();
+        for n in 2.. {
+            if !Self::dir(root).join(format!("{id}.json")).exists() {
+                break;
+            }
+            id = format!("{base}-{n}");
+        }
+        let journal = Self {
+            id,
+            operation: operation.to_string(),
+            branch: branch.to_string(),
+            started,
+            status: OpStatus::Running,
+            repos: snapshots,
+            steps: Vec::new(),
+        };
+        journal.save(root)?;
+        Ok(journal)
+    }
+
+    pub fn load(root: &Path, id: &str) -> Result<Self> {
+        let path = Self::dir(root).join(format!("{id}.json"));
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("no recorded operation '{id}'"))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("failed to parse {}", path.display()))
+    }
+
+    /// Every recorded operation, latest first.
+    pub fn list(root: &Path) -> Result<Vec<Self>> {
+        let dir = Self::dir(root);
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut journals = Vec::new();
+        for entry in std::fs::read_dir(&dir).context("failed to read .smctl/ops")? {
+            let path = entry?.path();
+            let Some(id) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            if path.extension().is_some_and(|e| e == "json") {
+                journals.push(Self::load(root, id)?);
+            }
+        }
+        journals.sort_by(|a, b| b.started.cmp(&a.started).then(b.id.cmp(&a.id)));
+        Ok(journals)
+    }
+
+    fn save(&self, root: &Path) -> Result<()> {
+        let dir = Self::dir(root);
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(
+            dir.join(format!("{}.json", self.id)),
+            serde_json::to_string_pretty(self)?,
+        )
+        .context("failed to write operation journal")
+    }
+
+    /// Append the per-repo results of `step` and save.
+    fn record(&mut self, root: &Path, step: &str, results: &[FlowRepoResult]) -> Result<()> {
+        self.steps.extend(results.iter().map(|r| OpStep {
+            repo_name: r.repo_name.clone(),
+            step: step.to_string(),
+            success: r.success,
+            message: r.message.clone(),
+        }));
+        self.save(root)
+    }
+
+    /// Set the status from how the operation ended and save.
+    fn settle(&mut self, root: &Path, result: &Result<FlowResult>) -> Result<()> {
+        self.status = match result {
+            _ if FlowState::load(root)?.is_some() => OpStatus::Stopped,
+            Ok(r) if r.repos.iter().all(|repo| repo.success) => OpStatus::Completed,
+            _ => OpStatus::Failed,
+        };
+        self.save(root)
+    }
+
+    /// The latest journal of `branch` waiting on a merge conflict.
+    fn stopped(root: &Path, branch: &str) -> Result<Option<Self>> {
+        Ok(Self::list(root)?
+            .into_iter()
+            .find(|j| j.branch == branch && j.status == OpStatus::Stopped))
+    }
+}
+
+/// A feature or hotfix branch whose commits are all on a long-lived branch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StaleBranch {
+    pub repo_name: String,
+    pub branch: String,
+    /// Long-lived branch (`develop` or `main`) that contains it.
+    pub merged_into: String,
+    /// Commit time of the branch tip, in seconds since the Unix epoch.
+    pub last_commit: i64,
 }
 
 /// Active branch info for a repo.
@@ -34,6 +366,8 @@ pub enum BranchType {
     Feature,
     Release,
     Hotfix,
+    Bugfix,
+    Support,
     Other,
 }
 
@@ -49,28 +383,57 @@ pub fn classify_branch(name: &str, flow: &FlowConfig) -> BranchType {
         BranchType::Release
     } else if name.starts_with(&flow.hotfix_prefix) {
         BranchType::Hotfix
+    } else if name.starts_with(&flow.bugfix_prefix) {
+        BranchType::Bugfix
+    } else if name.starts_with(&flow.support_prefix) {
+        BranchType::Support
     } else {
         BranchType::Other
     }
 }
 
-/// Initialize git flow: ensure develop branch exists in all repos.
-pub fn init(root: &Path, manifest: &WorkspaceManifest) -> Result<FlowResult> {
-    let mut results = Vec::new();
+/// Initialize git flow: ensure develop branch exists in all repos. With
+/// `push`, develop is also pushed to that remote with upstream tracking;
+/// every repo must have the remote, or nothing is done.
+pub fn init(root: &Path, manifest: &WorkspaceManifest, push: Option<&str>) -> Result<FlowResult> {
+    let develop = &manifest.flow.develop_branch;
+    if let Some(remote) = push {
+        let mut missing = Vec::new();
+        for repo in &manifest.repos {
+            let git_repo = git2::Repository::open(root.join(repo.local_path()))
+                .with_context(|| format!("failed to open repo {}", repo.name))?;
+            if git_repo.find_remote(remote).is_err() {
+                missing.push(repo.name.as_str());
+            }
+        }
+        if !missing.is_empty() {
+            anyhow::bail!("remote '{remote}' not configured in {}", missing.join(", "));
+        }
+    }
 
+    let mut results = Vec::new();
     for repo in &manifest.repos {
         let repo_path = root.join(repo.local_path());
         let git_repo = git2::Repository::open(&repo_path)
             .with_context(|| format!("failed to open repo {}", repo.name))?;
 
-        let result = ensure_branch_exists(&git_repo, &manifest.flow.develop_branch);
+        let mut result = ensure_branch_exists(&git_repo, develop)
+            .map(|()| format!("'{develop}' branch ready"))
+            .map_err(|e| format!("{e}"));
+        if let (Ok(message), Some(remote)) = (&result, push) {
+            let output = git(&repo_path, &["push", "--quiet", "-u", remote, develop])?;
+            result = if output.status.success() {
+                Ok(format!("{message}, pushed to '{remote}'"))
+            } else {
+                Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+            };
+        }
         results.push(FlowRepoResult {
             repo_name: repo.name.clone(),
             success: result.is_ok(),
-            message: match &result {
-                Ok(_) => format!("'{}' branch ready", manifest.flow.develop_branch),
-                Err(e) => format!("{e}"),
-            },
+            message: result.unwrap_or_else(|e| e),
+            pull_request: None,
+            strategy: None,
         });
     }
 
@@ -78,6 +441,7 @@ pub fn init(root: &Path, manifest: &WorkspaceManifest) -> Result<FlowResult> {
         operation: "flow init".to_string(),
         branch_name: manifest.flow.develop_branch.clone(),
         repos: results,
+        hooks: Vec::new(),
     })
 }
 
@@ -87,17 +451,229 @@ pub fn feature_start(
     manifest: &WorkspaceManifest,
     name: &str,
     repos: Option<&[String]>,
+    dirty: DirtyTree,
 ) -> Result<FlowResult> {
     let branch = format!("{}{}", manifest.flow.feature_prefix, name);
     let base = &manifest.flow.develop_branch;
-    start_branch(root, manifest, &branch, base, repos, "feature start")
+    start_branch(root, manifest, &branch, base, repos, dirty, "feature start")
 }
 
-/// Finish a feature branch: merge into develop.
-pub fn feature_finish(root: &Path, manifest: &WorkspaceManifest, name: &str) -> Result<FlowResult> {
+/// Finish a feature branch: merge into develop, in the `repos` given or
+/// every repo with the branch.
+pub fn feature_finish(
+    root: &Path,
+    manifest: &WorkspaceManifest,
+    name: &str,
+    repos: Option<&[String]>,
+    options: &FinishOptions,
+) -> Result<FlowResult> {
+    let manifest = &manifest.scoped(repos);
     let branch = format!("{}{}", manifest.flow.feature_prefix, name);
     let target = &manifest.flow.develop_branch;
-    finish_branch(root, manifest, &branch, target, "feature finish")
+    with_finish_hooks(root, manifest, "feature finish", &branch, || {
+        let result = finish_branch(root, manifest, &branch, target, "feature finish", options)?;
+        delete_remote_after(root, manifest, options, result)
+    })
+}
+
+/// Push a feature branch to `remote` with upstream tracking in every repo
+/// that has it.
+pub fn feature_publish(
+    root: &Path,
+    manifest: &WorkspaceManifest,
+    name: &str,
+    remote: &str,
+) -> Result<FlowResult> {
+    let branch = format!("{}{}", manifest.flow.feature_prefix, name);
+    let mut results = Vec::new();
+    for repo in &manifest.repos {
+        let repo_path = root.join(repo.local_path());
+        let Ok(git_repo) = git2::Repository::open(&repo_path) else {
+            continue;
+        };
+        if git_repo
+            .find_branch(&branch, git2::BranchType::Local)
+            .is_err()
+        {
+            continue;
+        }
+        let push = std::process::Command::new("git")
+            .args(["push", "-u", remote, &branch])
+            .current_dir(&repo_path)
+            .output()
+            .context("failed to run git push")?;
+        results.push(FlowRepoResult {
+            repo_name: repo.name.clone(),
+            success: push.status.success(),
+            message: if push.status.success() {
+                format!("pushed '{branch}' to '{remote}'")
+            } else {
+                String::from_utf8_lossy(&push.stderr).trim().to_string()
+            },
+            pull_request: None,
+            strategy: None,
+        });
+    }
+    if results.is_empty() {
+        anyhow::bail!("feature '{name}' not found in any repo");
+    }
+
+    Ok(FlowResult {
+        operation: "feature publish".to_string(),
+        branch_name: branch,
+        repos: results,
+        hooks: Vec::new(),
+    })
+}
+
+/// Switch every repo that has feature `name` to it. The remote is fetched
+/// first, and a repo that only has `<remote>/<branch>` gets a local branch
+/// tracking it.
+pub fn feature_checkout(
+    root: &Path,
+    manifest: &WorkspaceManifest,
+    name: &str,
+    remote: &str,
+    dirty: DirtyTree,
+) -> Result<FlowResult> {
+    let branch = format!("{}{}", manifest.flow.feature_prefix, name);
+    let remote_ref = format!("refs/remotes/{remote}/{branch}");
+
+    // Phase 1: fetch and find the repos that have the feature
+    let mut found = Vec::new();
+    for repo in &manifest.repos {
+        let repo_path = root.join(repo.local_path());
+        let Ok(git_repo) = git2::Repository::open(&repo_path) else {
+            continue;
+        };
+        fetch_remote(&repo.name, &repo_path, &git_repo, remote)?;
+        let local = git_repo
+            .find_branch(&branch, git2::BranchType::Local)
+            .is_ok();
+        if local || git_repo.find_reference(&remote_ref).is_ok() {
+            found.push((repo, local));
+        }
+    }
+    if found.is_empty() {
+        anyhow::bail!("feature '{name}' not found locally or on '{remote}' in any repo");
+    }
+
+    let repos: Vec<&RepoConfig> = found.iter().map(|(repo, _)| *repo).collect();
+    let stashed = guard_dirty(root, &repos, dirty, "feature checkout")?;
+
+    // Phase 2: switch
+    let mut results = Vec::new();
+    for (repo, local) in found {
+        let git_repo = git2::Repository::open(root.join(repo.local_path()))
+            .with_context(|| format!("failed to open repo {}", repo.name))?;
+        let switched = if local {
+            checkout_branch(&git_repo, &branch).map(|()| format!("switched to '{branch}'"))
+        } else {
+            track_remote_branch(&git_repo, &branch, remote)
+                .and_then(|()| checkout_branch(&git_repo, &branch))
+                .map(|()| format!("created '{branch}' tracking '{remote}/{branch}'"))
+        };
+        results.push(FlowRepoResult {
+            repo_name: repo.name.clone(),
+            success: switched.is_ok(),
+            message: switched.unwrap_or_else(|e| e.message().to_string()),
+            pull_request: None,
+            strategy: None,
+        });
+    }
+    restore_stashed(root, &stashed, &mut results)?;
+
+    Ok(FlowResult {
+        operation: "feature checkout".to_string(),
+        branch_name: branch,
+        repos: results,
+        hooks: Vec::new(),
+    })
+}
+
+/// Create a local branch for feature `name` tracking `<remote>/<branch>` in
+/// every repo where the remote has it, without switching branches. Repos
+/// that already have the local branch are left alone.
+pub fn feature_track(
+    root: &Path,
+    manifest: &WorkspaceManifest,
+    name: &str,
+    remote: &str,
+) -> Result<FlowResult> {
+    let branch = format!("{}{}", manifest.flow.feature_prefix, name);
+    let remote_ref = format!("refs/remotes/{remote}/{branch}");
+    let mut results = Vec::new();
+    for repo in &manifest.repos {
+        let repo_path = root.join(repo.local_path());
+        let Ok(git_repo) = git2::Repository::open(&repo_path) else {
+            continue;
+        };
+        fetch_remote(&repo.name, &repo_path, &git_repo, remote)?;
+        if git_repo.find_reference(&remote_ref).is_err() {
+            continue;
+        }
+        let tracked = if git_repo
+            .find_branch(&branch, git2::BranchType::Local)
+            .is_ok()
+        {
+            Ok(format!("'{branch}' already exists"))
+        } else {
+            track_remote_branch(&git_repo, &branch, remote)
+                .map(|()| format!("created '{branch}' tracking '{remote}/{branch}'"))
+        };
+        results.push(FlowRepoResult {
+            repo_name: repo.name.clone(),
+            success: tracked.is_ok(),
+            message: tracked.unwrap_or_else(|e| e.message().to_string()),
+            pull_request: None,
+            strategy: None,
+        });
+    }
+    if results.is_empty() {
+        anyhow::bail!("feature '{name}' not found on '{remote}' in any repo");
+    }
+
+    Ok(FlowResult {
+        operation: "feature track".to_string(),
+        branch_name: branch,
+        repos: results,
+        hooks: Vec::new(),
+    })
+}
+
+/// Fetch `remote` if the repo has it; a failed fetch only warns, so work
+/// continues with the remote-tracking refs already there.
+fn fetch_remote(
+    repo_name: &str,
+    repo_path: &Path,
+    git_repo: &git2::Repository,
+    remote: &str,
+) -> Result<()> {
+    if git_repo.find_remote(remote).is_err() {
+        return Ok(());
+    }
+    let fetch = git(repo_path, &["fetch", "--quiet", remote])?;
+    if !fetch.status.success() {
+        tracing::warn!(
+            "could not fetch '{remote}' in {repo_name}: {}",
+            String::from_utf8_lossy(&fetch.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// Create local `branch` at `<remote>/<branch>` with it as the upstream.
+fn track_remote_branch(
+    git_repo: &git2::Repository,
+    branch: &str,
+    remote: &str,
+) -> std::result::Result<(), git2::Error> {
+    let commit = git_repo
+        .find_reference(&format!("refs/remotes/{remote}/{branch}"))?
+        .peel_to_commit()?;
+    git_repo
+        .branch(branch, &commit, false)?
+        .set_upstream(Some(&format!("{remote}/{branch}")))
 }
 
 /// List active feature branches across repos.
@@ -111,39 +687,305 @@ pub fn release_start(
     manifest: &WorkspaceManifest,
     version: &str,
     repos: Option<&[String]>,
+    dirty: DirtyTree,
 ) -> Result<FlowResult> {
     let branch = format!("{}{}", manifest.flow.release_prefix, version);
     let base = &manifest.flow.develop_branch;
-    start_branch(root, manifest, &branch, base, repos, "release start")
+    let mut result = start_branch(root, manifest, &branch, base, repos, dirty, "release start")?;
+    for repo_result in result.repos.iter_mut().filter(|r| r.success) {
+        let Some(repo) = manifest.find_repo(&repo_result.repo_name) else {
+            continue;
+        };
+        if repo.version_files.is_empty() {
+            continue;
+        }
+        let repo_path = root.join(repo.local_path());
+        match bump_version(&repo_path, &repo.version_files, version) {
+            Ok(()) => repo_result.message.push_str(&format!(
+                "; bumped version in {}",
+                repo.version_files.join(", ")
+            )),
+            Err(e) => {
+                repo_result.success = false;
+                repo_result
+                    .message
+                    .push_str(&format!("; version bump failed: {e:#}"));
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Set the version in each of `files` (relative to `repo_path`) and commit
+/// the bump. A leading `v` is dropped from `version`. `Cargo.toml` gets its
+/// `[workspace.package]` or `[package]` version, `package.json` its
+/// top-level `"version"`, and any other file is replaced by the version.
+pub fn bump_version(repo_path: &Path, files: &[String], version: &str) -> Result<()> {
+    let version = version.strip_prefix('v').unwrap_or(version);
+    for file in files {
+        let path = repo_path.join(file);
+        let content =
+            std::fs::read_to_string(&path).with_context(|| format!("failed to read {file}"))?;
+        let bumped = match Path::new(file).file_name().and_then(|n| n.to_str()) {
+            Some("Cargo.toml") => bump_cargo_version(&content, version),
+            Some("package.json") => bump_package_json_version(&content, version),
+            _ => Ok(format!("{version}\n")),
+        }
+        .with_context(|| format!("failed to bump {file}"))?;
+        std::fs::write(&path, bumped).with_context(|| format!("failed to write {file}"))?;
+    }
+
+    let mut add = vec!["add", "--"];
+    add.extend(files.iter().map(String::as_str));
+    let message = format!("Bump version to {version}");
+    for args in [add, vec!["commit", "-m", &message]] {
+        let output = git(repo_path, &args)?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "git {} failed: {}",
+                args[0],
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+    }
+    Ok(())
+}
+
+fn bump_cargo_version(content: &str, version: &str) -> Result<String> {
+    let mut doc: toml_edit::DocumentMut = content.parse().context("invalid TOML")?;
+    let has_version = |table: Option<&dyn toml_edit::TableLike>| {
+        table.is_some_and(|t| t.get("version").is_some_and(|v| v.is_str()))
+    };
+    let section = if has_version(
+        doc.get("workspace")
+            .and_then(|w| w.get("package"))
+            .and_then(|p| p.as_table_like()),
+    ) {
+        doc["workspace"]["package"].as_table_like_mut()
+    } else if has_version(doc.get("package").and_then(|p| p.as_table_like())) {
+        doc["package"].as_table_like_mut()
+    } else {
+        None
+    };
+    let value = section
+        .and_then(|t| t.get_mut("version"))
+        .and_then(|v| v.as_value_mut())
+        .context("no [workspace.package] or [package] version")?;
+    // Keep the surrounding whitespace and comments
+    let decor = value.decor().clone();
+    *value = version.into();
+    *value.decor_mut() = decor;
+    Ok(doc.to_string())
+}
+
+/// Replace the top-level `"version"` value in place, keeping the file's
+/// formatting.
+fn bump_package_json_version(content: &str, version: &str) -> Result<String> {
+    let json: serde_json::Value = serde_json::from_str(content).context("invalid JSON")?;
+    let old = json
+        .get("version")
+        .and_then(|v| v.as_str())
+        .context("no top-level \"version\"")?;
+    let old = format!("\"{old}\"");
+    for (idx, _) in content.match_indices("\"version\"") {
+        let rest = &content[idx + "\"version\"".len()..];
+        let Some(value) = rest.trim_start().strip_prefix(':') else {
+            continue;
+        };
+        let value = value.trim_start();
+        if value.starts_with(&old) {
+            let start = content.len() - value.len();
+            return Ok(format!(
+                "{}\"{version}\"{}",
+                &content[..start],
+                &content[start + old.len()..]
+            ));
+        }
+    }
+    anyhow::bail!("no top-level \"version\"")
 }
 
-/// Finish a release: merge to main + develop, tag.
+/// Finish a release: merge to main, tag main (annotated, per
+/// `flow.tag_format`; signed with `options.sign`), then merge to develop.
 pub fn release_finish(
     root: &Path,
     manifest: &WorkspaceManifest,
     version: &str,
+    options: &FinishOptions,
+) -> Result<FlowResult> {
+    let branch = format!("{}{}", manifest.flow.release_prefix, version);
+    with_finish_hooks(root, manifest, "release finish", &branch, || {
+        release_merges(root, manifest, version, options)
+    })
+}
+
+fn release_merges(
+    root: &Path,
+    manifest: &WorkspaceManifest,
+    version: &str,
+    options: &FinishOptions,
 ) -> Result<FlowResult> {
     let branch = format!("{}{}", manifest.flow.release_prefix, version);
     let main = &manifest.flow.main_branch;
-    // Phase 1: merge to main
-    let main_result = finish_branch(root, manifest, &branch, main, "release finish → main")?;
-    // Phase 2: merge to develop
-    let dev_result = finish_branch(
+    ensure_idle(root)?;
+    let strategy = options.strategy.unwrap_or(manifest.flow.merge_strategy);
+    preflight(
         root,
         manifest,
         &branch,
-        &manifest.flow.develop_branch,
-        "release finish → develop",
+        &[main, &manifest.flow.develop_branch],
+        strategy,
+    )?;
+    let repos = repos_with_branch(root, manifest, &branch);
+    let stashed = guard_dirty(root, &repos, options.dirty, "release finish")?;
+    let options = &FinishOptions {
+        dirty: DirtyTree::Force,
+        ..options.clone()
+    };
+    let tag = manifest.flow.release_tag(version);
+    let mut journal = OpJournal::begin(
+        root,
+        &repos,
+        "release finish",
+        &branch,
+        &[
+            format!("refs/heads/{main}"),
+            format!("refs/heads/{}", manifest.flow.develop_branch),
+            format!("refs/heads/{branch}"),
+            format!("refs/tags/{tag}"),
+        ],
     )?;
 
-    // Combine results
-    let mut repos = main_result.repos;
-    repos.extend(dev_result.repos);
-    Ok(FlowResult {
-        operation: "release finish".to_string(),
-        branch_name: branch,
-        repos,
-    })
+    let targets = [main.clone(), manifest.flow.develop_branch.clone()];
+    let result = (|| {
+        let mut repos = Vec::new();
+        Phases {
+            root,
+            manifest,
+            branch: &branch,
+            operation: "release finish",
+            strategy,
+            rollback: !options.keep_partial,
+            delete_remote: options.delete_remote.as_deref(),
+        }
+        .run(
+            &targets,
+            Some(PendingTag {
+                name: tag.clone(),
+                message: format!("Release {version}"),
+                sign: options.sign,
+            }),
+            &mut repos,
+            Some(&mut journal),
+        )?;
+        restore_stashed(root, &stashed, &mut repos)?;
+        Ok(FlowResult {
+            operation: with_progress(root, "release finish", &targets)?,
+            branch_name: branch.clone(),
+            repos,
+            hooks: Vec::new(),
+        })
+    })();
+    journal.settle(root, &result)?;
+    result
+}
+
+/// Postconditions of a finished release in one repo.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseCheck {
+    pub repo_name: String,
+    /// Commit the release tag points to.
+    pub tagged_commit: Option<String>,
+    /// Failed checks; empty when the repo is fine.
+    pub problems: Vec<String>,
+}
+
+/// Check that release `version` landed in every repo: the release tag
+/// exists, points to a commit reachable from main, and develop has the
+/// release merged (with squash or rebase merges, develop merging the tag
+/// would change nothing).
+pub fn verify_release(
+    root: &Path,
+    manifest: &WorkspaceManifest,
+    version: &str,
+) -> Result<Vec<ReleaseCheck>> {
+    let flow = &manifest.flow;
+    let tag = flow.release_tag(version);
+    let mut checks = Vec::new();
+    for repo in &manifest.repos {
+        let git_repo = git2::Repository::open(root.join(repo.local_path()))
+            .with_context(|| format!("failed to open repo {}", repo.name))?;
+        let mut check = ReleaseCheck {
+            repo_name: repo.name.clone(),
+            tagged_commit: None,
+            problems: Vec::new(),
+        };
+        let Ok(tagged) = git_repo
+            .find_reference(&format!("refs/tags/{tag}"))
+            .and_then(|r| r.peel_to_commit())
+        else {
+            check.problems.push(format!("tag '{tag}' not found"));
+            checks.push(check);
+            continue;
+        };
+        check.tagged_commit = Some(tagged.id().to_string());
+
+        let contains = |branch: &str| -> Result<Option<bool>> {
+            let Ok(tip) = git_repo.revparse_single(&format!("refs/heads/{branch}")) else {
+                return Ok(None);
+            };
+            let tip = tip.id();
+            Ok(Some(
+                tip == tagged.id() || git_repo.graph_descendant_of(tip, tagged.id())?,
+            ))
+        };
+        match contains(&flow.main_branch)? {
+            None => check
+                .problems
+                .push(format!("branch '{}' does not exist", flow.main_branch)),
+            Some(false) => check.problems.push(format!(
+                "'{tag}' is not reachable from '{}'",
+                flow.main_branch
+            )),
+            Some(true) => {}
+        }
+        let in_develop = match contains(&flow.develop_branch)? {
+            None => {
+                check
+                    .problems
+                    .push(format!("branch '{}' does not exist", flow.develop_branch));
+                true
+            }
+            Some(true) => true,
+            // The tag is main's merge commit, so look for the release tip
+            // (its second parent) in develop; squash and rebase merges leave
+            // none, so settle for develop already having the changes
+            Some(false) => {
+                let develop = git_repo
+                    .revparse_single(&format!("refs/heads/{}", flow.develop_branch))?
+                    .peel_to_commit()?;
+                match tagged.parent_id(1) {
+                    Ok(release_tip) => {
+                        release_tip == develop.id()
+                            || git_repo.graph_descendant_of(develop.id(), release_tip)?
+                    }
+                    Err(_) => {
+                        let mut index = git_repo.merge_commits(&develop, &tagged, None)?;
+                        !index.has_conflicts()
+                            && index.write_tree_to(&git_repo)? == develop.tree_id()
+                    }
+                }
+            }
+        };
+        if !in_develop {
+            check.problems.push(format!(
+                "'{}' does not have the release merged",
+                flow.develop_branch
+            ));
+        }
+        checks.push(check);
+    }
+    Ok(checks)
 }
 
 /// Start a hotfix branch from main.
@@ -152,34 +994,123 @@ pub fn hotfix_start(
     manifest: &WorkspaceManifest,
     name: &str,
     repos: Option<&[String]>,
+    dirty: DirtyTree,
 ) -> Result<FlowResult> {
     let branch = format!("{}{}", manifest.flow.hotfix_prefix, name);
     let base = &manifest.flow.main_branch;
-    start_branch(root, manifest, &branch, base, repos, "hotfix start")
+    start_branch(root, manifest, &branch, base, repos, dirty, "hotfix start")
 }
 
 /// Finish a hotfix: merge to main + develop.
-pub fn hotfix_finish(root: &Path, manifest: &WorkspaceManifest, name: &str) -> Result<FlowResult> {
+pub fn hotfix_finish(
+    root: &Path,
+    manifest: &WorkspaceManifest,
+    name: &str,
+    options: &FinishOptions,
+) -> Result<FlowResult> {
+    let branch = format!("{}{}", manifest.flow.hotfix_prefix, name);
+    with_finish_hooks(root, manifest, "hotfix finish", &branch, || {
+        let result = hotfix_merges(root, manifest, name, options)?;
+        delete_remote_after(root, manifest, options, result)
+    })
+}
+
+fn hotfix_merges(
+    root: &Path,
+    manifest: &WorkspaceManifest,
+    name: &str,
+    options: &FinishOptions,
+) -> Result<FlowResult> {
     let branch = format!("{}{}", manifest.flow.hotfix_prefix, name);
     let main = &manifest.flow.main_branch;
-    let main_result = finish_branch(root, manifest, &branch, main, "hotfix finish → main")?;
-    let dev_result = finish_branch(
+    ensure_idle(root)?;
+    let strategy = options.strategy.unwrap_or(manifest.flow.merge_strategy);
+    preflight(
         root,
         manifest,
         &branch,
-        &manifest.flow.develop_branch,
-        "hotfix finish → develop",
+        &[main, &manifest.flow.develop_branch],
+        strategy,
     )?;
-
-    let mut repos = main_result.repos;
-    repos.extend(dev_result.repos);
+    let stashed = guard_dirty(
+        root,
+        &repos_with_branch(root, manifest, &branch),
+        options.dirty,
+        "hotfix finish",
+    )?;
+    let targets = [main.clone(), manifest.flow.develop_branch.clone()];
+    let mut repos = Vec::new();
+    Phases {
+        root,
+        manifest,
+        branch: &branch,
+        operation: "hotfix finish",
+        strategy,
+        rollback: !options.keep_partial,
+        delete_remote: options.delete_remote.as_deref(),
+    }
+    .run(&targets, None, &mut repos, None)?;
+    restore_stashed(root, &stashed, &mut repos)?;
     Ok(FlowResult {
-        operation: "hotfix finish".to_string(),
+        operation: with_progress(root, "hotfix finish", &targets)?,
         branch_name: branch,
         repos,
+        hooks: Vec::new(),
+    })
+}
+
+/// Start a bugfix branch from develop.
+pub fn bugfix_start(
+    root: &Path,
+    manifest: &WorkspaceManifest,
+    name: &str,
+    repos: Option<&[String]>,
+    dirty: DirtyTree,
+) -> Result<FlowResult> {
+    let branch = format!("{}{}", manifest.flow.bugfix_prefix, name);
+    let base = &manifest.flow.develop_branch;
+    start_branch(root, manifest, &branch, base, repos, dirty, "bugfix start")
+}
+
+/// Finish a bugfix branch: merge into develop.
+pub fn bugfix_finish(
+    root: &Path,
+    manifest: &WorkspaceManifest,
+    name: &str,
+    options: &FinishOptions,
+) -> Result<FlowResult> {
+    let branch = format!("{}{}", manifest.flow.bugfix_prefix, name);
+    let target = &manifest.flow.develop_branch;
+    with_finish_hooks(root, manifest, "bugfix finish", &branch, || {
+        let result = finish_branch(root, manifest, &branch, target, "bugfix finish", options)?;
+        delete_remote_after(root, manifest, options, result)
     })
 }
 
+/// List active bugfix branches.
+pub fn bugfix_list(root: &Path, manifest: &WorkspaceManifest) -> Result<Vec<BranchInfo>> {
+    list_branches_by_type(root, manifest, BranchType::Bugfix)
+}
+
+/// Start a support branch from main. Support branches maintain an older
+/// line of releases and are long-lived, so there is no `support_finish`.
+pub fn support_start(
+    root: &Path,
+    manifest: &WorkspaceManifest,
+    name: &str,
+    repos: Option<&[String]>,
+    dirty: DirtyTree,
+) -> Result<FlowResult> {
+    let branch = format!("{}{}", manifest.flow.support_prefix, name);
+    let base = &manifest.flow.main_branch;
+    start_branch(root, manifest, &branch, base, repos, dirty, "support start")
+}
+
+/// List support branches.
+pub fn support_list(root: &Path, manifest: &WorkspaceManifest) -> Result<Vec<BranchInfo>> {
+    list_branches_by_type(root, manifest, BranchType::Support)
+}
+
 /// List active hotfix branches.
 pub fn hotfix_list(root: &Path, manifest: &WorkspaceManifest) -> Result<Vec<BranchInfo>> {
     list_branches_by_type(root, manifest, BranchType::Hotfix)
@@ -223,55 +1154,23 @@ pub fn check_merge_conflicts(
             continue;
         }
 
-        // Save current HEAD
-        let head = git_repo.head().ok();
-        let original_branch = head.as_ref().and_then(|h| h.shorthand()).unwrap_or("HEAD");
-        let original_branch = original_branch.to_string();
-
-        // Checkout target
-        let checkout = std::process::Command::new("git")
-            .args(["checkout", target])
-            .current_dir(&repo_path)
-            .output()?;
-
-        if !checkout.status.success() {
+        // Merge in memory, leaving the working tree alone
+        let commit = |name: &str| {
+            git_repo
+                .revparse_single(&format!("refs/heads/{name}"))
+                .and_then(|o| o.peel_to_commit())
+        };
+        let (Ok(ours), Ok(theirs)) = (commit(target), commit(branch)) else {
             continue;
-        }
-
-        // Try merge --no-commit --no-ff
-        let merge = std::process::Command::new("git")
-            .args(["merge", "--no-commit", "--no-ff", branch])
-            .current_dir(&repo_path)
-            .output()?;
-
-        let has_conflicts = !merge.status.success();
-
-        // Collect conflicting files if any
+        };
+        let index = git_repo.merge_commits(&ours, &theirs, None)?;
+        let has_conflicts = index.has_conflicts();
         let conflicting_files = if has_conflicts {
-            let diff = std::process::Command::new("git")
-                .args(["diff", "--name-only", "--diff-filter=U"])
-                .current_dir(&repo_path)
-                .output()?;
-            String::from_utf8_lossy(&diff.stdout)
-                .lines()
-                .map(|l| l.to_string())
-                .collect()
+            index_conflicts(&index)?
         } else {
             Vec::new()
         };
 
-        // Abort the merge
-        let _ = std::process::Command::new("git")
-            .args(["merge", "--abort"])
-            .current_dir(&repo_path)
-            .output();
-
-        // Restore original branch
-        let _ = std::process::Command::new("git")
-            .args(["checkout", &original_branch])
-            .current_dir(&repo_path)
-            .output();
-
         results.push(MergeCheckResult {
             repo_name: repo.name.clone(),
             has_conflicts,
@@ -293,6 +1192,434 @@ pub fn feature_check_merge(
     check_merge_conflicts(root, manifest, &branch, target)
 }
 
+/// Branch consistency report across repos.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BranchConsistency {
+    /// True when every checked repo is on the same branch.
+    pub consistent: bool,
+    /// The branch most repos are on (first in manifest order on ties).
+    pub expected_branch: Option<String>,
+    pub repos: Vec<BranchInfo>,
+    /// Repos not on the expected branch.
+    pub divergent: Vec<String>,
+}
+
+/// Resume the finish operation stopped by a merge conflict: conclude the
+/// resolved merge, then merge the remaining repos (stopping again at the
+/// next conflict). The finish's `--no-rollback` and `--delete-remote` carry
+/// over, and `post_finish` runs once the branch merged everywhere.
+pub fn flow_continue(root: &Path, manifest: &WorkspaceManifest) -> Result<FlowResult> {
+    let state = FlowState::load(root)?.context("no flow operation in progress")?;
+    let name = &state.conflicted.repo_name;
+    let repo = manifest
+        .find_repo(name)
+        .with_context(|| format!("repo '{name}' is no longer in the workspace"))?;
+    let repo_path = root.join(repo.local_path());
+    let mut git_repo = git2::Repository::open(&repo_path)
+        .with_context(|| format!("failed to open repo {name}"))?;
+    if git_repo.index()?.has_conflicts() {
+        anyhow::bail!(
+            "'{name}' still has conflicts in {}; resolve them and `git add` the files",
+            conflicted_paths(&git_repo)?.join(", ")
+        );
+    }
+
+    // Conclude the merge, unless it was committed by hand
+    let mut merge_heads = Vec::new();
+    if git_repo.state() == git2::RepositoryState::Merge {
+        git_repo.mergehead_foreach(|oid| {
+            merge_heads.push(*oid);
+            true
+        })?;
+    }
+    let head = git_repo.head()?.peel_to_commit()?;
+    let tree = git_repo.find_tree(git_repo.index()?.write_tree()?)?;
+    let mut parents = vec![head.clone()];
+    let message = match state.strategy {
+        MergeStrategy::Squash => (tree.id() != head.tree_id())
+            .then(|| format!("Squash merge '{}' into '{}'", state.branch, state.target)),
+        _ if !merge_heads.is_empty() => {
+            for oid in merge_heads {
+                parents.push(git_repo.find_commit(oid)?);
+            }
+            Some(format!(
+                "Merge branch '{}' into {}",
+                state.branch, state.target
+            ))
+        }
+        _ => None,
+    };
+    if let Some(message) = message {
+        let signature = git_repo.signature()?;
+        let parents: Vec<&git2::Commit> = parents.iter().collect();
+        git_repo
+            .commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                &message,
+                &tree,
+                &parents,
+            )
+            .with_context(|| format!("failed to commit the merge in '{name}'"))?;
+        git_repo.cleanup_state()?;
+    }
+    if state.then.is_empty() {
+        delete_merged_branch(&repo_path, &state.branch);
+    }
+    FlowState::clear(root)?;
+
+    let mut results = vec![FlowRepoResult {
+        repo_name: name.clone(),
+        success: true,
+        message: format!(
+            "merged '{}' into '{}' ({}) after resolving conflicts",
+            state.branch, state.target, state.strategy
+        ),
+        pull_request: None,
+        strategy: Some(state.strategy),
+    }];
+    let mut merged = state.merged.clone();
+    merged.push(state.conflicted.clone());
+    let pending: Vec<&RepoConfig> = state
+        .pending
+        .iter()
+        .filter_map(|n| manifest.find_repo(n))
+        .collect();
+    let mut tag_repos: Vec<String> = merged.iter().map(|m| m.repo_name.clone()).collect();
+    Merge {
+        root,
+        manifest,
+        branch: &state.branch,
+        target: &state.target,
+        operation: &state.operation,
+        strategy: state.strategy,
+        rollback: !state.keep_partial,
+        delete_remote: state.delete_remote.as_deref(),
+        then: &state.then,
+        tag: state.tag.as_ref(),
+    }
+    .run(&pending, merged, &mut results)?;
+    let mut journal = OpJournal::stopped(root, &state.branch)?;
+    if let Some(journal) = journal.as_mut() {
+        journal.record(root, &format!("continue into {}", state.target), &results)?;
+    }
+
+    // Then the rest of the operation: the tag and the targets still to go
+    let operation = state
+        .operation
+        .split_once(" → ")
+        .map_or(state.operation.as_str(), |(operation, _)| operation);
+    if FlowState::load(root)?.is_none() && results.iter().all(|r| r.success) {
+        tag_repos.extend(results[1..].iter().map(|r| r.repo_name.clone()));
+        if let Some(tag) = &state.tag {
+            tag_merged(
+                root,
+                manifest,
+                &tag_repos,
+                &state.target,
+                tag,
+                &mut results,
+                journal.as_mut(),
+            )?;
+        }
+        Phases {
+            root,
+            manifest,
+            branch: &state.branch,
+            operation,
+            strategy: state.strategy,
+            rollback: !state.keep_partial,
+            delete_remote: state.delete_remote.as_deref(),
+        }
+        .run(&state.then, None, &mut results, journal.as_mut())?;
+    }
+    if let Some(journal) = journal.as_mut() {
+        journal.settle(
+            root,
+            &Ok(FlowResult {
+                operation: state.operation.clone(),
+                branch_name: state.branch.clone(),
+                repos: results.clone(),
+                hooks: Vec::new(),
+            }),
+        )?;
+    }
+
+    let mut hooks = Vec::new();
+    if FlowState::load(root)?.is_none() && results.iter().all(|r| r.success) {
+        if let Some(remote) = state.delete_remote.as_deref() {
+            let finished = state.merged.iter().map(|m| m.repo_name.clone());
+            let finished = finished.chain(results.iter().map(|r| r.repo_name.clone()));
+            delete_remote_in(
+                root,
+                manifest,
+                remote,
+                &state.branch,
+                finished.collect(),
+                &mut results,
+            )?;
+        }
+        hooks = run_hook(
+            root,
+            "post_finish",
+            manifest.flow.hooks.post_finish.as_ref(),
+            operation,
+            &state.branch,
+        )?;
+    }
+    Ok(FlowResult {
+        operation: format!("{} (continued)", state.operation),
+        branch_name: state.branch,
+        repos: results,
+        hooks,
+    })
+}
+
+/// Roll back the finish operation stopped by a merge conflict: abort the
+/// conflicted merge, reset the target branch of every repo merged before it
+/// and restore their deleted branches.
+pub fn flow_abort(root: &Path, manifest: &WorkspaceManifest) -> Result<FlowResult> {
+    let state = FlowState::load(root)?.context("no flow operation in progress")?;
+    let mut results = Vec::new();
+
+    let conflicted = &state.conflicted.repo_name;
+    if let Some(repo) = manifest.find_repo(conflicted) {
+        let repo_path = root.join(repo.local_path());
+        // Throw away the half-done merge in the index and working tree
+        let abort = git2::Repository::open(&repo_path).and_then(|r| {
+            let head = r.head()?.peel_to_commit()?;
+            r.reset(head.as_object(), git2::ResetType::Hard, None)?;
+            r.cleanup_state()
+        });
+        results.push(FlowRepoResult {
+            repo_name: conflicted.clone(),
+            success: abort.is_ok(),
+            message: match abort {
+                Ok(()) => "aborted the conflicted merge".to_string(),
+                Err(e) => e.message().to_string(),
+            },
+            pull_request: None,
+            strategy: None,
+        });
+    }
+
+    results.extend(reset_merged(
+        root,
+        manifest,
+        &state.branch,
+        &state.target,
+        &state.merged,
+    )?);
+
+    FlowState::clear(root)?;
+    if let Some(mut journal) = OpJournal::stopped(root, &state.branch)? {
+        journal.record(root, "abort", &results)?;
+        journal.status = OpStatus::Failed;
+        journal.save(root)?;
+    }
+    Ok(FlowResult {
+        operation: format!("{} (aborted)", state.operation),
+        branch_name: state.branch,
+        repos: results,
+        hooks: Vec::new(),
+    })
+}
+
+/// Revert the operation recorded in journal `id`, completed or not: a merge
+/// conflict it stopped at is thrown away, every recorded ref goes back to
+/// its target before the operation (refs it created are deleted) and each
+/// repo is checked out where it was.
+pub fn flow_undo(root: &Path, manifest: &WorkspaceManifest, id: &str) -> Result<FlowResult> {
+    let mut journal = OpJournal::load(root, id)?;
+    if journal.status == OpStatus::Undone {
+        anyhow::bail!("operation '{id}' was already undone");
+    }
+    let conflicted = match FlowState::load(root)? {
+        Some(state) if state.branch == journal.branch => Some(state.conflicted.repo_name),
+        Some(_) => {
+            ensure_idle(root)?;
+            None
+        }
+        None => None,
+    };
+    let repos: Vec<&RepoConfig> = journal
+        .repos
+        .iter()
+        .filter(|s| Some(&s.repo_name) != conflicted.as_ref())
+        .filter_map(|s| manifest.find_repo(&s.repo_name))
+        .collect();
+    guard_dirty(root, &repos, DirtyTree::Refuse, "flow undo")?;
+
+    let mut results = Vec::new();
+    for snapshot in &journal.repos {
+        let Some(repo) = manifest.find_repo(&snapshot.repo_name) else {
+            continue;
+        };
+        let restored = git2::Repository::open(root.join(repo.local_path()))
+            .and_then(|r| restore_snapshot(&r, snapshot));
+        results.push(FlowRepoResult {
+            repo_name: snapshot.repo_name.clone(),
+            success: restored.is_ok(),
+            message: match restored {
+                Ok(0) => "nothing to undo".to_string(),
+                Ok(n) => format!(
+                    "restored {n} ref{}, back on '{}'",
+                    if n == 1 { "" } else { "s" },
+                    snapshot.head.trim_start_matches("refs/heads/")
+                ),
+                Err(e) => e.message().to_string(),
+            },
+            pull_request: None,
+            strategy: None,
+        });
+    }
+    if conflicted.is_some() {
+        FlowState::clear(root)?;
+    }
+    journal.record(root, "undo", &results)?;
+    if results.iter().all(|r| r.success) {
+        journal.status = OpStatus::Undone;
+        journal.save(root)?;
+    }
+
+    Ok(FlowResult {
+        operation: format!("{} (undone)", journal.operation),
+        branch_name: journal.branch,
+        repos: results,
+        hooks: Vec::new(),
+    })
+}
+
+/// Put a repo back to `snapshot`, returning how many refs changed.
+fn restore_snapshot(
+    repo: &git2::Repository,
+    snapshot: &RepoSnapshot,
+) -> std::result::Result<usize, git2::Error> {
+    let mut changed = 0;
+    // Move refs back first, so the old HEAD can be checked out...
+    for (name, before) in &snapshot.refs {
+        let Some(before) = before else { continue };
+        let before = git2::Oid::from_str(before)?;
+        if repo.refname_to_id(name).ok() != Some(before) {
+            repo.reference(name, before, true, "smctl flow undo")?;
+            changed += 1;
+        }
+    }
+    match git2::Oid::from_str(&snapshot.head) {
+        Ok(oid) if !snapshot.head.starts_with("refs/") => repo.set_head_detached(oid)?,
+        _ => repo.set_head(&snapshot.head)?,
+    }
+    let head = repo.head()?.peel_to_commit()?;
+    repo.reset(head.as_object(), git2::ResetType::Hard, None)?;
+    repo.cleanup_state()?;
+    // ...then delete the ones the operation created
+    for (name, before) in &snapshot.refs {
+        if before.is_none()
+            && let Ok(mut reference) = repo.find_reference(name)
+        {
+            reference.delete()?;
+            changed += 1;
+        }
+    }
+    Ok(changed)
+}
+
+/// Undo finished merges: reset `target` to its commit before the merge and
+/// recreate the deleted branch at its old tip, latest merge first.
+fn reset_merged(
+    root: &Path,
+    manifest: &WorkspaceManifest,
+    branch: &str,
+    target: &str,
+    merged: &[MergedRepo],
+) -> Result<Vec<FlowRepoResult>> {
+    let mut results = Vec::new();
+    for repo_merge in merged.iter().rev() {
+        let Some(repo) = manifest.find_repo(&repo_merge.repo_name) else {
+            continue;
+        };
+        let repo_path = root.join(repo.local_path());
+        let reset = git2::Repository::open(&repo_path).and_then(|r| {
+            checkout_branch(&r, target)?;
+            let before = git2::Oid::from_str(&repo_merge.target_before)?;
+            move_branch(
+                &r,
+                target,
+                before,
+                &format!("reset: undo merge of {branch}"),
+            )?;
+            if r.find_branch(branch, git2::BranchType::Local).is_err() {
+                let tip = r.find_commit(git2::Oid::from_str(&repo_merge.branch_tip)?)?;
+                r.branch(branch, &tip, false)?;
+            }
+            Ok(())
+        });
+        results.push(FlowRepoResult {
+            repo_name: repo_merge.repo_name.clone(),
+            success: reset.is_ok(),
+            message: match reset {
+                Ok(()) => format!(
+                    "reset '{}' to {} and restored '{}'",
+                    target,
+                    &repo_merge.target_before[..7.min(repo_merge.target_before.len())],
+                    branch
+                ),
+                Err(e) => e.message().to_string(),
+            },
+            pull_request: None,
+            strategy: None,
+        });
+    }
+    Ok(results)
+}
+
+/// Check that all repos (or the given subset) are on the same branch.
+pub fn check_branches(
+    root: &Path,
+    manifest: &WorkspaceManifest,
+    repos: Option<&[String]>,
+) -> Result<BranchConsistency> {
+    let mut infos = Vec::new();
+    for repo in manifest.select_repos(repos) {
+        let repo_path = root.join(repo.local_path());
+        let git_repo = git2::Repository::open(&repo_path)
+            .with_context(|| format!("failed to open repo {}", repo.name))?;
+        let head = git_repo
+            .head()
+            .with_context(|| format!("failed to get HEAD in {}", repo.name))?;
+        let branch = head.shorthand().unwrap_or("detached").to_string();
+        infos.push(BranchInfo {
+            repo_name: repo.name.clone(),
+            branch_type: classify_branch(&branch, &manifest.flow),
+            branch,
+        });
+    }
+
+    // Pick the most common branch; ties go to the earliest repo.
+    let mut expected: Option<(&str, usize)> = None;
+    for info in &infos {
+        let count = infos.iter().filter(|i| i.branch == info.branch).count();
+        if expected.is_none_or(|(_, best)| count > best) {
+            expected = Some((&info.branch, count));
+        }
+    }
+    let expected_branch = expected.map(|(b, _)| b.to_string());
+
+    let divergent: Vec<String> = infos
+        .iter()
+        .filter(|i| Some(&i.branch) != expected_branch.as_ref())
+        .map(|i| i.repo_name.clone())
+        .collect();
+
+    Ok(BranchConsistency {
+        consistent: divergent.is_empty(),
+        expected_branch,
+        repos: infos,
+        divergent,
+    })
+}
+
 // --- Internal helpers ---
 
 fn start_branch(
@@ -301,16 +1628,10 @@ fn start_branch(
     branch: &str,
     base: &str,
     repos: Option<&[String]>,
+    dirty: DirtyTree,
     operation: &str,
 ) -> Result<FlowResult> {
-    let target_repos: Vec<_> = match repos {
-        Some(names) => manifest
-            .repos
-            .iter()
-            .filter(|r| names.iter().any(|n| n == &r.name))
-            .collect(),
-        None => manifest.repos.iter().collect(),
-    };
+    let target_repos = manifest.select_repos(repos);
 
     // Phase 1: validate all repos
     for repo in &target_repos {
@@ -324,31 +1645,45 @@ fn start_branch(
             .with_context(|| format!("base branch '{base}' not found in {}", repo.name))?;
     }
 
+    let hooks = run_hook(
+        root,
+        "pre_start",
+        manifest.flow.hooks.pre_start.as_ref(),
+        operation,
+        branch,
+    )?;
+    let stashed = guard_dirty(root, &target_repos, dirty, operation)?;
+
     // Phase 2: execute
     let mut results = Vec::new();
     for repo in &target_repos {
         let repo_path = root.join(repo.local_path());
-        let result = std::process::Command::new("git")
-            .args(["checkout", "-b", branch, base])
-            .current_dir(&repo_path)
-            .output()
-            .context("failed to run git checkout")?;
+        let git_repo = git2::Repository::open(&repo_path)
+            .with_context(|| format!("failed to open repo {}", repo.name))?;
+        let result = git_repo
+            .revparse_single(&format!("refs/heads/{base}"))
+            .and_then(|o| o.peel_to_commit())
+            .and_then(|base_commit| git_repo.branch(branch, &base_commit, false))
+            .and_then(|_| checkout_branch(&git_repo, branch));
 
         results.push(FlowRepoResult {
             repo_name: repo.name.clone(),
-            success: result.status.success(),
-            message: if result.status.success() {
-                format!("created '{branch}' from '{base}'")
-            } else {
-                String::from_utf8_lossy(&result.stderr).trim().to_string()
+            success: result.is_ok(),
+            message: match result {
+                Ok(()) => format!("created '{branch}' from '{base}'"),
+                Err(e) => e.message().to_string(),
             },
+            pull_request: None,
+            strategy: None,
         });
     }
+    restore_stashed(root, &stashed, &mut results)?;
 
     Ok(FlowResult {
         operation: operation.to_string(),
         branch_name: branch.to_string(),
         repos: results,
+        hooks,
     })
 }
 
@@ -358,72 +1693,841 @@ fn finish_branch(
     branch: &str,
     target: &str,
     operation: &str,
+    options: &FinishOptions,
 ) -> Result<FlowResult> {
+    ensure_idle(root)?;
+    let strategy = options.strategy.unwrap_or(manifest.flow.merge_strategy);
+    preflight(root, manifest, branch, &[target], strategy)?;
+    let stashed = guard_dirty(
+        root,
+        &repos_with_branch(root, manifest, branch),
+        options.dirty,
+        operation,
+    )?;
+    let repos: Vec<&RepoConfig> = manifest.repos.iter().collect();
     let mut results = Vec::new();
+    Merge {
+        root,
+        manifest,
+        branch,
+        target,
+        operation,
+        strategy,
+        rollback: !options.keep_partial,
+        delete_remote: options.delete_remote.as_deref(),
+        then: &[],
+        tag: None,
+    }
+    .run(&repos, Vec::new(), &mut results)?;
+    restore_stashed(root, &stashed, &mut results)?;
+
+    Ok(FlowResult {
+        operation: operation.to_string(),
+        branch_name: branch.to_string(),
+        repos: results,
+        hooks: Vec::new(),
+    })
+}
 
+/// Check, before merging anything, that every repo with `branch` can
+/// finish into each of `targets`: an existing target branch, shared history
+/// and, for ff-only, a target the branch contains. Merge conflicts don't
+/// fail the check; `flow continue` handles those, and uncommitted changes
+/// are left to the [`DirtyTree`] policy.
+pub fn preflight(
+    root: &Path,
+    manifest: &WorkspaceManifest,
+    branch: &str,
+    targets: &[&str],
+    strategy: MergeStrategy,
+) -> Result<()> {
+    let mut problems = Vec::new();
     for repo in &manifest.repos {
         let repo_path = root.join(repo.local_path());
-        let git_repo = git2::Repository::open(&repo_path);
-        let git_repo = match git_repo {
-            Ok(r) => r,
-            Err(_) => continue,
+        let Ok(git_repo) = git2::Repository::open(&repo_path) else {
+            continue;
         };
-
-        // Check if branch exists in this repo
-        if git_repo
-            .find_branch(branch, git2::BranchType::Local)
-            .is_err()
-        {
+        let Ok(branch_ref) = git_repo.find_branch(branch, git2::BranchType::Local) else {
             continue;
+        };
+        let Some(branch_oid) = branch_ref.get().target() else {
+            continue;
+        };
+        for target in targets {
+            let Some(target_oid) = git_repo
+                .find_branch(target, git2::BranchType::Local)
+                .ok()
+                .and_then(|b| b.get().target())
+            else {
+                problems.push(format!("{}: branch '{target}' does not exist", repo.name));
+                continue;
+            };
+            match git_repo.merge_base(branch_oid, target_oid) {
+                Err(_) => problems.push(format!(
+                    "{}: '{branch}' shares no history with '{target}'",
+                    repo.name
+                )),
+                Ok(base) if strategy == MergeStrategy::FfOnly && base != target_oid => problems
+                    .push(format!(
+                        "{}: '{target}' has diverged from '{branch}'; can't fast-forward",
+                        repo.name
+                    )),
+                Ok(_) => {}
+            }
         }
+    }
+    if !problems.is_empty() {
+        anyhow::bail!(
+            "preflight failed, nothing was merged:\n  {}",
+            problems.join("\n  ")
+        );
+    }
+    Ok(())
+}
 
-        // Checkout target
-        let checkout = std::process::Command::new("git")
-            .args(["checkout", target])
-            .current_dir(&repo_path)
-            .output()?;
+/// One merge of `branch` into `target` across repos.
+struct Merge<'a> {
+    root: &'a Path,
+    manifest: &'a WorkspaceManifest,
+    branch: &'a str,
+    target: &'a str,
+    operation: &'a str,
+    strategy: MergeStrategy,
+    /// Reset the merged repos when a merge fails in another repo.
+    rollback: bool,
+    /// Remote to delete the branch on once finished, saved with a conflict.
+    delete_remote: Option<&'a str>,
+    /// Targets still to merge into after this one; the branch is only
+    /// deleted once there are none.
+    then: &'a [String],
+    /// Tag for `target`, saved with a conflict for `flow continue`.
+    tag: Option<&'a PendingTag>,
+}
+
+impl Merge<'_> {
+    /// Merge in each of `repos` that has the branch. A merge conflict stops
+    /// the loop: the conflicted merge is left for the user to resolve and the
+    /// operation is saved as a [`FlowState`], with `merged` plus the repos
+    /// merged here recorded for `flow abort`. Any other failure rolls the
+    /// merged repos back, unless `rollback` is off.
+    fn run(
+        &self,
+        repos: &[&RepoConfig],
+        mut merged: Vec<MergedRepo>,
+        results: &mut Vec<FlowRepoResult>,
+    ) -> Result<()> {
+        let Merge {
+            root,
+            branch,
+            target,
+            operation,
+            strategy,
+            ..
+        } = *self;
+        for (idx, repo) in repos.iter().enumerate() {
+            let repo_path = root.join(repo.local_path());
+            let git_repo = git2::Repository::open(&repo_path);
+            let git_repo = match git_repo {
+                Ok(r) => r,
+                Err(_) => continue,
+            };
+
+            // Check if branch exists in this repo
+            let Ok(branch_ref) = git_repo.find_branch(branch, git2::BranchType::Local) else {
+                continue;
+            };
+            let record = MergedRepo {
+                repo_name: repo.name.clone(),
+                target_before: git_repo
+                    .revparse_single(target)
+                    .map(|o| o.id().to_string())
+                    .unwrap_or_default(),
+                branch_tip: branch_ref
+                    .get()
+                    .target()
+                    .map(|o| o.to_string())
+                    .unwrap_or_default(),
+            };
+
+            // Nothing on the branch that the target lacks: skip the merge
+            // rather than record an empty merge commit
+            let no_changes = branch_ref.get().target().is_some_and(|tip| {
+                git_repo
+                    .revparse_single(target)
+                    .and_then(|t| git_repo.merge_base(tip, t.id()))
+                    .is_ok_and(|base| base == tip)
+            });
+            let outcome = if no_changes {
+                checkout_branch(&git_repo, target)
+                    .map_err(|e| format!("failed to check out '{target}': {}", e.message()))
+            } else {
+                merge_branch(&repo_path, branch, target, strategy)?
+            };
+            let (success, message) = match outcome {
+                Ok(()) => {
+                    if self.then.is_empty() {
+                        delete_merged_branch(&repo_path, branch);
+                    }
+                    merged.push(record);
+                    if no_changes {
+                        results.push(FlowRepoResult {
+                            repo_name: repo.name.clone(),
+                            success: true,
+                            message: format!(
+                                "no changes on '{branch}'; skipped merge into '{target}'"
+                            ),
+                            pull_request: None,
+                            strategy: None,
+                        });
+                        continue;
+                    }
+                    (
+                        true,
+                        format!("merged '{branch}' into '{target}' ({strategy})"),
+                    )
+                }
+                Err(detail) if !no_changes && git_repo.index()?.has_conflicts() => {
+                    tracing::debug!("merge of '{branch}' in {} conflicted: {detail}", repo.name);
+                    let conflicting_files = conflicted_paths(&git_repo)?;
+                    let message = format!(
+                        "merge conflict in {}; resolve it, then run `smctl flow continue` (or `smctl flow abort`)",
+                        conflicting_files.join(", ")
+                    );
+                    FlowState {
+                        operation: operation.to_string(),
+                        branch: branch.to_string(),
+                        target: target.to_string(),
+                        strategy,
+                        merged,
+                        conflicted: record,
+                        conflicting_files,
+                        pending: repos[idx + 1..].iter().map(|r| r.name.clone()).collect(),
+                        then: self.then.to_vec(),
+                        tag: self.tag.cloned(),
+                        keep_partial: !self.rollback,
+                        delete_remote: self.delete_remote.map(str::to_string),
+                    }
+                    .save(root)?;
+                    results.push(FlowRepoResult {
+                        repo_name: repo.name.clone(),
+                        success: false,
+                        message,
+                        pull_request: None,
+                        strategy: Some(strategy),
+                    });
+                    return Ok(());
+                }
+                Err(message) if self.rollback => {
+                    results.push(FlowRepoResult {
+                        repo_name: repo.name.clone(),
+                        success: false,
+                        message,
+                        pull_request: None,
+                        strategy: Some(strategy),
+                    });
+                    for mut undone in reset_merged(root, self.manifest, branch, target, &merged)? {
+                        undone.message = format!("rolled back: {}", undone.message);
+                        results.push(undone);
+                    }
+                    return Ok(());
+                }
+                Err(message) => (false, message),
+            };
 
-        if !checkout.status.success() {
             results.push(FlowRepoResult {
                 repo_name: repo.name.clone(),
-                success: false,
-                message: format!(
-                    "failed to checkout '{target}': {}",
-                    String::from_utf8_lossy(&checkout.stderr).trim()
-                ),
+                success,
+                message,
+                pull_request: None,
+                strategy: Some(strategy),
             });
-            continue;
         }
+        Ok(())
+    }
+}
 
-        // Merge
-        let merge = std::process::Command::new("git")
-            .args(["merge", "--no-ff", branch])
-            .current_dir(&repo_path)
-            .output()?;
-
-        let success = merge.status.success();
-        let message = if success {
-            // Delete the feature branch
-            let _ = std::process::Command::new("git")
-                .args(["branch", "-d", branch])
-                .current_dir(&repo_path)
-                .output();
-            format!("merged '{branch}' into '{target}'")
+/// The merges of a branch that finishes into several targets in turn:
+/// `release finish` and `hotfix finish` merge into main, then develop.
+struct Phases<'a> {
+    root: &'a Path,
+    manifest: &'a WorkspaceManifest,
+    branch: &'a str,
+    operation: &'a str,
+    strategy: MergeStrategy,
+    rollback: bool,
+    delete_remote: Option<&'a str>,
+}
+
+impl Phases<'_> {
+    /// Merge into each of `targets` in order, tagging the first with `tag`
+    /// in the repos merged into it. The branch is kept until the last
+    /// merge, so every phase sees it. A merge conflict saves the targets
+    /// still to go in the [`FlowState`] for `flow continue`; a phase that
+    /// was rolled back ends the run.
+    fn run(
+        &self,
+        targets: &[String],
+        mut tag: Option<PendingTag>,
+        results: &mut Vec<FlowRepoResult>,
+        mut journal: Option<&mut OpJournal>,
+    ) -> Result<()> {
+        let repos: Vec<&RepoConfig> = self.manifest.repos.iter().collect();
+        for (idx, target) in targets.iter().enumerate() {
+            let mut phase = Vec::new();
+            Merge {
+                root: self.root,
+                manifest: self.manifest,
+                branch: self.branch,
+                target,
+                operation: &format!("{} → {target}", self.operation),
+                strategy: self.strategy,
+                rollback: self.rollback,
+                delete_remote: self.delete_remote,
+                then: &targets[idx + 1..],
+                tag: tag.as_ref(),
+            }
+            .run(&repos, Vec::new(), &mut phase)?;
+            if let Some(journal) = journal.as_deref_mut() {
+                journal.record(self.root, &format!("merge into {target}"), &phase)?;
+            }
+            let halted = FlowState::load(self.root)?.is_some()
+                || (self.rollback && phase.iter().any(|r| !r.success));
+            let merged: Vec<String> = phase
+                .iter()
+                .filter(|r| r.success)
+                .map(|r| r.repo_name.clone())
+                .collect();
+            results.append(&mut phase);
+            if halted {
+                return Ok(());
+            }
+            if let Some(tag) = tag.take() {
+                tag_merged(
+                    self.root,
+                    self.manifest,
+                    &merged,
+                    target,
+                    &tag,
+                    results,
+                    journal.as_deref_mut(),
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Tag `target` in each of the `merged` repos.
+fn tag_merged(
+    root: &Path,
+    manifest: &WorkspaceManifest,
+    merged: &[String],
+    target: &str,
+    tag: &PendingTag,
+    results: &mut Vec<FlowRepoResult>,
+    mut journal: Option<&mut OpJournal>,
+) -> Result<()> {
+    for repo in manifest.repos.iter().filter(|r| merged.contains(&r.name)) {
+        let tagged = create_tag(
+            &repo.name,
+            &root.join(repo.local_path()),
+            &tag.name,
+            target,
+            &tag.message,
+            tag.sign,
+        )?;
+        if let Some(journal) = journal.as_deref_mut() {
+            journal.record(
+                root,
+                &format!("tag {}", tag.name),
+                std::slice::from_ref(&tagged),
+            )?;
+        }
+        results.push(tagged);
+    }
+    Ok(())
+}
+
+/// `operation`, noting how far through `targets` it got when a merge
+/// conflict stopped it, e.g. "release finish (main done, develop pending)".
+fn with_progress(root: &Path, operation: &str, targets: &[String]) -> Result<String> {
+    let Some(state) = FlowState::load(root)? else {
+        return Ok(operation.to_string());
+    };
+    let at = targets
+        .iter()
+        .position(|t| *t == state.target)
+        .unwrap_or_default();
+    let progress: Vec<String> = targets[..at]
+        .iter()
+        .map(|t| format!("{t} done"))
+        .chain(targets[at..].iter().map(|t| format!("{t} pending")))
+        .collect();
+    Ok(format!("{operation} ({})", progress.join(", ")))
+}
+
+/// Run a finish between the `pre_finish` and `post_finish` hooks. A failing
+/// `pre_finish` step aborts before anything merges; `post_finish` only runs
+/// once the branch merged in every repo.
+fn with_finish_hooks(
+    root: &Path,
+    manifest: &WorkspaceManifest,
+    operation: &str,
+    branch: &str,
+    finish: impl FnOnce() -> Result<FlowResult>,
+) -> Result<FlowResult> {
+    let hooks = &manifest.flow.hooks;
+    let mut ran = run_hook(
+        root,
+        "pre_finish",
+        hooks.pre_finish.as_ref(),
+        operation,
+        branch,
+    )?;
+    let mut result = finish()?;
+    if FlowState::load(root)?.is_none() && result.repos.iter().all(|r| r.success) {
+        ran.extend(run_hook(
+            root,
+            "post_finish",
+            hooks.post_finish.as_ref(),
+            operation,
+            branch,
+        )?);
+    }
+    ran.append(&mut result.hooks);
+    result.hooks = ran;
+    Ok(result)
+}
+
+/// Run the steps of `hook` in order, stopping at the first failure. A
+/// failing `pre_*` hook is an error; other hooks report failures in their
+/// results.
+fn run_hook(
+    root: &Path,
+    hook: &str,
+    spec: Option<&CommandSpec>,
+    operation: &str,
+    branch: &str,
+) -> Result<Vec<HookResult>> {
+    let mut results = Vec::new();
+    for command in spec.map(CommandSpec::steps).unwrap_or_default() {
+        let mut process = if cfg!(windows) {
+            let mut p = std::process::Command::new("cmd");
+            p.arg("/C");
+            p
         } else {
-            String::from_utf8_lossy(&merge.stderr).trim().to_string()
+            let mut p = std::process::Command::new("sh");
+            p.arg("-c");
+            p
+        };
+        let output = process
+            .arg(command)
+            .current_dir(root)
+            .env("SMCTL_FLOW_HOOK", hook)
+            .env("SMCTL_FLOW_OPERATION", operation)
+            .env("SMCTL_FLOW_BRANCH", branch)
+            .output()
+            .with_context(|| format!("failed to run {hook} hook `{command}`"))?;
+        let text = format!(
+            "{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .trim()
+        .to_string();
+        let success = output.status.success();
+        if !success && hook.starts_with("pre_") {
+            anyhow::bail!(
+                "{hook} hook `{command}` failed ({}), {operation} aborted{}",
+                output.status,
+                if text.is_empty() {
+                    String::new()
+                } else {
+                    format!(":\n{text}")
+                }
+            );
+        }
+        results.push(HookResult {
+            hook: hook.to_string(),
+            command: command.to_string(),
+            success,
+            output: text,
+        });
+        if !success {
+            break;
+        }
+    }
+    Ok(results)
+}
+
+/// Fail if a finish is waiting on `flow continue` or `flow abort`.
+fn ensure_idle(root: &Path) -> Result<()> {
+    if let Some(state) = FlowState::load(root)? {
+        anyhow::bail!(
+            "'{}' of '{}' is waiting on a merge conflict in {}; run `smctl flow continue` or `smctl flow abort` first",
+            state.operation,
+            state.branch,
+            state.conflicted.repo_name
+        );
+    }
+    Ok(())
+}
+
+/// The workspace repos that have a local `branch`.
+fn repos_with_branch<'a>(
+    root: &Path,
+    manifest: &'a WorkspaceManifest,
+    branch: &str,
+) -> Vec<&'a RepoConfig> {
+    manifest
+        .repos
+        .iter()
+        .filter(|repo| {
+            git2::Repository::open(root.join(repo.local_path()))
+                .map(|r| r.find_branch(branch, git2::BranchType::Local).is_ok())
+                .unwrap_or(false)
+        })
+        .collect()
+}
+
+/// Apply the `dirty` policy to `repos` before checking out branches in
+/// them. Returns the repos whose changes were stashed.
+fn guard_dirty<'a>(
+    root: &Path,
+    repos: &[&'a RepoConfig],
+    dirty: DirtyTree,
+    operation: &str,
+) -> Result<Vec<&'a RepoConfig>> {
+    if dirty == DirtyTree::Force {
+        return Ok(Vec::new());
+    }
+    let mut dirty_repos = Vec::new();
+    for repo in repos {
+        let Ok(git_repo) = git2::Repository::open(root.join(repo.local_path())) else {
+            continue;
         };
+        let mut status_opts = git2::StatusOptions::new();
+        status_opts.include_untracked(false);
+        if !git_repo.statuses(Some(&mut status_opts))?.is_empty() {
+            dirty_repos.push(*repo);
+        }
+    }
+    if dirty_repos.is_empty() {
+        return Ok(dirty_repos);
+    }
+    if dirty == DirtyTree::Refuse {
+        let names: Vec<&str> = dirty_repos.iter().map(|r| r.name.as_str()).collect();
+        anyhow::bail!(
+            "uncommitted changes in {}; commit them, or rerun with --stash or --force",
+            names.join(", ")
+        );
+    }
+    for repo in &dirty_repos {
+        let output = git(
+            &root.join(repo.local_path()),
+            &["stash", "push", "-m", &format!("smctl {operation}")],
+        )?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "failed to stash changes in {}: {}",
+                repo.name,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+    }
+    Ok(dirty_repos)
+}
 
+/// Pop the stashes made by [`guard_dirty`], one result per repo. While a
+/// merge conflict waits for `flow continue` they stay stashed.
+fn restore_stashed(
+    root: &Path,
+    stashed: &[&RepoConfig],
+    results: &mut Vec<FlowRepoResult>,
+) -> Result<()> {
+    let waiting = FlowState::load(root)?.is_some();
+    for repo in stashed {
+        let (success, message) = if waiting {
+            (
+                true,
+                "uncommitted changes stay stashed; `git stash pop` once the finish completes"
+                    .to_string(),
+            )
+        } else {
+            let output = git(&root.join(repo.local_path()), &["stash", "pop"])?;
+            if output.status.success() {
+                (true, "restored stashed changes".to_string())
+            } else {
+                (
+                    false,
+                    format!(
+                        "stashed changes could not be restored; they are still in `git stash list`: {}",
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    ),
+                )
+            }
+        };
         results.push(FlowRepoResult {
             repo_name: repo.name.clone(),
             success,
             message,
+            pull_request: None,
+            strategy: None,
         });
     }
+    Ok(())
+}
 
-    Ok(FlowResult {
-        operation: operation.to_string(),
-        branch_name: branch.to_string(),
-        repos: results,
+/// Delete a branch once it has been merged.
+fn delete_merged_branch(repo_path: &Path, branch: &str) {
+    let _ = git2::Repository::open(repo_path)
+        .and_then(|r| r.find_branch(branch, git2::BranchType::Local)?.delete());
+}
+
+/// Paths with unresolved conflicts in the index.
+fn conflicted_paths(repo: &git2::Repository) -> Result<Vec<String>> {
+    index_conflicts(&repo.index()?)
+}
+
+fn index_conflicts(index: &git2::Index) -> Result<Vec<String>> {
+    let mut paths = Vec::new();
+    for conflict in index.conflicts()? {
+        let conflict = conflict?;
+        if let Some(entry) = conflict.our.or(conflict.their).or(conflict.ancestor) {
+            paths.push(String::from_utf8_lossy(&entry.path).to_string());
+        }
+    }
+    Ok(paths)
+}
+
+/// Merge `branch` into `target` with `strategy`, leaving `target` checked
+/// out. The inner error explains why the merge failed; a conflicted merge
+/// is left in the index and working tree for `flow continue`.
+fn merge_branch(
+    repo_path: &Path,
+    branch: &str,
+    target: &str,
+    strategy: MergeStrategy,
+) -> Result<std::result::Result<(), String>> {
+    let repo = git2::Repository::open(repo_path).context("failed to open repo")?;
+    Ok(merge_into(&repo, branch, target, strategy))
+}
+
+fn merge_into(
+    repo: &git2::Repository,
+    branch: &str,
+    target: &str,
+    strategy: MergeStrategy,
+) -> std::result::Result<(), String> {
+    let fail = |e: git2::Error| e.message().to_string();
+    let commit = |name: &str| {
+        repo.revparse_single(&format!("refs/heads/{name}"))
+            .and_then(|o| o.peel_to_commit())
+            .map_err(fail)
+    };
+
+    checkout_branch(repo, target)
+        .map_err(|e| format!("failed to checkout '{target}': {}", e.message()))?;
+    let target_commit = commit(target)?;
+    let mut branch_commit = commit(branch)?;
+    let base = repo
+        .merge_base(branch_commit.id(), target_commit.id())
+        .map_err(fail)?;
+    if strategy == MergeStrategy::Rebase && base != target_commit.id() {
+        let tip = rebase_onto(repo, branch, target)?;
+        branch_commit = repo.find_commit(tip).map_err(fail)?;
+    }
+
+    let annotated = repo
+        .find_annotated_commit(branch_commit.id())
+        .map_err(fail)?;
+    let (analysis, _) = repo.merge_analysis(&[&annotated]).map_err(fail)?;
+    if analysis.is_up_to_date() {
+        return Ok(());
+    }
+    if matches!(strategy, MergeStrategy::Rebase | MergeStrategy::FfOnly) {
+        if !analysis.is_fast_forward() {
+            return Err(format!(
+                "'{target}' has diverged from '{branch}'; not possible to fast-forward"
+            ));
+        }
+        return move_branch(
+            repo,
+            target,
+            branch_commit.id(),
+            &format!("merge {branch}: Fast-forward"),
+        )
+        .map_err(fail);
+    }
+
+    // A true merge (or a squash, committed with a single parent)
+    repo.merge(&[&annotated], None, None).map_err(fail)?;
+    let mut index = repo.index().map_err(fail)?;
+    if index.has_conflicts() {
+        if strategy == MergeStrategy::Squash {
+            // Like `git merge --squash`: no MERGE_HEAD to turn it into a merge
+            repo.cleanup_state().map_err(fail)?;
+        }
+        return Err(format!("merging '{branch}' into '{target}' conflicted"));
+    }
+    let tree = repo
+        .find_tree(index.write_tree().map_err(fail)?)
+        .map_err(fail)?;
+    let signature = repo.signature().map_err(fail)?;
+    let (message, parents) = if strategy == MergeStrategy::Squash {
+        (
+            format!("Squash merge '{branch}' into '{target}'"),
+            vec![&target_commit],
+        )
+    } else {
+        (
+            format!("Merge branch '{branch}' into {target}"),
+            vec![&target_commit, &branch_commit],
+        )
+    };
+    repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        &message,
+        &tree,
+        &parents,
+    )
+    .map_err(fail)?;
+    repo.cleanup_state().map_err(fail)
+}
+
+/// Replay `branch`'s commits onto `target` in memory and point `branch` at
+/// the result, like `git rebase target branch`. A conflict aborts the
+/// rebase and leaves `branch` alone.
+fn rebase_onto(
+    repo: &git2::Repository,
+    branch: &str,
+    target: &str,
+) -> std::result::Result<git2::Oid, String> {
+    let fail = |e: git2::Error| {
+        format!(
+            "failed to rebase '{branch}' onto '{target}': {}",
+            e.message()
+        )
+    };
+    let annotated = |name: &str| {
+        repo.find_reference(&format!("refs/heads/{name}"))
+            .and_then(|r| repo.reference_to_annotated_commit(&r))
+            .map_err(fail)
+    };
+    let (branch_head, onto) = (annotated(branch)?, annotated(target)?);
+    let mut options = git2::RebaseOptions::new();
+    options.inmemory(true);
+    let mut rebase = repo
+        .rebase(Some(&branch_head), Some(&onto), None, Some(&mut options))
+        .map_err(fail)?;
+    let signature = repo.signature().map_err(fail)?;
+
+    let mut tip = onto.id();
+    while let Some(operation) = rebase.next() {
+        let id = operation.map_err(fail)?.id();
+        if rebase.inmemory_index().map_err(fail)?.has_conflicts() {
+            let _ = rebase.abort();
+            return Err(format!(
+                "failed to rebase '{branch}' onto '{target}': {} conflicts",
+                &id.to_string()[..7]
+            ));
+        }
+        let original = repo.find_commit(id).map_err(fail)?;
+        match rebase.commit(Some(&original.author()), &signature, None) {
+            Ok(id) => tip = id,
+            // Already upstream: nothing to replay
+            Err(e) if e.code() == git2::ErrorCode::Applied => {}
+            Err(e) => return Err(fail(e)),
+        }
+    }
+    rebase.finish(Some(&signature)).map_err(fail)?;
+    repo.reference(
+        &format!("refs/heads/{branch}"),
+        tip,
+        true,
+        &format!("rebase onto {target}"),
+    )
+    .map_err(fail)?;
+    Ok(tip)
+}
+
+/// Check out local `branch`, carrying over uncommitted changes that don't
+/// clash with it, like `git checkout`.
+fn checkout_branch(repo: &git2::Repository, branch: &str) -> std::result::Result<(), git2::Error> {
+    let refname = format!("refs/heads/{branch}");
+    let tree = repo.revparse_single(&refname)?.peel_to_tree()?;
+    repo.checkout_tree(
+        tree.as_object(),
+        Some(git2::build::CheckoutBuilder::new().safe()),
+    )?;
+    repo.set_head(&refname)
+}
+
+/// Point `branch` at `oid`, updating the working tree when it's checked out.
+fn move_branch(
+    repo: &git2::Repository,
+    branch: &str,
+    oid: git2::Oid,
+    log_message: &str,
+) -> std::result::Result<(), git2::Error> {
+    let refname = format!("refs/heads/{branch}");
+    let checked_out = repo
+        .head()
+        .is_ok_and(|head| head.name() == Some(refname.as_str()));
+    if checked_out {
+        let commit = repo.find_commit(oid)?;
+        repo.checkout_tree(
+            commit.as_object(),
+            Some(git2::build::CheckoutBuilder::new().safe()),
+        )?;
+    }
+    repo.reference(&refname, oid, true, log_message)?;
+    Ok(())
+}
+
+fn git(repo_path: &Path, args: &[&str]) -> Result<std::process::Output> {
+    std::process::Command::new("git")
+        .args(args)
+        .current_dir(repo_path)
+        .output()
+        .with_context(|| format!("failed to run git {}", args.first().unwrap_or(&"")))
+}
+
+/// Create annotated (or, with `sign`, signed) tag `tag` on `target`.
+fn create_tag(
+    repo_name: &str,
+    repo_path: &Path,
+    tag: &str,
+    target: &str,
+    message: &str,
+    sign: bool,
+) -> Result<FlowRepoResult> {
+    let output = std::process::Command::new("git")
+        .arg("tag")
+        .arg(if sign { "-s" } else { "-a" })
+        .args([tag, target, "-m", message])
+        .current_dir(repo_path)
+        .output()
+        .context("failed to run git tag")?;
+    let success = output.status.success();
+    Ok(FlowRepoResult {
+        repo_name: repo_name.to_string(),
+        success,
+        message: if success {
+            format!(
+                "tagged '{target}' as '{tag}'{}",
+                if sign { " (signed)" } else { "" }
+            )
+        } else {
+            format!(
+                "failed to tag '{tag}': {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )
+        },
+        pull_request: None,
+        strategy: None,
     })
 }
 
@@ -444,6 +2548,186 @@ fn ensure_branch_exists(repo: &git2::Repository, branch_name: &str) -> Result<()
     Ok(())
 }
 
+/// Feature, bugfix and hotfix branches, across repos, already merged into
+/// develop or main. The checked-out branch is never reported, nor is a
+/// branch still at develop's or main's tip.
+pub fn stale_branches(root: &Path, manifest: &WorkspaceManifest) -> Result<Vec<StaleBranch>> {
+    let flow = &manifest.flow;
+    let mut stale = Vec::new();
+    for repo in &manifest.repos {
+        let repo_path = root.join(repo.local_path());
+        let Ok(git_repo) = git2::Repository::open(&repo_path) else {
+            continue;
+        };
+        let head = git_repo
+            .head()
+            .ok()
+            .and_then(|h| h.shorthand().map(String::from));
+        let targets: Vec<(&str, git2::Oid)> = [&flow.develop_branch, &flow.main_branch]
+            .into_iter()
+            .filter_map(|name| {
+                let oid = git_repo
+                    .find_branch(name, git2::BranchType::Local)
+                    .ok()?
+                    .get()
+                    .target()?;
+                Some((name.as_str(), oid))
+            })
+            .collect();
+
+        for branch_result in git_repo.branches(Some(git2::BranchType::Local))? {
+            let (branch, _) = branch_result?;
+            let Some(name) = branch.name()? else {
+                continue;
+            };
+            if !matches!(
+                classify_branch(name, flow),
+                BranchType::Feature | BranchType::Hotfix | BranchType::Bugfix
+            ) || head.as_deref() == Some(name)
+            {
+                continue;
+            }
+            let Some(tip) = branch.get().target() else {
+                continue;
+            };
+            // A branch at a target's tip has no commits of its own yet, like
+            // one `feature start` just created, so it is not merged
+            if targets.iter().any(|(_, target)| *target == tip) {
+                continue;
+            }
+            let merged_into = targets
+                .iter()
+                .find(|(_, target)| git_repo.graph_descendant_of(*target, tip).unwrap_or(false));
+            if let Some((target, _)) = merged_into {
+                stale.push(StaleBranch {
+                    repo_name: repo.name.clone(),
+                    branch: name.to_string(),
+                    merged_into: target.to_string(),
+                    last_commit: git_repo.find_commit(tip)?.time().seconds(),
+                });
+            }
+        }
+    }
+    Ok(stale)
+}
+
+/// Delete `branches` locally and, with `remote`, on that remote too.
+pub fn delete_branches(
+    root: &Path,
+    manifest: &WorkspaceManifest,
+    branches: &[StaleBranch],
+    remote: Option<&str>,
+) -> Result<Vec<FlowRepoResult>> {
+    let mut results = Vec::new();
+    for stale in branches {
+        let Some(repo) = manifest.find_repo(&stale.repo_name) else {
+            continue;
+        };
+        let repo_path = root.join(repo.local_path());
+        let local = git2::Repository::open(&repo_path).and_then(|r| {
+            r.find_branch(&stale.branch, git2::BranchType::Local)?
+                .delete()
+        });
+        let (mut success, mut message) = match local {
+            Ok(()) => (true, format!("deleted '{}'", stale.branch)),
+            Err(e) => (
+                false,
+                format!("failed to delete '{}': {}", stale.branch, e.message()),
+            ),
+        };
+        if success && let Some(remote) = remote {
+            match delete_remote_branch(&repo_path, remote, &stale.branch)? {
+                Ok(()) => message.push_str(&format!(" and {remote}/{}", stale.branch)),
+                Err(e) => {
+                    success = false;
+                    message.push_str(&format!("; failed to delete it on {remote}: {e}"));
+                }
+            }
+        }
+        results.push(FlowRepoResult {
+            repo_name: stale.repo_name.clone(),
+            success,
+            message,
+            pull_request: None,
+            strategy: None,
+        });
+    }
+    Ok(results)
+}
+
+/// Delete `branch` on `remote`; the inner error is git's explanation.
+fn delete_remote_branch(
+    repo_path: &Path,
+    remote: &str,
+    branch: &str,
+) -> Result<std::result::Result<(), String>> {
+    let push = git(repo_path, &["push", remote, "--delete", branch])?;
+    Ok(if push.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&push.stderr).trim().to_string())
+    })
+}
+
+/// With `options.delete_remote`, delete the finished branch on that remote
+/// in every repo that tracks it, once the finish merged everywhere.
+fn delete_remote_after(
+    root: &Path,
+    manifest: &WorkspaceManifest,
+    options: &FinishOptions,
+    mut result: FlowResult,
+) -> Result<FlowResult> {
+    let Some(remote) = options.delete_remote.as_deref() else {
+        return Ok(result);
+    };
+    if FlowState::load(root)?.is_some() || result.repos.iter().any(|r| !r.success) {
+        return Ok(result);
+    }
+    let branch = result.branch_name.clone();
+    let finished = result.repos.iter().map(|r| r.repo_name.clone()).collect();
+    delete_remote_in(root, manifest, remote, &branch, finished, &mut result.repos)?;
+    Ok(result)
+}
+
+/// Delete `branch` on `remote` in each of the `finished` repos that tracks
+/// it, reporting each deletion in `results`.
+fn delete_remote_in(
+    root: &Path,
+    manifest: &WorkspaceManifest,
+    remote: &str,
+    branch: &str,
+    mut finished: Vec<String>,
+    results: &mut Vec<FlowRepoResult>,
+) -> Result<()> {
+    finished.sort();
+    finished.dedup();
+    for name in finished {
+        let Some(repo) = manifest.find_repo(&name) else {
+            continue;
+        };
+        let repo_path = root.join(repo.local_path());
+        let tracked = git2::Repository::open(&repo_path).is_ok_and(|r| {
+            r.find_reference(&format!("refs/remotes/{remote}/{branch}"))
+                .is_ok()
+        });
+        if !tracked {
+            continue;
+        }
+        let deleted = delete_remote_branch(&repo_path, remote, branch)?;
+        results.push(FlowRepoResult {
+            repo_name: name,
+            success: deleted.is_ok(),
+            message: match deleted {
+                Ok(()) => format!("deleted '{remote}/{branch}'"),
+                Err(e) => format!("failed to delete '{remote}/{branch}': {e}"),
+            },
+            pull_request: None,
+            strategy: None,
+        });
+    }
+    Ok(())
+}
+
 fn list_branches_by_type(
     root: &Path,
     manifest: &WorkspaceManifest,
@@ -489,6 +2773,30 @@ mod tests {
         assert_eq!(classify_branch("feature/foo", &flow), BranchType::Feature);
         assert_eq!(classify_branch("release/1.0", &flow), BranchType::Release);
         assert_eq!(classify_branch("hotfix/fix", &flow), BranchType::Hotfix);
+        assert_eq!(classify_branch("bugfix/fix", &flow), BranchType::Bugfix);
+        assert_eq!(classify_branch("support/1.x", &flow), BranchType::Support);
         assert_eq!(classify_branch("random", &flow), BranchType::Other);
     }
+
+    #[test]
+    fn test_bump_versions() {
+        let workspace = "[workspace.package]\nversion = \"0.1.0\" # lockstep\n\n[package]\nversion.workspace = true\n";
+        let bumped = bump_cargo_version(workspace, "1.2.0").unwrap();
+        assert!(bumped.contains("[workspace.package]\nversion = \"1.2.0\" # lockstep"));
+        assert!(bumped.contains("version.workspace = true"));
+
+        let package = "[package]\nname = \"x\"\nversion = \"0.1.0\"\n";
+        assert_eq!(
+            bump_cargo_version(package, "1.2.0").unwrap(),
+            "[package]\nname = \"x\"\nversion = \"1.2.0\"\n"
+        );
+        assert!(bump_cargo_version("[dependencies]\n", "1.2.0").is_err());
+
+        let json = "{\n  \"name\": \"x\",\n  \"version\" : \"0.1.0\",\n  \"deps\": {}\n}\n";
+        assert_eq!(
+            bump_package_json_version(json, "1.2.0").unwrap(),
+            "{\n  \"name\": \"x\",\n  \"version\" : \"1.2.0\",\n  \"deps\": {}\n}\n"
+        );
+        assert!(bump_package_json_version("{}", "1.2.0").is_err());
+    }
 }
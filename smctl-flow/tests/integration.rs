@@ -3,8 +3,11 @@
 use std::path::Path;
 
 use smctl_flow::{
-    BranchType, classify_branch, feature_check_merge, feature_finish, feature_list, feature_start,
-    init,
+    BranchType, DirtyTree, FinishOptions, FlowState, OpJournal, OpStatus, bugfix_finish,
+    bugfix_list, bugfix_start, check_branches, classify_branch, delete_branches,
+    feature_check_merge, feature_checkout, feature_finish, feature_list, feature_publish,
+    feature_start, feature_track, flow_abort, flow_continue, flow_undo, init, release_finish,
+    release_start, stale_branches, support_list, support_start, verify_release,
 };
 use smctl_workspace::WorkspaceManifest;
 
@@ -13,7 +16,12 @@ fn setup_workspace(root: &Path, repo_name: &str) -> WorkspaceManifest {
     let repo_path = root.join(repo_name);
     std::fs::create_dir_all(&repo_path).unwrap();
 
-    let cmds: &[&[&str]] = &[&["git", "init"], &["git", "checkout", "-b", "main"]];
+    let cmds: &[&[&str]] = &[
+        &["git", "init"],
+        &["git", "checkout", "-b", "main"],
+        &["git", "config", "user.name", "Test"],
+        &["git", "config", "user.email", "test@test.com"],
+    ];
     for cmd in cmds {
         let output = std::process::Command::new(cmd[0])
             .args(&cmd[1..])
@@ -78,7 +86,12 @@ fn setup_multi_repo_workspace(root: &Path) -> WorkspaceManifest {
         let repo_path = root.join(name);
         std::fs::create_dir_all(&repo_path).unwrap();
 
-        let cmds: &[&[&str]] = &[&["git", "init"], &["git", "checkout", "-b", "main"]];
+        let cmds: &[&[&str]] = &[
+            &["git", "init"],
+            &["git", "checkout", "-b", "main"],
+            &["git", "config", "user.name", "Test"],
+            &["git", "config", "user.email", "test@test.com"],
+        ];
         for cmd in cmds {
             std::process::Command::new(cmd[0])
                 .args(&cmd[1..])
@@ -134,7 +147,7 @@ fn test_flow_init_creates_develop() {
     let dir = tempfile::tempdir().unwrap();
     let manifest = setup_workspace(dir.path(), "repo1");
 
-    let result = init(dir.path(), &manifest).unwrap();
+    let result = init(dir.path(), &manifest, None).unwrap();
     assert_eq!(result.operation, "flow init");
     assert!(result.repos[0].success);
 
@@ -152,22 +165,71 @@ fn test_flow_init_idempotent() {
     let dir = tempfile::tempdir().unwrap();
     let manifest = setup_workspace(dir.path(), "repo1");
 
-    init(dir.path(), &manifest).unwrap();
+    init(dir.path(), &manifest, None).unwrap();
     // Second call should succeed (branch already exists)
-    let result = init(dir.path(), &manifest).unwrap();
+    let result = init(dir.path(), &manifest, None).unwrap();
     assert!(result.repos[0].success);
 }
 
+#[test]
+fn test_flow_init_push_publishes_develop() {
+    let dir = tempfile::tempdir().unwrap();
+    let manifest = setup_multi_repo_workspace(dir.path());
+    let remote = dir.path().join("alpha-remote.git");
+    git2::Repository::init_bare(&remote).unwrap();
+    git2::Repository::open(dir.path().join("alpha"))
+        .unwrap()
+        .remote("origin", remote.to_str().unwrap())
+        .unwrap();
+
+    // beta has no origin: refused before anything is created
+    let err = init(dir.path(), &manifest, Some("origin")).unwrap_err();
+    assert_eq!(err.to_string(), "remote 'origin' not configured in beta");
+    let beta = git2::Repository::open(dir.path().join("beta")).unwrap();
+    assert!(
+        beta.find_branch("develop", git2::BranchType::Local)
+            .is_err()
+    );
+
+    let beta_remote = dir.path().join("beta-remote.git");
+    git2::Repository::init_bare(&beta_remote).unwrap();
+    beta.remote("origin", beta_remote.to_str().unwrap())
+        .unwrap();
+    let result = init(dir.path(), &manifest, Some("origin")).unwrap();
+    assert!(result.repos.iter().all(|r| r.success), "{result:?}");
+    assert_eq!(
+        result.repos[0].message,
+        "'develop' branch ready, pushed to 'origin'"
+    );
+    let bare = git2::Repository::open_bare(&remote).unwrap();
+    assert!(bare.find_branch("develop", git2::BranchType::Local).is_ok());
+    let alpha = git2::Repository::open(dir.path().join("alpha")).unwrap();
+    let develop = alpha
+        .find_branch("develop", git2::BranchType::Local)
+        .unwrap();
+    assert_eq!(
+        develop.upstream().unwrap().name().unwrap(),
+        Some("origin/develop")
+    );
+}
+
 #[test]
 fn test_feature_start_finish_lifecycle() {
     let dir = tempfile::tempdir().unwrap();
     let manifest = setup_workspace(dir.path(), "repo1");
 
     // Init flow first (creates develop)
-    init(dir.path(), &manifest).unwrap();
+    init(dir.path(), &manifest, None).unwrap();
 
     // Start a feature
-    let start = feature_start(dir.path(), &manifest, "my-feature", None).unwrap();
+    let start = feature_start(
+        dir.path(),
+        &manifest,
+        "my-feature",
+        None,
+        DirtyTree::default(),
+    )
+    .unwrap();
     assert_eq!(start.branch_name, "feature/my-feature");
     assert!(start.repos[0].success);
 
@@ -200,7 +262,14 @@ fn test_feature_start_finish_lifecycle() {
     }
 
     // Finish the feature (merges into develop)
-    let finish = feature_finish(dir.path(), &manifest, "my-feature").unwrap();
+    let finish = feature_finish(
+        dir.path(),
+        &manifest,
+        "my-feature",
+        None,
+        &FinishOptions::default(),
+    )
+    .unwrap();
     assert!(finish.repos[0].success);
     assert!(finish.repos[0].message.contains("merged"));
 
@@ -222,8 +291,8 @@ fn test_feature_list() {
     let dir = tempfile::tempdir().unwrap();
     let manifest = setup_workspace(dir.path(), "repo1");
 
-    init(dir.path(), &manifest).unwrap();
-    feature_start(dir.path(), &manifest, "feat-a", None).unwrap();
+    init(dir.path(), &manifest, None).unwrap();
+    feature_start(dir.path(), &manifest, "feat-a", None, DirtyTree::default()).unwrap();
 
     // Checkout develop so we can create another branch
     std::process::Command::new("git")
@@ -231,7 +300,7 @@ fn test_feature_list() {
         .current_dir(dir.path().join("repo1"))
         .output()
         .unwrap();
-    feature_start(dir.path(), &manifest, "feat-b", None).unwrap();
+    feature_start(dir.path(), &manifest, "feat-b", None, DirtyTree::default()).unwrap();
 
     let branches = feature_list(dir.path(), &manifest).unwrap();
     let names: Vec<_> = branches.iter().map(|b| b.branch.as_str()).collect();
@@ -244,8 +313,15 @@ fn test_feature_start_across_multiple_repos() {
     let dir = tempfile::tempdir().unwrap();
     let manifest = setup_multi_repo_workspace(dir.path());
 
-    init(dir.path(), &manifest).unwrap();
-    let result = feature_start(dir.path(), &manifest, "cross-repo", None).unwrap();
+    init(dir.path(), &manifest, None).unwrap();
+    let result = feature_start(
+        dir.path(),
+        &manifest,
+        "cross-repo",
+        None,
+        DirtyTree::default(),
+    )
+    .unwrap();
 
     assert_eq!(result.repos.len(), 2);
     assert!(result.repos.iter().all(|r| r.success));
@@ -267,11 +343,18 @@ fn test_feature_start_with_repo_filter() {
     let dir = tempfile::tempdir().unwrap();
     let manifest = setup_multi_repo_workspace(dir.path());
 
-    init(dir.path(), &manifest).unwrap();
+    init(dir.path(), &manifest, None).unwrap();
 
     // Only start in alpha
     let repos = vec!["alpha".to_string()];
-    let result = feature_start(dir.path(), &manifest, "filtered", Some(&repos)).unwrap();
+    let result = feature_start(
+        dir.path(),
+        &manifest,
+        "filtered",
+        Some(&repos),
+        DirtyTree::default(),
+    )
+    .unwrap();
 
     assert_eq!(result.repos.len(), 1);
     assert_eq!(result.repos[0].repo_name, "alpha");
@@ -291,6 +374,85 @@ fn test_feature_start_with_repo_filter() {
     );
 }
 
+#[test]
+fn test_feature_finish_with_repo_filter() {
+    let dir = tempfile::tempdir().unwrap();
+    let manifest = setup_multi_repo_workspace(dir.path());
+    init(dir.path(), &manifest, None).unwrap();
+    feature_start(dir.path(), &manifest, "split", None, DirtyTree::default()).unwrap();
+    for name in ["alpha", "beta"] {
+        let repo_path = dir.path().join(name);
+        std::fs::write(repo_path.join("split.txt"), name).unwrap();
+        git_commit(&repo_path, "split work");
+    }
+
+    // Only finish in alpha; beta keeps working on the feature
+    let repos = vec!["alpha".to_string()];
+    let result = feature_finish(
+        dir.path(),
+        &manifest,
+        "split",
+        Some(&repos),
+        &FinishOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(result.repos.len(), 1);
+    assert_eq!(result.repos[0].repo_name, "alpha");
+    assert!(result.repos[0].success);
+
+    let alpha = git2::Repository::open(dir.path().join("alpha")).unwrap();
+    assert!(
+        alpha
+            .find_branch("feature/split", git2::BranchType::Local)
+            .is_err()
+    );
+    let beta = git2::Repository::open(dir.path().join("beta")).unwrap();
+    assert_eq!(beta.head().unwrap().shorthand(), Some("feature/split"));
+    let develop = beta
+        .revparse_single("develop")
+        .unwrap()
+        .peel_to_commit()
+        .unwrap();
+    assert!(develop.tree().unwrap().get_name("split.txt").is_none());
+}
+
+#[test]
+fn test_check_branches_detects_divergence() {
+    let dir = tempfile::tempdir().unwrap();
+    let manifest = setup_multi_repo_workspace(dir.path());
+
+    init(dir.path(), &manifest, None).unwrap();
+    let report = check_branches(dir.path(), &manifest, None).unwrap();
+    assert!(report.consistent);
+    assert_eq!(report.expected_branch.as_deref(), Some("main"));
+
+    // Start the feature in alpha only — beta stays behind on main
+    let repos = vec!["alpha".to_string()];
+    feature_start(
+        dir.path(),
+        &manifest,
+        "drift",
+        Some(&repos),
+        DirtyTree::default(),
+    )
+    .unwrap();
+
+    let report = check_branches(dir.path(), &manifest, None).unwrap();
+    assert!(!report.consistent);
+    assert_eq!(report.divergent.len(), 1);
+    let alpha = report
+        .repos
+        .iter()
+        .find(|r| r.repo_name == "alpha")
+        .unwrap();
+    assert_eq!(alpha.branch_type, BranchType::Feature);
+
+    // Checking just one repo is trivially consistent
+    let report = check_branches(dir.path(), &manifest, Some(&repos)).unwrap();
+    assert!(report.consistent);
+    assert_eq!(report.expected_branch.as_deref(), Some("feature/drift"));
+}
+
 #[test]
 fn test_classify_all_branch_types() {
     let flow = smctl_workspace::FlowConfig::default();
@@ -299,6 +461,8 @@ fn test_classify_all_branch_types() {
     assert_eq!(classify_branch("feature/x", &flow), BranchType::Feature);
     assert_eq!(classify_branch("release/1.0", &flow), BranchType::Release);
     assert_eq!(classify_branch("hotfix/fix", &flow), BranchType::Hotfix);
+    assert_eq!(classify_branch("bugfix/fix", &flow), BranchType::Bugfix);
+    assert_eq!(classify_branch("support/1.x", &flow), BranchType::Support);
     assert_eq!(classify_branch("other", &flow), BranchType::Other);
 }
 
@@ -330,8 +494,15 @@ fn test_merge_conflict_detection_clean() {
     let dir = tempfile::tempdir().unwrap();
     let manifest = setup_workspace(dir.path(), "repo1");
 
-    init(dir.path(), &manifest).unwrap();
-    feature_start(dir.path(), &manifest, "clean-merge", None).unwrap();
+    init(dir.path(), &manifest, None).unwrap();
+    feature_start(
+        dir.path(),
+        &manifest,
+        "clean-merge",
+        None,
+        DirtyTree::default(),
+    )
+    .unwrap();
 
     // Make a non-conflicting change on the feature branch
     let repo_path = dir.path().join("repo1");
@@ -350,8 +521,15 @@ fn test_merge_conflict_detection_with_conflict() {
     let dir = tempfile::tempdir().unwrap();
     let manifest = setup_workspace(dir.path(), "repo1");
 
-    init(dir.path(), &manifest).unwrap();
-    feature_start(dir.path(), &manifest, "conflicting", None).unwrap();
+    init(dir.path(), &manifest, None).unwrap();
+    feature_start(
+        dir.path(),
+        &manifest,
+        "conflicting",
+        None,
+        DirtyTree::default(),
+    )
+    .unwrap();
 
     let repo_path = dir.path().join("repo1");
 
@@ -379,3 +557,1148 @@ fn test_merge_conflict_detection_with_conflict() {
             .any(|f| f.contains("README"))
     );
 }
+
+#[test]
+fn test_release_finish_tags_main() {
+    let dir = tempfile::tempdir().unwrap();
+    let manifest = setup_workspace(dir.path(), "repo1");
+    let repo_path = dir.path().join("repo1");
+    init(dir.path(), &manifest, None).unwrap();
+
+    release_start(dir.path(), &manifest, "1.2.0", None, DirtyTree::default()).unwrap();
+    std::fs::write(repo_path.join("CHANGELOG.md"), "1.2.0\n").unwrap();
+    git_commit(&repo_path, "release notes");
+
+    let result = release_finish(dir.path(), &manifest, "1.2.0", &FinishOptions::default()).unwrap();
+    assert!(result.repos.iter().all(|r| r.success), "{result:?}");
+    assert!(
+        result
+            .repos
+            .iter()
+            .any(|r| r.message == "tagged 'main' as 'v1.2.0'")
+    );
+
+    let git_repo = git2::Repository::open(&repo_path).unwrap();
+    let tag = git_repo
+        .revparse_single("v1.2.0")
+        .unwrap()
+        .into_tag()
+        .expect("annotated tag");
+    assert_eq!(tag.message(), Some("Release 1.2.0\n"));
+    let main = git_repo.revparse_single("main").unwrap().id();
+    assert_eq!(tag.target_id(), main);
+
+    // Tagging the same version again fails for that repo
+    release_start(dir.path(), &manifest, "1.2.0", None, DirtyTree::default()).unwrap();
+    let again = release_finish(dir.path(), &manifest, "1.2.0", &FinishOptions::default()).unwrap();
+    assert!(
+        again
+            .repos
+            .iter()
+            .any(|r| !r.success && r.message.starts_with("failed to tag 'v1.2.0'"))
+    );
+}
+
+#[test]
+fn test_release_finish_journal_and_undo() {
+    let dir = tempfile::tempdir().unwrap();
+    let manifest = setup_workspace(dir.path(), "repo1");
+    let repo_path = dir.path().join("repo1");
+    init(dir.path(), &manifest, None).unwrap();
+    let git_repo = git2::Repository::open(&repo_path).unwrap();
+    let main_before = git_repo.revparse_single("main").unwrap().id();
+    let develop_before = git_repo.revparse_single("develop").unwrap().id();
+
+    release_start(dir.path(), &manifest, "1.0.0", None, DirtyTree::default()).unwrap();
+    std::fs::write(repo_path.join("CHANGELOG.md"), "1.0.0\n").unwrap();
+    git_commit(&repo_path, "release notes");
+    let release_tip = git_repo.revparse_single("release/1.0.0").unwrap().id();
+    release_finish(dir.path(), &manifest, "1.0.0", &FinishOptions::default()).unwrap();
+
+    let journals = OpJournal::list(dir.path()).unwrap();
+    assert_eq!(journals.len(), 1);
+    let journal = &journals[0];
+    assert_eq!(journal.status, OpStatus::Completed);
+    assert_eq!(journal.repos[0].head, "refs/heads/release/1.0.0");
+    assert_eq!(journal.repos[0].refs["refs/tags/v1.0.0"], None);
+    let steps: Vec<_> = journal.steps.iter().map(|s| s.step.as_str()).collect();
+    assert_eq!(
+        steps,
+        ["merge into main", "tag v1.0.0", "merge into develop"]
+    );
+
+    let undo = flow_undo(dir.path(), &manifest, &journal.id).unwrap();
+    assert!(undo.repos[0].success, "{undo:?}");
+    assert_eq!(
+        undo.repos[0].message,
+        "restored 4 refs, back on 'release/1.0.0'"
+    );
+    let git_repo = git2::Repository::open(&repo_path).unwrap();
+    assert_eq!(git_repo.revparse_single("main").unwrap().id(), main_before);
+    assert_eq!(
+        git_repo.revparse_single("develop").unwrap().id(),
+        develop_before
+    );
+    assert_eq!(
+        git_repo.revparse_single("release/1.0.0").unwrap().id(),
+        release_tip
+    );
+    assert!(git_repo.revparse_single("v1.0.0").is_err());
+    assert_eq!(git_repo.head().unwrap().shorthand(), Some("release/1.0.0"));
+    assert!(repo_path.join("CHANGELOG.md").exists());
+    assert_eq!(
+        OpJournal::load(dir.path(), &journal.id).unwrap().status,
+        OpStatus::Undone
+    );
+    assert!(flow_undo(dir.path(), &manifest, &journal.id).is_err());
+
+    // A release stopped by a conflict is undone, conflict and all
+    git_repo.set_head("refs/heads/main").unwrap();
+    git_repo
+        .checkout_head(Some(git2::build::CheckoutBuilder::new().force()))
+        .unwrap();
+    std::fs::write(repo_path.join("CHANGELOG.md"), "hotfixed\n").unwrap();
+    git_commit(&repo_path, "conflicting change on main");
+    let main_before = git_repo.revparse_single("main").unwrap().id();
+    std::process::Command::new("git")
+        .args(["checkout", "release/1.0.0"])
+        .current_dir(&repo_path)
+        .output()
+        .unwrap();
+    release_finish(dir.path(), &manifest, "1.0.0", &FinishOptions::default()).unwrap();
+    assert!(FlowState::load(dir.path()).unwrap().is_some());
+    let journal = OpJournal::list(dir.path()).unwrap().remove(0);
+    assert_eq!(journal.status, OpStatus::Stopped);
+
+    let undo = flow_undo(dir.path(), &manifest, &journal.id).unwrap();
+    assert!(undo.repos[0].success, "{undo:?}");
+    assert!(FlowState::load(dir.path()).unwrap().is_none());
+    let git_repo = git2::Repository::open(&repo_path).unwrap();
+    assert_eq!(git_repo.state(), git2::RepositoryState::Clean);
+    assert_eq!(git_repo.revparse_single("main").unwrap().id(), main_before);
+    assert_eq!(git_repo.head().unwrap().shorthand(), Some("release/1.0.0"));
+}
+
+#[test]
+fn test_release_finish_resumes_develop_after_conflict() {
+    let dir = tempfile::tempdir().unwrap();
+    let manifest = setup_workspace(dir.path(), "repo1");
+    let repo_path = dir.path().join("repo1");
+    init(dir.path(), &manifest, None).unwrap();
+    let checkout = |branch: &str| {
+        std::process::Command::new("git")
+            .args(["checkout", branch])
+            .current_dir(&repo_path)
+            .output()
+            .unwrap();
+    };
+    let resolve = |content: &str| {
+        std::fs::write(repo_path.join("README.md"), content).unwrap();
+        std::process::Command::new("git")
+            .args(["add", "README.md"])
+            .current_dir(&repo_path)
+            .output()
+            .unwrap();
+    };
+
+    // develop diverges from the release on README.md after it was cut
+    release_start(dir.path(), &manifest, "2.0.0", None, DirtyTree::default()).unwrap();
+    std::fs::write(repo_path.join("README.md"), "# Release notes\n").unwrap();
+    git_commit(&repo_path, "release notes");
+    checkout("develop");
+    std::fs::write(repo_path.join("README.md"), "# Next version\n").unwrap();
+    git_commit(&repo_path, "develop work");
+    checkout("release/2.0.0");
+    let git_repo = git2::Repository::open(&repo_path).unwrap();
+    let release_tip = git_repo.revparse_single("release/2.0.0").unwrap().id();
+
+    let result = release_finish(dir.path(), &manifest, "2.0.0", &FinishOptions::default()).unwrap();
+    assert_eq!(
+        result.operation,
+        "release finish (main done, develop pending)"
+    );
+    assert!(
+        result
+            .repos
+            .iter()
+            .any(|r| r.message == "tagged 'main' as 'v2.0.0'")
+    );
+    let state = FlowState::load(dir.path()).unwrap().unwrap();
+    assert_eq!(state.target, "develop");
+    assert!(state.then.is_empty());
+    assert!(
+        git_repo
+            .find_branch("release/2.0.0", git2::BranchType::Local)
+            .is_ok(),
+        "the release branch is kept until develop merged"
+    );
+
+    resolve("# Next version, with release notes\n");
+    let result = flow_continue(dir.path(), &manifest).unwrap();
+    assert!(result.repos.iter().all(|r| r.success), "{result:?}");
+    assert!(FlowState::load(dir.path()).unwrap().is_none());
+    assert!(
+        git_repo
+            .find_branch("release/2.0.0", git2::BranchType::Local)
+            .is_err()
+    );
+    let develop = git_repo.revparse_single("develop").unwrap().id();
+    assert!(git_repo.graph_descendant_of(develop, release_tip).unwrap());
+    assert_eq!(
+        OpJournal::list(dir.path()).unwrap()[0].status,
+        OpStatus::Completed
+    );
+
+    // A conflict on main: continuing also tags main and merges develop
+    release_start(dir.path(), &manifest, "2.1.0", None, DirtyTree::default()).unwrap();
+    std::fs::write(repo_path.join("README.md"), "# 2.1 notes\n").unwrap();
+    git_commit(&repo_path, "2.1 notes");
+    checkout("main");
+    std::fs::write(repo_path.join("README.md"), "# Hotfixed\n").unwrap();
+    git_commit(&repo_path, "hotfix on main");
+    checkout("release/2.1.0");
+
+    let result = release_finish(dir.path(), &manifest, "2.1.0", &FinishOptions::default()).unwrap();
+    assert_eq!(
+        result.operation,
+        "release finish (main pending, develop pending)"
+    );
+    let state = FlowState::load(dir.path()).unwrap().unwrap();
+    assert_eq!(state.then, ["develop"]);
+    assert_eq!(state.tag.unwrap().name, "v2.1.0");
+
+    resolve("# 2.1 notes, hotfixed\n");
+    let result = flow_continue(dir.path(), &manifest).unwrap();
+    assert!(result.repos.iter().all(|r| r.success), "{result:?}");
+    let messages: Vec<_> = result.repos.iter().map(|r| r.message.as_str()).collect();
+    assert_eq!(
+        messages,
+        [
+            "merged 'release/2.1.0' into 'main' (no-ff) after resolving conflicts",
+            "tagged 'main' as 'v2.1.0'",
+            "merged 'release/2.1.0' into 'develop' (no-ff)",
+        ]
+    );
+    assert!(
+        git_repo
+            .find_branch("release/2.1.0", git2::BranchType::Local)
+            .is_err()
+    );
+}
+
+#[test]
+fn test_verify_release() {
+    let dir = tempfile::tempdir().unwrap();
+    let manifest = setup_multi_repo_workspace(dir.path());
+    init(dir.path(), &manifest, None).unwrap();
+    release_start(dir.path(), &manifest, "3.0.0", None, DirtyTree::default()).unwrap();
+    for name in ["alpha", "beta"] {
+        let repo_path = dir.path().join(name);
+        std::fs::write(repo_path.join("NOTES.md"), "3.0.0\n").unwrap();
+        git_commit(&repo_path, "release notes");
+    }
+    release_finish(dir.path(), &manifest, "3.0.0", &FinishOptions::default()).unwrap();
+
+    let checks = verify_release(dir.path(), &manifest, "3.0.0").unwrap();
+    assert_eq!(checks.len(), 2);
+    assert!(checks.iter().all(|c| c.problems.is_empty()), "{checks:?}");
+
+    // alpha's develop loses the release merge, beta loses the tag
+    let alpha = git2::Repository::open(dir.path().join("alpha")).unwrap();
+    let develop = alpha
+        .revparse_single("develop")
+        .unwrap()
+        .peel_to_commit()
+        .unwrap();
+    alpha
+        .reference(
+            "refs/heads/develop",
+            develop.parent_id(0).unwrap(),
+            true,
+            "drop the release merge",
+        )
+        .unwrap();
+    let beta = git2::Repository::open(dir.path().join("beta")).unwrap();
+    beta.tag_delete("v3.0.0").unwrap();
+
+    let checks = verify_release(dir.path(), &manifest, "3.0.0").unwrap();
+    assert_eq!(
+        checks[0].problems,
+        ["'develop' does not have the release merged"]
+    );
+    assert_eq!(checks[1].problems, ["tag 'v3.0.0' not found"]);
+    assert!(checks[1].tagged_commit.is_none());
+}
+
+#[test]
+fn test_feature_publish_pushes_with_upstream() {
+    let dir = tempfile::tempdir().unwrap();
+    let manifest = setup_workspace(dir.path(), "repo1");
+    let repo_path = dir.path().join("repo1");
+    let remote = dir.path().join("remote.git");
+    git2::Repository::init_bare(&remote).unwrap();
+    git2::Repository::open(&repo_path)
+        .unwrap()
+        .remote("origin", remote.to_str().unwrap())
+        .unwrap();
+    init(dir.path(), &manifest, None).unwrap();
+    feature_start(dir.path(), &manifest, "shared", None, DirtyTree::default()).unwrap();
+
+    let result = feature_publish(dir.path(), &manifest, "shared", "origin").unwrap();
+    assert_eq!(result.repos.len(), 1);
+    assert!(result.repos[0].success, "{}", result.repos[0].message);
+
+    let bare = git2::Repository::open_bare(&remote).unwrap();
+    assert!(
+        bare.find_branch("feature/shared", git2::BranchType::Local)
+            .is_ok()
+    );
+    let local = git2::Repository::open(&repo_path).unwrap();
+    let branch = local
+        .find_branch("feature/shared", git2::BranchType::Local)
+        .unwrap();
+    assert_eq!(
+        branch.upstream().unwrap().name().unwrap(),
+        Some("origin/feature/shared")
+    );
+
+    // Unknown remote: the push fails for that repo
+    let result = feature_publish(dir.path(), &manifest, "shared", "nowhere").unwrap();
+    assert!(!result.repos[0].success);
+}
+
+#[test]
+fn test_feature_track_creates_tracking_branches() {
+    let dir = tempfile::tempdir().unwrap();
+    let manifest = setup_workspace(dir.path(), "repo1");
+    let repo_path = dir.path().join("repo1");
+    let remote = dir.path().join("remote.git");
+    git2::Repository::init_bare(&remote).unwrap();
+    git2::Repository::open(&repo_path)
+        .unwrap()
+        .remote("origin", remote.to_str().unwrap())
+        .unwrap();
+    init(dir.path(), &manifest, None).unwrap();
+    feature_start(dir.path(), &manifest, "shared", None, DirtyTree::default()).unwrap();
+    feature_publish(dir.path(), &manifest, "shared", "origin").unwrap();
+
+    // Drop the local branch, as if on another machine
+    std::process::Command::new("git")
+        .args(["checkout", "develop"])
+        .current_dir(&repo_path)
+        .output()
+        .unwrap();
+    git2::Repository::open(&repo_path)
+        .unwrap()
+        .find_branch("feature/shared", git2::BranchType::Local)
+        .unwrap()
+        .delete()
+        .unwrap();
+
+    let result = feature_track(dir.path(), &manifest, "shared", "origin").unwrap();
+    assert_eq!(
+        result.repos[0].message,
+        "created 'feature/shared' tracking 'origin/feature/shared'"
+    );
+    let local = git2::Repository::open(&repo_path).unwrap();
+    assert_eq!(local.head().unwrap().shorthand(), Some("develop"));
+    let branch = local
+        .find_branch("feature/shared", git2::BranchType::Local)
+        .unwrap();
+    assert_eq!(
+        branch.upstream().unwrap().name().unwrap(),
+        Some("origin/feature/shared")
+    );
+
+    let again = feature_track(dir.path(), &manifest, "shared", "origin").unwrap();
+    assert!(again.repos[0].success);
+    assert_eq!(again.repos[0].message, "'feature/shared' already exists");
+    assert!(feature_track(dir.path(), &manifest, "missing", "origin").is_err());
+    assert!(feature_publish(dir.path(), &manifest, "missing", "origin").is_err());
+}
+
+#[test]
+fn test_finish_merge_strategies() {
+    use smctl_workspace::MergeStrategy;
+
+    let dir = tempfile::tempdir().unwrap();
+    let manifest = setup_workspace(dir.path(), "repo1");
+    let repo_path = dir.path().join("repo1");
+    init(dir.path(), &manifest, None).unwrap();
+    let git_repo = git2::Repository::open(&repo_path).unwrap();
+    let develop_commits = || {
+        let mut walk = git_repo.revwalk().unwrap();
+        walk.push(git_repo.revparse_single("develop").unwrap().id())
+            .unwrap();
+        walk.count()
+    };
+    let options = |strategy| FinishOptions {
+        strategy: Some(strategy),
+        ..Default::default()
+    };
+
+    // Squash: two feature commits land as one
+    feature_start(
+        dir.path(),
+        &manifest,
+        "squashed",
+        None,
+        DirtyTree::default(),
+    )
+    .unwrap();
+    std::fs::write(repo_path.join("a.txt"), "a").unwrap();
+    git_commit(&repo_path, "a");
+    std::fs::write(repo_path.join("b.txt"), "b").unwrap();
+    git_commit(&repo_path, "b");
+    let before = develop_commits();
+    let result = feature_finish(
+        dir.path(),
+        &manifest,
+        "squashed",
+        None,
+        &options(MergeStrategy::Squash),
+    )
+    .unwrap();
+    assert!(result.repos[0].success, "{}", result.repos[0].message);
+    assert_eq!(result.repos[0].strategy, Some(MergeStrategy::Squash));
+    assert_eq!(develop_commits(), before + 1);
+    assert!(
+        git_repo
+            .find_branch("feature/squashed", git2::BranchType::Local)
+            .is_err()
+    );
+
+    // ff-only refuses once develop has moved on; rebase then fast-forwards
+    feature_start(dir.path(), &manifest, "linear", None, DirtyTree::default()).unwrap();
+    std::fs::write(repo_path.join("c.txt"), "c").unwrap();
+    git_commit(&repo_path, "c");
+    std::process::Command::new("git")
+        .args(["checkout", "develop"])
+        .current_dir(&repo_path)
+        .output()
+        .unwrap();
+    std::fs::write(repo_path.join("d.txt"), "d").unwrap();
+    git_commit(&repo_path, "d");
+
+    let err = feature_finish(
+        dir.path(),
+        &manifest,
+        "linear",
+        None,
+        &options(MergeStrategy::FfOnly),
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("can't fast-forward"), "{err}");
+
+    let before = develop_commits();
+    let result = feature_finish(
+        dir.path(),
+        &manifest,
+        "linear",
+        None,
+        &options(MergeStrategy::Rebase),
+    )
+    .unwrap();
+    assert!(result.repos[0].success, "{}", result.repos[0].message);
+    assert!(result.repos[0].message.ends_with("(rebase)"));
+    assert_eq!(develop_commits(), before + 1);
+    let head = git_repo.revparse_single("develop").unwrap();
+    assert_eq!(head.peel_to_commit().unwrap().parent_count(), 1);
+}
+
+#[test]
+fn test_finish_conflict_abort_and_continue() {
+    let dir = tempfile::tempdir().unwrap();
+    let manifest = setup_multi_repo_workspace(dir.path());
+    init(dir.path(), &manifest, None).unwrap();
+    let alpha = dir.path().join("alpha");
+    let beta = dir.path().join("beta");
+    let develop_tip = |path: &Path| {
+        git2::Repository::open(path)
+            .unwrap()
+            .revparse_single("develop")
+            .unwrap()
+            .id()
+    };
+
+    // alpha merges cleanly, beta conflicts on README.md
+    feature_start(dir.path(), &manifest, "clash", None, DirtyTree::default()).unwrap();
+    std::fs::write(alpha.join("alpha.txt"), "alpha\n").unwrap();
+    git_commit(&alpha, "alpha work");
+    std::fs::write(beta.join("README.md"), "# Feature version\n").unwrap();
+    git_commit(&beta, "beta work");
+    std::process::Command::new("git")
+        .args(["checkout", "develop"])
+        .current_dir(&beta)
+        .output()
+        .unwrap();
+    std::fs::write(beta.join("README.md"), "# Develop version\n").unwrap();
+    git_commit(&beta, "develop work");
+    let alpha_before = develop_tip(&alpha);
+
+    let result = feature_finish(
+        dir.path(),
+        &manifest,
+        "clash",
+        None,
+        &FinishOptions::default(),
+    )
+    .unwrap();
+    assert!(result.repos[0].success);
+    assert!(!result.repos[1].success);
+    assert!(result.repos[1].message.contains("README.md"));
+    let state = FlowState::load(dir.path()).unwrap().unwrap();
+    assert_eq!(state.conflicted.repo_name, "beta");
+    assert_eq!(state.merged.len(), 1);
+    assert!(
+        feature_finish(
+            dir.path(),
+            &manifest,
+            "clash",
+            None,
+            &FinishOptions::default()
+        )
+        .is_err(),
+        "a new finish must wait for the conflict"
+    );
+
+    // Abort resets alpha's develop and restores its feature branch
+    let result = flow_abort(dir.path(), &manifest).unwrap();
+    assert!(result.repos.iter().all(|r| r.success));
+    assert!(FlowState::load(dir.path()).unwrap().is_none());
+    assert_eq!(develop_tip(&alpha), alpha_before);
+    assert!(
+        git2::Repository::open(&alpha)
+            .unwrap()
+            .find_branch("feature/clash", git2::BranchType::Local)
+            .is_ok()
+    );
+
+    // Finish again, resolve beta's conflict and continue
+    feature_finish(
+        dir.path(),
+        &manifest,
+        "clash",
+        None,
+        &FinishOptions::default(),
+    )
+    .unwrap();
+    assert!(flow_continue(dir.path(), &manifest).is_err(), "unresolved");
+    std::fs::write(beta.join("README.md"), "# Both versions\n").unwrap();
+    std::process::Command::new("git")
+        .args(["add", "README.md"])
+        .current_dir(&beta)
+        .output()
+        .unwrap();
+    let result = flow_continue(dir.path(), &manifest).unwrap();
+    assert!(result.repos.iter().all(|r| r.success), "{result:?}");
+    assert!(FlowState::load(dir.path()).unwrap().is_none());
+    assert_eq!(
+        std::fs::read_to_string(beta.join("README.md")).unwrap(),
+        "# Both versions\n"
+    );
+    assert!(
+        git2::Repository::open(&beta)
+            .unwrap()
+            .find_branch("feature/clash", git2::BranchType::Local)
+            .is_err()
+    );
+}
+
+#[test]
+fn test_continue_applies_finish_options() {
+    use smctl_workspace::CommandSpec;
+
+    let dir = tempfile::tempdir().unwrap();
+    let mut manifest = setup_workspace(dir.path(), "repo1");
+    let repo_path = dir.path().join("repo1");
+    let remote = dir.path().join("remote.git");
+    git2::Repository::init_bare(&remote).unwrap();
+    git2::Repository::open(&repo_path)
+        .unwrap()
+        .remote("origin", remote.to_str().unwrap())
+        .unwrap();
+    init(dir.path(), &manifest, None).unwrap();
+    manifest.flow.hooks.post_finish = Some(CommandSpec::from("echo finished > finished.txt"));
+
+    // The feature and develop both change README.md
+    feature_start(dir.path(), &manifest, "clash", None, DirtyTree::default()).unwrap();
+    std::fs::write(repo_path.join("README.md"), "# Feature version\n").unwrap();
+    git_commit(&repo_path, "feature work");
+    feature_publish(dir.path(), &manifest, "clash", "origin").unwrap();
+    std::process::Command::new("git")
+        .args(["checkout", "develop"])
+        .current_dir(&repo_path)
+        .output()
+        .unwrap();
+    std::fs::write(repo_path.join("README.md"), "# Develop version\n").unwrap();
+    git_commit(&repo_path, "develop work");
+
+    let options = FinishOptions {
+        keep_partial: true,
+        delete_remote: Some("origin".into()),
+        ..Default::default()
+    };
+    let result = feature_finish(dir.path(), &manifest, "clash", None, &options).unwrap();
+    assert!(result.hooks.is_empty());
+    let state = FlowState::load(dir.path()).unwrap().unwrap();
+    assert!(state.keep_partial);
+    assert_eq!(state.delete_remote.as_deref(), Some("origin"));
+
+    std::fs::write(repo_path.join("README.md"), "# Both versions\n").unwrap();
+    std::process::Command::new("git")
+        .args(["add", "README.md"])
+        .current_dir(&repo_path)
+        .output()
+        .unwrap();
+    let result = flow_continue(dir.path(), &manifest).unwrap();
+    assert!(result.repos.iter().all(|r| r.success), "{result:?}");
+    assert!(
+        result
+            .repos
+            .iter()
+            .any(|r| r.message == "deleted 'origin/feature/clash'")
+    );
+    let bare = git2::Repository::open_bare(&remote).unwrap();
+    assert!(
+        bare.find_branch("feature/clash", git2::BranchType::Local)
+            .is_err()
+    );
+    assert_eq!(result.hooks.len(), 1);
+    assert_eq!(result.hooks[0].hook, "post_finish");
+    assert!(dir.path().join("finished.txt").exists());
+}
+
+#[test]
+fn test_finish_preflight_and_rollback() {
+    use smctl_workspace::MergeStrategy;
+
+    let dir = tempfile::tempdir().unwrap();
+    let manifest = setup_multi_repo_workspace(dir.path());
+    init(dir.path(), &manifest, None).unwrap();
+    let alpha = dir.path().join("alpha");
+    let beta = dir.path().join("beta");
+    let develop_tip = |path: &Path| {
+        git2::Repository::open(path)
+            .unwrap()
+            .revparse_single("develop")
+            .unwrap()
+            .id()
+    };
+
+    feature_start(dir.path(), &manifest, "shaky", None, DirtyTree::default()).unwrap();
+    std::fs::write(alpha.join("alpha.txt"), "alpha\n").unwrap();
+    git_commit(&alpha, "alpha work");
+    std::fs::write(beta.join("README.md"), "# Feature version\n").unwrap();
+    git_commit(&beta, "beta work");
+    let alpha_before = develop_tip(&alpha);
+
+    // A dirty tree stops the finish before anything is merged
+    std::fs::write(beta.join("README.md"), "# Uncommitted\n").unwrap();
+    let err = feature_finish(
+        dir.path(),
+        &manifest,
+        "shaky",
+        None,
+        &FinishOptions::default(),
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("uncommitted changes in beta"), "{err}");
+    assert_eq!(develop_tip(&alpha), alpha_before);
+
+    // A rebase that can't apply in beta rolls alpha back
+    std::process::Command::new("git")
+        .args(["checkout", "."])
+        .current_dir(&beta)
+        .output()
+        .unwrap();
+    std::process::Command::new("git")
+        .args(["checkout", "develop"])
+        .current_dir(&beta)
+        .output()
+        .unwrap();
+    std::fs::write(beta.join("README.md"), "# Develop version\n").unwrap();
+    git_commit(&beta, "develop work");
+
+    let options = FinishOptions {
+        strategy: Some(MergeStrategy::Rebase),
+        ..Default::default()
+    };
+    let result = feature_finish(dir.path(), &manifest, "shaky", None, &options).unwrap();
+    let beta_result = result.repos.iter().find(|r| r.repo_name == "beta").unwrap();
+    assert!(!beta_result.success);
+    assert!(
+        result
+            .repos
+            .iter()
+            .any(|r| r.repo_name == "alpha" && r.message.starts_with("rolled back"))
+    );
+    assert_eq!(develop_tip(&alpha), alpha_before);
+    assert!(
+        git2::Repository::open(&alpha)
+            .unwrap()
+            .find_branch("feature/shaky", git2::BranchType::Local)
+            .is_ok()
+    );
+    assert!(FlowState::load(dir.path()).unwrap().is_none());
+}
+
+#[test]
+fn test_dirty_trees_refused_forced_or_stashed() {
+    let dir = tempfile::tempdir().unwrap();
+    let manifest = setup_multi_repo_workspace(dir.path());
+    init(dir.path(), &manifest, None).unwrap();
+    let beta = dir.path().join("beta");
+    std::fs::write(beta.join("README.md"), "# Work in progress\n").unwrap();
+
+    let err = feature_start(dir.path(), &manifest, "wip", None, DirtyTree::Refuse)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("uncommitted changes in beta"), "{err}");
+    assert!(!err.contains("alpha"));
+
+    // Stash carries the changes over to the new branch
+    let result = feature_start(dir.path(), &manifest, "wip", None, DirtyTree::Stash).unwrap();
+    assert!(
+        result
+            .repos
+            .iter()
+            .any(|r| r.repo_name == "beta" && r.message == "restored stashed changes")
+    );
+    assert_eq!(
+        std::fs::read_to_string(beta.join("README.md")).unwrap(),
+        "# Work in progress\n"
+    );
+    let beta_repo = git2::Repository::open(&beta).unwrap();
+    assert_eq!(beta_repo.head().unwrap().shorthand(), Some("feature/wip"));
+
+    // Finish refuses the dirty tree too; --force proceeds
+    assert!(
+        feature_finish(
+            dir.path(),
+            &manifest,
+            "wip",
+            None,
+            &FinishOptions::default()
+        )
+        .is_err()
+    );
+    let options = FinishOptions {
+        dirty: DirtyTree::Force,
+        ..Default::default()
+    };
+    let result = feature_finish(dir.path(), &manifest, "wip", None, &options).unwrap();
+    assert!(result.repos.iter().all(|r| r.success), "{result:?}");
+}
+
+#[test]
+fn test_release_start_bumps_versions() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut manifest = setup_workspace(dir.path(), "repo1");
+    let repo_path = dir.path().join("repo1");
+    std::fs::write(
+        repo_path.join("Cargo.toml"),
+        "[package]\nname = \"repo1\"\nversion = \"0.1.0\"\n",
+    )
+    .unwrap();
+    std::fs::write(repo_path.join("VERSION"), "0.1.0\n").unwrap();
+    git_commit(&repo_path, "add version files");
+    init(dir.path(), &manifest, None).unwrap();
+    manifest.repos[0].version_files = vec!["Cargo.toml".into(), "VERSION".into()];
+
+    let result =
+        release_start(dir.path(), &manifest, "v2.0.0", None, DirtyTree::default()).unwrap();
+    assert!(result.repos[0].success, "{}", result.repos[0].message);
+    assert!(
+        result.repos[0]
+            .message
+            .contains("bumped version in Cargo.toml, VERSION")
+    );
+    assert!(
+        std::fs::read_to_string(repo_path.join("Cargo.toml"))
+            .unwrap()
+            .contains("version = \"2.0.0\"")
+    );
+    assert_eq!(
+        std::fs::read_to_string(repo_path.join("VERSION")).unwrap(),
+        "2.0.0\n"
+    );
+    let git_repo = git2::Repository::open(&repo_path).unwrap();
+    let head = git_repo.head().unwrap().peel_to_commit().unwrap();
+    assert_eq!(head.message(), Some("Bump version to 2.0.0\n"));
+    assert!(git_repo.statuses(None).unwrap().is_empty());
+}
+
+#[test]
+fn test_finish_skips_branches_without_changes() {
+    let dir = tempfile::tempdir().unwrap();
+    let manifest = setup_multi_repo_workspace(dir.path());
+    init(dir.path(), &manifest, None).unwrap();
+    let alpha = dir.path().join("alpha");
+    let beta = dir.path().join("beta");
+    feature_start(dir.path(), &manifest, "partial", None, DirtyTree::default()).unwrap();
+    std::fs::write(alpha.join("alpha.txt"), "alpha\n").unwrap();
+    git_commit(&alpha, "alpha work");
+    let beta_repo = git2::Repository::open(&beta).unwrap();
+    let beta_develop = beta_repo.revparse_single("develop").unwrap().id();
+
+    let result = feature_finish(
+        dir.path(),
+        &manifest,
+        "partial",
+        None,
+        &FinishOptions::default(),
+    )
+    .unwrap();
+    assert!(result.repos.iter().all(|r| r.success));
+    let beta_result = result.repos.iter().find(|r| r.repo_name == "beta").unwrap();
+    assert!(
+        beta_result.message.starts_with("no changes"),
+        "{}",
+        beta_result.message
+    );
+    assert_eq!(
+        beta_repo.revparse_single("develop").unwrap().id(),
+        beta_develop
+    );
+    assert!(
+        beta_repo
+            .find_branch("feature/partial", git2::BranchType::Local)
+            .is_err()
+    );
+}
+
+#[test]
+fn test_finish_rolls_back_when_skipped_checkout_fails() {
+    let dir = tempfile::tempdir().unwrap();
+    let manifest = setup_multi_repo_workspace(dir.path());
+    init(dir.path(), &manifest, None).unwrap();
+    let alpha = dir.path().join("alpha");
+    let beta = dir.path().join("beta");
+    let checkout = |path: &Path, branch: &str| {
+        std::process::Command::new("git")
+            .args(["checkout", "-q", branch])
+            .current_dir(path)
+            .output()
+            .unwrap();
+    };
+    feature_start(dir.path(), &manifest, "partial", None, DirtyTree::default()).unwrap();
+    std::fs::write(alpha.join("alpha.txt"), "alpha\n").unwrap();
+    git_commit(&alpha, "alpha work");
+    let alpha_develop = git2::Repository::open(&alpha)
+        .unwrap()
+        .revparse_single("develop")
+        .unwrap()
+        .id();
+
+    // beta's branch has nothing develop lacks, but an uncommitted edit
+    // blocks checking develop out
+    checkout(&beta, "develop");
+    std::fs::write(beta.join("README.md"), "# Develop version\n").unwrap();
+    git_commit(&beta, "develop work");
+    checkout(&beta, "feature/partial");
+    std::fs::write(beta.join("README.md"), "# Local edit\n").unwrap();
+
+    let options = FinishOptions {
+        dirty: DirtyTree::Force,
+        ..Default::default()
+    };
+    let result = feature_finish(dir.path(), &manifest, "partial", None, &options).unwrap();
+    let beta_result = result.repos.iter().find(|r| r.repo_name == "beta").unwrap();
+    assert!(!beta_result.success);
+    assert!(
+        result
+            .repos
+            .iter()
+            .any(|r| r.repo_name == "alpha" && r.message.starts_with("rolled back")),
+        "{result:?}"
+    );
+    assert!(FlowState::load(dir.path()).unwrap().is_none());
+    let alpha_repo = git2::Repository::open(&alpha).unwrap();
+    assert_eq!(
+        alpha_repo.revparse_single("develop").unwrap().id(),
+        alpha_develop
+    );
+    assert!(
+        alpha_repo
+            .find_branch("feature/partial", git2::BranchType::Local)
+            .is_ok()
+    );
+}
+
+#[test]
+fn test_flow_hooks() {
+    use smctl_workspace::CommandSpec;
+
+    let dir = tempfile::tempdir().unwrap();
+    let mut manifest = setup_workspace(dir.path(), "repo1");
+    let repo_path = dir.path().join("repo1");
+    init(dir.path(), &manifest, None).unwrap();
+    manifest.flow.hooks.pre_start = Some(CommandSpec::from(
+        "echo \"$SMCTL_FLOW_OPERATION $SMCTL_FLOW_BRANCH\" > started.txt",
+    ));
+    manifest.flow.hooks.post_finish = Some(CommandSpec::from("echo done; exit 3"));
+
+    let result =
+        feature_start(dir.path(), &manifest, "hooked", None, DirtyTree::default()).unwrap();
+    assert_eq!(result.hooks.len(), 1);
+    assert!(result.hooks[0].success);
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("started.txt")).unwrap(),
+        "feature start feature/hooked\n"
+    );
+    std::fs::write(repo_path.join("work.txt"), "work\n").unwrap();
+    git_commit(&repo_path, "work");
+
+    // A failing pre_finish step aborts before anything merges
+    manifest.flow.hooks.pre_finish = Some(CommandSpec::Steps(vec![
+        "true".into(),
+        "echo tests failed >&2; false".into(),
+    ]));
+    let err = feature_finish(
+        dir.path(),
+        &manifest,
+        "hooked",
+        None,
+        &FinishOptions::default(),
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(
+        err.contains("pre_finish hook `echo tests failed >&2; false` failed"),
+        "{err}"
+    );
+    assert!(err.contains("tests failed"));
+    let git_repo = git2::Repository::open(&repo_path).unwrap();
+    assert!(
+        git_repo
+            .find_branch("feature/hooked", git2::BranchType::Local)
+            .is_ok()
+    );
+
+    // post_finish failures are reported after the merge
+    manifest.flow.hooks.pre_finish = Some(CommandSpec::from("true"));
+    let result = feature_finish(
+        dir.path(),
+        &manifest,
+        "hooked",
+        None,
+        &FinishOptions::default(),
+    )
+    .unwrap();
+    assert!(result.repos[0].success);
+    let hooks: Vec<_> = result
+        .hooks
+        .iter()
+        .map(|h| (h.hook.as_str(), h.success, h.output.as_str()))
+        .collect();
+    assert_eq!(
+        hooks,
+        [("pre_finish", true, ""), ("post_finish", false, "done")]
+    );
+}
+
+#[test]
+fn test_stale_branch_cleanup() {
+    let dir = tempfile::tempdir().unwrap();
+    let manifest = setup_workspace(dir.path(), "repo1");
+    let repo_path = dir.path().join("repo1");
+    let remote = dir.path().join("remote.git");
+    git2::Repository::init_bare(&remote).unwrap();
+    git2::Repository::open(&repo_path)
+        .unwrap()
+        .remote("origin", remote.to_str().unwrap())
+        .unwrap();
+    init(dir.path(), &manifest, None).unwrap();
+    let git = |args: &[&str]| {
+        let output = std::process::Command::new("git")
+            .args(args)
+            .current_dir(&repo_path)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{args:?}");
+    };
+
+    // 'landed' is merged into develop but kept; 'open' has unmerged work
+    feature_start(dir.path(), &manifest, "landed", None, DirtyTree::default()).unwrap();
+    std::fs::write(repo_path.join("landed.txt"), "landed\n").unwrap();
+    git_commit(&repo_path, "landed work");
+    git(&["push", "origin", "feature/landed"]);
+    git(&["checkout", "develop"]);
+    git(&["merge", "--no-ff", "-m", "merge landed", "feature/landed"]);
+    feature_start(dir.path(), &manifest, "open", None, DirtyTree::default()).unwrap();
+    std::fs::write(repo_path.join("open.txt"), "open\n").unwrap();
+    git_commit(&repo_path, "open work");
+    git(&["checkout", "develop"]);
+    // 'fresh' was just started and has no commits of its own yet
+    feature_start(dir.path(), &manifest, "fresh", None, DirtyTree::default()).unwrap();
+    git(&["checkout", "develop"]);
+
+    let stale = stale_branches(dir.path(), &manifest).unwrap();
+    assert_eq!(stale.len(), 1);
+    assert_eq!(stale[0].branch, "feature/landed");
+    assert_eq!(stale[0].merged_into, "develop");
+    assert!(stale[0].last_commit > 0);
+
+    let results = delete_branches(dir.path(), &manifest, &stale, Some("origin")).unwrap();
+    assert!(results[0].success, "{}", results[0].message);
+    let local = git2::Repository::open(&repo_path).unwrap();
+    assert!(
+        local
+            .find_branch("feature/landed", git2::BranchType::Local)
+            .is_err()
+    );
+    for kept in ["feature/open", "feature/fresh"] {
+        assert!(local.find_branch(kept, git2::BranchType::Local).is_ok());
+    }
+    let bare = git2::Repository::open_bare(&remote).unwrap();
+    assert!(
+        bare.find_branch("feature/landed", git2::BranchType::Local)
+            .is_err()
+    );
+}
+
+#[test]
+fn test_finish_deletes_remote_branch() {
+    let dir = tempfile::tempdir().unwrap();
+    let manifest = setup_workspace(dir.path(), "repo1");
+    let repo_path = dir.path().join("repo1");
+    let remote = dir.path().join("remote.git");
+    git2::Repository::init_bare(&remote).unwrap();
+    git2::Repository::open(&repo_path)
+        .unwrap()
+        .remote("origin", remote.to_str().unwrap())
+        .unwrap();
+    init(dir.path(), &manifest, None).unwrap();
+    feature_start(dir.path(), &manifest, "shipped", None, DirtyTree::default()).unwrap();
+    std::fs::write(repo_path.join("shipped.txt"), "shipped\n").unwrap();
+    git_commit(&repo_path, "shipped work");
+    feature_publish(dir.path(), &manifest, "shipped", "origin").unwrap();
+
+    let options = FinishOptions {
+        delete_remote: Some("origin".into()),
+        ..Default::default()
+    };
+    let result = feature_finish(dir.path(), &manifest, "shipped", None, &options).unwrap();
+    assert!(result.repos.iter().all(|r| r.success), "{result:?}");
+    assert!(
+        result
+            .repos
+            .iter()
+            .any(|r| r.message == "deleted 'origin/feature/shipped'")
+    );
+    let bare = git2::Repository::open_bare(&remote).unwrap();
+    assert!(
+        bare.find_branch("feature/shipped", git2::BranchType::Local)
+            .is_err()
+    );
+}
+
+#[test]
+fn test_feature_checkout_tracks_remote_branches() {
+    let dir = tempfile::tempdir().unwrap();
+    let manifest = setup_multi_repo_workspace(dir.path());
+    let alpha = dir.path().join("alpha");
+    let beta = dir.path().join("beta");
+    let remote = dir.path().join("beta-remote.git");
+    git2::Repository::init_bare(&remote).unwrap();
+    git2::Repository::open(&beta)
+        .unwrap()
+        .remote("origin", remote.to_str().unwrap())
+        .unwrap();
+    init(dir.path(), &manifest, None).unwrap();
+
+    // A teammate's feature: local in alpha, only on the remote for beta
+    feature_start(dir.path(), &manifest, "pickup", None, DirtyTree::default()).unwrap();
+    feature_publish(dir.path(), &manifest, "pickup", "origin").unwrap();
+    for path in [&alpha, &beta] {
+        let git_repo = git2::Repository::open(path).unwrap();
+        git_repo.set_head("refs/heads/develop").unwrap();
+        git_repo
+            .checkout_head(Some(git2::build::CheckoutBuilder::new().force()))
+            .unwrap();
+    }
+    let beta_repo = git2::Repository::open(&beta).unwrap();
+    beta_repo
+        .find_branch("feature/pickup", git2::BranchType::Local)
+        .unwrap()
+        .delete()
+        .unwrap();
+
+    let result = feature_checkout(
+        dir.path(),
+        &manifest,
+        "pickup",
+        "origin",
+        DirtyTree::default(),
+    )
+    .unwrap();
+    assert!(result.repos.iter().all(|r| r.success), "{result:?}");
+    let messages: Vec<_> = result.repos.iter().map(|r| r.message.as_str()).collect();
+    assert_eq!(
+        messages,
+        [
+            "switched to 'feature/pickup'",
+            "created 'feature/pickup' tracking 'origin/feature/pickup'"
+        ]
+    );
+    let beta_repo = git2::Repository::open(&beta).unwrap();
+    let branch = beta_repo
+        .find_branch("feature/pickup", git2::BranchType::Local)
+        .unwrap();
+    assert_eq!(
+        branch.upstream().unwrap().name().unwrap(),
+        Some("origin/feature/pickup")
+    );
+    assert_eq!(
+        beta_repo.head().unwrap().shorthand(),
+        Some("feature/pickup")
+    );
+
+    assert!(
+        feature_checkout(
+            dir.path(),
+            &manifest,
+            "missing",
+            "origin",
+            DirtyTree::default()
+        )
+        .is_err()
+    );
+}
+
+#[test]
+fn test_bugfix_and_support_branches() {
+    let dir = tempfile::tempdir().unwrap();
+    let manifest = setup_workspace(dir.path(), "repo1");
+    let repo_path = dir.path().join("repo1");
+    init(dir.path(), &manifest, None).unwrap();
+
+    // Bugfix branches come from develop and merge back into it
+    let start = bugfix_start(dir.path(), &manifest, "crash", None, DirtyTree::default()).unwrap();
+    assert_eq!(start.branch_name, "bugfix/crash");
+    assert_eq!(
+        start.repos[0].message,
+        "created 'bugfix/crash' from 'develop'"
+    );
+    std::fs::write(repo_path.join("fix.txt"), "fixed").unwrap();
+    git_commit(&repo_path, "fix crash");
+    let listed = bugfix_list(dir.path(), &manifest).unwrap();
+    assert_eq!(listed[0].branch, "bugfix/crash");
+    assert_eq!(listed[0].branch_type, BranchType::Bugfix);
+
+    let finish = bugfix_finish(dir.path(), &manifest, "crash", &FinishOptions::default()).unwrap();
+    assert!(finish.repos[0].success, "{finish:?}");
+    let git_repo = git2::Repository::open(&repo_path).unwrap();
+    assert_eq!(git_repo.head().unwrap().shorthand(), Some("develop"));
+    assert!(repo_path.join("fix.txt").exists());
+    assert!(bugfix_list(dir.path(), &manifest).unwrap().is_empty());
+
+    // Support branches come from main and stay around
+    let start = support_start(dir.path(), &manifest, "1.x", None, DirtyTree::default()).unwrap();
+    assert_eq!(start.repos[0].message, "created 'support/1.x' from 'main'");
+    assert!(!repo_path.join("fix.txt").exists());
+    let listed = support_list(dir.path(), &manifest).unwrap();
+    assert_eq!(listed[0].branch, "support/1.x");
+}
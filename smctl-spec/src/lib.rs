@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
@@ -10,6 +11,47 @@ pub enum SpecPhase {
     Draft,
     Active,
     Archived,
+    Abandoned,
+}
+
+impl SpecPhase {
+    /// The `status:` value stored in `.openspec.yaml`.
+    pub fn as_status(&self) -> &'static str {
+        match self {
+            SpecPhase::New => "new",
+            SpecPhase::Draft => "draft",
+            SpecPhase::Active => "active",
+            SpecPhase::Archived => "archived",
+            SpecPhase::Abandoned => "abandoned",
+        }
+    }
+
+    /// Parse a `status:` value (case-insensitive).
+    pub fn from_status(status: &str) -> Option<SpecPhase> {
+        match status.trim().to_ascii_lowercase().as_str() {
+            "new" => Some(SpecPhase::New),
+            "draft" => Some(SpecPhase::Draft),
+            "active" => Some(SpecPhase::Active),
+            "archived" => Some(SpecPhase::Archived),
+            "abandoned" => Some(SpecPhase::Abandoned),
+            _ => None,
+        }
+    }
+}
+
+/// Fields of a spec's `.openspec.yaml`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SpecMeta {
+    pub schema: Option<String>,
+    pub created: Option<String>,
+    pub status: Option<String>,
+    pub owner: Option<String>,
+    pub reviewers: Vec<String>,
+    pub labels: Vec<String>,
+    /// RFC 3339 timestamp set by [`archive`].
+    pub archived: Option<String>,
+    /// GitHub tracking issue number, when `[spec.github]` is configured.
+    pub issue: Option<u64>,
 }
 
 /// A parsed spec feature.
@@ -23,6 +65,531 @@ pub struct SpecInfo {
     pub has_tasks: bool,
     pub tasks_total: usize,
     pub tasks_done: usize,
+    pub created: Option<String>,
+    pub owner: Option<String>,
+    pub reviewers: Vec<String>,
+    pub labels: Vec<String>,
+    /// Task progress per tasks.md section.
+    pub sections: Vec<SectionProgress>,
+    /// From proposal.md/design.md frontmatter (see [`Frontmatter`]).
+    pub title: Option<String>,
+    pub requirements: Vec<String>,
+    pub risk: Option<String>,
+    /// GitHub tracking issue number.
+    pub issue: Option<u64>,
+}
+
+/// Optional YAML frontmatter at the top of proposal.md or design.md:
+///
+/// ```text
+/// ---
+/// title: Rate limiting
+/// requirements: [REQ-12, REQ-14]
+/// risk: high
+/// ---
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Frontmatter {
+    pub title: Option<String>,
+    pub requirements: Vec<String>,
+    pub risk: Option<String>,
+}
+
+impl Frontmatter {
+    /// Parse the `---` block at the start of `content`, if there is one.
+    pub fn parse(content: &str) -> Option<Frontmatter> {
+        let body = content
+            .strip_prefix("---\n")
+            .or_else(|| content.strip_prefix("---\r\n"))?;
+        let end = body.lines().position(|l| l.trim_end() == "---")?;
+        let block = body.lines().take(end).collect::<Vec<_>>().join("\n");
+        let field = |key: &str| {
+            meta_fields(&block)
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| v.to_string())
+                .filter(|v| !v.is_empty())
+        };
+        Some(Frontmatter {
+            title: field("title"),
+            requirements: meta_list(&block, "requirements"),
+            risk: field("risk"),
+        })
+    }
+}
+
+/// A checkbox task from tasks.md.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Task {
+    /// 1-based position among all tasks in the file.
+    pub number: usize,
+    /// Nearest preceding `##` heading, if any.
+    pub section: Option<String>,
+    pub text: String,
+    pub done: bool,
+    /// Nesting level: 0 for top-level tasks, 1 for their subtasks, ...
+    pub depth: usize,
+    /// Number of the task this one is nested under.
+    pub parent: Option<usize>,
+}
+
+/// Task progress within one `##` section of tasks.md.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SectionProgress {
+    /// `None` for tasks before the first heading.
+    pub section: Option<String>,
+    pub total: usize,
+    pub done: usize,
+}
+
+/// Per-section task counts, in the order sections first appear.
+pub fn section_progress(tasks: &[Task]) -> Vec<SectionProgress> {
+    let mut sections: Vec<SectionProgress> = Vec::new();
+    for task in tasks {
+        let idx = match sections.iter().position(|s| s.section == task.section) {
+            Some(idx) => idx,
+            None => {
+                sections.push(SectionProgress {
+                    section: task.section.clone(),
+                    total: 0,
+                    done: 0,
+                });
+                sections.len() - 1
+            }
+        };
+        sections[idx].total += 1;
+        sections[idx].done += usize::from(task.done);
+    }
+    sections
+}
+
+/// Tasks of a spec after `spec apply`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApplyReport {
+    pub name: String,
+    /// Task numbers marked complete by this run.
+    pub newly_completed: Vec<usize>,
+    pub completed: Vec<Task>,
+    pub remaining: Vec<Task>,
+}
+
+/// Readiness of a spec to move to its next phase, as reported by `spec ff`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Readiness {
+    pub name: String,
+    pub phase: SpecPhase,
+    pub has_proposal: bool,
+    pub has_design: bool,
+    pub has_tasks: bool,
+    pub tasks_total: usize,
+    pub tasks_done: usize,
+    /// Validation issues blocking the move; empty when ready.
+    pub issues: Vec<String>,
+    pub ready: bool,
+    /// Phase the spec can move to: `Active` while tasks remain, `Archived` once all are done.
+    pub next_phase: Option<SpecPhase>,
+}
+
+/// Outcome of `spec rename`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenameResult {
+    pub from: String,
+    pub to: String,
+    pub path: PathBuf,
+    /// Markdown files rewritten: the spec's own title headers plus references in other specs.
+    pub updated_files: Vec<PathBuf>,
+}
+
+/// A line matching `spec search`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub spec: String,
+    pub phase: SpecPhase,
+    /// Path relative to the spec directory, e.g. `design.md` or `specs/auth/spec.md`.
+    pub file: PathBuf,
+    /// Nearest preceding heading, without the `#`s.
+    pub section: Option<String>,
+    /// 1-based line number.
+    pub line: usize,
+    pub text: String,
+}
+
+/// Output format for `smctl spec report`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+    Json,
+}
+
+impl std::str::FromStr for ReportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "md" | "markdown" => Ok(Self::Markdown),
+            "html" => Ok(Self::Html),
+            "json" => Ok(Self::Json),
+            other => anyhow::bail!("unknown report format '{other}' (expected md, html or json)"),
+        }
+    }
+}
+
+/// One spec in a [`SpecReport`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpecReportEntry {
+    pub name: String,
+    pub phase: SpecPhase,
+    pub tasks_done: usize,
+    pub tasks_total: usize,
+    pub owner: Option<String>,
+    /// Repositories from the proposal's `### Affected Repos` table.
+    pub affected_repos: Vec<String>,
+    /// Branches linked to the spec, as `repo:branch`; filled in by the caller.
+    pub branches: Vec<String>,
+    pub archived: Option<String>,
+}
+
+/// Workspace-wide spec summary for `smctl spec report`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpecReport {
+    pub generated: String,
+    pub specs: Vec<SpecReportEntry>,
+    /// Most recently archived specs, newest first.
+    pub recently_archived: Vec<SpecReportEntry>,
+}
+
+impl SpecReport {
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!(
+            "# Spec report\n\nGenerated {}\n\n## Active specs\n\n",
+            self.generated
+        );
+        if self.specs.is_empty() {
+            out.push_str("No active specs.\n");
+        } else {
+            out.push_str("| Spec | Phase | Tasks | Owner | Repos | Branches |\n");
+            out.push_str("|---|---|---|---|---|---|\n");
+            for e in &self.specs {
+                out.push_str(&format!(
+                    "| {} | {} | {}/{} | {} | {} | {} |\n",
+                    e.name,
+                    e.phase.as_status(),
+                    e.tasks_done,
+                    e.tasks_total,
+                    e.owner.as_deref().unwrap_or(""),
+                    e.affected_repos.join(", "),
+                    e.branches.join(", "),
+                ));
+            }
+        }
+        out.push_str("\n## Recently archived\n\n");
+        if self.recently_archived.is_empty() {
+            out.push_str("None.\n");
+        }
+        for e in &self.recently_archived {
+            out.push_str(&format!(
+                "- {} ({}/{} tasks{})\n",
+                e.name,
+                e.tasks_done,
+                e.tasks_total,
+                e.archived
+                    .as_deref()
+                    .map_or(String::new(), |a| format!(", archived {a}"))
+            ));
+        }
+        out
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut out = format!(
+            "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>Spec report</title></head>\n<body>\n<h1>Spec report</h1>\n<p>Generated {}</p>\n<h2>Active specs</h2>\n",
+            html_escape(&self.generated)
+        );
+        if self.specs.is_empty() {
+            out.push_str("<p>No active specs.</p>\n");
+        } else {
+            out.push_str("<table>\n<tr><th>Spec</th><th>Phase</th><th>Tasks</th><th>Owner</th><th>Repos</th><th>Branches</th></tr>\n");
+            for e in &self.specs {
+                out.push_str(&format!(
+                    "<tr><td>{}</td><td>{}</td><td>{}/{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                    html_escape(&e.name),
+                    e.phase.as_status(),
+                    e.tasks_done,
+                    e.tasks_total,
+                    html_escape(e.owner.as_deref().unwrap_or("")),
+                    html_escape(&e.affected_repos.join(", ")),
+                    html_escape(&e.branches.join(", ")),
+                ));
+            }
+            out.push_str("</table>\n");
+        }
+        out.push_str("<h2>Recently archived</h2>\n<ul>\n");
+        for e in &self.recently_archived {
+            out.push_str(&format!(
+                "<li>{} ({}/{} tasks)</li>\n",
+                html_escape(&e.name),
+                e.tasks_done,
+                e.tasks_total
+            ));
+        }
+        out.push_str("</ul>\n</body>\n</html>\n");
+        out
+    }
+}
+
+/// Validation policy from `openspec/validation.toml`. Missing fields keep the
+/// built-in defaults.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ValidationRules {
+    /// Headings each document must contain, keyed by file name.
+    pub required_sections: BTreeMap<String, Vec<String>>,
+    /// Placeholder text that must not remain in proposal, design or tasks.
+    pub forbidden_text: Vec<String>,
+    /// Minimum number of checkbox tasks in tasks.md.
+    pub min_tasks: usize,
+    /// Repos that must appear in the proposal's Affected Repos table.
+    pub required_impact_rows: Vec<String>,
+    /// Minimum number of rows in the Affected Repos table.
+    pub min_impact_rows: usize,
+}
+
+impl Default for ValidationRules {
+    fn default() -> Self {
+        Self {
+            required_sections: BTreeMap::from([
+                (
+                    "proposal.md".to_string(),
+                    vec!["## Why".to_string(), "## What Changes".to_string()],
+                ),
+                ("design.md".to_string(), vec!["## Decisions".to_string()]),
+            ]),
+            forbidden_text: Vec::new(),
+            min_tasks: 1,
+            required_impact_rows: Vec::new(),
+            min_impact_rows: 0,
+        }
+    }
+}
+
+impl ValidationRules {
+    /// Load `<openspec_dir>/validation.toml`, or the defaults if it doesn't exist.
+    pub fn load(openspec_dir: &Path) -> Result<Self> {
+        let path = openspec_dir.join("validation.toml");
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path).context("failed to read validation.toml")?;
+        toml::from_str(&content).context("failed to parse validation.toml")
+    }
+}
+
+/// Sequential spec IDs, kept in `<openspec_dir>/index.toml` when
+/// `[spec] numbered = true`. Archived specs keep their entry, so IDs are
+/// never reused.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SpecIndex {
+    /// Zero-padded ID → spec name, e.g. `"0042" = "0042-add-router-cache"`.
+    #[serde(default)]
+    pub specs: BTreeMap<String, String>,
+}
+
+impl SpecIndex {
+    /// Load `<openspec_dir>/index.toml`, or an empty index if it doesn't exist.
+    pub fn load(openspec_dir: &Path) -> Result<Self> {
+        let path = openspec_dir.join("index.toml");
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path).context("failed to read index.toml")?;
+        toml::from_str(&content).context("failed to parse index.toml")
+    }
+
+    pub fn save(&self, openspec_dir: &Path) -> Result<()> {
+        std::fs::create_dir_all(openspec_dir)?;
+        std::fs::write(
+            openspec_dir.join("index.toml"),
+            toml::to_string_pretty(self).context("failed to serialize spec index")?,
+        )
+        .context("failed to write index.toml")
+    }
+
+    /// Spec name recorded for `id`.
+    pub fn get(&self, id: u32) -> Option<&str> {
+        self.specs
+            .iter()
+            .find(|(key, _)| key.parse() == Ok(id))
+            .map(|(_, name)| name.as_str())
+    }
+
+    /// One past the highest ID in use.
+    pub fn next_id(&self) -> u32 {
+        self.specs
+            .keys()
+            .filter_map(|key| key.parse::<u32>().ok())
+            .max()
+            .map_or(1, |id| id + 1)
+    }
+}
+
+/// Reserve the next sequential ID for `slug` and return the numbered spec
+/// name (`0042-<slug>`).
+pub fn assign_id(openspec_dir: &Path, slug: &str) -> Result<String> {
+    let mut index = SpecIndex::load(openspec_dir)?;
+    if let Some(existing) = index.specs.values().find(|name| strip_id(name) == slug) {
+        anyhow::bail!("spec '{slug}' already exists as '{existing}'");
+    }
+    let id = index.next_id();
+    let name = format!("{id:04}-{slug}");
+    index.specs.insert(format!("{id:04}"), name.clone());
+    index.save(openspec_dir)?;
+    Ok(name)
+}
+
+/// Resolve what the user typed for a spec — its name, its sequential ID
+/// (`42` or `0042`) or the slug of a numbered spec — to the spec name.
+/// Unknown names are returned unchanged so callers report them as usual.
+pub fn resolve_name(openspec_dir: &Path, reference: &str) -> Result<String> {
+    if openspec_dir.join("changes").join(reference).exists() {
+        return Ok(reference.to_string());
+    }
+    let index = SpecIndex::load(openspec_dir)?;
+    if !reference.is_empty() && reference.chars().all(|c| c.is_ascii_digit()) {
+        let id: u32 = reference
+            .parse()
+            .with_context(|| format!("invalid spec ID '{reference}'"))?;
+        return index
+            .get(id)
+            .map(str::to_string)
+            .with_context(|| format!("no spec with ID {reference}"));
+    }
+    Ok(index
+        .specs
+        .values()
+        .find(|name| strip_id(name) == reference)
+        .cloned()
+        .unwrap_or_else(|| reference.to_string()))
+}
+
+/// Changes `spec promote` made to one capability spec.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromoteResult {
+    pub capability: String,
+    /// `<openspec_dir>/specs/<capability>/spec.md`
+    pub path: PathBuf,
+    /// Whether the capability spec was created.
+    pub created: bool,
+    pub added: Vec<String>,
+    pub modified: Vec<String>,
+    pub removed: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+/// Outcome of `spec restore`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestoreResult {
+    /// Archive directory name, e.g. `2025-01-31-my-feature`.
+    pub archived: String,
+    pub name: String,
+    pub path: PathBuf,
+    pub phase: SpecPhase,
+}
+
+/// Sort order for [`list_specs_filtered`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SpecSort {
+    /// Active specs by name, then archived specs by archive date.
+    #[default]
+    Name,
+    /// Least complete first (share of tasks done).
+    Progress,
+    /// Oldest `created` timestamp first; specs without one last.
+    Created,
+}
+
+impl std::str::FromStr for SpecSort {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "name" => Ok(Self::Name),
+            "progress" => Ok(Self::Progress),
+            "created" => Ok(Self::Created),
+            other => {
+                anyhow::bail!("unknown sort '{other}' (expected name, progress or created)")
+            }
+        }
+    }
+}
+
+/// Which specs [`list_specs_filtered`] returns. The default matches
+/// [`list_specs`]: everything, archived included, sorted by name.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SpecFilter {
+    /// Only these phases; empty means any.
+    pub phases: Vec<SpecPhase>,
+    pub owner: Option<String>,
+    /// Specs must carry all of these labels.
+    pub labels: Vec<String>,
+    pub exclude_archived: bool,
+    pub sort: SpecSort,
+}
+
+/// Task counts of tasks.md at one commit.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BurndownPoint {
+    /// Commit id, or `None` for uncommitted changes in the working tree.
+    pub commit: Option<String>,
+    /// Commit time (RFC 3339), or the current time for the working tree.
+    pub date: String,
+    pub total: usize,
+    pub done: usize,
+    pub remaining: usize,
+}
+
+/// Task completion of a spec over its git history, oldest first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Burndown {
+    pub name: String,
+    pub points: Vec<BurndownPoint>,
+}
+
+impl Burndown {
+    /// Remaining tasks per point as a block-character sparkline.
+    pub fn sparkline(&self) -> String {
+        const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+        let max = self.points.iter().map(|p| p.remaining).max().unwrap_or(0);
+        self.points
+            .iter()
+            .map(|p| {
+                let level = (p.remaining * (BARS.len() - 1)).checked_div(max);
+                BARS[level.unwrap_or(0)]
+            })
+            .collect()
+    }
+}
+
+/// One requirement in a [`TraceMatrix`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TraceRow {
+    pub id: String,
+    /// Numbers of the tasks mentioning the requirement.
+    pub tasks: Vec<usize>,
+    /// `test_*` names quoted in those tasks.
+    pub tests: Vec<String>,
+    /// Whether every referencing task is checked (false when there are none).
+    pub done: bool,
+}
+
+/// Requirement → tasks → tests matrix for `smctl spec trace`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceMatrix {
+    pub name: String,
+    pub requirements: Vec<TraceRow>,
+    /// Requirements no task refers to.
+    pub untraced: Vec<String>,
+    /// `REQ-` IDs used by tasks but not defined in proposal/design, with the task number.
+    pub unknown: Vec<(usize, String)>,
 }
 
 /// Spec validation result.
@@ -31,8 +598,33 @@ pub struct ValidationResult {
     pub name: String,
     pub valid: bool,
     pub issues: Vec<String>,
+    /// Problems that don't make the spec invalid, e.g. Affected Repos rows
+    /// naming repos the workspace manifest doesn't know.
+    #[serde(default)]
+    pub warnings: Vec<String>,
+    /// Capability deltas found under `specs/`.
+    pub deltas: Vec<DeltaSummary>,
+}
+
+/// Requirement counts of one capability delta (`specs/<capability>/spec.md`).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DeltaSummary {
+    pub capability: String,
+    pub added: usize,
+    pub modified: usize,
+    pub removed: usize,
 }
 
+/// Documents every spec has.
+const CORE_DOCUMENTS: [&str; 3] = ["proposal.md", "design.md", "tasks.md"];
+
+/// Delta sections recognised in `specs/<capability>/spec.md`.
+const DELTA_SECTIONS: [&str; 3] = [
+    "ADDED Requirements",
+    "MODIFIED Requirements",
+    "REMOVED Requirements",
+];
+
 /// Create a new OpenSpec feature folder with scaffolded documents.
 pub fn new_spec(openspec_dir: &Path, name: &str) -> Result<SpecInfo> {
     let spec_dir = openspec_dir.join("changes").join(name);
@@ -47,7 +639,7 @@ pub fn new_spec(openspec_dir: &Path, name: &str) -> Result<SpecInfo> {
         spec_dir.join(".openspec.yaml"),
         format!(
             "schema: spec-driven\ncreated: {}\nstatus: draft\n",
-            chrono_date()
+            timestamp()
         ),
     )?;
 
@@ -118,35 +710,329 @@ pub fn new_spec(openspec_dir: &Path, name: &str) -> Result<SpecInfo> {
 
     tracing::info!("created spec '{name}' at {}", spec_dir.display());
 
-    Ok(SpecInfo {
-        name: name.to_string(),
-        phase: SpecPhase::Draft,
-        path: spec_dir,
-        has_proposal: true,
-        has_design: true,
-        has_tasks: true,
-        tasks_total: 1,
-        tasks_done: 0,
-    })
+    build_spec_info(name, &spec_dir, SpecPhase::Draft)
+}
+
+/// Create a spec from an existing document: the document becomes
+/// proposal.md (its first `# ` heading retitled for the spec, the original
+/// kept as the frontmatter `title`), and any checkbox lists in it seed
+/// tasks.md, keeping their `## ` sections, nesting and checked state.
+pub fn import_spec(openspec_dir: &Path, name: &str, document: &str) -> Result<SpecInfo> {
+    let info = new_spec(openspec_dir, name)?;
+
+    let (frontmatter, body) = match document.strip_prefix("---\n") {
+        Some(rest) => match rest.find("\n---\n") {
+            Some(end) => (Some(&rest[..end]), &rest[end + 5..]),
+            None => (None, document),
+        },
+        None => (None, document),
+    };
+    let mut title = None;
+    let mut proposal = String::new();
+    for line in body.trim_start().lines() {
+        match line.strip_prefix("# ") {
+            Some(heading) if title.is_none() => {
+                title = Some(heading.trim().to_string());
+                proposal.push_str(&format!("# {name} — Proposal\n"));
+            }
+            _ => {
+                proposal.push_str(line);
+                proposal.push('\n');
+            }
+        }
+    }
+    if title.is_none() {
+        proposal.insert_str(0, &format!("# {name} — Proposal\n\n"));
+    }
+    let frontmatter = match (frontmatter, &title) {
+        (Some(fm), _) => format!("---\n{fm}\n---\n"),
+        (None, Some(title)) => format!("---\ntitle: {title}\n---\n"),
+        (None, None) => String::new(),
+    };
+    std::fs::write(info.path.join("proposal.md"), frontmatter + &proposal)
+        .context("failed to write proposal.md")?;
+
+    let tasks: Vec<Task> = task_lines(document).map(|(_, task)| task).collect();
+    if !tasks.is_empty() {
+        let mut content = format!("# {name} — Tasks\n");
+        let mut section = None;
+        for task in &tasks {
+            let heading = task.section.as_deref().unwrap_or("Implementation");
+            if section != Some(heading) {
+                content.push_str(&format!("\n## {heading}\n\n"));
+                section = Some(heading);
+            }
+            content.push_str(&format!(
+                "{}- [{}] {}\n",
+                "  ".repeat(task.depth),
+                if task.done { 'x' } else { ' ' },
+                task.text
+            ));
+        }
+        std::fs::write(info.path.join("tasks.md"), content).context("failed to write tasks.md")?;
+    }
+
+    spec_info(openspec_dir, name)
+}
+
+/// Scaffold per-repo work into a spec: a `### <repo>` task subsection per
+/// repo at the end of tasks.md's Implementation section (replacing the
+/// template's empty task), and an Affected Repos row per repo in the
+/// proposal's Impact table (replacing its empty row). Repos already listed
+/// are left alone.
+pub fn scaffold_repos(openspec_dir: &Path, name: &str, repos: &[String]) -> Result<SpecInfo> {
+    let spec_dir = openspec_dir.join("changes").join(name);
+    if !spec_dir.exists() {
+        anyhow::bail!("spec '{name}' not found");
+    }
+
+    let tasks_path = spec_dir.join("tasks.md");
+    let tasks = std::fs::read_to_string(&tasks_path).unwrap_or_default();
+    let subsections: String = repos
+        .iter()
+        .filter(|repo| !tasks.contains(&format!("### {repo}\n")))
+        .map(|repo| format!("### {repo}\n\n- [ ] \n\n"))
+        .collect();
+    std::fs::write(
+        &tasks_path,
+        insert_into_section(&tasks, "## Implementation", "\n", &subsections, |line| {
+            line.trim() == "- [ ]"
+        }),
+    )
+    .context("failed to write tasks.md")?;
+
+    let proposal_path = spec_dir.join("proposal.md");
+    let proposal = std::fs::read_to_string(&proposal_path).unwrap_or_default();
+    let listed = affected_repos(&proposal);
+    let rows: String = repos
+        .iter()
+        .filter(|repo| !listed.contains(repo))
+        .map(|repo| format!("| {repo} | |\n"))
+        .collect();
+    let proposal = if proposal.contains("### Affected Repos") {
+        insert_into_section(&proposal, "### Affected Repos", "", &rows, |line| {
+            line.trim_matches(|c: char| c == '|' || c.is_whitespace())
+                .is_empty()
+                && line.contains('|')
+        })
+    } else {
+        format!(
+            "{}\n\n## Impact\n\n### Affected Repos\n\n| Repository | Impact |\n|---|---|\n{rows}",
+            proposal.trim_end()
+        )
+    };
+    std::fs::write(&proposal_path, proposal).context("failed to write proposal.md")?;
+
+    spec_info(openspec_dir, name)
 }
 
 /// Parse tasks.md checkboxes and return progress info.
 pub fn parse_tasks(tasks_path: &Path) -> Result<(usize, usize)> {
+    let tasks = read_tasks(tasks_path)?;
+    let done = tasks.iter().filter(|t| t.done).count();
+    Ok((tasks.len(), done))
+}
+
+/// Read all checkbox tasks from tasks.md.
+pub fn read_tasks(tasks_path: &Path) -> Result<Vec<Task>> {
     let content = std::fs::read_to_string(tasks_path).context("failed to read tasks.md")?;
+    Ok(task_lines(&content).map(|(_, task)| task).collect())
+}
 
-    let mut total = 0;
-    let mut done = 0;
-    for line in content.lines() {
+/// Checkbox tasks in `content` with the index of the line each one is on.
+/// A task indented deeper than the one before it is nested under it.
+fn task_lines(content: &str) -> impl Iterator<Item = (usize, Task)> + '_ {
+    let mut section = None;
+    let mut number = 0;
+    // (indent, task number) of the enclosing tasks
+    let mut stack: Vec<(usize, usize)> = Vec::new();
+    content.lines().enumerate().filter_map(move |(idx, line)| {
         let trimmed = line.trim();
-        if trimmed.starts_with("- [x]") || trimmed.starts_with("- [X]") {
-            total += 1;
-            done += 1;
-        } else if trimmed.starts_with("- [ ]") {
-            total += 1;
+        if let Some(heading) = trimmed.strip_prefix("## ") {
+            section = Some(heading.trim().to_string());
+            stack.clear();
+            return None;
+        }
+        let (done, text) = if let Some(text) = trimmed
+            .strip_prefix("- [x]")
+            .or_else(|| trimmed.strip_prefix("- [X]"))
+        {
+            (true, text)
+        } else if let Some(text) = trimmed.strip_prefix("- [ ]") {
+            (false, text)
+        } else {
+            return None;
+        };
+        number += 1;
+        let indent: usize = line
+            .chars()
+            .take_while(|c| c.is_whitespace())
+            .map(|c| if c == '\t' { 4 } else { 1 })
+            .sum();
+        while stack.last().is_some_and(|(i, _)| *i >= indent) {
+            stack.pop();
+        }
+        let parent = stack.last().map(|(_, n)| *n);
+        let depth = stack.len();
+        stack.push((indent, number));
+        Some((
+            idx,
+            Task {
+                number,
+                section: section.clone(),
+                text: text.trim().to_string(),
+                done,
+                depth,
+                parent,
+            },
+        ))
+    })
+}
+
+/// Mark tasks complete by number and rewrite tasks.md, changing only their
+/// checkboxes. Returns the numbers that were pending before.
+pub fn complete_tasks(tasks_path: &Path, numbers: &[usize]) -> Result<Vec<usize>> {
+    set_tasks(tasks_path, numbers, true)
+}
+
+/// Check (`done = true`) or uncheck tasks by number, rewriting only their
+/// checkboxes. Returns the numbers whose state changed.
+pub fn set_tasks(tasks_path: &Path, numbers: &[usize], done: bool) -> Result<Vec<usize>> {
+    let content = std::fs::read_to_string(tasks_path).context("failed to read tasks.md")?;
+    let tasks: Vec<(usize, Task)> = task_lines(&content).collect();
+    for n in numbers {
+        if !tasks.iter().any(|(_, t)| t.number == *n) {
+            anyhow::bail!("no task #{n} (tasks.md has {} tasks)", tasks.len());
+        }
+    }
+
+    let to_change: Vec<(usize, usize)> = tasks
+        .iter()
+        .filter(|(_, t)| t.done != done && numbers.contains(&t.number))
+        .map(|(idx, t)| (*idx, t.number))
+        .collect();
+    if to_change.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Rewrite line by line, keeping original line endings and indentation.
+    let mut out = String::with_capacity(content.len());
+    for (idx, line) in content.split_inclusive('\n').enumerate() {
+        if to_change.iter().any(|(i, _)| *i == idx) {
+            let line = if done {
+                line.replacen("- [ ]", "- [x]", 1)
+            } else {
+                line.replacen("- [x]", "- [ ]", 1)
+                    .replacen("- [X]", "- [ ]", 1)
+            };
+            out.push_str(&line);
+        } else {
+            out.push_str(line);
+        }
+    }
+    std::fs::write(tasks_path, out).context("failed to write tasks.md")?;
+    Ok(to_change.into_iter().map(|(_, n)| n).collect())
+}
+
+/// Add a pending task after the last task of `section` (or of the file when
+/// `None`), creating the `##` section at the end if it doesn't exist. Other
+/// lines are left untouched.
+pub fn add_task(tasks_path: &Path, section: Option<&str>, text: &str) -> Result<Task> {
+    let text = text.trim();
+    if text.is_empty() || text.contains('\n') {
+        anyhow::bail!("task text must be a single non-empty line");
+    }
+    let content = std::fs::read_to_string(tasks_path).context("failed to read tasks.md")?;
+    let newline = if content.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let mut lines: Vec<String> = content.split_inclusive('\n').map(str::to_string).collect();
+    if let Some(last) = lines.last_mut()
+        && !last.ends_with('\n')
+    {
+        last.push_str(newline);
+    }
+
+    let tasks: Vec<(usize, Task)> = task_lines(&content).collect();
+    let heading = section.map(|s| s.trim());
+    let anchor = tasks
+        .iter()
+        .rfind(|(_, t)| heading.is_none() || t.section.as_deref() == heading)
+        .map(|(idx, _)| *idx)
+        .or_else(|| {
+            // Section without tasks yet: insert after its heading's blank line.
+            let heading = heading?;
+            let idx = lines
+                .iter()
+                .position(|l| l.trim().strip_prefix("## ").map(str::trim) == Some(heading))?;
+            Some(if lines.get(idx + 1).is_some_and(|l| l.trim().is_empty()) {
+                idx + 1
+            } else {
+                idx
+            })
+        });
+
+    match anchor {
+        Some(idx) => {
+            let indent: String = match tasks.iter().find(|(i, _)| *i == idx) {
+                Some(_) => lines[idx]
+                    .chars()
+                    .take_while(|c| c.is_whitespace())
+                    .collect(),
+                None => String::new(),
+            };
+            lines.insert(idx + 1, format!("{indent}- [ ] {text}{newline}"));
+        }
+        None => {
+            if lines.last().is_some_and(|l| !l.trim().is_empty()) {
+                lines.push(newline.to_string());
+            }
+            if let Some(heading) = heading {
+                lines.push(format!("## {heading}{newline}"));
+                lines.push(newline.to_string());
+            }
+            lines.push(format!("- [ ] {text}{newline}"));
         }
     }
 
-    Ok((total, done))
+    let out = lines.concat();
+    std::fs::write(tasks_path, &out).context("failed to write tasks.md")?;
+    let added = task_lines(&out)
+        .map(|(_, t)| t)
+        .filter(|t| {
+            t.text == text && !t.done && (heading.is_none() || t.section.as_deref() == heading)
+        })
+        .last()
+        .context("added task not found in tasks.md")?;
+    Ok(added)
+}
+
+/// Path of a spec's tasks.md, erroring if the spec or file doesn't exist.
+pub fn tasks_path(openspec_dir: &Path, name: &str) -> Result<PathBuf> {
+    let spec_dir = openspec_dir.join("changes").join(name);
+    if !spec_dir.exists() {
+        anyhow::bail!("spec '{name}' not found");
+    }
+    let tasks_path = spec_dir.join("tasks.md");
+    if !tasks_path.exists() {
+        anyhow::bail!("spec '{name}' has no tasks.md");
+    }
+    Ok(tasks_path)
+}
+
+/// Mark `numbers` complete in a spec's tasks.md and report the task lists.
+pub fn apply(openspec_dir: &Path, name: &str, numbers: &[usize]) -> Result<ApplyReport> {
+    let tasks_path = tasks_path(openspec_dir, name)?;
+    let newly_completed = complete_tasks(&tasks_path, numbers)?;
+    let (completed, remaining) = read_tasks(&tasks_path)?.into_iter().partition(|t| t.done);
+    Ok(ApplyReport {
+        name: name.to_string(),
+        newly_completed,
+        completed,
+        remaining,
+    })
 }
 
 /// Get info about a spec.
@@ -168,72 +1054,332 @@ pub fn spec_info(openspec_dir: &Path, name: &str) -> Result<SpecInfo> {
         anyhow::bail!("spec '{name}' not found");
     }
 
-    let phase = if spec_dir.join("tasks.md").exists() {
-        let (total, done) = parse_tasks(&spec_dir.join("tasks.md"))?;
-        if total > 0 && total == done {
-            SpecPhase::Active
-        } else {
-            SpecPhase::Draft
+    let status = read_meta(&spec_dir)?.and_then(|m| m.status);
+    let phase = match status.as_deref().map(|s| (s, SpecPhase::from_status(s))) {
+        Some((_, Some(phase))) => phase,
+        Some((status, None)) => {
+            tracing::warn!(
+                "spec '{name}': unknown status '{status}' in .openspec.yaml; inferring phase from tasks"
+            );
+            infer_phase(&spec_dir)?
+        }
+        None => {
+            tracing::warn!(
+                "spec '{name}': no status in .openspec.yaml; inferring phase from tasks"
+            );
+            infer_phase(&spec_dir)?
         }
-    } else {
-        SpecPhase::New
     };
 
     build_spec_info(name, &spec_dir, phase)
 }
 
-/// Validate a spec for completeness.
-pub fn validate(openspec_dir: &Path, name: &str) -> Result<ValidationResult> {
+/// Record a spec's phase as `status:` in `.openspec.yaml`. Archiving moves
+/// the spec, so `Archived` is set by [`archive`] instead.
+pub fn set_status(openspec_dir: &Path, name: &str, phase: SpecPhase) -> Result<SpecInfo> {
+    if phase == SpecPhase::Archived {
+        anyhow::bail!("use `spec archive` to archive a spec");
+    }
     let spec_dir = openspec_dir.join("changes").join(name);
     if !spec_dir.exists() {
         anyhow::bail!("spec '{name}' not found");
     }
+    set_meta_field(&spec_dir, "status", phase.as_status())?;
+    build_spec_info(name, &spec_dir, phase)
+}
 
-    let mut issues = Vec::new();
-
-    if !spec_dir.join("proposal.md").exists() {
-        issues.push("missing proposal.md".to_string());
-    }
-    if !spec_dir.join("design.md").exists() {
-        issues.push("missing design.md".to_string());
+/// Read a spec's `.openspec.yaml`, or `None` if it has none. Only flat
+/// `key: value` lines are recognised; other content is ignored.
+pub fn read_meta(spec_dir: &Path) -> Result<Option<SpecMeta>> {
+    let path = spec_dir.join(".openspec.yaml");
+    if !path.exists() {
+        return Ok(None);
     }
-    if !spec_dir.join("tasks.md").exists() {
-        issues.push("missing tasks.md".to_string());
-    } else {
-        let content = std::fs::read_to_string(spec_dir.join("tasks.md"))?;
-        if !content.contains("- [") {
-            issues.push("tasks.md has no task checkboxes".to_string());
+    let content = std::fs::read_to_string(&path).context("failed to read .openspec.yaml")?;
+    let mut meta = SpecMeta::default();
+    for (key, value) in meta_fields(&content) {
+        let value = Some(value.to_string());
+        match key {
+            "schema" => meta.schema = value,
+            "created" => meta.created = value,
+            "status" => meta.status = value,
+            "owner" => meta.owner = value,
+            "archived" => meta.archived = value,
+            "issue" => meta.issue = value.and_then(|v| v.parse().ok()),
+            _ => {}
         }
     }
+    meta.reviewers = meta_list(&content, "reviewers");
+    meta.labels = meta_list(&content, "labels");
+    Ok(Some(meta))
+}
 
-    // Check proposal.md content
-    if spec_dir.join("proposal.md").exists() {
-        let content = std::fs::read_to_string(spec_dir.join("proposal.md"))?;
-        if !content.contains("## Why") {
-            issues.push("proposal.md missing '## Why' section".to_string());
-        }
-        if !content.contains("## What Changes") {
-            issues.push("proposal.md missing '## What Changes' section".to_string());
-        }
+/// Update owner, reviewers and/or labels in a spec's `.openspec.yaml`;
+/// `None` leaves a field unchanged, lists are replaced as a whole.
+pub fn set_metadata(
+    openspec_dir: &Path,
+    name: &str,
+    owner: Option<&str>,
+    reviewers: Option<&[String]>,
+    labels: Option<&[String]>,
+) -> Result<SpecInfo> {
+    let spec_dir = openspec_dir.join("changes").join(name);
+    if !spec_dir.exists() {
+        anyhow::bail!("spec '{name}' not found");
     }
-
-    // Check design.md content
-    if spec_dir.join("design.md").exists() {
-        let content = std::fs::read_to_string(spec_dir.join("design.md"))?;
-        if !content.contains("## Decisions") {
-            issues.push("design.md missing '## Decisions' section".to_string());
-        }
+    if let Some(owner) = owner {
+        set_meta_field(&spec_dir, "owner", owner)?;
+    }
+    if let Some(reviewers) = reviewers {
+        set_meta_field(
+            &spec_dir,
+            "reviewers",
+            &format!("[{}]", reviewers.join(", ")),
+        )?;
     }
+    if let Some(labels) = labels {
+        set_meta_field(&spec_dir, "labels", &format!("[{}]", labels.join(", ")))?;
+    }
+    spec_info(openspec_dir, name)
+}
 
-    Ok(ValidationResult {
-        name: name.to_string(),
+/// Validate a spec for completeness. With a `manifest`, the proposal's
+/// Affected Repos table is also checked against the workspace's repos.
+pub fn validate(
+    openspec_dir: &Path,
+    name: &str,
+    manifest: Option<&smctl_workspace::WorkspaceManifest>,
+) -> Result<ValidationResult> {
+    let spec_dir = openspec_dir.join("changes").join(name);
+    if !spec_dir.exists() {
+        anyhow::bail!("spec '{name}' not found");
+    }
+
+    let rules = ValidationRules::load(openspec_dir)?;
+    let mut issues = Vec::new();
+    let read = |doc: &str| std::fs::read_to_string(spec_dir.join(doc)).ok();
+
+    for doc in CORE_DOCUMENTS {
+        if !spec_dir.join(doc).exists() {
+            issues.push(format!("missing {doc}"));
+        }
+    }
+
+    if spec_dir.join("tasks.md").exists() {
+        let tasks = read_tasks(&spec_dir.join("tasks.md"))?.len();
+        if tasks == 0 && rules.min_tasks > 0 {
+            issues.push("tasks.md has no task checkboxes".to_string());
+        } else if tasks < rules.min_tasks {
+            issues.push(format!(
+                "tasks.md has {tasks} tasks (minimum {})",
+                rules.min_tasks
+            ));
+        }
+    }
+
+    for (doc, sections) in &rules.required_sections {
+        let Some(content) = read(doc) else {
+            // The core documents are reported missing above.
+            if !CORE_DOCUMENTS.contains(&doc.as_str()) {
+                issues.push(format!("missing {doc}"));
+            }
+            continue;
+        };
+        for section in sections {
+            if !content.contains(section.as_str()) {
+                issues.push(format!("{doc} missing '{section}' section"));
+            }
+        }
+    }
+
+    for doc in CORE_DOCUMENTS {
+        let Some(content) = read(doc) else {
+            continue;
+        };
+        for (idx, line) in content.lines().enumerate() {
+            for text in &rules.forbidden_text {
+                if line.contains(text.as_str()) {
+                    issues.push(format!("{doc}:{} contains placeholder '{text}'", idx + 1));
+                }
+            }
+        }
+    }
+
+    if !rules.required_impact_rows.is_empty() || rules.min_impact_rows > 0 {
+        let repos = read("proposal.md")
+            .map(|p| affected_repos(&p))
+            .unwrap_or_default();
+        if repos.len() < rules.min_impact_rows {
+            issues.push(format!(
+                "proposal.md Affected Repos table has {} rows (minimum {})",
+                repos.len(),
+                rules.min_impact_rows
+            ));
+        }
+        for repo in &rules.required_impact_rows {
+            if !repos.contains(repo) {
+                issues.push(format!("proposal.md Affected Repos table missing '{repo}'"));
+            }
+        }
+    }
+
+    let mut warnings = Vec::new();
+    if let (Some(manifest), Some(proposal)) = (manifest, read("proposal.md")) {
+        let rows = impact_rows(&proposal);
+        if rows.iter().all(|(repo, _)| repo.is_empty()) {
+            warnings.push("proposal.md Affected Repos table is empty".to_string());
+        }
+        for (repo, impact) in &rows {
+            if repo.is_empty() {
+                continue;
+            }
+            if !manifest.repos.iter().any(|r| &r.name == repo) {
+                warnings.push(format!(
+                    "proposal.md Affected Repos names unknown repo '{repo}'"
+                ));
+            }
+            if impact.is_empty() {
+                warnings.push(format!(
+                    "proposal.md Affected Repos row for '{repo}' has no impact"
+                ));
+            }
+        }
+    }
+
+    let mut deltas = Vec::new();
+    for capability in delta_capabilities(&spec_dir)? {
+        let path = spec_dir.join("specs").join(&capability).join("spec.md");
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let (summary, delta_issues) = check_delta(&capability, &content);
+        issues.extend(delta_issues);
+        deltas.push(summary);
+    }
+
+    // Capabilities named in the proposal should each have a delta
+    if spec_dir.join("proposal.md").exists() {
+        let content = std::fs::read_to_string(spec_dir.join("proposal.md"))?;
+        for capability in proposal_capabilities(&content) {
+            if !deltas.iter().any(|d| d.capability == capability) {
+                issues.push(format!(
+                    "capability '{capability}' has no delta in specs/{capability}/spec.md"
+                ));
+            }
+        }
+    }
+
+    Ok(ValidationResult {
+        name: name.to_string(),
         valid: issues.is_empty(),
         issues,
+        warnings,
+        deltas,
+    })
+}
+
+/// Fast-forward check: documents present, required sections filled in, and task progress.
+pub fn fast_forward(openspec_dir: &Path, name: &str) -> Result<Readiness> {
+    let validation = validate(openspec_dir, name, None)?;
+    let info = spec_info(openspec_dir, name)?;
+    let mut issues = validation.issues;
+    if info.phase == SpecPhase::Abandoned {
+        issues.push("spec is abandoned".to_string());
+    }
+
+    let ready = issues.is_empty();
+    let next_phase = ready.then_some(
+        if info.tasks_total > 0 && info.tasks_done == info.tasks_total {
+            SpecPhase::Archived
+        } else {
+            SpecPhase::Active
+        },
+    );
+
+    Ok(Readiness {
+        name: info.name,
+        phase: info.phase,
+        has_proposal: info.has_proposal,
+        has_design: info.has_design,
+        has_tasks: info.has_tasks,
+        tasks_total: info.tasks_total,
+        tasks_done: info.tasks_done,
+        issues,
+        ready,
+        next_phase,
     })
 }
 
-/// List all specs (active + archived).
+/// Scaffold `specs/<capability>/spec.md` in a spec with empty ADDED,
+/// MODIFIED and REMOVED requirement sections.
+pub fn new_delta(openspec_dir: &Path, name: &str, capability: &str) -> Result<PathBuf> {
+    let spec_dir = openspec_dir.join("changes").join(name);
+    if !spec_dir.exists() {
+        anyhow::bail!("spec '{name}' not found");
+    }
+    if capability.is_empty() || capability.contains(['/', '\\']) {
+        anyhow::bail!("invalid capability name '{capability}'");
+    }
+    let path = spec_dir.join("specs").join(capability).join("spec.md");
+    if path.exists() {
+        anyhow::bail!(
+            "delta for '{capability}' already exists at {}",
+            path.display()
+        );
+    }
+    std::fs::create_dir_all(path.parent().unwrap_or(&spec_dir))
+        .context("failed to create delta directory")?;
+    std::fs::write(
+        &path,
+        format!(
+            "# {capability} \u{2014} Delta\n\n\
+             ## ADDED Requirements\n\n\
+             ### Requirement: \n\n\
+             The system SHALL \n\n\
+             #### Scenario: \n\n\
+             - **WHEN** \n\
+             - **THEN** \n\n\
+             ## MODIFIED Requirements\n\n\
+             ## REMOVED Requirements\n"
+        ),
+    )?;
+    Ok(path)
+}
+
+/// List all specs: active ones by name, then archived ones by folder name
+/// (i.e. by archive date).
 pub fn list_specs(openspec_dir: &Path) -> Result<Vec<SpecInfo>> {
+    list_specs_filtered(openspec_dir, &SpecFilter::default())
+}
+
+/// List specs matching `filter`, in the order it asks for. Sorting is stable,
+/// so ties keep the [`list_specs`] order.
+pub fn list_specs_filtered(openspec_dir: &Path, filter: &SpecFilter) -> Result<Vec<SpecInfo>> {
+    let mut specs: Vec<SpecInfo> = read_specs(openspec_dir, !filter.exclude_archived)?
+        .into_iter()
+        .filter(|s| filter.phases.is_empty() || filter.phases.contains(&s.phase))
+        .filter(|s| filter.owner.is_none() || s.owner == filter.owner)
+        .filter(|s| filter.labels.iter().all(|l| s.labels.contains(l)))
+        .collect();
+
+    match filter.sort {
+        SpecSort::Name => {}
+        SpecSort::Progress => specs.sort_by(|a, b| {
+            // Cross-multiplied fractions; specs without tasks count as 0%.
+            let a_key = a.tasks_done * b.tasks_total.max(1);
+            let b_key = b.tasks_done * a.tasks_total.max(1);
+            a_key.cmp(&b_key)
+        }),
+        SpecSort::Created => specs.sort_by(|a, b| match (&a.created, &b.created) {
+            (Some(a), Some(b)) => a.cmp(b),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        }),
+    }
+    Ok(specs)
+}
+
+fn read_specs(openspec_dir: &Path, include_archived: bool) -> Result<Vec<SpecInfo>> {
     let mut specs = Vec::new();
     let changes_dir = openspec_dir.join("changes");
 
@@ -256,9 +1402,12 @@ pub fn list_specs(openspec_dir: &Path) -> Result<Vec<SpecInfo>> {
         }
     }
 
+    specs.sort_by(|a, b| a.name.cmp(&b.name));
+    let active = specs.len();
+
     // List archived specs
     let archive_dir = changes_dir.join("archive");
-    if archive_dir.exists() {
+    if include_archived && archive_dir.exists() {
         let entries = std::fs::read_dir(&archive_dir)?;
         for entry in entries {
             let entry = entry?;
@@ -270,60 +1419,1241 @@ pub fn list_specs(openspec_dir: &Path) -> Result<Vec<SpecInfo>> {
             }
         }
     }
+    specs[active..].sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(specs)
+}
+
+/// Case-insensitive search of the markdown documents of active and archived
+/// specs. An empty `phases` matches specs in any phase.
+pub fn search(openspec_dir: &Path, query: &str, phases: &[SpecPhase]) -> Result<Vec<SearchHit>> {
+    let needle = query.to_lowercase();
+    if needle.trim().is_empty() {
+        anyhow::bail!("search query is empty");
+    }
+
+    let mut hits = Vec::new();
+    for spec in list_specs(openspec_dir)? {
+        if !phases.is_empty() && !phases.contains(&spec.phase) {
+            continue;
+        }
+        let mut files = markdown_files(&spec.path)?;
+        files.sort();
+        for path in files {
+            let content = std::fs::read_to_string(&path)
+                .with_context(|| format!("failed to read {}", path.display()))?;
+            let file = path.strip_prefix(&spec.path).unwrap_or(&path).to_path_buf();
+            let mut section = None;
+            for (idx, line) in content.lines().enumerate() {
+                let trimmed = line.trim();
+                if trimmed.starts_with('#') {
+                    section = Some(trimmed.trim_start_matches('#').trim().to_string());
+                }
+                if line.to_lowercase().contains(&needle) {
+                    hits.push(SearchHit {
+                        spec: spec.name.clone(),
+                        phase: spec.phase.clone(),
+                        file: file.clone(),
+                        section: section.clone(),
+                        line: idx + 1,
+                        text: trimmed.to_string(),
+                    });
+                }
+            }
+        }
+    }
+    Ok(hits)
+}
+
+/// Summarise active specs and the `recent` most recently archived ones.
+/// `branches` is left empty for the caller to fill in from the repos.
+pub fn report(openspec_dir: &Path, recent: usize) -> Result<SpecReport> {
+    let mut specs = Vec::new();
+    let mut archived = Vec::new();
+    for info in list_specs(openspec_dir)? {
+        let meta = read_meta(&info.path).ok().flatten().unwrap_or_default();
+        let affected_repos = std::fs::read_to_string(info.path.join("proposal.md"))
+            .map(|p| affected_repos(&p))
+            .unwrap_or_default();
+        let entry = SpecReportEntry {
+            name: info.name,
+            phase: info.phase,
+            tasks_done: info.tasks_done,
+            tasks_total: info.tasks_total,
+            owner: info.owner,
+            affected_repos,
+            branches: Vec::new(),
+            archived: meta.archived,
+        };
+        if entry.phase == SpecPhase::Archived {
+            archived.push(entry);
+        } else {
+            specs.push(entry);
+        }
+    }
+    specs.sort_by(|a, b| a.name.cmp(&b.name));
+    // Archive directories are prefixed with their date, so names sort chronologically.
+    archived.sort_by(|a, b| {
+        b.archived
+            .cmp(&a.archived)
+            .then_with(|| b.name.cmp(&a.name))
+    });
+    archived.truncate(recent);
+
+    Ok(SpecReport {
+        generated: timestamp(),
+        specs,
+        recently_archived: archived,
+    })
+}
+
+/// Rename an active spec: move its directory, retitle the `# <name> — ...`
+/// headers of its documents, and update references to it in other active specs.
+pub fn rename(openspec_dir: &Path, from: &str, to: &str) -> Result<RenameResult> {
+    let changes_dir = openspec_dir.join("changes");
+    let spec_dir = changes_dir.join(from);
+    if !spec_dir.exists() {
+        anyhow::bail!("spec '{from}' not found");
+    }
+    if to.is_empty() || to == "archive" || to.contains(['/', '\\']) {
+        anyhow::bail!("invalid spec name '{to}'");
+    }
+    let dest = changes_dir.join(to);
+    if dest.exists() {
+        anyhow::bail!("spec '{to}' already exists at {}", dest.display());
+    }
+    std::fs::rename(&spec_dir, &dest).context("failed to rename spec directory")?;
+
+    let mut updated_files = Vec::new();
+    let old_title = format!("# {from} \u{2014} ");
+    for doc in CORE_DOCUMENTS {
+        let path = dest.join(doc);
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let Some(pos) = content.find(&old_title) else {
+            continue;
+        };
+        if pos != 0 && !content[..pos].ends_with('\n') {
+            continue;
+        }
+        let mut renamed = content.clone();
+        renamed.replace_range(pos..pos + old_title.len(), &format!("# {to} \u{2014} "));
+        std::fs::write(&path, renamed).with_context(|| format!("failed to write {doc}"))?;
+        updated_files.push(path);
+    }
+
+    for entry in std::fs::read_dir(&changes_dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !entry.file_type()?.is_dir() || name == "archive" || name == to {
+            continue;
+        }
+        for path in markdown_files(&entry.path())? {
+            let content = std::fs::read_to_string(&path)?;
+            if let Some(replaced) = replace_name(&content, from, to) {
+                std::fs::write(&path, replaced)
+                    .with_context(|| format!("failed to write {}", path.display()))?;
+                updated_files.push(path);
+            }
+        }
+    }
+
+    let mut index = SpecIndex::load(openspec_dir)?;
+    if let Some(entry) = index.specs.values_mut().find(|name| *name == from) {
+        *entry = to.to_string();
+        index.save(openspec_dir)?;
+    }
+
+    tracing::info!("renamed spec '{from}' to '{to}'");
+    Ok(RenameResult {
+        from: from.to_string(),
+        to: to.to_string(),
+        path: dest,
+        updated_files,
+    })
+}
+
+/// Archive a spec: move to archive directory. When the spec is tracked in
+/// git, the move is staged like `git mv` so history follows the files.
+pub fn archive(openspec_dir: &Path, name: &str) -> Result<PathBuf> {
+    let spec_dir = openspec_dir.join("changes").join(name);
+    if !spec_dir.exists() {
+        anyhow::bail!("spec '{name}' not found");
+    }
+
+    let archive_dir = openspec_dir.join("changes").join("archive");
+    std::fs::create_dir_all(&archive_dir)?;
+
+    let date = archive_date();
+    let dest = archive_dir.join(format!("{date}-{name}"));
+    std::fs::rename(&spec_dir, &dest).context("failed to move spec to archive")?;
+    set_meta_field(&dest, "status", SpecPhase::Archived.as_status())?;
+    set_meta_field(&dest, "archived", &timestamp())?;
+    stage_move(&spec_dir, &dest)?;
+
+    tracing::info!("archived spec '{name}' to {}", dest.display());
+    Ok(dest)
+}
+
+/// Commit an archived spec: stages its archive directory and the capability
+/// specs under `<openspec_dir>/specs/`, then commits the archive move and
+/// those specs as `Archive spec '<name>'`. Anything else already staged stays
+/// staged, out of the commit. Returns the commit ID.
+pub fn commit_archive(openspec_dir: &Path, name: &str, dest: &Path) -> Result<String> {
+    let repo = git2::Repository::discover(dest)
+        .context("the openspec directory is not in a git repository")?;
+    let workdir = repo
+        .workdir()
+        .context("the openspec repository has no working tree")?
+        .canonicalize()?;
+    let openspec_dir = openspec_dir.canonicalize()?;
+    let paths = [
+        repo_path(&workdir, &openspec_dir.join("changes").join(name))?,
+        repo_path(&workdir, dest)?,
+        repo_path(&workdir, &openspec_dir.join("specs"))?,
+    ];
+    let mut index = repo.index()?;
+    for path in &paths {
+        if workdir.join(path).exists() {
+            index.add_all([path], git2::IndexAddOption::DEFAULT, None)?;
+        } else {
+            index.remove_all([path], None)?;
+        }
+    }
+    index.write()?;
+
+    // HEAD's tree with only those paths taken from the index
+    let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+    let mut staged = git2::Index::new()?;
+    if let Some(parent) = &parent {
+        staged.read_tree(&parent.tree()?)?;
+    }
+    for path in &paths {
+        staged.remove_dir(Path::new(path), 0)?;
+    }
+    for entry in index.iter() {
+        let under = |path: &String| {
+            entry.path.starts_with(path.as_bytes()) && entry.path.get(path.len()) == Some(&b'/')
+        };
+        if paths.iter().any(under) {
+            staged.add(&entry)?;
+        }
+    }
+    let tree = repo.find_tree(staged.write_tree_to(&repo)?)?;
+    let signature = repo
+        .signature()
+        .context("git user.name and user.email must be set to commit")?;
+    let parents: Vec<&git2::Commit> = parent.iter().collect();
+    let oid = repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        &format!("Archive spec '{name}'"),
+        &tree,
+        &parents,
+    )?;
+    tracing::info!("committed archive of '{name}' as {oid}");
+    Ok(oid.to_string())
+}
+
+/// Move an archived spec back to `changes/`. `archived` is the archive
+/// directory name or the spec name without its date prefix (the most recent
+/// archive wins). The spec is restored as `rename_to`, or under its name with
+/// the date prefix stripped unless `keep_date` is set; an existing active spec
+/// with that name is an error.
+pub fn restore(
+    openspec_dir: &Path,
+    archived: &str,
+    rename_to: Option<&str>,
+    keep_date: bool,
+) -> Result<RestoreResult> {
+    let archive_dir = openspec_dir.join("changes").join("archive");
+    let dir_name = find_archived(openspec_dir, archived)?;
+
+    let name = match rename_to {
+        Some(name) => name.to_string(),
+        None if keep_date => dir_name.clone(),
+        None => strip_date_prefix(&dir_name).to_string(),
+    };
+    if name.is_empty() || name == "archive" || name.contains(['/', '\\']) {
+        anyhow::bail!("invalid spec name '{name}'");
+    }
+    let dest = openspec_dir.join("changes").join(&name);
+    if dest.exists() {
+        anyhow::bail!(
+            "spec '{name}' already exists at {}; restore under another name with --as",
+            dest.display()
+        );
+    }
+    std::fs::rename(archive_dir.join(&dir_name), &dest)
+        .context("failed to move spec out of archive")?;
+
+    let phase = infer_phase(&dest)?;
+    set_meta_field(&dest, "status", phase.as_status())?;
+    remove_meta_field(&dest, "archived")?;
+
+    tracing::info!("restored spec '{dir_name}' to {}", dest.display());
+    Ok(RestoreResult {
+        archived: dir_name,
+        name,
+        path: dest,
+        phase,
+    })
+}
+
+/// Merge the capability deltas of an archived spec (`archived` as for
+/// [`restore`]) into the long-lived capability specs at
+/// `<openspec_dir>/specs/<capability>/spec.md`: ADDED and MODIFIED
+/// requirements replace the requirement of the same name or are appended,
+/// REMOVED ones are dropped. Missing capability specs are created.
+pub fn promote(openspec_dir: &Path, archived: &str) -> Result<Vec<PromoteResult>> {
+    let dir_name = find_archived(openspec_dir, archived)?;
+    let spec_dir = openspec_dir.join("changes").join("archive").join(&dir_name);
+
+    let mut results = Vec::new();
+    for capability in delta_capabilities(&spec_dir)? {
+        let delta_path = spec_dir.join("specs").join(&capability).join("spec.md");
+        let delta = std::fs::read_to_string(&delta_path)
+            .with_context(|| format!("failed to read {}", delta_path.display()))?;
+        let path = openspec_dir.join("specs").join(&capability).join("spec.md");
+        let created = !path.exists();
+        let current = if created {
+            format!("# {capability} Specification\n\n## Purpose\n\nTBD\n\n## Requirements\n")
+        } else {
+            std::fs::read_to_string(&path)
+                .with_context(|| format!("failed to read {}", path.display()))?
+        };
+
+        let mut result = PromoteResult {
+            capability: capability.clone(),
+            path: path.clone(),
+            created,
+            added: Vec::new(),
+            modified: Vec::new(),
+            removed: Vec::new(),
+            warnings: Vec::new(),
+        };
+        let (head, mut requirements, tail) = split_requirements(&current);
+        for (section, name, block) in delta_requirements(&delta) {
+            if name.is_empty() {
+                result
+                    .warnings
+                    .push(format!("skipped unnamed requirement in {section}"));
+                continue;
+            }
+            let existing = requirements.iter().position(|(n, _)| *n == name);
+            match (section, existing) {
+                ("REMOVED Requirements", Some(idx)) => {
+                    requirements.remove(idx);
+                    result.removed.push(name);
+                }
+                ("REMOVED Requirements", None) => result
+                    .warnings
+                    .push(format!("removed requirement '{name}' not found")),
+                (_, Some(idx)) => {
+                    if section == "ADDED Requirements" {
+                        result.warnings.push(format!(
+                            "added requirement '{name}' already existed; replaced"
+                        ));
+                    }
+                    requirements[idx].1 = block;
+                    result.modified.push(name);
+                }
+                (_, None) => {
+                    if section == "MODIFIED Requirements" {
+                        result
+                            .warnings
+                            .push(format!("modified requirement '{name}' not found; added"));
+                    }
+                    requirements.push((name.clone(), block));
+                    result.added.push(name);
+                }
+            }
+        }
+
+        let mut content = format!("{}\n\n", head.trim_end());
+        for (_, block) in &requirements {
+            content.push_str(block.trim_end());
+            content.push_str("\n\n");
+        }
+        if tail.is_empty() {
+            content.truncate(content.trim_end().len());
+            content.push('\n');
+        } else {
+            content.push_str(&tail);
+        }
+        std::fs::create_dir_all(path.parent().unwrap_or(openspec_dir))
+            .context("failed to create capability spec directory")?;
+        std::fs::write(&path, content)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        results.push(result);
+    }
+
+    tracing::info!("promoted deltas of '{dir_name}'");
+    Ok(results)
+}
+
+/// The spec being worked on, judged from `cwd`: the worktree set it is in
+/// (`<root>/<worktree.base_dir>/<name>/...`, or under a repo's
+/// `worktree_base_dir`), then the feature branch of the
+/// repo it is in, then the feature branches of the other repos. The first
+/// candidate naming an active spec wins.
+pub fn current_spec(
+    root: &Path,
+    manifest: &smctl_workspace::WorkspaceManifest,
+    openspec_dir: &Path,
+    cwd: &Path,
+) -> Option<String> {
+    let mut candidates = Vec::new();
+
+    for base in smctl_workspace::worktree::base_dirs(root, manifest) {
+        if let Ok(rel) = cwd.strip_prefix(&base)
+            && let Some(set) = rel.components().next()
+        {
+            candidates.push(set.as_os_str().to_string_lossy().to_string());
+            break;
+        }
+    }
+
+    // Repo containing cwd first, then the rest in manifest order.
+    let mut repos: Vec<_> = manifest.repos.iter().collect();
+    repos.sort_by_key(|r| !cwd.starts_with(root.join(r.local_path())));
+    for repo in repos {
+        if let Ok(status) = smctl_workspace::repo_status(root, repo)
+            && let Some(name) = status.branch.strip_prefix(&manifest.flow.feature_prefix)
+        {
+            candidates.push(name.to_string());
+        }
+    }
+
+    candidates
+        .into_iter()
+        .find(|c| !c.is_empty() && openspec_dir.join("changes").join(c).is_dir())
+}
+
+/// Walk the history of a spec's tasks.md in the git repo hosting the openspec
+/// directory and record task counts at every commit that changed it, plus the
+/// working tree if it differs. Archived specs also follow their history from
+/// before the archive move.
+pub fn burndown(openspec_dir: &Path, name: &str) -> Result<Burndown> {
+    let info = spec_info(openspec_dir, name)?;
+    let tasks_path = info.path.join("tasks.md");
+    if !tasks_path.exists() {
+        anyhow::bail!("spec '{name}' has no tasks.md");
+    }
+    let repo = git2::Repository::discover(&info.path)
+        .context("the openspec directory is not in a git repository")?;
+    let workdir = repo
+        .workdir()
+        .context("the openspec repository has no working tree")?
+        .canonicalize()?;
+    let rel = tasks_path
+        .canonicalize()?
+        .strip_prefix(&workdir)
+        .context("tasks.md is outside the git working tree")?
+        .to_path_buf();
+    let mut paths = vec![rel];
+    if info.phase == SpecPhase::Archived {
+        // changes/archive/<date>-<name>/tasks.md was changes/<name>/tasks.md
+        let openspec_rel = openspec_dir
+            .canonicalize()?
+            .strip_prefix(&workdir)
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        let dir_name = info.path.file_name().unwrap_or_default().to_string_lossy();
+        paths.push(
+            openspec_rel
+                .join("changes")
+                .join(strip_date_prefix(&dir_name))
+                .join("tasks.md"),
+        );
+    }
+
+    let mut revwalk = repo.revwalk()?;
+    if revwalk.push_head().is_ok() {
+        revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME | git2::Sort::REVERSE)?;
+    }
+
+    let mut points: Vec<BurndownPoint> = Vec::new();
+    let mut last_blob = None;
+    for oid in revwalk {
+        let commit = repo.find_commit(oid?)?;
+        let tree = commit.tree()?;
+        let Some(entry) = paths.iter().find_map(|p| tree.get_path(p).ok()) else {
+            continue;
+        };
+        if last_blob == Some(entry.id()) {
+            continue;
+        }
+        last_blob = Some(entry.id());
+        let blob = repo.find_blob(entry.id())?;
+        let content = String::from_utf8_lossy(blob.content());
+        let date = chrono::DateTime::from_timestamp(commit.time().seconds(), 0)
+            .unwrap_or_default()
+            .to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+        points.push(burndown_point(
+            Some(commit.id().to_string()),
+            date,
+            &content,
+        ));
+    }
+
+    let current = std::fs::read_to_string(&tasks_path).context("failed to read tasks.md")?;
+    let working = burndown_point(None, timestamp(), &current);
+    if points
+        .last()
+        .is_none_or(|p| (p.total, p.done) != (working.total, working.done))
+    {
+        points.push(working);
+    }
+
+    Ok(Burndown {
+        name: info.name,
+        points,
+    })
+}
+
+fn burndown_point(commit: Option<String>, date: String, tasks_md: &str) -> BurndownPoint {
+    let tasks: Vec<Task> = task_lines(tasks_md).map(|(_, t)| t).collect();
+    let done = tasks.iter().filter(|t| t.done).count();
+    BurndownPoint {
+        commit,
+        date,
+        total: tasks.len(),
+        done,
+        remaining: tasks.len() - done,
+    }
+}
+
+/// Trace requirements to tasks. Requirements are the `requirements` listed in
+/// proposal/design frontmatter plus every `REQ-<n>` ID mentioned in those
+/// documents; a task covers a requirement by mentioning its ID, and names
+/// its tests as `` `test_...` ``.
+pub fn trace(openspec_dir: &Path, name: &str) -> Result<TraceMatrix> {
+    let info = spec_info(openspec_dir, name)?;
+    let mut ids = info.requirements.clone();
+    for doc in ["proposal.md", "design.md"] {
+        if let Ok(content) = std::fs::read_to_string(info.path.join(doc)) {
+            for id in requirement_ids(&content) {
+                if !ids.contains(&id) {
+                    ids.push(id);
+                }
+            }
+        }
+    }
+
+    let tasks_path = info.path.join("tasks.md");
+    let tasks = if tasks_path.exists() {
+        read_tasks(&tasks_path)?
+    } else {
+        Vec::new()
+    };
+
+    let mut requirements = Vec::new();
+    for id in &ids {
+        let covering: Vec<&Task> = tasks
+            .iter()
+            .filter(|t| mentions_word(&t.text, id))
+            .collect();
+        let mut tests = Vec::new();
+        for task in &covering {
+            for test in task.text.split('`').skip(1).step_by(2) {
+                if test.starts_with("test_") && !tests.iter().any(|t| t == test) {
+                    tests.push(test.to_string());
+                }
+            }
+        }
+        requirements.push(TraceRow {
+            id: id.clone(),
+            tasks: covering.iter().map(|t| t.number).collect(),
+            tests,
+            done: !covering.is_empty() && covering.iter().all(|t| t.done),
+        });
+    }
+
+    let untraced = requirements
+        .iter()
+        .filter(|r| r.tasks.is_empty())
+        .map(|r| r.id.clone())
+        .collect();
+    let unknown = tasks
+        .iter()
+        .flat_map(|t| {
+            requirement_ids(&t.text)
+                .into_iter()
+                .filter(|id| !ids.contains(id))
+                .map(|id| (t.number, id))
+        })
+        .collect();
+
+    Ok(TraceMatrix {
+        name: info.name,
+        requirements,
+        untraced,
+        unknown,
+    })
+}
+
+/// Record the GitHub tracking issue of a spec (active or archived) in its
+/// `.openspec.yaml`.
+pub fn set_tracking_issue(spec_dir: &Path, number: u64) -> Result<()> {
+    set_meta_field(spec_dir, "issue", &number.to_string())
+}
+
+/// Body of a spec's tracking issue: a pointer back to the spec plus its
+/// tasks.md checkboxes, grouped by section.
+pub fn tracking_issue_body(spec_dir: &Path) -> Result<String> {
+    let name = spec_dir
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let mut body = format!(
+        "Tracking issue for spec `{name}`.\n\n\
+         Task checkboxes are synced from `tasks.md` by smctl; edit them there.\n"
+    );
+    let tasks_path = spec_dir.join("tasks.md");
+    let tasks = if tasks_path.exists() {
+        read_tasks(&tasks_path)?
+    } else {
+        Vec::new()
+    };
+    let mut section: Option<&Option<String>> = None;
+    for task in tasks.iter().filter(|t| !t.text.is_empty()) {
+        if section != Some(&task.section) {
+            section = Some(&task.section);
+            let heading = task.section.as_deref().unwrap_or("Tasks");
+            body.push_str(&format!("\n## {heading}\n\n"));
+        }
+        let mark = if task.done { "x" } else { " " };
+        body.push_str(&format!(
+            "{}- [{mark}] {}\n",
+            "  ".repeat(task.depth),
+            task.text
+        ));
+    }
+    Ok(body)
+}
+
+// --- Internal helpers ---
+
+/// `REQ-<digits>` IDs in `text`, in order of first appearance.
+fn requirement_ids(text: &str) -> Vec<String> {
+    let mut ids: Vec<String> = Vec::new();
+    for (pos, _) in text.match_indices("REQ-") {
+        if text[..pos]
+            .chars()
+            .last()
+            .is_some_and(char::is_alphanumeric)
+        {
+            continue;
+        }
+        let digits: String = text[pos + 4..]
+            .chars()
+            .take_while(char::is_ascii_digit)
+            .collect();
+        if digits.is_empty() {
+            continue;
+        }
+        let id = format!("REQ-{digits}");
+        if !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+    ids
+}
+
+/// Whether `word` occurs in `text` not surrounded by alphanumerics.
+fn mentions_word(text: &str, word: &str) -> bool {
+    text.match_indices(word).any(|(pos, _)| {
+        let before = text[..pos].chars().last();
+        let after = text[pos + word.len()..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+/// Stage the move of `from` to `to` like `git mv`: if `from` has tracked
+/// files in the git repo around it, their index entries are replaced by the
+/// files now under `to`. Outside git, or for untracked specs, nothing happens.
+fn stage_move(from: &Path, to: &Path) -> Result<()> {
+    let Ok(repo) = git2::Repository::discover(to) else {
+        return Ok(());
+    };
+    let Some(workdir) = repo.workdir() else {
+        return Ok(());
+    };
+    let workdir = workdir.canonicalize()?;
+    let from = match (from.parent(), from.file_name()) {
+        (Some(parent), Some(name)) => parent.canonicalize()?.join(name),
+        _ => return Ok(()),
+    };
+    let from_rel = repo_path(&workdir, &from)?;
+    let to_rel = repo_path(&workdir, to)?;
+
+    let mut index = repo.index()?;
+    let prefix = format!("{from_rel}/");
+    let tracked = index
+        .iter()
+        .any(|entry| entry.path.starts_with(prefix.as_bytes()));
+    if !tracked {
+        return Ok(());
+    }
+    index.remove_all([&from_rel], None)?;
+    index.add_all([&to_rel], git2::IndexAddOption::DEFAULT, None)?;
+    index.write().context("failed to stage the archive move")?;
+    Ok(())
+}
+
+/// `path` relative to a repository's working tree, with `/` separators.
+fn repo_path(workdir: &Path, path: &Path) -> Result<String> {
+    let rel = path
+        .canonicalize()
+        .unwrap_or_else(|_| path.to_path_buf())
+        .strip_prefix(workdir)
+        .with_context(|| format!("{} is outside the git working tree", path.display()))?
+        .to_string_lossy()
+        .replace('\\', "/");
+    Ok(rel)
+}
+
+/// Archive directory for `archived`: the directory name itself, or the most
+/// recent archive of a spec with that name.
+fn find_archived(openspec_dir: &Path, archived: &str) -> Result<String> {
+    let archive_dir = openspec_dir.join("changes").join("archive");
+    let mut candidates = Vec::new();
+    if archive_dir.exists() {
+        for entry in std::fs::read_dir(&archive_dir)? {
+            let entry = entry?;
+            let dir_name = entry.file_name().to_string_lossy().to_string();
+            if entry.file_type()?.is_dir()
+                && (dir_name == archived || strip_date_prefix(&dir_name) == archived)
+            {
+                candidates.push(dir_name);
+            }
+        }
+    }
+    candidates.sort();
+    candidates
+        .pop()
+        .with_context(|| format!("no archived spec '{archived}'"))
+}
+
+/// Split a capability spec into the text up to and including its
+/// `## Requirements` heading, the `### Requirement:` blocks of that section
+/// as (name, text), and the sections after it. A spec without the heading
+/// gets one.
+fn split_requirements(content: &str) -> (String, Vec<(String, String)>, String) {
+    let mut head = String::new();
+    let mut requirements: Vec<(String, String)> = Vec::new();
+    let mut tail = String::new();
+    // 0: before the section, 1: inside it, 2: after it
+    let mut state = 0;
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("## ") {
+            if state == 0 && trimmed == "## Requirements" {
+                state = 1;
+                head.push_str(line);
+                head.push('\n');
+                continue;
+            }
+            if state == 1 {
+                state = 2;
+            }
+        }
+        if state == 1
+            && let Some(name) = trimmed.strip_prefix("### Requirement:")
+        {
+            requirements.push((name.trim().to_string(), String::new()));
+        }
+        let target = match (state, requirements.last_mut()) {
+            (1, Some((_, block))) => block,
+            (2, _) => &mut tail,
+            _ => &mut head,
+        };
+        target.push_str(line);
+        target.push('\n');
+    }
+    if state == 0 {
+        head = format!("{}\n\n## Requirements\n", head.trim_end());
+    }
+    (head, requirements, tail)
+}
+
+/// `(section, name, block)` for each `### Requirement:` in a delta's
+/// ADDED/MODIFIED/REMOVED sections.
+fn delta_requirements(content: &str) -> Vec<(&'static str, String, String)> {
+    let mut requirements: Vec<(&'static str, String, String)> = Vec::new();
+    let mut section = None;
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if let Some(heading) = trimmed.strip_prefix("## ") {
+            section = DELTA_SECTIONS
+                .iter()
+                .copied()
+                .find(|s| *s == heading.trim());
+            continue;
+        }
+        let Some(sec) = section else {
+            continue;
+        };
+        if let Some(name) = trimmed.strip_prefix("### Requirement:") {
+            requirements.push((sec, name.trim().to_string(), String::new()));
+        }
+        if let Some((_, _, block)) = requirements.last_mut().filter(|r| r.0 == sec) {
+            block.push_str(line);
+            block.push('\n');
+        }
+    }
+    requirements
+}
+
+/// Append `text` to the section under `heading` (up to the next heading of
+/// the same or a higher level) after its last non-blank line and `gap`,
+/// dropping the section's lines that match `drop`. A missing section is
+/// added at the end.
+fn insert_into_section(
+    content: &str,
+    heading: &str,
+    gap: &str,
+    text: &str,
+    drop: impl Fn(&str) -> bool,
+) -> String {
+    let level = heading.chars().take_while(|c| *c == '#').count();
+    let mut out = String::new();
+    let mut in_section = false;
+    let mut found = false;
+    let flush = |out: &mut String| {
+        let trimmed = out.trim_end().len();
+        out.truncate(trimmed);
+        out.push('\n');
+        out.push_str(gap);
+        out.push_str(text);
+        if !text.ends_with("\n\n") {
+            out.push('\n');
+        }
+    };
+    for line in content.lines() {
+        let hashes = line.chars().take_while(|c| *c == '#').count();
+        let is_heading = hashes > 0 && line[hashes..].starts_with(' ');
+        if in_section && is_heading && hashes <= level {
+            flush(&mut out);
+            in_section = false;
+        }
+        if line.trim_end() == heading {
+            in_section = true;
+            found = true;
+        } else if in_section && drop(line) {
+            continue;
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    if in_section {
+        flush(&mut out);
+    } else if !found {
+        out = format!("{}\n\n{heading}\n\n{text}", out.trim_end());
+    }
+    format!("{}\n", out.trim_end())
+}
+
+/// Strip a sequential `NNNN-` ID prefix, if present.
+fn strip_id(name: &str) -> &str {
+    match name.split_once('-') {
+        Some((id, slug)) if !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()) => slug,
+        _ => name,
+    }
+}
+
+/// Strip a `YYYY-MM-DD-` archive prefix, if present.
+fn strip_date_prefix(dir_name: &str) -> &str {
+    match (dir_name.get(..10), dir_name.get(10..11)) {
+        (Some(date), Some("-")) if chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok() => {
+            &dir_name[11..]
+        }
+        _ => dir_name,
+    }
+}
+
+fn build_spec_info(name: &str, path: &Path, phase: SpecPhase) -> Result<SpecInfo> {
+    let has_proposal = path.join("proposal.md").exists();
+    let has_design = path.join("design.md").exists();
+    let has_tasks = path.join("tasks.md").exists();
+
+    let tasks = if has_tasks {
+        read_tasks(&path.join("tasks.md")).unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+    let tasks_done = tasks.iter().filter(|t| t.done).count();
+    let meta = read_meta(path).ok().flatten().unwrap_or_default();
+
+    // Proposal frontmatter wins; design.md fills gaps and adds requirement IDs.
+    let mut front = Frontmatter::default();
+    for doc in ["proposal.md", "design.md"] {
+        let Some(f) = std::fs::read_to_string(path.join(doc))
+            .ok()
+            .and_then(|c| Frontmatter::parse(&c))
+        else {
+            continue;
+        };
+        front.title = front.title.or(f.title);
+        front.risk = front.risk.or(f.risk);
+        for req in f.requirements {
+            if !front.requirements.contains(&req) {
+                front.requirements.push(req);
+            }
+        }
+    }
+
+    Ok(SpecInfo {
+        name: name.to_string(),
+        phase,
+        path: path.to_path_buf(),
+        has_proposal,
+        has_design,
+        has_tasks,
+        tasks_total: tasks.len(),
+        tasks_done,
+        sections: section_progress(&tasks),
+        title: front.title,
+        requirements: front.requirements,
+        risk: front.risk,
+        issue: meta.issue,
+        created: meta.created,
+        owner: meta.owner,
+        reviewers: meta.reviewers,
+        labels: meta.labels,
+    })
+}
+
+/// Phase from task progress, for specs whose `.openspec.yaml` has no usable status.
+fn infer_phase(spec_dir: &Path) -> Result<SpecPhase> {
+    if !spec_dir.join("tasks.md").exists() {
+        return Ok(SpecPhase::New);
+    }
+    let (total, done) = parse_tasks(&spec_dir.join("tasks.md"))?;
+    Ok(if total > 0 && total == done {
+        SpecPhase::Active
+    } else {
+        SpecPhase::Draft
+    })
+}
+
+/// `key: value` pairs of a flat YAML document, with surrounding quotes removed.
+fn meta_fields(content: &str) -> impl Iterator<Item = (&str, &str)> {
+    content.lines().filter_map(|line| {
+        if line.starts_with([' ', '\t', '#', '-']) {
+            return None;
+        }
+        let (key, value) = line.split_once(':')?;
+        let value = value.trim();
+        let value = value
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .unwrap_or(value);
+        Some((key.trim(), value))
+    })
+}
+
+/// A list field, written either inline (`labels: [a, b]`) or as a block of
+/// `- item` lines under the key.
+fn meta_list(content: &str, key: &str) -> Vec<String> {
+    let unquote = |s: &str| s.trim().trim_matches(['"', '\'']).to_string();
+    let mut lines = content.lines();
+    let Some(value) = lines.by_ref().find_map(|l| {
+        meta_fields(l)
+            .find(|(k, _)| *k == key)
+            .map(|(_, v)| v.to_string())
+    }) else {
+        return Vec::new();
+    };
+    if let Some(inline) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+        return inline
+            .split(',')
+            .map(unquote)
+            .filter(|s| !s.is_empty())
+            .collect();
+    }
+    if !value.is_empty() {
+        return vec![unquote(&value)];
+    }
+    lines
+        .map_while(|l| {
+            l.trim_start()
+                .strip_prefix("- ")
+                .filter(|_| l.starts_with([' ', '-']))
+        })
+        .map(unquote)
+        .collect()
+}
+
+/// Set one top-level field in `.openspec.yaml`, replacing its line (and any
+/// block list items under it) in place or appending it; other lines are kept
+/// as they are.
+fn set_meta_field(spec_dir: &Path, key: &str, value: &str) -> Result<()> {
+    let path = spec_dir.join(".openspec.yaml");
+    let content = if path.exists() {
+        std::fs::read_to_string(&path).context("failed to read .openspec.yaml")?
+    } else {
+        String::new()
+    };
+    let line = format!("{key}: {value}");
+    let mut found = false;
+    let mut replacing = false;
+    let mut lines = Vec::new();
+    for l in content.lines() {
+        if replacing && l.trim_start().starts_with("- ") && l.starts_with([' ', '-']) {
+            continue;
+        }
+        replacing = false;
+        if !found && meta_fields(l).any(|(k, _)| k == key) {
+            found = true;
+            replacing = true;
+            lines.push(line.clone());
+        } else {
+            lines.push(l.to_string());
+        }
+    }
+    if !found {
+        lines.push(line);
+    }
+    std::fs::write(&path, lines.join("\n") + "\n").context("failed to write .openspec.yaml")
+}
+
+/// Remove a top-level field (and any block list items under it) from `.openspec.yaml`.
+fn remove_meta_field(spec_dir: &Path, key: &str) -> Result<()> {
+    let path = spec_dir.join(".openspec.yaml");
+    if !path.exists() {
+        return Ok(());
+    }
+    let content = std::fs::read_to_string(&path).context("failed to read .openspec.yaml")?;
+    let mut removing = false;
+    let mut lines = Vec::new();
+    for l in content.lines() {
+        if removing && l.trim_start().starts_with("- ") && l.starts_with([' ', '-']) {
+            continue;
+        }
+        removing = meta_fields(l).any(|(k, _)| k == key);
+        if !removing {
+            lines.push(l);
+        }
+    }
+    std::fs::write(&path, lines.join("\n") + "\n").context("failed to write .openspec.yaml")
+}
+
+/// Capability names with a `specs/<capability>/spec.md`, sorted.
+fn delta_capabilities(spec_dir: &Path) -> Result<Vec<String>> {
+    let specs_dir = spec_dir.join("specs");
+    if !specs_dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut capabilities = Vec::new();
+    for entry in std::fs::read_dir(&specs_dir)? {
+        let entry = entry?;
+        if entry.path().join("spec.md").is_file() {
+            capabilities.push(entry.file_name().to_string_lossy().to_string());
+        }
+    }
+    capabilities.sort();
+    Ok(capabilities)
+}
+
+/// Count requirements per delta section and report structural problems:
+/// no delta sections, unknown `##` sections, requirements outside a section,
+/// and added/modified requirements without a scenario.
+fn check_delta(capability: &str, content: &str) -> (DeltaSummary, Vec<String>) {
+    let mut summary = DeltaSummary {
+        capability: capability.to_string(),
+        added: 0,
+        modified: 0,
+        removed: 0,
+    };
+    let mut issues = Vec::new();
+    let mut section: Option<&str> = None;
+    let mut seen_section = false;
+    // Requirement awaiting a scenario: (name, section)
+    let mut open: Option<(String, &str)> = None;
+
+    let close = |open: &mut Option<(String, &str)>, issues: &mut Vec<String>| {
+        if let Some((req, sec)) = open.take()
+            && sec != "REMOVED Requirements"
+        {
+            issues.push(format!(
+                "specs/{capability}/spec.md: requirement '{req}' has no scenario"
+            ));
+        }
+    };
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if let Some(heading) = trimmed.strip_prefix("## ") {
+            close(&mut open, &mut issues);
+            let heading = heading.trim();
+            section = DELTA_SECTIONS.iter().copied().find(|s| *s == heading);
+            seen_section |= section.is_some();
+            if section.is_none() {
+                issues.push(format!(
+                    "specs/{capability}/spec.md: unknown section '## {heading}'"
+                ));
+            }
+        } else if let Some(req) = trimmed.strip_prefix("### Requirement:") {
+            close(&mut open, &mut issues);
+            let req = req.trim().to_string();
+            match section {
+                Some(sec) => {
+                    match sec {
+                        "ADDED Requirements" => summary.added += 1,
+                        "MODIFIED Requirements" => summary.modified += 1,
+                        _ => summary.removed += 1,
+                    }
+                    open = Some((req, sec));
+                }
+                None => issues.push(format!(
+                    "specs/{capability}/spec.md: requirement '{req}' is outside ADDED/MODIFIED/REMOVED sections"
+                )),
+            }
+        } else if trimmed.starts_with("#### Scenario:") {
+            open = None;
+        }
+    }
+    close(&mut open, &mut issues);
+
+    if !seen_section {
+        issues.push(format!(
+            "specs/{capability}/spec.md: no ADDED/MODIFIED/REMOVED Requirements sections"
+        ));
+    }
+    (summary, issues)
+}
+
+/// Capability names listed under `### New Capabilities` and `### Modified
+/// Capabilities` in a proposal. Placeholder items (`- `, `- (None)`) are skipped;
+/// for `` - `auth`: description `` the name is `auth`.
+fn proposal_capabilities(proposal: &str) -> Vec<String> {
+    let mut capabilities = Vec::new();
+    let mut listing = false;
+    for line in proposal.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('#') {
+            listing = matches!(
+                trimmed,
+                "### New Capabilities" | "### Modified Capabilities"
+            );
+            continue;
+        }
+        if !listing {
+            continue;
+        }
+        let Some(item) = trimmed.strip_prefix("- ") else {
+            continue;
+        };
+        let name = item
+            .split(':')
+            .next()
+            .unwrap_or("")
+            .trim()
+            .trim_matches('`')
+            .trim();
+        if !name.is_empty() && !name.starts_with('(') {
+            capabilities.push(name.to_string());
+        }
+    }
+    capabilities
+}
+
+/// First-column entries of the `### Affected Repos` table in a proposal,
+/// skipping the header, separator and empty rows.
+fn affected_repos(proposal: &str) -> Vec<String> {
+    impact_rows(proposal)
+        .into_iter()
+        .map(|(repo, _)| repo)
+        .filter(|repo| !repo.is_empty())
+        .collect()
+}
+
+/// `(repo, impact)` rows of the `### Affected Repos` table, skipping the
+/// header and separator; cells may be empty.
+fn impact_rows(proposal: &str) -> Vec<(String, String)> {
+    let mut rows = Vec::new();
+    let mut in_section = false;
+    let mut header_seen = false;
+    for line in proposal.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('#') {
+            in_section = trimmed == "### Affected Repos";
+            header_seen = false;
+            continue;
+        }
+        if !in_section || !trimmed.starts_with('|') {
+            continue;
+        }
+        if !header_seen {
+            header_seen = true;
+            continue;
+        }
+        let mut cells = trimmed.trim_matches('|').split('|').map(str::trim);
+        let repo = cells.next().unwrap_or("");
+        if !repo.is_empty() && repo.chars().all(|c| c == '-' || c == ':') {
+            continue;
+        }
+        let impact = cells.collect::<Vec<_>>().join(" ").trim().to_string();
+        rows.push((repo.trim_matches('`').to_string(), impact));
+    }
+    rows
+}
 
-    Ok(specs)
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
 }
 
-/// Archive a spec: move to archive directory.
-pub fn archive(openspec_dir: &Path, name: &str) -> Result<PathBuf> {
-    let spec_dir = openspec_dir.join("changes").join(name);
-    if !spec_dir.exists() {
-        anyhow::bail!("spec '{name}' not found");
+fn markdown_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            files.extend(markdown_files(&path)?);
+        } else if path.extension().is_some_and(|e| e == "md") {
+            files.push(path);
+        }
     }
-
-    let archive_dir = openspec_dir.join("changes").join("archive");
-    std::fs::create_dir_all(&archive_dir)?;
-
-    let date = chrono_date();
-    let dest = archive_dir.join(format!("{date}-{name}"));
-    std::fs::rename(&spec_dir, &dest).context("failed to move spec to archive")?;
-
-    tracing::info!("archived spec '{name}' to {}", dest.display());
-    Ok(dest)
+    Ok(files)
 }
 
-// --- Internal helpers ---
-
-fn build_spec_info(name: &str, path: &Path, phase: SpecPhase) -> Result<SpecInfo> {
-    let has_proposal = path.join("proposal.md").exists();
-    let has_design = path.join("design.md").exists();
-    let has_tasks = path.join("tasks.md").exists();
-
-    let (tasks_total, tasks_done) = if has_tasks {
-        parse_tasks(&path.join("tasks.md")).unwrap_or((0, 0))
-    } else {
-        (0, 0)
-    };
+/// Replace whole-word occurrences of a spec name (words may contain `-` and
+/// `_`). Returns `None` if nothing matched.
+fn replace_name(content: &str, from: &str, to: &str) -> Option<String> {
+    let is_word = |c: char| c.is_alphanumeric() || c == '-' || c == '_';
+    let mut out = String::with_capacity(content.len());
+    let mut rest = content;
+    let mut replaced = false;
+    while let Some(pos) = rest.find(from) {
+        let before = rest[..pos].chars().last().or_else(|| out.chars().last());
+        let after = rest[pos + from.len()..].chars().next();
+        out.push_str(&rest[..pos]);
+        if before.is_some_and(is_word) || after.is_some_and(is_word) {
+            out.push_str(from);
+        } else {
+            out.push_str(to);
+            replaced = true;
+        }
+        rest = &rest[pos + from.len()..];
+    }
+    out.push_str(rest);
+    replaced.then_some(out)
+}
 
-    Ok(SpecInfo {
-        name: name.to_string(),
-        phase,
-        path: path.to_path_buf(),
-        has_proposal,
-        has_design,
-        has_tasks,
-        tasks_total,
-        tasks_done,
-    })
+/// Local date used as the archive directory prefix.
+fn archive_date() -> String {
+    chrono::Local::now().format("%Y-%m-%d").to_string()
 }
 
-fn chrono_date() -> String {
-    // Simple date without external chrono dependency
-    let output = std::process::Command::new("date").arg("+%Y-%m-%d").output();
-    match output {
-        Ok(o) if o.status.success() => String::from_utf8_lossy(&o.stdout).trim().to_string(),
-        _ => "unknown".to_string(),
-    }
+/// UTC timestamp stored in `.openspec.yaml`.
+fn timestamp() -> String {
+    chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
 }
 
 #[cfg(test)]
@@ -349,6 +2679,779 @@ mod tests {
         assert_eq!(done, 2);
     }
 
+    #[test]
+    fn test_apply_completes_tasks_preserving_formatting() {
+        let dir = tempfile::tempdir().unwrap();
+        new_spec(dir.path(), "apply-spec").unwrap();
+        let tasks_path = dir.path().join("changes/apply-spec/tasks.md");
+        let original = "# Tasks\r\n\n## Build\n\n  - [ ] First  task\n- [x] Done\n\n## Docs\n- [ ] Write `docs` - [ ] literally\n";
+        std::fs::write(&tasks_path, original).unwrap();
+
+        let tasks = read_tasks(&tasks_path).unwrap();
+        assert_eq!(tasks.len(), 3);
+        assert_eq!(tasks[0].section.as_deref(), Some("Build"));
+        assert_eq!(tasks[2].section.as_deref(), Some("Docs"));
+        assert_eq!(tasks[0].text, "First  task");
+
+        let report = apply(dir.path(), "apply-spec", &[2, 3]).unwrap();
+        assert_eq!(report.newly_completed, [3]);
+        assert_eq!(report.remaining.len(), 1);
+        assert_eq!(report.remaining[0].number, 1);
+        assert_eq!(
+            std::fs::read_to_string(&tasks_path).unwrap(),
+            original.replace("- [ ] Write", "- [x] Write")
+        );
+
+        assert!(apply(dir.path(), "apply-spec", &[9]).is_err());
+    }
+
+    #[test]
+    fn test_task_check_uncheck_add() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tasks.md");
+        let original = "# Tasks\n\n## Build\n\n  - [ ] one\n- [x] two\n\n## Docs\n\nNotes.\n";
+        std::fs::write(&path, original).unwrap();
+
+        assert_eq!(set_tasks(&path, &[2], false).unwrap(), vec![2]);
+        assert_eq!(set_tasks(&path, &[2], false).unwrap(), Vec::<usize>::new());
+        assert!(set_tasks(&path, &[3], true).is_err());
+
+        let added = add_task(&path, Some("Build"), "three").unwrap();
+        assert_eq!(added.number, 3);
+        assert_eq!(added.section.as_deref(), Some("Build"));
+        let docs = add_task(&path, Some("Docs"), "readme").unwrap();
+        assert_eq!(docs.number, 4);
+        let verify = add_task(&path, Some("Verify"), "tests pass").unwrap();
+        assert_eq!(verify.section.as_deref(), Some("Verify"));
+
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "# Tasks\n\n## Build\n\n  - [ ] one\n- [ ] two\n- [ ] three\n\n## Docs\n\n- [ ] readme\nNotes.\n\n## Verify\n\n- [ ] tests pass\n"
+        );
+    }
+
+    #[test]
+    fn test_rename_spec() {
+        let dir = tempfile::tempdir().unwrap();
+        new_spec(dir.path(), "old-name").unwrap();
+        let other = new_spec(dir.path(), "other").unwrap();
+        std::fs::write(
+            other.path.join("design.md"),
+            "Depends on old-name (not old-names).\n",
+        )
+        .unwrap();
+
+        let result = rename(dir.path(), "old-name", "new-name").unwrap();
+        assert!(!dir.path().join("changes/old-name").exists());
+        assert_eq!(result.updated_files.len(), 4);
+
+        let proposal = std::fs::read_to_string(result.path.join("proposal.md")).unwrap();
+        assert!(proposal.starts_with("# new-name \u{2014} Proposal"));
+        assert!(validate(dir.path(), "new-name", None).unwrap().valid);
+        assert_eq!(
+            std::fs::read_to_string(other.path.join("design.md")).unwrap(),
+            "Depends on new-name (not old-names).\n"
+        );
+
+        assert!(rename(dir.path(), "new-name", "other").is_err());
+    }
+
+    #[test]
+    fn test_delta_specs_in_validate() {
+        let dir = tempfile::tempdir().unwrap();
+        let info = new_spec(dir.path(), "deltas").unwrap();
+        assert!(
+            validate(dir.path(), "deltas", None)
+                .unwrap()
+                .deltas
+                .is_empty()
+        );
+
+        let proposal = std::fs::read_to_string(info.path.join("proposal.md")).unwrap();
+        let proposal = proposal.replacen(
+            "### New Capabilities\n\n- \n",
+            "### New Capabilities\n\n- `auth`: login\n- `audit`\n",
+            1,
+        );
+        std::fs::write(info.path.join("proposal.md"), proposal).unwrap();
+
+        new_delta(dir.path(), "deltas", "auth").unwrap();
+        assert!(new_delta(dir.path(), "deltas", "auth").is_err());
+        let result = validate(dir.path(), "deltas", None).unwrap();
+        assert_eq!(
+            result.deltas,
+            vec![DeltaSummary {
+                capability: "auth".to_string(),
+                added: 1,
+                modified: 0,
+                removed: 0,
+            }]
+        );
+        assert_eq!(
+            result.issues,
+            vec!["capability 'audit' has no delta in specs/audit/spec.md"]
+        );
+
+        std::fs::create_dir_all(info.path.join("specs/audit")).unwrap();
+        std::fs::write(
+            info.path.join("specs/audit/spec.md"),
+            "### Requirement: Log\n\n## MODIFIED Requirements\n\n### Requirement: Retain\n",
+        )
+        .unwrap();
+        let result = validate(dir.path(), "deltas", None).unwrap();
+        assert_eq!(result.issues.len(), 2);
+        assert!(result.issues[0].contains("'Log' is outside"));
+        assert!(result.issues[1].contains("'Retain' has no scenario"));
+    }
+
+    #[test]
+    fn test_search_specs() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = new_spec(dir.path(), "alpha").unwrap();
+        new_spec(dir.path(), "beta").unwrap();
+        std::fs::write(
+            a.path.join("design.md"),
+            "# alpha\n\n## Decisions\n\nUse a Token bucket.\n",
+        )
+        .unwrap();
+        archive(dir.path(), "beta").unwrap();
+
+        let hits = search(dir.path(), "token", &[]).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].spec, "alpha");
+        assert_eq!(hits[0].file, PathBuf::from("design.md"));
+        assert_eq!(hits[0].section.as_deref(), Some("Decisions"));
+        assert_eq!(hits[0].line, 5);
+
+        let archived =
+            search(dir.path(), "beta \u{2014} proposal", &[SpecPhase::Archived]).unwrap();
+        assert_eq!(archived.len(), 1);
+        assert!(
+            search(dir.path(), "token", &[SpecPhase::Archived])
+                .unwrap()
+                .is_empty()
+        );
+        assert!(search(dir.path(), " ", &[]).is_err());
+    }
+
+    #[test]
+    fn test_spec_report() {
+        let dir = tempfile::tempdir().unwrap();
+        let info = new_spec(dir.path(), "impact").unwrap();
+        let proposal = std::fs::read_to_string(info.path.join("proposal.md"))
+            .unwrap()
+            .replacen(
+                "| | |\n",
+                "| SmallAIOS | scheduler |\n| `ModelGate` | cli |\n",
+                1,
+            );
+        std::fs::write(info.path.join("proposal.md"), proposal).unwrap();
+        for name in ["old-a", "old-b"] {
+            new_spec(dir.path(), name).unwrap();
+            archive(dir.path(), name).unwrap();
+        }
+
+        let report = report(dir.path(), 1).unwrap();
+        assert_eq!(report.specs.len(), 1);
+        assert_eq!(
+            report.specs[0].affected_repos,
+            vec!["SmallAIOS", "ModelGate"]
+        );
+        assert_eq!(report.recently_archived.len(), 1);
+
+        let md = report.to_markdown();
+        assert!(md.contains("| impact | draft | 0/"));
+        assert!(md.contains("SmallAIOS, ModelGate"));
+        assert!(report.to_html().contains("<td>impact</td>"));
+    }
+
+    #[test]
+    fn test_spec_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        new_spec(dir.path(), "owned").unwrap();
+        let spec_dir = dir.path().join("changes/owned");
+        let yaml = std::fs::read_to_string(spec_dir.join(".openspec.yaml")).unwrap();
+        std::fs::write(
+            spec_dir.join(".openspec.yaml"),
+            format!("{yaml}labels:\n  - security\n  - \"cli\"\nowner: alice\n"),
+        )
+        .unwrap();
+
+        let info = spec_info(dir.path(), "owned").unwrap();
+        assert_eq!(info.owner.as_deref(), Some("alice"));
+        assert_eq!(info.labels, vec!["security", "cli"]);
+        assert!(info.reviewers.is_empty());
+
+        let reviewers = vec!["bob".to_string(), "carol".to_string()];
+        let labels = vec!["perf".to_string()];
+        let info =
+            set_metadata(dir.path(), "owned", None, Some(&reviewers), Some(&labels)).unwrap();
+        assert_eq!(info.owner.as_deref(), Some("alice"));
+        assert_eq!(info.reviewers, reviewers);
+        assert_eq!(info.labels, labels);
+
+        let yaml = std::fs::read_to_string(spec_dir.join(".openspec.yaml")).unwrap();
+        assert!(yaml.contains("labels: [perf]\nowner: alice\nreviewers: [bob, carol]\n"));
+        assert!(yaml.starts_with("schema: spec-driven\n"));
+    }
+
+    #[test]
+    fn test_validation_rules_from_toml() {
+        let dir = tempfile::tempdir().unwrap();
+        new_spec(dir.path(), "strict").unwrap();
+        assert!(validate(dir.path(), "strict", None).unwrap().valid);
+
+        std::fs::write(
+            dir.path().join("validation.toml"),
+            r###"
+forbidden_text = ["<!--"]
+min_tasks = 10
+required_impact_rows = ["ModelGate"]
+
+[required_sections]
+"design.md" = ["## Context", "## Rollout"]
+"###,
+        )
+        .unwrap();
+        let result = validate(dir.path(), "strict", None).unwrap();
+        let issues = result.issues.join("\n");
+        assert!(issues.contains("tasks.md has 4 tasks (minimum 10)"));
+        assert!(issues.contains("design.md missing '## Rollout' section"));
+        assert!(!issues.contains("## Context"));
+        // Replacing required_sections drops the proposal defaults
+        assert!(!issues.contains("## Why"));
+        assert!(issues.contains("proposal.md:5 contains placeholder '<!--'"));
+        assert!(issues.contains("Affected Repos table missing 'ModelGate'"));
+
+        std::fs::write(dir.path().join("validation.toml"), "min_tasks = 0\n").unwrap();
+        std::fs::write(dir.path().join("changes/strict/tasks.md"), "# Tasks\n").unwrap();
+        assert!(validate(dir.path(), "strict", None).unwrap().valid);
+    }
+
+    #[test]
+    fn test_restore_spec() {
+        let dir = tempfile::tempdir().unwrap();
+        new_spec(dir.path(), "revived").unwrap();
+        let archived = archive(dir.path(), "revived").unwrap();
+        let dir_name = archived.file_name().unwrap().to_string_lossy().to_string();
+        new_spec(dir.path(), "revived").unwrap();
+
+        // Name collision with the new active spec
+        let err = restore(dir.path(), "revived", None, false).unwrap_err();
+        assert!(err.to_string().contains("--as"));
+
+        let result = restore(dir.path(), &dir_name, Some("revived-v1"), false).unwrap();
+        assert_eq!(result.name, "revived-v1");
+        assert_eq!(result.phase, SpecPhase::Draft);
+        assert!(!archived.exists());
+        let meta = read_meta(&result.path).unwrap().unwrap();
+        assert_eq!(meta.status.as_deref(), Some("draft"));
+        assert_eq!(meta.archived, None);
+
+        archive(dir.path(), "revived").unwrap();
+        let result = restore(dir.path(), "revived", None, true).unwrap();
+        assert_eq!(result.name, result.archived);
+        assert_eq!(strip_date_prefix(&result.name), "revived");
+        assert!(restore(dir.path(), "revived", None, false).is_err());
+    }
+
+    #[test]
+    fn test_list_specs_filtered() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["charlie", "alpha", "bravo", "gone"] {
+            new_spec(dir.path(), name).unwrap();
+        }
+        archive(dir.path(), "gone").unwrap();
+        std::fs::write(
+            dir.path().join("changes/bravo/tasks.md"),
+            "- [x] one\n- [ ] two\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("changes/alpha/tasks.md"), "- [x] one\n").unwrap();
+        set_status(dir.path(), "charlie", SpecPhase::Abandoned).unwrap();
+
+        let names = |filter: &SpecFilter| -> Vec<String> {
+            list_specs_filtered(dir.path(), filter)
+                .unwrap()
+                .into_iter()
+                .map(|s| s.name)
+                .collect()
+        };
+
+        let all = names(&SpecFilter::default());
+        assert_eq!(all[..3], ["alpha", "bravo", "charlie"]);
+        assert!(all[3].ends_with("-gone"));
+
+        let filter = SpecFilter {
+            exclude_archived: true,
+            sort: SpecSort::Progress,
+            ..Default::default()
+        };
+        assert_eq!(names(&filter), ["charlie", "bravo", "alpha"]);
+
+        let filter = SpecFilter {
+            phases: vec![SpecPhase::Draft],
+            ..Default::default()
+        };
+        assert_eq!(names(&filter), ["alpha", "bravo"]);
+    }
+
+    #[test]
+    fn test_nested_tasks_and_section_progress() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tasks.md");
+        std::fs::write(
+            &path,
+            "- [x] loose\n## Implementation\n- [ ] parser\n  - [x] lexer\n    - [x] tokens\n  - [ ] ast\n- [x] cli\n## Testing\n\t- [ ] unit\n",
+        )
+        .unwrap();
+
+        let tasks = read_tasks(&path).unwrap();
+        let shape: Vec<_> = tasks
+            .iter()
+            .map(|t| (t.number, t.depth, t.parent))
+            .collect();
+        assert_eq!(
+            shape,
+            vec![
+                (1, 0, None),
+                (2, 0, None),
+                (3, 1, Some(2)),
+                (4, 2, Some(3)),
+                (5, 1, Some(2)),
+                (6, 0, None),
+                (7, 0, None),
+            ]
+        );
+
+        let sections = section_progress(&tasks);
+        assert_eq!(sections.len(), 3);
+        assert_eq!(sections[0].section, None);
+        assert_eq!(
+            sections[1],
+            SectionProgress {
+                section: Some("Implementation".to_string()),
+                total: 5,
+                done: 3,
+            }
+        );
+        assert_eq!((sections[2].total, sections[2].done), (1, 0));
+    }
+
+    #[test]
+    fn test_frontmatter_in_spec_info() {
+        let dir = tempfile::tempdir().unwrap();
+        let info = new_spec(dir.path(), "fm").unwrap();
+        assert_eq!(info.title, None);
+
+        let proposal = std::fs::read_to_string(info.path.join("proposal.md")).unwrap();
+        std::fs::write(
+            info.path.join("proposal.md"),
+            format!("---\ntitle: \"Rate limiting\"\nrequirements: [REQ-1, REQ-2]\n---\n{proposal}"),
+        )
+        .unwrap();
+        let design = std::fs::read_to_string(info.path.join("design.md")).unwrap();
+        std::fs::write(
+            info.path.join("design.md"),
+            format!("---\ntitle: ignored\nrisk: high\nrequirements:\n  - REQ-2\n  - REQ-3\n---\n{design}"),
+        )
+        .unwrap();
+
+        let info = spec_info(dir.path(), "fm").unwrap();
+        assert_eq!(info.title.as_deref(), Some("Rate limiting"));
+        assert_eq!(info.risk.as_deref(), Some("high"));
+        assert_eq!(info.requirements, vec!["REQ-1", "REQ-2", "REQ-3"]);
+        assert!(validate(dir.path(), "fm", None).unwrap().valid);
+
+        assert_eq!(Frontmatter::parse("# No frontmatter\n---\n"), None);
+        assert_eq!(Frontmatter::parse("---\ntitle: unterminated\n"), None);
+    }
+
+    #[test]
+    fn test_tracking_issue_body() {
+        let dir = tempfile::tempdir().unwrap();
+        let info = new_spec(dir.path(), "tracked").unwrap();
+        std::fs::write(
+            info.path.join("tasks.md"),
+            "# Tasks\n\n## Build\n\n- [x] parser\n  - [ ] ast\n- [ ] \n\n## Verify\n\n- [ ] tests\n",
+        )
+        .unwrap();
+
+        let body = tracking_issue_body(&info.path).unwrap();
+        assert!(body.starts_with("Tracking issue for spec `tracked`."));
+        assert!(
+            body.ends_with("\n## Build\n\n- [x] parser\n  - [ ] ast\n\n## Verify\n\n- [ ] tests\n")
+        );
+
+        set_tracking_issue(&info.path, 42).unwrap();
+        assert_eq!(spec_info(dir.path(), "tracked").unwrap().issue, Some(42));
+    }
+
+    #[test]
+    fn test_current_spec_from_branch_and_worktree() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let manifest = smctl_workspace::WorkspaceManifest::parse(
+            r#"
+[workspace]
+name = "ws"
+
+[[repos]]
+name = "app"
+url = "https://example.com/app"
+"#,
+        )
+        .unwrap();
+        let openspec_dir = root.join("openspec");
+        new_spec(&openspec_dir, "router-cache").unwrap();
+        new_spec(&openspec_dir, "in-worktree").unwrap();
+
+        let app = root.join("app");
+        std::fs::create_dir_all(&app).unwrap();
+        for args in [
+            vec!["init", "-q", "-b", "main"],
+            vec![
+                "-c",
+                "user.name=T",
+                "-c",
+                "user.email=t@t",
+                "commit",
+                "-q",
+                "--allow-empty",
+                "-m",
+                "init",
+            ],
+        ] {
+            std::process::Command::new("git")
+                .args(&args)
+                .current_dir(&app)
+                .output()
+                .unwrap();
+        }
+        assert_eq!(current_spec(root, &manifest, &openspec_dir, &app), None);
+
+        std::process::Command::new("git")
+            .args(["checkout", "-q", "-b", "feature/router-cache"])
+            .current_dir(&app)
+            .output()
+            .unwrap();
+        assert_eq!(
+            current_spec(root, &manifest, &openspec_dir, &app).as_deref(),
+            Some("router-cache")
+        );
+
+        let worktree = root.join(".worktrees/in-worktree/app");
+        assert_eq!(
+            current_spec(root, &manifest, &openspec_dir, &worktree).as_deref(),
+            Some("in-worktree")
+        );
+    }
+
+    #[test]
+    fn test_burndown_from_git_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let git = |args: &[&str]| {
+            let out = std::process::Command::new("git")
+                .args(["-c", "user.name=T", "-c", "user.email=t@t"])
+                .args(args)
+                .current_dir(dir.path())
+                .output()
+                .unwrap();
+            assert!(
+                out.status.success(),
+                "{}",
+                String::from_utf8_lossy(&out.stderr)
+            );
+        };
+        git(&["init", "-q", "-b", "main"]);
+        let openspec = dir.path().join("openspec");
+        let info = new_spec(&openspec, "burn").unwrap();
+        let tasks = info.path.join("tasks.md");
+
+        std::fs::write(&tasks, "- [ ] a\n- [ ] b\n- [ ] c\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-q", "-m", "start"]);
+        std::fs::write(&tasks, "- [x] a\n- [ ] b\n- [ ] c\n").unwrap();
+        git(&["commit", "-q", "-am", "a"]);
+        git(&["commit", "-q", "--allow-empty", "-m", "unrelated"]);
+        std::fs::write(&tasks, "- [x] a\n- [x] b\n- [x] c\n").unwrap();
+
+        let report = burndown(&openspec, "burn").unwrap();
+        let remaining: Vec<_> = report.points.iter().map(|p| p.remaining).collect();
+        assert_eq!(remaining, vec![3, 2, 0]);
+        assert!(report.points[0].commit.is_some());
+        assert_eq!(report.points[2].commit, None);
+        assert_eq!(report.sparkline(), "█▅▁");
+
+        git(&["commit", "-q", "-am", "done"]);
+        let dest = archive(&openspec, "burn").unwrap();
+        git(&["add", "-A"]);
+        git(&["commit", "-q", "-m", "archive"]);
+        let archived = dest.file_name().unwrap().to_string_lossy().to_string();
+        let report = burndown(&openspec, &archived).unwrap();
+        assert_eq!(report.points.len(), 3);
+    }
+
+    #[test]
+    fn test_validate_impact_against_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let info = new_spec(dir.path(), "impact").unwrap();
+        let manifest = smctl_workspace::WorkspaceManifest::parse(
+            r#"
+[workspace]
+name = "ws"
+
+[[repos]]
+name = "ModelGate"
+url = "https://example.com/ModelGate"
+"#,
+        )
+        .unwrap();
+
+        // The template's blank row
+        let result = validate(dir.path(), "impact", Some(&manifest)).unwrap();
+        assert_eq!(
+            result.warnings,
+            ["proposal.md Affected Repos table is empty"]
+        );
+        assert!(
+            validate(dir.path(), "impact", None)
+                .unwrap()
+                .warnings
+                .is_empty()
+        );
+
+        let proposal = std::fs::read_to_string(info.path.join("proposal.md")).unwrap();
+        std::fs::write(
+            info.path.join("proposal.md"),
+            proposal.replace(
+                "| | |",
+                "| `ModelGate` | new endpoint |\n| ghost | |\n| | |",
+            ),
+        )
+        .unwrap();
+        let result = validate(dir.path(), "impact", Some(&manifest)).unwrap();
+        assert!(result.valid);
+        assert_eq!(
+            result.warnings,
+            [
+                "proposal.md Affected Repos names unknown repo 'ghost'",
+                "proposal.md Affected Repos row for 'ghost' has no impact",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scaffold_repos() {
+        let dir = tempfile::tempdir().unwrap();
+        let info = new_spec(dir.path(), "multi").unwrap();
+        let repos = vec!["api".to_string(), "web".to_string()];
+        scaffold_repos(dir.path(), "multi", &repos).unwrap();
+
+        let tasks = std::fs::read_to_string(info.path.join("tasks.md")).unwrap();
+        assert!(tasks.contains(
+            "## Implementation\n\n### api\n\n- [ ] \n\n### web\n\n- [ ] \n\n## Testing\n"
+        ));
+        let proposal = std::fs::read_to_string(info.path.join("proposal.md")).unwrap();
+        assert!(
+            proposal.contains(
+                "| Repository | Impact |\n|---|---|\n| api | |\n| web | |\n\n## References"
+            )
+        );
+
+        // Idempotent for repos already scaffolded
+        scaffold_repos(dir.path(), "multi", &["web".to_string(), "db".to_string()]).unwrap();
+        let tasks = std::fs::read_to_string(info.path.join("tasks.md")).unwrap();
+        assert_eq!(tasks.matches("### web").count(), 1);
+        assert!(tasks.contains("### db\n\n- [ ] \n\n## Testing"));
+        let proposal = std::fs::read_to_string(info.path.join("proposal.md")).unwrap();
+        assert_eq!(affected_repos(&proposal), ["api", "web", "db"]);
+    }
+
+    #[test]
+    fn test_archive_stages_git_move() {
+        let dir = tempfile::tempdir().unwrap();
+        let git = |args: &[&str]| {
+            let out = std::process::Command::new("git")
+                .args(args)
+                .current_dir(dir.path())
+                .output()
+                .unwrap();
+            assert!(
+                out.status.success(),
+                "{}",
+                String::from_utf8_lossy(&out.stderr)
+            );
+            String::from_utf8_lossy(&out.stdout).to_string()
+        };
+        git(&["init", "-q", "-b", "main"]);
+        git(&["config", "user.name", "T"]);
+        git(&["config", "user.email", "t@t"]);
+        let openspec = dir.path().join("openspec");
+        new_spec(&openspec, "moved").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-q", "-m", "start"]);
+
+        let dest = archive(&openspec, "moved").unwrap();
+        let archived = dest.file_name().unwrap().to_string_lossy().to_string();
+        let status = git(&["status", "--porcelain"]);
+        assert!(
+            status.contains(&format!(
+                "R  openspec/changes/moved/tasks.md -> openspec/changes/archive/{archived}/tasks.md"
+            )),
+            "{status}"
+        );
+        assert!(!status.contains("??"), "{status}");
+
+        // Unrelated staged work stays out of the archive commit
+        std::fs::write(dir.path().join("other.txt"), "wip\n").unwrap();
+        git(&["add", "other.txt"]);
+
+        let oid = commit_archive(&openspec, "moved", &dest).unwrap();
+        assert_eq!(git(&["rev-parse", "HEAD"]).trim(), oid);
+        assert_eq!(
+            git(&["log", "-1", "--format=%s"]).trim(),
+            "Archive spec 'moved'"
+        );
+        let committed = git(&["show", "--name-status", "--no-renames", "--format=", "HEAD"]);
+        assert!(!committed.contains("other.txt"), "{committed}");
+        assert!(
+            committed.contains("D\topenspec/changes/moved/tasks.md"),
+            "{committed}"
+        );
+        assert_eq!(git(&["status", "--porcelain"]), "A  other.txt\n");
+    }
+
+    #[test]
+    fn test_promote_deltas() {
+        let dir = tempfile::tempdir().unwrap();
+        let write_delta = |spec: &str, delta: &str| {
+            new_spec(dir.path(), spec).unwrap();
+            let path = new_delta(dir.path(), spec, "routing").unwrap();
+            std::fs::write(path, delta).unwrap();
+            archive(dir.path(), spec).unwrap();
+        };
+
+        write_delta(
+            "first",
+            "## ADDED Requirements\n\n### Requirement: Cache\n\nThe router SHALL cache.\n\n#### Scenario: hit\n\n- **WHEN** x\n\n### Requirement: Log\n\nThe router SHALL log.\n",
+        );
+        let results = promote(dir.path(), "first").unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].created);
+        assert_eq!(results[0].added, ["Cache", "Log"]);
+        let path = dir.path().join("specs/routing/spec.md");
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "# routing Specification\n\n## Purpose\n\nTBD\n\n## Requirements\n\n### Requirement: Cache\n\nThe router SHALL cache.\n\n#### Scenario: hit\n\n- **WHEN** x\n\n### Requirement: Log\n\nThe router SHALL log.\n"
+        );
+
+        // Sections after Requirements are kept
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, format!("{content}\n## Notes\n\nKeep me.\n")).unwrap();
+        write_delta(
+            "second",
+            "## ADDED Requirements\n\n### Requirement: Metrics\n\nSHALL count.\n\n## MODIFIED Requirements\n\n### Requirement: Cache\n\nThe router SHALL cache for 60s.\n\n## REMOVED Requirements\n\n### Requirement: Log\n\n### Requirement: Ghost\n",
+        );
+        let result = &promote(dir.path(), "second").unwrap()[0];
+        assert!(!result.created);
+        assert_eq!(result.added, ["Metrics"]);
+        assert_eq!(result.modified, ["Cache"]);
+        assert_eq!(result.removed, ["Log"]);
+        assert_eq!(result.warnings, ["removed requirement 'Ghost' not found"]);
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "# routing Specification\n\n## Purpose\n\nTBD\n\n## Requirements\n\n### Requirement: Cache\n\nThe router SHALL cache for 60s.\n\n### Requirement: Metrics\n\nSHALL count.\n\n## Notes\n\nKeep me.\n"
+        );
+
+        assert!(promote(dir.path(), "missing").is_err());
+    }
+
+    #[test]
+    fn test_sequential_ids() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = assign_id(dir.path(), "router-cache").unwrap();
+        assert_eq!(first, "0001-router-cache");
+        new_spec(dir.path(), &first).unwrap();
+        assert_eq!(assign_id(dir.path(), "auth").unwrap(), "0002-auth");
+        assert!(assign_id(dir.path(), "router-cache").is_err());
+
+        assert_eq!(resolve_name(dir.path(), "1").unwrap(), first);
+        assert_eq!(resolve_name(dir.path(), "0001").unwrap(), first);
+        assert_eq!(resolve_name(dir.path(), "router-cache").unwrap(), first);
+        assert_eq!(resolve_name(dir.path(), &first).unwrap(), first);
+        assert_eq!(resolve_name(dir.path(), "other").unwrap(), "other");
+        assert!(resolve_name(dir.path(), "7").is_err());
+
+        // Archived specs keep their ID; renames follow it
+        rename(dir.path(), &first, "0001-route-cache").unwrap();
+        assert_eq!(resolve_name(dir.path(), "1").unwrap(), "0001-route-cache");
+        archive(dir.path(), "0001-route-cache").unwrap();
+        assert_eq!(
+            resolve_name(dir.path(), "route-cache").unwrap(),
+            "0001-route-cache"
+        );
+        assert_eq!(SpecIndex::load(dir.path()).unwrap().next_id(), 3);
+    }
+
+    #[test]
+    fn test_import_spec() {
+        let dir = tempfile::tempdir().unwrap();
+        let document = "# Router cache design\n\nCache routes.\n\n## Plan\n\n- [x] sketch\n  - [ ] benchmark\n\n## Rollout\n\n- [ ] flag\n";
+        let info = import_spec(dir.path(), "router-cache", document).unwrap();
+        assert_eq!(info.title.as_deref(), Some("Router cache design"));
+        assert_eq!((info.tasks_total, info.tasks_done), (3, 1));
+
+        let proposal = std::fs::read_to_string(info.path.join("proposal.md")).unwrap();
+        assert!(proposal.starts_with(
+            "---\ntitle: Router cache design\n---\n# router-cache — Proposal\n\nCache routes.\n"
+        ));
+        let tasks = std::fs::read_to_string(info.path.join("tasks.md")).unwrap();
+        assert_eq!(
+            tasks,
+            "# router-cache — Tasks\n\n## Plan\n\n- [x] sketch\n  - [ ] benchmark\n\n## Rollout\n\n- [ ] flag\n"
+        );
+
+        // No checkboxes: the template tasks stay
+        let info = import_spec(dir.path(), "plain", "Just prose.\n").unwrap();
+        assert!(info.path.join("tasks.md").exists());
+        assert_eq!(info.tasks_total, 4);
+        assert!(import_spec(dir.path(), "plain", "again").is_err());
+    }
+
+    #[test]
+    fn test_trace_requirements() {
+        let dir = tempfile::tempdir().unwrap();
+        let info = new_spec(dir.path(), "traced").unwrap();
+        let proposal = std::fs::read_to_string(info.path.join("proposal.md")).unwrap();
+        std::fs::write(
+            info.path.join("proposal.md"),
+            format!("---\nrequirements: [SEC-7]\n---\n{proposal}\nREQ-1 caches routes; REQ-2 expires them. Not XREQ-9.\n"),
+        )
+        .unwrap();
+        std::fs::write(
+            info.path.join("tasks.md"),
+            "## Implementation\n\n- [x] cache (REQ-1) `test_cache_hit`\n- [ ] REQ-1 metrics\n- [x] audit SEC-7 `test_audit`\n- [ ] REQ-12 typo\n",
+        )
+        .unwrap();
+
+        let matrix = trace(dir.path(), "traced").unwrap();
+        let ids: Vec<_> = matrix.requirements.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, ["SEC-7", "REQ-1", "REQ-2"]);
+        assert_eq!(
+            matrix.requirements[1],
+            TraceRow {
+                id: "REQ-1".to_string(),
+                tasks: vec![1, 2],
+                tests: vec!["test_cache_hit".to_string()],
+                done: false,
+            }
+        );
+        assert!(matrix.requirements[0].done);
+        assert_eq!(matrix.untraced, ["REQ-2"]);
+        assert_eq!(matrix.unknown, [(4, "REQ-12".to_string())]);
+    }
+
     #[test]
     fn test_new_spec() {
         let dir = tempfile::tempdir().unwrap();
@@ -374,7 +3477,7 @@ mod tests {
     fn test_validate_spec() {
         let dir = tempfile::tempdir().unwrap();
         new_spec(dir.path(), "valid-spec").unwrap();
-        let result = validate(dir.path(), "valid-spec").unwrap();
+        let result = validate(dir.path(), "valid-spec", None).unwrap();
         assert!(result.valid, "issues: {:?}", result.issues);
     }
 
@@ -407,9 +3510,50 @@ mod tests {
     }
 
     #[test]
-    fn test_spec_info_phase_active_when_all_done() {
+    fn test_spec_info_phase_from_openspec_yaml() {
+        let dir = tempfile::tempdir().unwrap();
+        new_spec(dir.path(), "yaml-spec").unwrap();
+        let spec_dir = dir.path().join("changes/yaml-spec");
+        std::fs::write(
+            spec_dir.join("tasks.md"),
+            "# Tasks\n- [x] Task one\n- [x] Task two\n",
+        )
+        .unwrap();
+
+        // The stored status wins over task completion
+        let info = spec_info(dir.path(), "yaml-spec").unwrap();
+        assert_eq!(info.phase, SpecPhase::Draft);
+        assert!(
+            info.created
+                .as_deref()
+                .is_some_and(|c| chrono::DateTime::parse_from_rfc3339(c).is_ok())
+        );
+
+        let info = set_status(dir.path(), "yaml-spec", SpecPhase::Abandoned).unwrap();
+        assert_eq!(info.phase, SpecPhase::Abandoned);
+        assert_eq!(
+            spec_info(dir.path(), "yaml-spec").unwrap().phase,
+            SpecPhase::Abandoned
+        );
+        let meta = read_meta(&spec_dir).unwrap().unwrap();
+        assert_eq!(meta.status.as_deref(), Some("abandoned"));
+        assert_eq!(meta.schema.as_deref(), Some("spec-driven"));
+        assert!(set_status(dir.path(), "yaml-spec", SpecPhase::Archived).is_err());
+
+        let dest = archive(dir.path(), "yaml-spec").unwrap();
+        let meta = read_meta(&dest).unwrap().unwrap();
+        assert_eq!(meta.status.as_deref(), Some("archived"));
+        assert!(meta.archived.is_some_and(|t| t.ends_with('Z')));
+        let prefix = dest.file_name().unwrap().to_string_lossy()[..10].to_string();
+        assert!(chrono::NaiveDate::parse_from_str(&prefix, "%Y-%m-%d").is_ok());
+    }
+
+    #[test]
+    fn test_spec_info_phase_falls_back_to_tasks() {
         let dir = tempfile::tempdir().unwrap();
         new_spec(dir.path(), "done-spec").unwrap();
+        // Without .openspec.yaml the phase is inferred from tasks
+        std::fs::remove_file(dir.path().join("changes/done-spec/.openspec.yaml")).unwrap();
         // Overwrite tasks.md with all tasks completed
         std::fs::write(
             dir.path().join("changes/done-spec/tasks.md"),
@@ -422,13 +3566,29 @@ mod tests {
         assert_eq!(info.tasks_total, 2);
     }
 
+    #[test]
+    fn test_fast_forward_readiness() {
+        let dir = tempfile::tempdir().unwrap();
+        let info = new_spec(dir.path(), "ff").unwrap();
+
+        let report = fast_forward(dir.path(), "ff").unwrap();
+        assert!(report.ready);
+        assert_eq!(report.next_phase, Some(SpecPhase::Active));
+
+        std::fs::write(info.path.join("design.md"), "# Design\n").unwrap();
+        let report = fast_forward(dir.path(), "ff").unwrap();
+        assert!(!report.ready);
+        assert_eq!(report.next_phase, None);
+        assert!(report.issues.iter().any(|i| i.contains("## Decisions")));
+    }
+
     #[test]
     fn test_validate_missing_sections() {
         let dir = tempfile::tempdir().unwrap();
         new_spec(dir.path(), "bad-spec").unwrap();
         // Overwrite proposal.md with empty content
         std::fs::write(dir.path().join("changes/bad-spec/proposal.md"), "# Empty\n").unwrap();
-        let result = validate(dir.path(), "bad-spec").unwrap();
+        let result = validate(dir.path(), "bad-spec", None).unwrap();
         assert!(!result.valid);
         assert!(result.issues.iter().any(|i| i.contains("Why")));
     }
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::path::Path;
 
 use anyhow::{Context, Result};
@@ -15,6 +16,8 @@ pub struct WorkspaceManifest {
     pub worktree: WorktreeConfig,
     #[serde(default)]
     pub spec: SpecConfig,
+    #[serde(default)]
+    pub build: BuildConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,18 +42,116 @@ pub struct RepoConfig {
     /// If true, this repo is where smctl lives.
     #[serde(default)]
     pub smctl_home: bool,
-    /// Build command for this repo.
+    /// Build command for this repo: a single command or a list of steps run in order.
     #[serde(default)]
-    pub build_cmd: Option<String>,
+    pub build_cmd: Option<CommandSpec>,
     /// Test command for this repo.
     #[serde(default)]
     pub test_cmd: Option<String>,
     /// Clean command for this repo.
     #[serde(default)]
     pub clean_cmd: Option<String>,
+    /// Coverage command printing `llvm-cov export` JSON (for `smctl coverage`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coverage_cmd: Option<String>,
+    /// Benchmark command (for `smctl bench`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bench_cmd: Option<String>,
+    /// Command run before `build_cmd` (e.g. codegen).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_build_cmd: Option<String>,
+    /// Command run after a successful `build_cmd`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_build_cmd: Option<String>,
+    /// Run commands through `sh -c` (`cmd /C` on Windows) instead of lexing them.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub shell: bool,
+    /// Build and test on a remote host over ssh (`[repos.remote]`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote: Option<RemoteConfig>,
+    /// Globs (relative to the repo) of build outputs to collect after a successful build.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub artifacts: Vec<String>,
     /// Repos this repo depends on (for build ordering).
     #[serde(default)]
     pub depends_on: Vec<String>,
+    /// Share of the parallel build budget this repo occupies while building
+    /// (default 1; raise it for memory-heavy repos).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub build_weight: Option<usize>,
+    /// Cedar policies validated by `smctl build --cedar` (`[repos.cedar]`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cedar: Option<CedarConfig>,
+    /// Named build profiles (`[repos.profiles.<name>]`) overriding build/test commands.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, BuildProfile>,
+    /// Files whose version `flow release start` bumps to the release's:
+    /// `Cargo.toml`, `package.json` or a plain version file like `VERSION`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub version_files: Vec<String>,
+    /// Untracked files (relative to the repo) copied from the main checkout
+    /// into each new worktree, e.g. `.env`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub worktree_copy: Vec<String>,
+    /// Where this repo's worktree sets live instead of `[worktree] base_dir`
+    /// (relative to the workspace root, or absolute), e.g. on another disk.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub worktree_base_dir: Option<String>,
+}
+
+/// Cedar policy locations for a repo, relative to its checkout.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CedarConfig {
+    /// Directories searched recursively for `*.cedar` policy files.
+    pub policies: Vec<String>,
+    /// Schema to validate against; without one policies are only parsed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<String>,
+}
+
+/// Remote build host for a repo.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteConfig {
+    /// ssh destination (`host` or `user@host`).
+    pub host: String,
+    /// Checkout directory on the remote host.
+    pub path: String,
+    /// ssh program and options (default: `ssh`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ssh: Option<String>,
+}
+
+/// A command given either as one string or as an array of steps.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum CommandSpec {
+    Single(String),
+    Steps(Vec<String>),
+}
+
+impl CommandSpec {
+    /// Commands to run, in order.
+    pub fn steps(&self) -> Vec<&str> {
+        match self {
+            CommandSpec::Single(cmd) => vec![cmd.as_str()],
+            CommandSpec::Steps(steps) => steps.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+impl From<&str> for CommandSpec {
+    fn from(cmd: &str) -> Self {
+        CommandSpec::Single(cmd.to_string())
+    }
+}
+
+/// Build/test command overrides for a named profile.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BuildProfile {
+    #[serde(default)]
+    pub build_cmd: Option<CommandSpec>,
+    #[serde(default)]
+    pub test_cmd: Option<String>,
 }
 
 fn default_branch() -> String {
@@ -69,6 +170,159 @@ pub struct FlowConfig {
     pub release_prefix: String,
     #[serde(default = "default_hotfix_prefix")]
     pub hotfix_prefix: String,
+    #[serde(default = "default_bugfix_prefix")]
+    pub bugfix_prefix: String,
+    #[serde(default = "default_support_prefix")]
+    pub support_prefix: String,
+    /// Tag created on main by `release finish`; `{version}` is replaced.
+    #[serde(default = "default_tag_format")]
+    pub tag_format: String,
+    /// How `feature finish` lands features: merged locally, or as pull requests.
+    #[serde(default)]
+    pub finish: FinishMode,
+    /// How finish operations merge branches; `--strategy` overrides it.
+    #[serde(default)]
+    pub merge_strategy: MergeStrategy,
+    /// Pull request settings (`[flow.pull_request]`); defaults to GitHub.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pull_request: Option<PullRequestConfig>,
+    /// Commands run around flow operations (`[flow.hooks]`).
+    #[serde(default, skip_serializing_if = "FlowHooks::is_empty")]
+    pub hooks: FlowHooks,
+}
+
+/// Hook commands for flow operations, run from the workspace root through
+/// the platform shell with `SMCTL_FLOW_HOOK`, `SMCTL_FLOW_OPERATION` and
+/// `SMCTL_FLOW_BRANCH` set.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FlowHooks {
+    /// Before `start`; a failing step aborts it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_start: Option<CommandSpec>,
+    /// Before `finish` merges anything; a failing step aborts it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_finish: Option<CommandSpec>,
+    /// After a `finish` merged in every repo; failures are only reported.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_finish: Option<CommandSpec>,
+}
+
+impl FlowHooks {
+    pub fn is_empty(&self) -> bool {
+        self.pre_start.is_none() && self.pre_finish.is_none() && self.post_finish.is_none()
+    }
+}
+
+/// How `feature finish` lands a feature (`[flow] finish`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FinishMode {
+    /// Merge into develop locally.
+    #[default]
+    Merge,
+    /// Push the branch and open a pull (merge) request against develop.
+    PullRequest,
+}
+
+/// How a finished branch is merged into its target.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MergeStrategy {
+    /// Always create a merge commit (`merge --no-ff`).
+    #[default]
+    NoFf,
+    /// Squash the branch into a single commit on the target.
+    Squash,
+    /// Rebase the branch onto the target, then fast-forward.
+    Rebase,
+    /// Fast-forward only; fails if the target has moved on.
+    FfOnly,
+}
+
+impl MergeStrategy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::NoFf => "no-ff",
+            Self::Squash => "squash",
+            Self::Rebase => "rebase",
+            Self::FfOnly => "ff-only",
+        }
+    }
+}
+
+impl std::fmt::Display for MergeStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for MergeStrategy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "no-ff" | "merge" => Ok(Self::NoFf),
+            "squash" => Ok(Self::Squash),
+            "rebase" => Ok(Self::Rebase),
+            "ff-only" => Ok(Self::FfOnly),
+            other => anyhow::bail!(
+                "unknown merge strategy '{other}' (expected no-ff, squash, rebase or ff-only)"
+            ),
+        }
+    }
+}
+
+/// Code host used for pull requests.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PullRequestProvider {
+    #[default]
+    Github,
+    Gitlab,
+}
+
+/// Pull request settings for `finish = "pull-request"` (`[flow.pull_request]`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PullRequestConfig {
+    #[serde(default)]
+    pub provider: PullRequestProvider,
+    /// Environment variable holding the API token (default `GITHUB_TOKEN`
+    /// or `GITLAB_TOKEN`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_env: Option<String>,
+    /// API base URL, for GitHub Enterprise or self-hosted GitLab.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_url: Option<String>,
+    /// Remote the feature branch is pushed to.
+    #[serde(default = "default_remote")]
+    pub remote: String,
+}
+
+impl PullRequestConfig {
+    pub fn token_env(&self) -> &str {
+        self.token_env.as_deref().unwrap_or(match self.provider {
+            PullRequestProvider::Github => "GITHUB_TOKEN",
+            PullRequestProvider::Gitlab => "GITLAB_TOKEN",
+        })
+    }
+
+    pub fn api_url(&self) -> &str {
+        self.api_url.as_deref().unwrap_or(match self.provider {
+            PullRequestProvider::Github => "https://api.github.com",
+            PullRequestProvider::Gitlab => "https://gitlab.com/api/v4",
+        })
+    }
+}
+
+fn default_remote() -> String {
+    "origin".to_string()
+}
+
+impl FlowConfig {
+    /// Release tag for `version`, per `tag_format`.
+    pub fn release_tag(&self, version: &str) -> String {
+        self.tag_format.replace("{version}", version)
+    }
 }
 
 fn default_main_branch() -> String {
@@ -86,6 +340,15 @@ fn default_release_prefix() -> String {
 fn default_hotfix_prefix() -> String {
     "hotfix/".to_string()
 }
+fn default_bugfix_prefix() -> String {
+    "bugfix/".to_string()
+}
+fn default_support_prefix() -> String {
+    "support/".to_string()
+}
+fn default_tag_format() -> String {
+    "v{version}".to_string()
+}
 
 impl Default for FlowConfig {
     fn default() -> Self {
@@ -95,6 +358,13 @@ impl Default for FlowConfig {
             feature_prefix: default_feature_prefix(),
             release_prefix: default_release_prefix(),
             hotfix_prefix: default_hotfix_prefix(),
+            bugfix_prefix: default_bugfix_prefix(),
+            support_prefix: default_support_prefix(),
+            tag_format: default_tag_format(),
+            finish: FinishMode::default(),
+            merge_strategy: MergeStrategy::default(),
+            pull_request: None,
+            hooks: FlowHooks::default(),
         }
     }
 }
@@ -103,6 +373,20 @@ impl Default for FlowConfig {
 pub struct WorktreeConfig {
     #[serde(default = "default_worktree_base")]
     pub base_dir: String,
+    /// How `worktree sync` brings worktree branches up to date.
+    #[serde(default)]
+    pub sync: SyncMode,
+}
+
+/// How `worktree sync` updates a worktree branch from its base (`[worktree] sync`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SyncMode {
+    /// Rebase the branch onto its base.
+    #[default]
+    Rebase,
+    /// Merge the base into the branch.
+    Merge,
 }
 
 fn default_worktree_base() -> String {
@@ -113,6 +397,7 @@ impl Default for WorktreeConfig {
     fn default() -> Self {
         Self {
             base_dir: default_worktree_base(),
+            sync: SyncMode::default(),
         }
     }
 }
@@ -121,6 +406,13 @@ impl Default for WorktreeConfig {
 pub struct SpecConfig {
     #[serde(default = "default_openspec_dir")]
     pub openspec_dir: String,
+    /// Prefix new spec names with a sequential ID (`0042-add-router-cache`),
+    /// recorded in `<openspec_dir>/index.toml`.
+    #[serde(default)]
+    pub numbered: bool,
+    /// Opt-in GitHub issue tracking for specs (`[spec.github]`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub github: Option<SpecGithubConfig>,
 }
 
 fn default_openspec_dir() -> String {
@@ -131,15 +423,93 @@ impl Default for SpecConfig {
     fn default() -> Self {
         Self {
             openspec_dir: default_openspec_dir(),
+            numbered: false,
+            github: None,
         }
     }
 }
 
+/// Tracking issues for specs (`[spec.github]`): created by `spec new`,
+/// task checkboxes synced on change, closed by `spec archive`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpecGithubConfig {
+    /// `owner/name` of the repository that holds the issues.
+    pub repo: String,
+    /// Environment variable holding the API token.
+    #[serde(default = "default_github_token_env")]
+    pub token_env: String,
+    /// Labels added to new tracking issues.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
+    /// API base URL, for GitHub Enterprise.
+    #[serde(default = "default_github_api_url")]
+    pub api_url: String,
+}
+
+fn default_github_token_env() -> String {
+    "GITHUB_TOKEN".to_string()
+}
+
+fn default_github_api_url() -> String {
+    "https://api.github.com".to_string()
+}
+
+/// Workspace-wide build settings (`[build]`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BuildConfig {
+    /// Maximum total `build_weight` of repos built at once in parallel mode
+    /// (with default weights, the number of concurrent repos).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_parallel: Option<usize>,
+    /// Times a failed test command is rerun before being marked failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub test_retries: Option<u32>,
+    /// Cedar CLI used by `smctl build --cedar` (default: `cedar`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cedar_cmd: Option<String>,
+    #[serde(default)]
+    pub hooks: BuildHooks,
+}
+
+/// Workspace-level build hooks (`[build.hooks]`), run from the workspace root.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BuildHooks {
+    /// Run once before any repo is built.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_build: Option<String>,
+    /// Run once after every repo built successfully.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_build: Option<String>,
+}
+
 impl RepoConfig {
     /// Effective local path for this repo within the workspace.
     pub fn local_path(&self) -> &str {
         self.path.as_deref().unwrap_or(&self.name)
     }
+
+    /// Build steps for `profile`, falling back to `build_cmd` and then `cargo build`.
+    pub fn build_cmd_for(&self, profile: Option<&str>) -> Vec<&str> {
+        profile
+            .and_then(|p| self.profiles.get(p))
+            .and_then(|p| p.build_cmd.as_ref())
+            .or(self.build_cmd.as_ref())
+            .map_or_else(|| vec!["cargo build"], CommandSpec::steps)
+    }
+
+    /// Parallel build weight, at least 1.
+    pub fn build_weight(&self) -> usize {
+        self.build_weight.unwrap_or(1).max(1)
+    }
+
+    /// Test command for `profile`, falling back to `test_cmd` and then `cargo test`.
+    pub fn test_cmd_for(&self, profile: Option<&str>) -> &str {
+        profile
+            .and_then(|p| self.profiles.get(p))
+            .and_then(|p| p.test_cmd.as_deref())
+            .or(self.test_cmd.as_deref())
+            .unwrap_or("cargo test")
+    }
 }
 
 impl WorkspaceManifest {
@@ -185,6 +555,27 @@ impl WorkspaceManifest {
     pub fn repo_names(&self) -> Vec<&str> {
         self.repos.iter().map(|r| r.name.as_str()).collect()
     }
+
+    /// Select repos by name, in manifest order (all repos when `names` is None).
+    pub fn select_repos(&self, names: Option<&[String]>) -> Vec<&RepoConfig> {
+        match names {
+            Some(names) => self
+                .repos
+                .iter()
+                .filter(|r| names.iter().any(|n| n == &r.name))
+                .collect(),
+            None => self.repos.iter().collect(),
+        }
+    }
+
+    /// This manifest with only the repos `names` selects, for operations
+    /// that walk every repo.
+    pub fn scoped(&self, names: Option<&[String]>) -> Self {
+        Self {
+            repos: self.select_repos(names).into_iter().cloned().collect(),
+            ..self.clone()
+        }
+    }
 }
 
 /// Repo status information.
@@ -196,6 +587,16 @@ pub struct RepoStatus {
     pub ahead: usize,
     pub behind: usize,
     pub modified_files: usize,
+    /// Number of entries in `git stash list`.
+    #[serde(default)]
+    pub stash_count: usize,
+    #[serde(default)]
+    pub last_commit_sha: Option<String>,
+    #[serde(default)]
+    pub last_commit_summary: Option<String>,
+    /// Commit time of HEAD, in seconds since the Unix epoch.
+    #[serde(default)]
+    pub last_commit_time: Option<i64>,
 }
 
 /// Initialize a new workspace at the given path.
@@ -212,6 +613,7 @@ pub fn init_workspace(root: &Path, name: &str) -> Result<WorkspaceManifest> {
         flow: FlowConfig::default(),
         worktree: WorktreeConfig::default(),
         spec: SpecConfig::default(),
+        build: BuildConfig::default(),
     };
 
     manifest.save_to_root(root)?;
@@ -239,7 +641,20 @@ pub fn add_repo(
         build_cmd: None,
         test_cmd: None,
         clean_cmd: None,
+        coverage_cmd: None,
+        bench_cmd: None,
+        pre_build_cmd: None,
+        post_build_cmd: None,
+        shell: false,
+        remote: None,
+        artifacts: Vec::new(),
         depends_on: Vec::new(),
+        build_weight: None,
+        cedar: None,
+        profiles: BTreeMap::new(),
+        version_files: Vec::new(),
+        worktree_copy: Vec::new(),
+        worktree_base_dir: None,
     });
 
     tracing::info!("added repo '{name}' to workspace");
@@ -260,19 +675,34 @@ pub fn remove_repo(manifest: &mut WorkspaceManifest, name: &str) -> Result<()> {
 /// Get status for a single repo.
 pub fn repo_status(root: &Path, repo: &RepoConfig) -> Result<RepoStatus> {
     let repo_path = root.join(repo.local_path());
-    let git_repo = git2::Repository::open(&repo_path)
+    let mut git_repo = git2::Repository::open(&repo_path)
         .with_context(|| format!("failed to open git repo at {}", repo_path.display()))?;
 
     let head = git_repo.head().context("failed to get HEAD")?;
     let branch = head.shorthand().unwrap_or("detached").to_string();
-
-    let statuses = git_repo
+    let last_commit = head.peel_to_commit().ok();
+    let last_commit_sha = last_commit.as_ref().map(|c| c.id().to_string());
+    let last_commit_summary = last_commit
+        .as_ref()
+        .and_then(|c| c.summary().map(|s| s.to_string()));
+    let last_commit_time = last_commit.as_ref().map(|c| c.time().seconds());
+    drop(last_commit);
+    drop(head);
+
+    let modified_files = git_repo
         .statuses(None)
-        .context("failed to get git status")?;
-
-    let modified_files = statuses.len();
+        .context("failed to get git status")?
+        .len();
     let clean = modified_files == 0;
 
+    let mut stash_count = 0;
+    git_repo
+        .stash_foreach(|_, _, _| {
+            stash_count += 1;
+            true
+        })
+        .context("failed to list stashes")?;
+
     Ok(RepoStatus {
         name: repo.name.clone(),
         branch,
@@ -280,6 +710,448 @@ pub fn repo_status(root: &Path, repo: &RepoConfig) -> Result<RepoStatus> {
         ahead: 0,
         behind: 0,
         modified_files,
+        stash_count,
+        last_commit_sha,
+        last_commit_summary,
+        last_commit_time,
+    })
+}
+
+/// Outcome of syncing a single repo.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum SyncStatus {
+    UpToDate,
+    FastForwarded,
+    NoUpstream,
+    /// Local and upstream have diverged; a fast-forward is not possible.
+    Diverged,
+}
+
+/// Sync result for a single repo.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncResult {
+    pub name: String,
+    pub branch: String,
+    pub status: SyncStatus,
+    pub message: String,
+}
+
+/// Fetch progress reported while syncing.
+#[derive(Debug, Clone, Copy)]
+pub struct SyncProgress {
+    pub received_objects: usize,
+    pub total_objects: usize,
+    pub received_bytes: usize,
+}
+
+/// Fetch the current branch's upstream and fast-forward to it (`git pull --ff-only`).
+pub fn sync_repo(
+    root: &Path,
+    repo: &RepoConfig,
+    mut on_progress: impl FnMut(SyncProgress),
+) -> Result<SyncResult> {
+    let repo_path = root.join(repo.local_path());
+    let git_repo = git2::Repository::open(&repo_path)
+        .with_context(|| format!("failed to open git repo at {}", repo_path.display()))?;
+
+    let head = git_repo.head().context("failed to get HEAD")?;
+    let branch = head.shorthand().unwrap_or("detached").to_string();
+    if !head.is_branch() {
+        return Ok(SyncResult {
+            name: repo.name.clone(),
+            branch,
+            status: SyncStatus::NoUpstream,
+            message: "detached HEAD".to_string(),
+        });
+    }
+
+    let local = git2::Branch::wrap(head);
+    let Ok(upstream) = local.upstream() else {
+        return Ok(SyncResult {
+            name: repo.name.clone(),
+            branch,
+            status: SyncStatus::NoUpstream,
+            message: "no upstream configured".to_string(),
+        });
+    };
+    let upstream_ref = upstream
+        .get()
+        .name()
+        .context("upstream ref name is not valid UTF-8")?
+        .to_string();
+    let remote_name = git_repo
+        .branch_remote_name(&upstream_ref)
+        .context("failed to resolve upstream remote")?;
+    let remote_name = remote_name
+        .as_str()
+        .context("remote name is not valid UTF-8")?
+        .to_string();
+
+    // Fetch
+    let mut callbacks = git2::RemoteCallbacks::new();
+    callbacks.transfer_progress(|stats| {
+        on_progress(SyncProgress {
+            received_objects: stats.received_objects(),
+            total_objects: stats.total_objects(),
+            received_bytes: stats.received_bytes(),
+        });
+        true
+    });
+    callbacks.credentials(|url, username, allowed| {
+        if allowed.contains(git2::CredentialType::SSH_KEY) {
+            git2::Cred::ssh_key_from_agent(username.unwrap_or("git"))
+        } else {
+            let config = git2::Config::open_default()?;
+            git2::Cred::credential_helper(&config, url, username)
+        }
+    });
+    let mut fetch_opts = git2::FetchOptions::new();
+    fetch_opts.remote_callbacks(callbacks);
+
+    let mut remote = git_repo
+        .find_remote(&remote_name)
+        .with_context(|| format!("remote '{remote_name}' not found"))?;
+    remote
+        .fetch::<&str>(&[], Some(&mut fetch_opts), None)
+        .with_context(|| format!("failed to fetch from '{remote_name}'"))?;
+    drop(remote);
+
+    // Fast-forward
+    let upstream_ref = git_repo.find_reference(&upstream_ref)?;
+    let upstream_commit = git_repo.reference_to_annotated_commit(&upstream_ref)?;
+    let (analysis, _) = git_repo.merge_analysis(&[&upstream_commit])?;
+
+    let (status, message) = if analysis.is_up_to_date() {
+        (SyncStatus::UpToDate, "already up to date".to_string())
+    } else if analysis.is_fast_forward() {
+        let target = git_repo.find_object(upstream_commit.id(), None)?;
+        git_repo
+            .checkout_tree(&target, Some(git2::build::CheckoutBuilder::new().safe()))
+            .context("fast-forward would overwrite local changes")?;
+        let mut head_ref = git_repo.head()?;
+        head_ref.set_target(upstream_commit.id(), "smctl sync: fast-forward")?;
+        (
+            SyncStatus::FastForwarded,
+            format!(
+                "fast-forwarded to {}",
+                &upstream_commit.id().to_string()[..7]
+            ),
+        )
+    } else {
+        (
+            SyncStatus::Diverged,
+            "diverged from upstream, not fast-forwardable".to_string(),
+        )
+    };
+
+    Ok(SyncResult {
+        name: repo.name.clone(),
+        branch,
+        status,
+        message,
+    })
+}
+
+/// Options for `gc_repo`.
+#[derive(Debug, Clone, Default)]
+pub struct GcOptions {
+    /// Pass `--aggressive` to `git gc`.
+    pub aggressive: bool,
+    /// Expire reflog entries older than this (e.g. "30.days") before gc.
+    pub expire_reflogs: Option<String>,
+}
+
+/// Maintenance result for a single repo.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GcResult {
+    pub name: String,
+    pub success: bool,
+    pub message: String,
+    /// Size of the git directory before maintenance, in bytes.
+    pub size_before: u64,
+    pub size_after: u64,
+}
+
+/// Run git maintenance on a repo: prune stale worktree entries, optionally
+/// expire reflogs, then `git gc` (which prunes and repacks).
+pub fn gc_repo(root: &Path, repo: &RepoConfig, opts: &GcOptions) -> Result<GcResult> {
+    let repo_path = root.join(repo.local_path());
+    let git_repo = git2::Repository::open(&repo_path)
+        .with_context(|| format!("failed to open git repo at {}", repo_path.display()))?;
+    let git_dir = git_repo.commondir().to_path_buf();
+    let size_before = dir_size(&git_dir);
+
+    let expire = opts
+        .expire_reflogs
+        .as_ref()
+        .map(|e| format!("--expire={e}"));
+    let mut steps: Vec<Vec<&str>> = vec![vec!["worktree", "prune"]];
+    if let Some(expire) = &expire {
+        steps.push(vec!["reflog", "expire", expire, "--all"]);
+    }
+    let mut gc = vec!["gc", "--quiet"];
+    if opts.aggressive {
+        gc.push("--aggressive");
+    }
+    steps.push(gc);
+
+    for args in &steps {
+        let output = std::process::Command::new("git")
+            .args(args)
+            .current_dir(&repo_path)
+            .output()
+            .with_context(|| format!("failed to run git {}", args.join(" ")))?;
+        if !output.status.success() {
+            return Ok(GcResult {
+                name: repo.name.clone(),
+                success: false,
+                message: format!(
+                    "git {} failed: {}",
+                    args.join(" "),
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ),
+                size_before,
+                size_after: dir_size(&git_dir),
+            });
+        }
+    }
+
+    Ok(GcResult {
+        name: repo.name.clone(),
+        success: true,
+        message: "maintenance complete".to_string(),
+        size_before,
+        size_after: dir_size(&git_dir),
+    })
+}
+
+/// Diffstat totals (files changed, lines added/removed).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DiffStat {
+    pub files_changed: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+impl DiffStat {
+    fn add(&mut self, other: &DiffStat) {
+        self.files_changed += other.files_changed;
+        self.insertions += other.insertions;
+        self.deletions += other.deletions;
+    }
+}
+
+/// Diff summary for a single repo: uncommitted work plus commits not yet
+/// on the repo's default branch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoDiff {
+    pub name: String,
+    pub branch: String,
+    /// Branch the unmerged commits are compared against.
+    pub base: String,
+    pub uncommitted: DiffStat,
+    pub unmerged_commits: usize,
+    pub unmerged: DiffStat,
+}
+
+/// Aggregated diff summary across repos.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DiffReport {
+    pub repos: Vec<RepoDiff>,
+    pub total_uncommitted: DiffStat,
+    pub total_unmerged_commits: usize,
+    pub total_unmerged: DiffStat,
+}
+
+impl DiffReport {
+    /// Build a report from per-repo diffs, summing the totals.
+    pub fn from_repos(repos: Vec<RepoDiff>) -> Self {
+        let mut report = DiffReport::default();
+        for repo in &repos {
+            report.total_uncommitted.add(&repo.uncommitted);
+            report.total_unmerged_commits += repo.unmerged_commits;
+            report.total_unmerged.add(&repo.unmerged);
+        }
+        report.repos = repos;
+        report
+    }
+}
+
+/// Compute the diff summary for a single repo.
+pub fn repo_diff(root: &Path, repo: &RepoConfig) -> Result<RepoDiff> {
+    let repo_path = root.join(repo.local_path());
+    let git_repo = git2::Repository::open(&repo_path)
+        .with_context(|| format!("failed to open git repo at {}", repo_path.display()))?;
+
+    let head = git_repo.head().context("failed to get HEAD")?;
+    let branch = head.shorthand().unwrap_or("detached").to_string();
+    let head_commit = head.peel_to_commit().context("HEAD is not a commit")?;
+    let head_tree = head_commit.tree()?;
+
+    // Uncommitted: HEAD vs index + working tree, including untracked files.
+    let mut opts = git2::DiffOptions::new();
+    opts.include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .show_untracked_content(true);
+    let diff = git_repo
+        .diff_tree_to_workdir_with_index(Some(&head_tree), Some(&mut opts))
+        .context("failed to diff working tree")?;
+    let uncommitted = diff_stat(&diff)?;
+
+    // Unmerged: merge-base(default branch, HEAD)..HEAD.
+    let base = repo.default_branch.clone();
+    let base_commit = git_repo
+        .find_branch(&base, git2::BranchType::Local)
+        .or_else(|_| git_repo.find_branch(&format!("origin/{base}"), git2::BranchType::Remote))
+        .ok()
+        .and_then(|b| b.get().peel_to_commit().ok());
+
+    let (unmerged_commits, unmerged) = match base_commit {
+        Some(base_commit) => {
+            let merge_base = git_repo.merge_base(base_commit.id(), head_commit.id())?;
+            let mut walk = git_repo.revwalk()?;
+            walk.push(head_commit.id())?;
+            walk.hide(merge_base)?;
+            let count = walk.count();
+
+            let base_tree = git_repo.find_commit(merge_base)?.tree()?;
+            let diff = git_repo.diff_tree_to_tree(Some(&base_tree), Some(&head_tree), None)?;
+            (count, diff_stat(&diff)?)
+        }
+        None => (0, DiffStat::default()),
+    };
+
+    Ok(RepoDiff {
+        name: repo.name.clone(),
+        branch,
+        base,
+        uncommitted,
+        unmerged_commits,
+        unmerged,
+    })
+}
+
+/// Whether a repo has changes relative to `base` (any revision, e.g. `origin/develop`):
+/// commits since the merge-base plus uncommitted and untracked files.
+///
+/// Returns `Ok(true)` when `base` cannot be resolved, so unknown repos are rebuilt.
+pub fn repo_changed_since(root: &Path, repo: &RepoConfig, base: &str) -> Result<bool> {
+    match changed_files_since(root, repo, base)? {
+        Some(files) => Ok(!files.is_empty()),
+        None => {
+            tracing::warn!(
+                "{}: cannot resolve '{base}', treating as changed",
+                repo.name
+            );
+            Ok(true)
+        }
+    }
+}
+
+/// Files changed in a repo relative to `base`: commits since the merge-base
+/// plus uncommitted and untracked files, as repo-relative paths.
+///
+/// Returns `Ok(None)` when `base` cannot be resolved.
+pub fn changed_files_since(
+    root: &Path,
+    repo: &RepoConfig,
+    base: &str,
+) -> Result<Option<Vec<String>>> {
+    let repo_path = root.join(repo.local_path());
+    let git_repo = git2::Repository::open(&repo_path)
+        .with_context(|| format!("failed to open git repo at {}", repo_path.display()))?;
+
+    let head_commit = git_repo
+        .head()
+        .context("failed to get HEAD")?
+        .peel_to_commit()
+        .context("HEAD is not a commit")?;
+    let Ok(base_commit) = git_repo
+        .revparse_single(base)
+        .and_then(|o| o.peel_to_commit())
+    else {
+        return Ok(None);
+    };
+
+    let merge_base = git_repo.merge_base(base_commit.id(), head_commit.id())?;
+    let base_tree = git_repo.find_commit(merge_base)?.tree()?;
+    let mut opts = git2::DiffOptions::new();
+    opts.include_untracked(true).recurse_untracked_dirs(true);
+    let diff = git_repo
+        .diff_tree_to_workdir_with_index(Some(&base_tree), Some(&mut opts))
+        .context("failed to diff against base")?;
+    Ok(Some(diff_paths(&diff)))
+}
+
+/// Hash of a repo's build inputs: its HEAD tree plus the contents of every
+/// uncommitted or untracked (non-ignored) file, as a hex git object id.
+pub fn input_hash(root: &Path, repo: &RepoConfig) -> Result<String> {
+    let repo_path = root.join(repo.local_path());
+    let git_repo = git2::Repository::open(&repo_path)
+        .with_context(|| format!("failed to open git repo at {}", repo_path.display()))?;
+    let head_tree = git_repo
+        .head()
+        .context("failed to get HEAD")?
+        .peel_to_tree()
+        .context("HEAD has no tree")?;
+
+    let mut opts = git2::DiffOptions::new();
+    opts.include_untracked(true).recurse_untracked_dirs(true);
+    let diff = git_repo
+        .diff_tree_to_workdir_with_index(Some(&head_tree), Some(&mut opts))
+        .context("failed to diff working tree")?;
+
+    let mut inputs = format!("tree {}\n", head_tree.id());
+    for path in diff_paths(&diff) {
+        let full = repo_path.join(&path);
+        let blob = if full.is_file() {
+            git2::Oid::hash_file(git2::ObjectType::Blob, &full)
+                .with_context(|| format!("failed to hash {}", full.display()))?
+                .to_string()
+        } else {
+            "deleted".to_string()
+        };
+        inputs.push_str(&format!("{blob} {path}\n"));
+    }
+    Ok(git2::Oid::hash_object(git2::ObjectType::Blob, inputs.as_bytes())?.to_string())
+}
+
+/// Paths touched by a diff, sorted.
+fn diff_paths(diff: &git2::Diff) -> Vec<String> {
+    let mut paths: Vec<String> = diff
+        .deltas()
+        .filter_map(|d| d.new_file().path().or(d.old_file().path()))
+        .map(|p| p.to_string_lossy().into_owned())
+        .collect();
+    paths.sort();
+    paths.dedup();
+    paths
+}
+
+/// Total size in bytes of all files under `path` (0 if it doesn't exist).
+pub fn dir_size(path: &Path) -> u64 {
+    let Ok(meta) = std::fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !meta.is_dir() {
+        return meta.len();
+    }
+    std::fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| dir_size(&e.path()))
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+fn diff_stat(diff: &git2::Diff) -> Result<DiffStat> {
+    let stats = diff.stats().context("failed to compute diff stats")?;
+    Ok(DiffStat {
+        files_changed: stats.files_changed(),
+        insertions: stats.insertions(),
+        deletions: stats.deletions(),
     })
 }
 
@@ -298,6 +1170,61 @@ pub mod worktree {
     pub struct WorktreeSet {
         pub name: String,
         pub worktrees: Vec<WorktreeInfo>,
+        /// What smctl recorded when it created the set; `None` for sets made
+        /// before metadata was kept, or by hand.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub metadata: Option<SetMetadata>,
+    }
+
+    /// What a worktree set was created for, kept in
+    /// `.smctl/worktrees/<name>.json`.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct SetMetadata {
+        pub name: String,
+        pub branch: String,
+        /// Ref new branches were started from, if not HEAD.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub start: Option<String>,
+        /// Repos in the set, in manifest order.
+        pub repos: Vec<String>,
+        /// Creation time, in seconds since the Unix epoch.
+        pub created: i64,
+    }
+
+    impl SetMetadata {
+        pub fn path(root: &Path, name: &str) -> PathBuf {
+            root.join(".smctl")
+                .join("worktrees")
+                .join(format!("{name}.json"))
+        }
+
+        /// Metadata of set `name`, if recorded.
+        pub fn load(root: &Path, name: &str) -> Result<Option<Self>> {
+            let path = Self::path(root, name);
+            if !path.exists() {
+                return Ok(None);
+            }
+            let content = std::fs::read_to_string(&path)
+                .with_context(|| format!("failed to read {}", path.display()))?;
+            let meta = serde_json::from_str(&content)
+                .with_context(|| format!("failed to parse {}", path.display()))?;
+            Ok(Some(meta))
+        }
+
+        pub fn save(&self, root: &Path) -> Result<()> {
+            let path = Self::path(root, &self.name);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&path, serde_json::to_string_pretty(self)? + "\n")
+                .with_context(|| format!("failed to write {}", path.display()))
+        }
+
+        /// Drop the metadata and generated files of a removed set.
+        fn forget(root: &Path, name: &str) {
+            std::fs::remove_file(Self::path(root, name)).ok();
+            std::fs::remove_file(code_workspace_path(root, name)).ok();
+        }
     }
 
     /// Info about a single worktree.
@@ -307,126 +1234,248 @@ pub mod worktree {
         pub branch: String,
         pub path: PathBuf,
         pub exists: bool,
+        /// Uncommitted changes in the worktree.
+        #[serde(default)]
+        pub dirty: bool,
+        /// Branch `ahead`/`behind` are counted against: develop for feature
+        /// and bugfix branches, main for release, hotfix and support
+        /// branches, the repo's default branch otherwise. `None` when that
+        /// branch does not exist locally.
+        #[serde(default)]
+        pub base: Option<String>,
+        #[serde(default)]
+        pub ahead: usize,
+        #[serde(default)]
+        pub behind: usize,
+        #[serde(default)]
+        pub last_commit_summary: Option<String>,
     }
 
-    /// List all worktree sets in the workspace.
-    pub fn list_worktrees(root: &Path, manifest: &WorkspaceManifest) -> Result<Vec<WorktreeSet>> {
-        let base = root.join(&manifest.worktree.base_dir);
-        let mut sets = Vec::new();
-
-        if !base.exists() {
-            return Ok(sets);
+    /// Directories worktree sets live in: `[worktree] base_dir`, then each
+    /// distinct per-repo `worktree_base_dir`.
+    pub fn base_dirs(root: &Path, manifest: &WorkspaceManifest) -> Vec<PathBuf> {
+        let mut dirs = vec![root.join(&manifest.worktree.base_dir)];
+        for repo in &manifest.repos {
+            let dir = repo_base_dir(root, manifest, repo);
+            if !dirs.contains(&dir) {
+                dirs.push(dir);
+            }
         }
+        dirs
+    }
+
+    fn repo_base_dir(
+        root: &Path,
+        manifest: &WorkspaceManifest,
+        repo: &crate::RepoConfig,
+    ) -> PathBuf {
+        root.join(
+            repo.worktree_base_dir
+                .as_deref()
+                .unwrap_or(&manifest.worktree.base_dir),
+        )
+    }
+
+    /// Path of `repo`'s worktree in set `name`.
+    pub fn repo_worktree_path(
+        root: &Path,
+        manifest: &WorkspaceManifest,
+        repo: &crate::RepoConfig,
+        name: &str,
+    ) -> PathBuf {
+        repo_base_dir(root, manifest, repo)
+            .join(name)
+            .join(repo.local_path())
+    }
 
-        let entries = std::fs::read_dir(&base).context("failed to read worktree base dir")?;
-        for entry in entries {
-            let entry = entry?;
-            if !entry.file_type()?.is_dir() {
+    /// List all worktree sets in the workspace.
+    pub fn list_worktrees(root: &Path, manifest: &WorkspaceManifest) -> Result<Vec<WorktreeSet>> {
+        let mut names = std::collections::BTreeSet::new();
+        for base in base_dirs(root, manifest) {
+            if !base.exists() {
                 continue;
             }
-            let name = entry.file_name().to_string_lossy().to_string();
+            let entries = std::fs::read_dir(&base).context("failed to read worktree base dir")?;
+            for entry in entries {
+                let entry = entry?;
+                if entry.file_type()?.is_dir() {
+                    names.insert(entry.file_name().to_string_lossy().to_string());
+                }
+            }
+        }
+
+        let mut sets = Vec::new();
+        for name in names {
             let mut worktrees = Vec::new();
 
             for repo in &manifest.repos {
-                let wt_path = base.join(&name).join(repo.local_path());
-                let exists = wt_path.exists();
-                let branch = if exists {
-                    read_worktree_branch(&wt_path).unwrap_or_default()
-                } else {
-                    String::new()
-                };
-                worktrees.push(WorktreeInfo {
-                    repo_name: repo.name.clone(),
-                    branch,
-                    path: wt_path,
-                    exists,
-                });
+                let wt_path = repo_worktree_path(root, manifest, repo, &name);
+                worktrees.push(worktree_info(manifest, repo, wt_path));
             }
 
             if worktrees.iter().any(|w| w.exists) {
-                sets.push(WorktreeSet { name, worktrees });
+                let metadata = SetMetadata::load(root, &name)?;
+                sets.push(WorktreeSet {
+                    name,
+                    worktrees,
+                    metadata,
+                });
             }
         }
 
         Ok(sets)
     }
 
-    /// Add linked worktrees for a feature across specified repos.
+    /// Add linked worktrees on `branch` across specified repos. A branch
+    /// that already exists is checked out as is, and giving it a `start`
+    /// is an error; otherwise it is created at `start` (any ref, default
+    /// HEAD).
     pub fn add_worktree(
         root: &Path,
         manifest: &WorkspaceManifest,
         name: &str,
         repos: Option<&[String]>,
         branch: &str,
+        start: Option<&str>,
     ) -> Result<Vec<WorktreeInfo>> {
-        let base = root.join(&manifest.worktree.base_dir).join(name);
-        std::fs::create_dir_all(&base).context("failed to create worktree directory")?;
+        if manifest
+            .repos
+            .iter()
+            .any(|r| repo_worktree_path(root, manifest, r, name).exists())
+        {
+            anyhow::bail!("worktree set '{name}' already exists (use --extend to add repos to it)");
+        }
 
-        let target_repos: Vec<_> = match repos {
-            Some(names) => manifest
-                .repos
-                .iter()
-                .filter(|r| names.iter().any(|n| n == &r.name))
-                .collect(),
-            None => manifest.repos.iter().collect(),
-        };
+        let infos = create_worktrees(
+            root,
+            manifest,
+            name,
+            &manifest.select_repos(repos),
+            branch,
+            start,
+        )?;
+        SetMetadata {
+            name: name.to_string(),
+            branch: branch.to_string(),
+            start: start.map(str::to_string),
+            repos: infos.iter().map(|i| i.repo_name.clone()).collect(),
+            created: now_secs(),
+        }
+        .save(root)?;
+        tracing::info!("added worktree set '{name}' for {} repos", infos.len());
+        Ok(infos)
+    }
+
+    /// Add worktrees for more repos to an existing set. Repos already in the
+    /// set are left alone; `branch` and `start` work as in [`add_worktree`].
+    pub fn extend_worktree(
+        root: &Path,
+        manifest: &WorkspaceManifest,
+        name: &str,
+        repos: Option<&[String]>,
+        branch: &str,
+        start: Option<&str>,
+    ) -> Result<Vec<WorktreeInfo>> {
+        worktree_path(root, manifest, name)?;
+        let missing: Vec<_> = manifest
+            .select_repos(repos)
+            .into_iter()
+            .filter(|r| !repo_worktree_path(root, manifest, r, name).exists())
+            .collect();
+        if missing.is_empty() {
+            anyhow::bail!("worktree set '{name}' already has every selected repo");
+        }
+
+        let infos = create_worktrees(root, manifest, name, &missing, branch, start)?;
+        if let Some(mut meta) = SetMetadata::load(root, name)? {
+            meta.repos = manifest
+                .repos
+                .iter()
+                .filter(|r| {
+                    meta.repos.contains(&r.name) || infos.iter().any(|i| i.repo_name == r.name)
+                })
+                .map(|r| r.name.clone())
+                .collect();
+            meta.save(root)?;
+        }
+        tracing::info!("extended worktree set '{name}' by {} repos", infos.len());
+        Ok(infos)
+    }
+
+    fn create_worktrees(
+        root: &Path,
+        manifest: &WorkspaceManifest,
+        name: &str,
+        repos: &[&crate::RepoConfig],
+        branch: &str,
+        start: Option<&str>,
+    ) -> Result<Vec<WorktreeInfo>> {
+        // Existing branches are checked out as they are, so `start` only
+        // applies when the branch is new in every repo
+        let mut existing = Vec::new();
+        for repo in repos {
+            let exists = git2::Repository::open(root.join(repo.local_path()))
+                .with_context(|| format!("failed to open repo {}", repo.name))?
+                .find_branch(branch, git2::BranchType::Local)
+                .is_ok();
+            if let (true, Some(start)) = (exists, start) {
+                anyhow::bail!(
+                    "branch '{branch}' already exists in {}; drop --base '{start}' to check it out as is",
+                    repo.name
+                );
+            }
+            existing.push(exists);
+        }
 
         let mut infos = Vec::new();
-        for repo in &target_repos {
+        for (repo, exists) in repos.iter().zip(existing) {
             let repo_path = root.join(repo.local_path());
-            let wt_path = base.join(repo.local_path());
+            let wt_path = repo_worktree_path(root, manifest, repo, name);
+            if let Some(parent) = wt_path.parent() {
+                std::fs::create_dir_all(parent).context("failed to create worktree directory")?;
+            }
 
-            let result = std::process::Command::new("git")
-                .args(["worktree", "add", wt_path.to_str().unwrap(), "-b", branch])
+            let mut cmd = std::process::Command::new("git");
+            cmd.args(["worktree", "add", wt_path.to_str().unwrap()]);
+            if exists {
+                cmd.arg(branch);
+            } else {
+                cmd.args(["-b", branch]).args(start);
+            }
+            let result = cmd
                 .current_dir(&repo_path)
                 .output()
                 .context("failed to run git worktree add")?;
 
             if !result.status.success() {
-                // Try without -b if branch already exists
-                let result = std::process::Command::new("git")
-                    .args(["worktree", "add", wt_path.to_str().unwrap(), branch])
-                    .current_dir(&repo_path)
-                    .output()
-                    .context("failed to run git worktree add")?;
-
-                if !result.status.success() {
-                    let stderr = String::from_utf8_lossy(&result.stderr);
-                    anyhow::bail!(
-                        "failed to add worktree for {} at {}: {}",
-                        repo.name,
-                        wt_path.display(),
-                        stderr.trim()
-                    );
-                }
+                let stderr = String::from_utf8_lossy(&result.stderr);
+                anyhow::bail!(
+                    "failed to add worktree for {} at {}: {}",
+                    repo.name,
+                    wt_path.display(),
+                    stderr.trim()
+                );
             }
 
-            infos.push(WorktreeInfo {
-                repo_name: repo.name.clone(),
-                branch: branch.to_string(),
-                path: wt_path,
-                exists: true,
-            });
+            copy_bootstrap_files(repo, &repo_path, &wt_path)?;
+            infos.push(worktree_info(manifest, repo, wt_path));
         }
-
-        tracing::info!("added worktree set '{name}' for {} repos", infos.len());
         Ok(infos)
     }
 
-    /// Remove a worktree set.
+    /// Remove a worktree set, or with `repos`, only those repos' worktrees
+    /// from it; the set itself goes once it has none left.
     pub fn remove_worktree(
         root: &Path,
         manifest: &WorkspaceManifest,
         name: &str,
+        repos: Option<&[String]>,
         force: bool,
     ) -> Result<()> {
-        let base = root.join(&manifest.worktree.base_dir).join(name);
-        if !base.exists() {
-            anyhow::bail!("worktree set '{name}' does not exist");
-        }
+        worktree_path(root, manifest, name)?;
 
-        for repo in &manifest.repos {
-            let wt_path = base.join(repo.local_path());
+        for repo in manifest.select_repos(repos) {
+            let wt_path = repo_worktree_path(root, manifest, repo, name);
             if !wt_path.exists() {
                 continue;
             }
@@ -454,29 +1503,1153 @@ pub mod worktree {
             }
         }
 
-        // Clean up the directory
-        if base.exists() {
-            std::fs::remove_dir_all(&base).ok();
+        // Clean up the set directories
+        for base in base_dirs(root, manifest) {
+            let set = base.join(name);
+            if !set.exists() {
+                continue;
+            }
+            if repos.is_some() {
+                remove_empty_dirs(&set)?;
+            } else {
+                std::fs::remove_dir_all(&set).ok();
+            }
+        }
+        if worktree_path(root, manifest, name).is_ok() {
+            if let Some(removed) = repos
+                && let Some(mut meta) = SetMetadata::load(root, name)?
+            {
+                meta.repos.retain(|r| !removed.contains(r));
+                meta.save(root)?;
+            }
+            tracing::info!("removed repos from worktree set '{name}'");
+            return Ok(());
         }
+        SetMetadata::forget(root, name);
 
         tracing::info!("removed worktree set '{name}'");
         Ok(())
     }
 
-    /// Get the path to a worktree set (for shell integration / `cd`).
+    /// The manifest narrowed to the repos in set `name`, each pointing at its
+    /// worktree, for building or testing the set.
+    pub fn set_manifest(
+        root: &Path,
+        manifest: &WorkspaceManifest,
+        name: &str,
+    ) -> Result<WorkspaceManifest> {
+        worktree_path(root, manifest, name)?;
+        let mut set = manifest.clone();
+        set.repos.retain_mut(|repo| {
+            let path = repo_worktree_path(root, manifest, repo, name);
+            repo.path = Some(path.to_string_lossy().into_owned());
+            path.exists()
+        });
+        Ok(set)
+    }
+
+    /// Rename worktree set `old` to `new`: move each repo's worktree with
+    /// `git worktree move`, so git's administrative links follow, optionally
+    /// rename the checked-out branch to `branch`, and carry the set metadata
+    /// over. Every repo is checked before anything moves, and if a move or
+    /// branch rename still fails, the repos already done are moved back.
+    pub fn rename_worktree(
+        root: &Path,
+        manifest: &WorkspaceManifest,
+        old: &str,
+        new: &str,
+        branch: Option<&str>,
+    ) -> Result<Vec<WorktreeInfo>> {
+        worktree_path(root, manifest, old)?;
+        if worktree_path(root, manifest, new).is_ok() || SetMetadata::path(root, new).exists() {
+            anyhow::bail!("worktree set '{new}' already exists");
+        }
+
+        let mut moves = Vec::new();
+        for repo in &manifest.repos {
+            let from = repo_worktree_path(root, manifest, repo, old);
+            if !from.exists() {
+                continue;
+            }
+            let to = repo_worktree_path(root, manifest, repo, new);
+            if to.exists() {
+                anyhow::bail!("{} already exists", to.display());
+            }
+            let rename = match branch {
+                Some(branch) => {
+                    let git_repo = git2::Repository::open(&from)
+                        .with_context(|| format!("failed to open {}", from.display()))?;
+                    if git_repo.head_detached().unwrap_or(true) {
+                        anyhow::bail!("{} has no branch checked out to rename", from.display());
+                    }
+                    let current = worktree_info(manifest, repo, from.clone()).branch;
+                    if current != branch
+                        && git_repo
+                            .find_branch(branch, git2::BranchType::Local)
+                            .is_ok()
+                    {
+                        anyhow::bail!("branch '{branch}' already exists in {}", repo.name);
+                    }
+                    (current != branch).then_some(current)
+                }
+                None => None,
+            };
+            moves.push((repo, from, to, rename));
+        }
+
+        let mut infos = Vec::new();
+        for (idx, (repo, from, to, current)) in moves.iter().enumerate() {
+            if let Err(e) = move_worktree(root, repo, from, to, current.as_deref(), branch) {
+                for (repo, from, to, current) in moves[..idx].iter().rev() {
+                    if let (Some(current), Some(branch)) = (current, branch) {
+                        git(to, &["branch", "-m", branch, current]).ok();
+                    }
+                    git(
+                        &root.join(repo.local_path()),
+                        &[
+                            "worktree",
+                            "move",
+                            &to.to_string_lossy(),
+                            &from.to_string_lossy(),
+                        ],
+                    )
+                    .ok();
+                }
+                for base in base_dirs(root, manifest) {
+                    let set = base.join(new);
+                    if set.exists() {
+                        remove_empty_dirs(&set).ok();
+                    }
+                }
+                return Err(e.context(format!("worktree set '{old}' was left as it was")));
+            }
+            infos.push(worktree_info(manifest, repo, to.clone()));
+        }
+
+        for base in base_dirs(root, manifest) {
+            let set = base.join(old);
+            if set.exists() {
+                remove_empty_dirs(&set)?;
+            }
+        }
+        if let Some(mut meta) = SetMetadata::load(root, old)? {
+            meta.name = new.to_string();
+            if let Some(branch) = branch {
+                meta.branch = branch.to_string();
+            }
+            meta.save(root)?;
+        }
+        SetMetadata::forget(root, old);
+
+        tracing::info!("renamed worktree set '{old}' to '{new}'");
+        Ok(infos)
+    }
+
+    /// Register worktrees made by hand as worktree set `name` (default: the
+    /// set directory's name). `path` is either a directory holding linked
+    /// worktrees of workspace repos at their local paths, or one such
+    /// worktree. Worktrees outside the set's location are moved there with
+    /// `git worktree move`, and the set metadata is written or extended.
+    pub fn adopt_worktree(
+        root: &Path,
+        manifest: &WorkspaceManifest,
+        path: &Path,
+        name: Option<&str>,
+    ) -> Result<WorktreeSet> {
+        let path = path
+            .canonicalize()
+            .with_context(|| format!("{} does not exist", path.display()))?;
+        let found: Vec<_> = match linked_worktree_of(root, manifest, &path) {
+            Some(repo) => vec![(repo, path.clone())],
+            None => manifest
+                .repos
+                .iter()
+                .map(|repo| (repo, path.join(repo.local_path())))
+                .filter(|(repo, wt)| {
+                    linked_worktree_of(root, manifest, wt).is_some_and(|r| r.name == repo.name)
+                })
+                .collect(),
+        };
+        let Some((first_repo, first)) = found.first() else {
+            anyhow::bail!(
+                "{} is not a linked worktree of a workspace repo, nor holds any",
+                path.display()
+            );
+        };
+
+        let name = match name {
+            Some(name) => name.to_string(),
+            None => {
+                // A lone worktree already inside a set directory keeps that
+                // set's name; otherwise the set is named after `path`
+                let base = repo_base_dir(root, manifest, first_repo)
+                    .canonicalize()
+                    .ok();
+                let depth = Path::new(first_repo.local_path()).components().count();
+                let set_dir = match first.ancestors().nth(depth) {
+                    Some(dir)
+                        if *first == path
+                            && path.ends_with(first_repo.local_path())
+                            && dir.parent() == base.as_deref() =>
+                    {
+                        dir
+                    }
+                    _ => path.as_path(),
+                };
+                set_dir
+                    .file_name()
+                    .context("cannot name the set after the path (use --name)")?
+                    .to_string_lossy()
+                    .to_string()
+            }
+        };
+
+        let mut meta = SetMetadata::load(root, &name)?.unwrap_or_else(|| SetMetadata {
+            name: name.clone(),
+            branch: String::new(),
+            start: None,
+            repos: Vec::new(),
+            created: now_secs(),
+        });
+        let mut moves = Vec::new();
+        for (repo, from) in &found {
+            let git_repo = git2::Repository::open(from)?;
+            if git_repo.head_detached().unwrap_or(true) {
+                anyhow::bail!(
+                    "worktree {} has no branch checked out; check one out first",
+                    from.display()
+                );
+            }
+            let to = repo_worktree_path(root, manifest, repo, &name);
+            if to.canonicalize().is_ok_and(|to| to == *from) {
+                if meta.repos.contains(&repo.name) {
+                    anyhow::bail!(
+                        "{} is already part of worktree set '{name}'",
+                        from.display()
+                    );
+                }
+            } else if to.exists() {
+                anyhow::bail!(
+                    "worktree set '{name}' already has a worktree for {} at {}",
+                    repo.name,
+                    to.display()
+                );
+            }
+            moves.push((*repo, from, to));
+        }
+
+        let mut worktrees = Vec::new();
+        for (repo, from, to) in moves {
+            if !to.exists() {
+                if let Some(parent) = to.parent() {
+                    std::fs::create_dir_all(parent)
+                        .context("failed to create worktree directory")?;
+                }
+                let output = git(
+                    &root.join(repo.local_path()),
+                    &[
+                        "worktree",
+                        "move",
+                        &from.to_string_lossy(),
+                        &to.to_string_lossy(),
+                    ],
+                )?;
+                if !output.status.success() {
+                    anyhow::bail!(
+                        "failed to move worktree for {}: {}",
+                        repo.name,
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    );
+                }
+            }
+            worktrees.push(worktree_info(manifest, repo, to));
+        }
+        if path.exists() {
+            remove_empty_dirs(&path)?;
+        }
+
+        if meta.branch.is_empty() {
+            meta.branch = worktrees[0].branch.clone();
+        }
+        meta.repos = manifest
+            .repos
+            .iter()
+            .filter(|r| {
+                meta.repos.contains(&r.name) || worktrees.iter().any(|w| w.repo_name == r.name)
+            })
+            .map(|r| r.name.clone())
+            .collect();
+        meta.save(root)?;
+
+        tracing::info!("adopted {} worktrees into set '{name}'", worktrees.len());
+        Ok(WorktreeSet {
+            name,
+            worktrees,
+            metadata: Some(meta),
+        })
+    }
+
+    /// The workspace repo `path` is a linked worktree of, if any.
+    fn linked_worktree_of<'a>(
+        root: &Path,
+        manifest: &'a WorkspaceManifest,
+        path: &Path,
+    ) -> Option<&'a crate::RepoConfig> {
+        let worktree = git2::Repository::open(path).ok()?;
+        if !worktree.is_worktree() {
+            return None;
+        }
+        let common = worktree.commondir().canonicalize().ok()?;
+        manifest.repos.iter().find(|repo| {
+            git2::Repository::open(root.join(repo.local_path()))
+                .ok()
+                .and_then(|r| r.path().canonicalize().ok())
+                .is_some_and(|p| p == common)
+        })
+    }
+
+    /// Move one repo's worktree from `from` to `to`, then rename its
+    /// branch `current` to `branch`. The move is undone if the rename fails.
+    fn move_worktree(
+        root: &Path,
+        repo: &crate::RepoConfig,
+        from: &Path,
+        to: &Path,
+        current: Option<&str>,
+        branch: Option<&str>,
+    ) -> Result<()> {
+        let repo_path = root.join(repo.local_path());
+        if let Some(parent) = to.parent() {
+            std::fs::create_dir_all(parent).context("failed to create worktree directory")?;
+        }
+        let output = git(
+            &repo_path,
+            &[
+                "worktree",
+                "move",
+                &from.to_string_lossy(),
+                &to.to_string_lossy(),
+            ],
+        )?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "failed to move worktree for {}: {}",
+                repo.name,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+
+        if let (Some(current), Some(branch)) = (current, branch) {
+            let output = git(to, &["branch", "-m", current, branch])?;
+            if !output.status.success() {
+                git(
+                    &repo_path,
+                    &[
+                        "worktree",
+                        "move",
+                        &to.to_string_lossy(),
+                        &from.to_string_lossy(),
+                    ],
+                )
+                .ok();
+                anyhow::bail!(
+                    "failed to rename branch '{current}' in {}: {}",
+                    repo.name,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// A stale worktree found by [`prune_worktrees`].
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct PrunedWorktree {
+        pub repo_name: String,
+        pub path: PathBuf,
+        /// Why it is stale.
+        pub reason: String,
+        /// Left in place because it has (or may have) uncommitted or
+        /// untracked files; pruning it takes `force`.
+        #[serde(default)]
+        pub kept: bool,
+    }
+
+    /// Remove stale worktrees: git's entries for worktrees whose directory
+    /// is gone (as `git worktree prune` does), worktrees in a set whose
+    /// branch was deleted, and set directories git no longer tracks as
+    /// worktrees. Set directories left empty are removed too. Locked
+    /// worktrees are kept, and so, unless `force`, are worktrees with
+    /// uncommitted or untracked files and untracked set directories, whose
+    /// state git can no longer tell; both are reported with `kept`. With
+    /// `dry_run`, only report what would go.
+    pub fn prune_worktrees(
+        root: &Path,
+        manifest: &WorkspaceManifest,
+        dry_run: bool,
+        force: bool,
+    ) -> Result<Vec<PrunedWorktree>> {
+        let mut pruned = Vec::new();
+
+        for repo in &manifest.repos {
+            let base = repo_base_dir(root, manifest, repo);
+            let base = base.canonicalize().unwrap_or(base);
+            let Ok(git_repo) = git2::Repository::open(root.join(repo.local_path())) else {
+                continue;
+            };
+            for name in git_repo.worktrees()?.iter().flatten() {
+                let wt = git_repo.find_worktree(name)?;
+                let path = wt.path().to_path_buf();
+                let (reason, kept) = if wt.validate().is_err() {
+                    if !wt.is_prunable(None)? {
+                        continue;
+                    }
+                    ("worktree directory is missing".to_string(), false)
+                } else if path.starts_with(&base)
+                    && wt.is_locked()? == git2::WorktreeLockStatus::Unlocked
+                    && let Some(branch) = deleted_branch(&path)
+                {
+                    (
+                        format!("branch '{branch}' was deleted"),
+                        !force && has_local_changes(&path),
+                    )
+                } else {
+                    continue;
+                };
+                if !dry_run && !kept {
+                    wt.prune(Some(
+                        git2::WorktreePruneOptions::new()
+                            .valid(true)
+                            .working_tree(true),
+                    ))
+                    .with_context(|| format!("failed to prune worktree {}", path.display()))?;
+                }
+                pruned.push(PrunedWorktree {
+                    repo_name: repo.name.clone(),
+                    path,
+                    reason,
+                    kept,
+                });
+            }
+
+            // Set directories whose git entry is gone
+            if !base.exists() {
+                continue;
+            }
+            for entry in std::fs::read_dir(&base).context("failed to read worktree base dir")? {
+                let wt_path = entry?.path().join(repo.local_path());
+                if !wt_path.join(".git").is_file() || git2::Repository::open(&wt_path).is_ok() {
+                    continue;
+                }
+                if !dry_run && force {
+                    std::fs::remove_dir_all(&wt_path)
+                        .with_context(|| format!("failed to remove {}", wt_path.display()))?;
+                }
+                pruned.push(PrunedWorktree {
+                    repo_name: repo.name.clone(),
+                    path: wt_path,
+                    reason: "git no longer tracks this worktree".to_string(),
+                    kept: !force,
+                });
+            }
+        }
+
+        for base in base_dirs(root, manifest) {
+            if dry_run || !base.exists() {
+                continue;
+            }
+            for entry in std::fs::read_dir(&base).context("failed to read worktree base dir")? {
+                let path = entry?.path();
+                if path.is_dir() && remove_empty_dirs(&path)? {
+                    let name = path.file_name().unwrap_or_default().to_string_lossy();
+                    if worktree_path(root, manifest, &name).is_err() {
+                        SetMetadata::forget(root, &name);
+                    }
+                }
+            }
+        }
+        Ok(pruned)
+    }
+
+    /// Seconds since the Unix epoch.
+    fn now_secs() -> i64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or_default()
+    }
+
+    /// The branch a worktree has checked out, if it no longer exists.
+    fn deleted_branch(path: &Path) -> Option<String> {
+        let repo = git2::Repository::open(path).ok()?;
+        let head = repo.find_reference("HEAD").ok()?;
+        let target = head.symbolic_target()?;
+        repo.find_reference(target)
+            .is_err()
+            .then(|| target.trim_start_matches("refs/heads/").to_string())
+    }
+
+    /// Whether the worktree at `path` has uncommitted or untracked files.
+    /// Staged changes are compared with the last commit HEAD pointed at,
+    /// which still works once its branch is deleted.
+    fn has_local_changes(path: &Path) -> bool {
+        let Ok(repo) = git2::Repository::open(path) else {
+            return true;
+        };
+        let workdir_clean = repo
+            .statuses(Some(
+                git2::StatusOptions::new()
+                    .show(git2::StatusShow::Workdir)
+                    .include_untracked(true)
+                    .include_ignored(false),
+            ))
+            .is_ok_and(|s| s.is_empty());
+        if !workdir_clean {
+            return true;
+        }
+        let Ok(index) = repo.index() else {
+            return true;
+        };
+        let last = repo.head().ok().and_then(|h| h.target()).or_else(|| {
+            repo.reflog("HEAD")
+                .ok()
+                .and_then(|log| log.get(0).map(|e| e.id_new()))
+        });
+        match last.and_then(|oid| repo.find_commit(oid).ok()) {
+            Some(commit) => !repo
+                .diff_tree_to_index(commit.tree().ok().as_ref(), Some(&index), None)
+                .is_ok_and(|d| d.deltas().len() == 0),
+            None => !index.is_empty(),
+        }
+    }
+
+    /// Remove `dir` and its subdirectories if they hold no files; returns
+    /// whether `dir` was removed.
+    fn remove_empty_dirs(dir: &Path) -> Result<bool> {
+        let mut empty = true;
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            empty &= path.is_dir() && remove_empty_dirs(&path)?;
+        }
+        if empty {
+            std::fs::remove_dir(dir)?;
+        }
+        Ok(empty)
+    }
+
+    /// Get the path to a worktree set (for shell integration / `cd`): its
+    /// directory under `[worktree] base_dir`, or under a per-repo
+    /// `worktree_base_dir` when only repos placed elsewhere are in the set.
     pub fn worktree_path(root: &Path, manifest: &WorkspaceManifest, name: &str) -> Result<PathBuf> {
-        let base = root.join(&manifest.worktree.base_dir).join(name);
-        if !base.exists() {
-            anyhow::bail!("worktree set '{name}' does not exist");
+        base_dirs(root, manifest)
+            .into_iter()
+            .map(|base| base.join(name))
+            .find(|set| set.exists())
+            .with_context(|| format!("worktree set '{name}' does not exist"))
+    }
+
+    /// Outcome of syncing one worktree of a set with its base branch.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct WorktreeSyncResult {
+        pub repo_name: String,
+        pub branch: String,
+        pub success: bool,
+        /// Files that conflicted; the rebase or merge was aborted.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        pub conflicts: Vec<String>,
+        pub message: String,
+    }
+
+    /// Bring each worktree of set `name` up to date with its base branch
+    /// (see [`WorktreeInfo::base`]): fetch `origin` when the repo has it,
+    /// then rebase onto or merge the remote base branch, or the local one
+    /// when there is no remote copy. Dirty worktrees are skipped, and a
+    /// conflicting rebase or merge is aborted, leaving that worktree as it was.
+    pub fn sync_worktree(
+        root: &Path,
+        manifest: &WorkspaceManifest,
+        name: &str,
+        mode: crate::SyncMode,
+    ) -> Result<Vec<WorktreeSyncResult>> {
+        worktree_path(root, manifest, name)?;
+        let mut results = Vec::new();
+
+        for repo in &manifest.repos {
+            let path = repo_worktree_path(root, manifest, repo, name);
+            if !path.exists() {
+                continue;
+            }
+            let info = worktree_info(manifest, repo, path.clone());
+            let mut result = WorktreeSyncResult {
+                repo_name: repo.name.clone(),
+                branch: info.branch.clone(),
+                success: false,
+                conflicts: Vec::new(),
+                message: String::new(),
+            };
+            if info.dirty {
+                result.message = "uncommitted changes, skipped".to_string();
+                results.push(result);
+                continue;
+            }
+
+            let git_repo = git2::Repository::open(&path)?;
+            let target = base_branch(manifest, repo, &info.branch);
+            if git_repo.find_remote("origin").is_ok() {
+                let output = git(&path, &["fetch", "--quiet", "origin"])?;
+                if !output.status.success() {
+                    result.message = format!(
+                        "fetch failed: {}",
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    );
+                    results.push(result);
+                    continue;
+                }
+            }
+            let remote = format!("origin/{target}");
+            let upstream = if git_repo
+                .find_branch(&remote, git2::BranchType::Remote)
+                .is_ok()
+            {
+                remote
+            } else if git_repo
+                .find_branch(target, git2::BranchType::Local)
+                .is_ok()
+            {
+                target.to_string()
+            } else {
+                result.message = format!("base branch '{target}' not found");
+                results.push(result);
+                continue;
+            };
+
+            let head = git_repo.head()?.peel_to_commit()?.id();
+            let upstream_oid = git_repo.revparse_single(&upstream)?.peel_to_commit()?.id();
+            if git_repo.graph_descendant_of(head, upstream_oid)? || head == upstream_oid {
+                result.success = true;
+                result.message = format!("up to date with '{upstream}'");
+                results.push(result);
+                continue;
+            }
+
+            let (args, abort, done): (&[&str], _, _) = match mode {
+                crate::SyncMode::Rebase => (&["rebase", "--quiet"], "rebase", "rebased onto"),
+                crate::SyncMode::Merge => (&["merge", "--no-edit", "--quiet"], "merge", "merged"),
+            };
+            let output = git(&path, &[args, &[upstream.as_str()]].concat())?;
+            if output.status.success() {
+                result.success = true;
+                result.message = format!("{done} '{upstream}'");
+            } else {
+                let conflicted = git(&path, &["diff", "--name-only", "--diff-filter=U"])?;
+                result.conflicts = String::from_utf8_lossy(&conflicted.stdout)
+                    .lines()
+                    .map(str::to_string)
+                    .collect();
+                git(&path, &[abort, "--abort"])?;
+                result.message = if result.conflicts.is_empty() {
+                    format!(
+                        "{abort} onto '{upstream}' failed: {}",
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    )
+                } else {
+                    format!(
+                        "conflicts with '{upstream}' in {}; {abort} aborted",
+                        result.conflicts.join(", ")
+                    )
+                };
+            }
+            results.push(result);
+        }
+        Ok(results)
+    }
+
+    fn git(dir: &Path, args: &[&str]) -> Result<std::process::Output> {
+        std::process::Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .with_context(|| format!("failed to run git {}", args.join(" ")))
+    }
+
+    /// Disk usage of one worktree, from [`disk_usage`].
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct RepoUsage {
+        pub repo_name: String,
+        pub path: PathBuf,
+        pub bytes: u64,
+        /// Newest file modification time, in seconds since the Unix epoch.
+        pub last_modified: Option<i64>,
+    }
+
+    /// Disk usage of a worktree set, from [`disk_usage`].
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct SetUsage {
+        pub name: String,
+        pub bytes: u64,
+        pub last_modified: Option<i64>,
+        /// Nothing in the set changed for longer than the staleness window:
+        /// a candidate for `worktree remove` or `prune`.
+        pub stale: bool,
+        pub repos: Vec<RepoUsage>,
+    }
+
+    /// Disk usage of every worktree set, largest first. A set is `stale` when
+    /// none of its files changed in the last `stale_days` days.
+    pub fn disk_usage(
+        root: &Path,
+        manifest: &WorkspaceManifest,
+        stale_days: u64,
+    ) -> Result<Vec<SetUsage>> {
+        let now = now_secs();
+        let cutoff = now - (stale_days * 24 * 60 * 60) as i64;
+
+        let mut usage = Vec::new();
+        for set in list_worktrees(root, manifest)? {
+            let repos: Vec<_> = set
+                .worktrees
+                .into_iter()
+                .filter(|w| w.exists)
+                .map(|w| RepoUsage {
+                    repo_name: w.repo_name,
+                    bytes: crate::dir_size(&w.path),
+                    last_modified: last_modified(&w.path),
+                    path: w.path,
+                })
+                .collect();
+            let last_modified = repos.iter().filter_map(|r| r.last_modified).max();
+            usage.push(SetUsage {
+                name: set.name,
+                bytes: repos.iter().map(|r| r.bytes).sum(),
+                stale: last_modified.is_none_or(|t| t < cutoff),
+                last_modified,
+                repos,
+            });
+        }
+        usage.sort_by_key(|u| std::cmp::Reverse(u.bytes));
+        Ok(usage)
+    }
+
+    /// Newest modification time under `path`, not following symlinks.
+    fn last_modified(path: &Path) -> Option<i64> {
+        let meta = std::fs::symlink_metadata(path).ok()?;
+        let own = meta
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs() as i64);
+        if !meta.is_dir() {
+            return own;
+        }
+        std::fs::read_dir(path)
+            .ok()?
+            .filter_map(|e| e.ok())
+            .filter_map(|e| last_modified(&e.path()))
+            .chain(own)
+            .max()
+    }
+
+    /// Work saved from one repo by [`archive_worktree`].
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct ArchivedRepo {
+        pub repo_name: String,
+        pub branch: String,
+        /// Commit the worktree was on.
+        pub head: String,
+        /// Branch unmerged commits were counted against: [`WorktreeInfo::base`],
+        /// else the first of develop, main and the repo's default branch that
+        /// exists locally.
+        pub base: Option<String>,
+        /// Commits not on `base`, saved as `unmerged.patch`; with no `base`,
+        /// every commit of the branch, saved as `unmerged.bundle`.
+        pub unmerged_commits: usize,
+        /// Uncommitted changes were saved as `uncommitted.patch`.
+        pub uncommitted: bool,
+        /// Untracked files copied under `untracked/`.
+        pub untracked_files: usize,
+    }
+
+    /// A worktree set archived by [`archive_worktree`].
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct ArchivedSet {
+        pub name: String,
+        /// The `.tar.gz` written.
+        pub path: PathBuf,
+        pub repos: Vec<ArchivedRepo>,
+    }
+
+    /// Save the work in set `name` (or only `repos` of it) before removal to
+    /// `.smctl/worktree-archive/<name>.tar.gz`: per repo, commits not on its
+    /// base branch as `unmerged.patch` (`git am`-able), uncommitted changes
+    /// as `uncommitted.patch`, untracked files, and a `set.json` listing
+    /// branches and heads. An existing archive is never overwritten; a
+    /// timestamped name is used instead. Needs `tar` on `PATH`.
+    pub fn archive_worktree(
+        root: &Path,
+        manifest: &WorkspaceManifest,
+        name: &str,
+        repos: Option<&[String]>,
+    ) -> Result<ArchivedSet> {
+        worktree_path(root, manifest, name)?;
+        let dir = root.join(".smctl").join("worktree-archive");
+        let staging = dir.join(format!(".{name}"));
+        if staging.exists() {
+            std::fs::remove_dir_all(&staging)?;
+        }
+        let result =
+            stage_archive(root, manifest, name, repos, &staging.join(name)).and_then(|archived| {
+                let secs = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or_default();
+                let mut path = dir.join(format!("{name}.tar.gz"));
+                let mut n = 1;
+                while path.exists() {
+                    let suffix = if n == 1 {
+                        String::new()
+                    } else {
+                        format!("-{n}")
+                    };
+                    path = dir.join(format!("{name}-{secs}{suffix}.tar.gz"));
+                    n += 1;
+                }
+                let output = std::process::Command::new("tar")
+                    .arg("-czf")
+                    .arg(&path)
+                    .arg("-C")
+                    .arg(&staging)
+                    .arg(name)
+                    .output()
+                    .context("failed to run tar")?;
+                if !output.status.success() {
+                    anyhow::bail!(
+                        "failed to write {}: {}",
+                        path.display(),
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    );
+                }
+                Ok(ArchivedSet {
+                    name: name.to_string(),
+                    path,
+                    repos: archived,
+                })
+            });
+        std::fs::remove_dir_all(&staging).ok();
+        let archived = result?;
+        tracing::info!(
+            "archived worktree set '{name}' to {}",
+            archived.path.display()
+        );
+        Ok(archived)
+    }
+
+    /// Write the archive contents of set `name` into `dest`.
+    fn stage_archive(
+        root: &Path,
+        manifest: &WorkspaceManifest,
+        name: &str,
+        repos: Option<&[String]>,
+        dest: &Path,
+    ) -> Result<Vec<ArchivedRepo>> {
+        let mut archived = Vec::new();
+        for repo in manifest.select_repos(repos) {
+            let path = repo_worktree_path(root, manifest, repo, name);
+            if !path.exists() {
+                continue;
+            }
+            let info = worktree_info(manifest, repo, path.clone());
+            let git_repo = git2::Repository::open(&path)?;
+            let head = git_repo.head()?.peel_to_commit()?.id().to_string();
+            let out = dest.join(repo.local_path());
+            std::fs::create_dir_all(&out)?;
+
+            // Without the usual base, compare with whichever of develop,
+            // main and the default branch exists; failing all, bundle the
+            // whole branch
+            let flow = &manifest.flow;
+            let base = info.base.clone().or_else(|| {
+                [
+                    &flow.develop_branch,
+                    &flow.main_branch,
+                    &repo.default_branch,
+                ]
+                .into_iter()
+                .find(|b| git_repo.find_branch(b, git2::BranchType::Local).is_ok())
+                .cloned()
+            });
+            let unmerged_commits = match &base {
+                Some(base) => {
+                    let range = format!("{base}..HEAD");
+                    let count = git(&path, &["rev-list", "--count", &range])?;
+                    let count = String::from_utf8_lossy(&count.stdout)
+                        .trim()
+                        .parse()
+                        .unwrap_or_default();
+                    if count > 0 {
+                        let patch = git(&path, &["format-patch", "--stdout", &range])?;
+                        std::fs::write(out.join("unmerged.patch"), &patch.stdout)?;
+                    }
+                    count
+                }
+                None => {
+                    let bundle = out.join("unmerged.bundle");
+                    let output = git(
+                        &path,
+                        &["bundle", "create", &bundle.to_string_lossy(), "HEAD"],
+                    )?;
+                    if !output.status.success() {
+                        anyhow::bail!(
+                            "failed to bundle {}'s commits: {}",
+                            repo.name,
+                            String::from_utf8_lossy(&output.stderr).trim()
+                        );
+                    }
+                    let count = git(&path, &["rev-list", "--count", "HEAD"])?;
+                    String::from_utf8_lossy(&count.stdout)
+                        .trim()
+                        .parse()
+                        .unwrap_or_default()
+                }
+            };
+
+            let diff = git(&path, &["diff", "--binary", "HEAD"])?;
+            let uncommitted = !diff.stdout.is_empty();
+            if uncommitted {
+                std::fs::write(out.join("uncommitted.patch"), &diff.stdout)?;
+            }
+
+            let others = git(&path, &["ls-files", "--others", "--exclude-standard", "-z"])?;
+            let untracked: Vec<_> = others
+                .stdout
+                .split(|b| *b == 0)
+                .filter(|f| !f.is_empty())
+                .map(|f| String::from_utf8_lossy(f).into_owned())
+                .collect();
+            for file in &untracked {
+                let target = out.join("untracked").join(file);
+                if let Some(parent) = target.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::copy(path.join(file), &target)
+                    .with_context(|| format!("failed to copy untracked {file}"))?;
+            }
+
+            archived.push(ArchivedRepo {
+                repo_name: repo.name.clone(),
+                branch: info.branch,
+                head,
+                base,
+                unmerged_commits,
+                uncommitted,
+                untracked_files: untracked.len(),
+            });
         }
-        Ok(base)
+        std::fs::write(
+            dest.join("set.json"),
+            serde_json::to_string_pretty(&archived)? + "\n",
+        )?;
+        Ok(archived)
     }
 
-    /// Read the current branch of a worktree by examining its .git file.
-    fn read_worktree_branch(path: &Path) -> Result<String> {
-        let repo = git2::Repository::open(path)?;
-        let head = repo.head()?;
-        Ok(head.shorthand().unwrap_or("detached").to_string())
+    /// Path of the generated VS Code workspace file for set `name`.
+    pub fn code_workspace_path(root: &Path, name: &str) -> PathBuf {
+        root.join(".smctl")
+            .join("worktrees")
+            .join(format!("{name}.code-workspace"))
+    }
+
+    /// Write a VS Code multi-root workspace file listing each repo worktree
+    /// of set `name`, and return its path.
+    pub fn write_code_workspace(
+        root: &Path,
+        manifest: &WorkspaceManifest,
+        name: &str,
+    ) -> Result<PathBuf> {
+        worktree_path(root, manifest, name)?;
+        let folders: Vec<_> = manifest
+            .repos
+            .iter()
+            .filter_map(|r| {
+                let path = repo_worktree_path(root, manifest, r, name)
+                    .canonicalize()
+                    .ok()?;
+                Some(serde_json::json!({ "name": r.name, "path": path }))
+            })
+            .collect();
+        let content = serde_json::to_string_pretty(&serde_json::json!({
+            "folders": folders,
+            "settings": {},
+        }))?;
+
+        let path = code_workspace_path(root, name);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, content + "\n")
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        Ok(path)
+    }
+
+    /// Result of running a command in one worktree of a set.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct ExecResult {
+        pub repo_name: String,
+        pub path: PathBuf,
+        pub success: bool,
+        /// Exit code, `None` when killed by a signal.
+        pub code: Option<i32>,
+        pub stdout: String,
+        pub stderr: String,
+    }
+
+    /// Run `cmd` (program and arguments, no shell) in the worktree of each of
+    /// `repos`, in the given order, and collect the results. Repos without a
+    /// worktree in the set are skipped; a failing repo does not stop the rest.
+    pub fn exec_in_worktree(
+        root: &Path,
+        manifest: &WorkspaceManifest,
+        name: &str,
+        repos: &[&crate::RepoConfig],
+        cmd: &[String],
+    ) -> Result<Vec<ExecResult>> {
+        worktree_path(root, manifest, name)?;
+        let Some((program, args)) = cmd.split_first() else {
+            anyhow::bail!("no command given");
+        };
+
+        let mut results = Vec::new();
+        for repo in repos {
+            let path = repo_worktree_path(root, manifest, repo, name);
+            if !path.exists() {
+                continue;
+            }
+            let output = std::process::Command::new(program)
+                .args(args)
+                .current_dir(&path)
+                .output()
+                .with_context(|| format!("failed to run '{program}' in {}", path.display()))?;
+            results.push(ExecResult {
+                repo_name: repo.name.clone(),
+                path,
+                success: output.status.success(),
+                code: output.status.code(),
+                stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+                stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+            });
+        }
+        Ok(results)
+    }
+
+    /// Copy the repo's `worktree_copy` files from the main checkout into a
+    /// new worktree. Missing files, and files the worktree already has, are
+    /// skipped; paths leaving the repo are ignored.
+    fn copy_bootstrap_files(
+        repo: &crate::RepoConfig,
+        repo_path: &Path,
+        wt_path: &Path,
+    ) -> Result<()> {
+        for file in &repo.worktree_copy {
+            let rel = Path::new(file);
+            if !rel
+                .components()
+                .all(|c| matches!(c, std::path::Component::Normal(_)))
+            {
+                tracing::warn!("{}: ignoring worktree_copy entry '{file}'", repo.name);
+                continue;
+            }
+            let (src, dest) = (repo_path.join(rel), wt_path.join(rel));
+            if !src.is_file() || dest.exists() {
+                continue;
+            }
+            if let Some(parent) = dest.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::copy(&src, &dest)
+                .with_context(|| format!("failed to copy {file} into {}", wt_path.display()))?;
+            tracing::debug!("{}: copied {file} into worktree", repo.name);
+        }
+        Ok(())
+    }
+
+    /// Describe the worktree of `repo` at `path`, with its branch, dirty
+    /// state and position against its base branch.
+    fn worktree_info(
+        manifest: &WorkspaceManifest,
+        repo: &crate::RepoConfig,
+        path: PathBuf,
+    ) -> WorktreeInfo {
+        let mut info = WorktreeInfo {
+            repo_name: repo.name.clone(),
+            branch: String::new(),
+            exists: path.exists(),
+            path,
+            dirty: false,
+            base: None,
+            ahead: 0,
+            behind: 0,
+            last_commit_summary: None,
+        };
+        let Ok(git_repo) = git2::Repository::open(&info.path) else {
+            return info;
+        };
+        let Ok(head) = git_repo.head() else {
+            return info;
+        };
+        info.branch = head.shorthand().unwrap_or("detached").to_string();
+        info.dirty = git_repo
+            .statuses(Some(
+                git2::StatusOptions::new()
+                    .include_untracked(true)
+                    .include_ignored(false),
+            ))
+            .is_ok_and(|s| !s.is_empty());
+
+        let Ok(commit) = head.peel_to_commit() else {
+            return info;
+        };
+        info.last_commit_summary = commit.summary().map(str::to_string);
+
+        let base = base_branch(manifest, repo, &info.branch);
+        if base == info.branch {
+            return info;
+        }
+        if let Ok(base_ref) = git_repo.find_branch(base, git2::BranchType::Local)
+            && let Some(base_oid) = base_ref.get().target()
+            && let Ok((ahead, behind)) = git_repo.graph_ahead_behind(commit.id(), base_oid)
+        {
+            info.base = Some(base.to_string());
+            info.ahead = ahead;
+            info.behind = behind;
+        }
+        info
+    }
+
+    /// Branch a worktree branch is based on: develop for feature and bugfix
+    /// branches, main for release, hotfix and support branches, the repo's
+    /// default branch otherwise.
+    fn base_branch<'a>(
+        manifest: &'a WorkspaceManifest,
+        repo: &'a crate::RepoConfig,
+        branch: &str,
+    ) -> &'a str {
+        let flow = &manifest.flow;
+        if [&flow.feature_prefix, &flow.bugfix_prefix]
+            .iter()
+            .any(|p| branch.starts_with(p.as_str()))
+        {
+            &flow.develop_branch
+        } else if [
+            &flow.release_prefix,
+            &flow.hotfix_prefix,
+            &flow.support_prefix,
+        ]
+        .iter()
+        .any(|p| branch.starts_with(p.as_str()))
+        {
+            &flow.main_branch
+        } else {
+            &repo.default_branch
+        }
     }
 
     #[cfg(test)]
@@ -534,12 +2707,91 @@ depends_on = ["SmallAIOS"]
         assert_eq!(manifest.repos[1].depends_on, vec!["SmallAIOS"]);
     }
 
+    #[test]
+    fn test_spec_github_config() {
+        let manifest = WorkspaceManifest::parse(SAMPLE_TOML).unwrap();
+        assert!(manifest.spec.github.is_none());
+        assert!(!manifest.spec.numbered);
+
+        let manifest = WorkspaceManifest::parse(
+            r#"
+[workspace]
+name = "test"
+
+[spec.github]
+repo = "SmallAIOS/ModelGate"
+labels = ["spec"]
+"#,
+        )
+        .unwrap();
+        let github = manifest.spec.github.unwrap();
+        assert_eq!(github.repo, "SmallAIOS/ModelGate");
+        assert_eq!(github.token_env, "GITHUB_TOKEN");
+        assert_eq!(github.api_url, "https://api.github.com");
+        assert_eq!(github.labels, vec!["spec"]);
+    }
+
     #[test]
     fn test_default_flow_config() {
         let flow = FlowConfig::default();
         assert_eq!(flow.main_branch, "main");
         assert_eq!(flow.develop_branch, "develop");
         assert_eq!(flow.feature_prefix, "feature/");
+        assert_eq!(flow.bugfix_prefix, "bugfix/");
+        assert_eq!(flow.support_prefix, "support/");
+        assert_eq!(flow.release_tag("1.2.0"), "v1.2.0");
+        assert_eq!(flow.finish, FinishMode::Merge);
+        assert_eq!(flow.merge_strategy, MergeStrategy::NoFf);
+        assert_eq!(
+            "ff-only".parse::<MergeStrategy>().unwrap(),
+            MergeStrategy::FfOnly
+        );
+        assert!("octopus".parse::<MergeStrategy>().is_err());
+    }
+
+    #[test]
+    fn test_pull_request_config() {
+        let manifest = WorkspaceManifest::parse(
+            r#"
+[workspace]
+name = "test"
+
+[flow]
+finish = "pull-request"
+
+[flow.pull_request]
+provider = "gitlab"
+"#,
+        )
+        .unwrap();
+        assert_eq!(manifest.flow.finish, FinishMode::PullRequest);
+        let pr = manifest.flow.pull_request.unwrap();
+        assert_eq!(pr.token_env(), "GITLAB_TOKEN");
+        assert_eq!(pr.api_url(), "https://gitlab.com/api/v4");
+        assert_eq!(pr.remote, "origin");
+    }
+
+    #[test]
+    fn test_flow_hooks() {
+        let manifest = WorkspaceManifest::parse(
+            r#"
+[workspace]
+name = "test"
+
+[flow.hooks]
+pre_finish = ["smctl build", "smctl build --test"]
+post_finish = "./notify.sh"
+"#,
+        )
+        .unwrap();
+        let hooks = &manifest.flow.hooks;
+        assert!(hooks.pre_start.is_none());
+        assert_eq!(
+            hooks.pre_finish.as_ref().unwrap().steps(),
+            ["smctl build", "smctl build --test"]
+        );
+        assert_eq!(hooks.post_finish.as_ref().unwrap().steps(), ["./notify.sh"]);
+        assert!(FlowConfig::default().hooks.is_empty());
     }
 
     #[test]
@@ -549,6 +2801,69 @@ depends_on = ["SmallAIOS"]
         assert!(manifest.find_repo("NonExistent").is_none());
     }
 
+    #[test]
+    fn test_build_profiles() {
+        let manifest = WorkspaceManifest::parse(
+            r#"
+[workspace]
+name = "test"
+
+[[repos]]
+name = "A"
+url = "https://example.com/a"
+build_cmd = "make"
+
+[repos.profiles.release]
+build_cmd = "make release"
+test_cmd = "make check-release"
+"#,
+        )
+        .unwrap();
+        let repo = manifest.find_repo("A").unwrap();
+        assert_eq!(repo.build_cmd_for(None), ["make"]);
+        assert_eq!(repo.build_cmd_for(Some("release")), ["make release"]);
+        assert_eq!(repo.build_cmd_for(Some("ci")), ["make"]);
+        assert_eq!(repo.test_cmd_for(None), "cargo test");
+        assert_eq!(repo.test_cmd_for(Some("release")), "make check-release");
+    }
+
+    #[test]
+    fn test_multi_step_build_cmd() {
+        let manifest = WorkspaceManifest::parse(
+            r#"
+[workspace]
+name = "test"
+
+[[repos]]
+name = "A"
+url = "https://example.com/a"
+build_cmd = ["cargo fmt --check", "cargo build"]
+
+[[repos]]
+name = "B"
+url = "https://example.com/b"
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            manifest.find_repo("A").unwrap().build_cmd_for(None),
+            ["cargo fmt --check", "cargo build"]
+        );
+        assert_eq!(
+            manifest.find_repo("B").unwrap().build_cmd_for(None),
+            ["cargo build"]
+        );
+
+        let reparsed = WorkspaceManifest::parse(&toml::to_string(&manifest).unwrap()).unwrap();
+        assert_eq!(
+            reparsed.find_repo("A").unwrap().build_cmd,
+            Some(CommandSpec::Steps(vec![
+                "cargo fmt --check".to_string(),
+                "cargo build".to_string()
+            ]))
+        );
+    }
+
     #[test]
     fn test_add_remove_repo() {
         let mut manifest = WorkspaceManifest::parse(SAMPLE_TOML).unwrap();
@@ -573,6 +2888,16 @@ depends_on = ["SmallAIOS"]
         assert!(dir.path().join(".smctl/workspace.toml").exists());
     }
 
+    #[test]
+    fn test_dir_size() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(dir_size(&dir.path().join("missing")), 0);
+        std::fs::create_dir_all(dir.path().join("a/b")).unwrap();
+        std::fs::write(dir.path().join("a/one.txt"), "12345").unwrap();
+        std::fs::write(dir.path().join("a/b/two.txt"), "123").unwrap();
+        assert_eq!(dir_size(dir.path()), 8);
+    }
+
     #[test]
     fn test_roundtrip_save_load() {
         let dir = tempfile::tempdir().unwrap();
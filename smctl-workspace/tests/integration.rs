@@ -3,7 +3,10 @@
 use std::path::Path;
 
 use smctl_workspace::worktree;
-use smctl_workspace::{WorkspaceManifest, add_repo, init_workspace, remove_repo, repo_status};
+use smctl_workspace::{
+    DiffReport, GcOptions, SyncMode, SyncStatus, WorkspaceManifest, add_repo, gc_repo,
+    init_workspace, remove_repo, repo_diff, repo_status, sync_repo,
+};
 
 /// Create a bare git repo and a clone of it within the workspace root.
 fn setup_git_repo(root: &Path, name: &str) -> String {
@@ -129,12 +132,166 @@ fn test_workspace_repo_status_with_real_git() {
     assert_eq!(status.branch, "main");
     assert!(status.clean);
     assert_eq!(status.modified_files, 0);
+    assert_eq!(status.stash_count, 0);
+    assert_eq!(status.last_commit_summary.as_deref(), Some("init"));
+    assert_eq!(status.last_commit_sha.as_ref().map(|s| s.len()), Some(40));
+    assert!(status.last_commit_time.unwrap() > 0);
 
     // Dirty the repo
     std::fs::write(dir.path().join("my-repo/dirty.txt"), "dirty").unwrap();
     let status = repo_status(dir.path(), manifest.find_repo("my-repo").unwrap()).unwrap();
     assert!(!status.clean);
     assert!(status.modified_files > 0);
+
+    // Stash a tracked change
+    std::fs::write(dir.path().join("my-repo/README.md"), "# Changed\n").unwrap();
+    let output = std::process::Command::new("git")
+        .args([
+            "-c",
+            "user.name=Test",
+            "-c",
+            "user.email=test@test.com",
+            "stash",
+        ])
+        .current_dir(dir.path().join("my-repo"))
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let status = repo_status(dir.path(), manifest.find_repo("my-repo").unwrap()).unwrap();
+    assert_eq!(status.stash_count, 1);
+}
+
+#[test]
+fn test_workspace_sync_fast_forwards_with_git2() {
+    let dir = tempfile::tempdir().unwrap();
+    let bare_url = setup_git_repo(dir.path(), "my-repo");
+
+    let mut manifest = init_workspace(dir.path(), "sync-test").unwrap();
+    add_repo(&mut manifest, "my-repo", &bare_url, Some("my-repo")).unwrap();
+    let repo = manifest.find_repo("my-repo").unwrap();
+
+    let result = sync_repo(dir.path(), repo, |_| {}).unwrap();
+    assert_eq!(result.status, SyncStatus::UpToDate);
+
+    // Push a new commit from a second clone
+    let other = dir.path().join("other");
+    let output = std::process::Command::new("git")
+        .args(["clone", &bare_url, other.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    std::fs::write(other.join("new.txt"), "upstream\n").unwrap();
+    let cmds: &[&[&str]] = &[
+        &["git", "add", "."],
+        &[
+            "git",
+            "-c",
+            "user.name=Test",
+            "-c",
+            "user.email=test@test.com",
+            "commit",
+            "-m",
+            "upstream change",
+        ],
+        &["git", "push", "origin", "main"],
+    ];
+    for cmd in cmds {
+        let output = std::process::Command::new(cmd[0])
+            .args(&cmd[1..])
+            .current_dir(&other)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{} failed", cmd.join(" "));
+    }
+
+    let result = sync_repo(dir.path(), repo, |_| {}).unwrap();
+    assert_eq!(result.status, SyncStatus::FastForwarded);
+    assert!(dir.path().join("my-repo/new.txt").exists());
+
+    let status = repo_status(dir.path(), repo).unwrap();
+    assert!(status.clean);
+    assert_eq!(
+        status.last_commit_summary.as_deref(),
+        Some("upstream change")
+    );
+}
+
+#[test]
+fn test_workspace_gc_reports_sizes() {
+    let dir = tempfile::tempdir().unwrap();
+    let bare_url = setup_git_repo(dir.path(), "my-repo");
+
+    let mut manifest = init_workspace(dir.path(), "gc-test").unwrap();
+    add_repo(&mut manifest, "my-repo", &bare_url, Some("my-repo")).unwrap();
+    let repo = manifest.find_repo("my-repo").unwrap();
+
+    let opts = GcOptions {
+        aggressive: false,
+        expire_reflogs: Some("now".to_string()),
+    };
+    let result = gc_repo(dir.path(), repo, &opts).unwrap();
+    assert!(result.success, "{}", result.message);
+    assert!(result.size_before > 0);
+    assert!(result.size_after > 0);
+
+    // Repo is still intact afterwards
+    let status = repo_status(dir.path(), repo).unwrap();
+    assert_eq!(status.last_commit_summary.as_deref(), Some("init"));
+}
+
+#[test]
+fn test_workspace_repo_diff_with_real_git() {
+    let dir = tempfile::tempdir().unwrap();
+    let bare_url = setup_git_repo(dir.path(), "my-repo");
+
+    let mut manifest = init_workspace(dir.path(), "diff-test").unwrap();
+    add_repo(&mut manifest, "my-repo", &bare_url, Some("my-repo")).unwrap();
+    let repo = manifest.find_repo("my-repo").unwrap();
+
+    // Clean repo on the default branch has nothing to report
+    let diff = repo_diff(dir.path(), repo).unwrap();
+    assert_eq!(diff.base, "main");
+    assert_eq!(diff.uncommitted.files_changed, 0);
+    assert_eq!(diff.unmerged_commits, 0);
+
+    // Commit on a feature branch, then leave an uncommitted file behind
+    let repo_path = dir.path().join("my-repo");
+    std::fs::write(repo_path.join("feature.txt"), "one\ntwo\n").unwrap();
+    let cmds: &[&[&str]] = &[
+        &["git", "checkout", "-b", "feature/x"],
+        &["git", "add", "."],
+        &[
+            "git",
+            "-c",
+            "user.name=Test",
+            "-c",
+            "user.email=test@test.com",
+            "commit",
+            "-m",
+            "feature",
+        ],
+    ];
+    for cmd in cmds {
+        let output = std::process::Command::new(cmd[0])
+            .args(&cmd[1..])
+            .current_dir(&repo_path)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{} failed", cmd.join(" "));
+    }
+    std::fs::write(repo_path.join("wip.txt"), "wip\n").unwrap();
+
+    let diff = repo_diff(dir.path(), repo).unwrap();
+    assert_eq!(diff.branch, "feature/x");
+    assert_eq!(diff.unmerged_commits, 1);
+    assert_eq!(diff.unmerged.files_changed, 1);
+    assert_eq!(diff.unmerged.insertions, 2);
+    assert_eq!(diff.uncommitted.files_changed, 1);
+    assert_eq!(diff.uncommitted.insertions, 1);
+
+    let report = DiffReport::from_repos(vec![diff]);
+    assert_eq!(report.total_unmerged_commits, 1);
+    assert_eq!(report.total_uncommitted.files_changed, 1);
 }
 
 #[test]
@@ -218,14 +375,44 @@ path = "my-repo"
     WorkspaceManifest::parse(toml).unwrap()
 }
 
+/// Add a second repo, "other", to a worktree workspace.
+fn add_other_repo(root: &Path, manifest: &mut WorkspaceManifest) {
+    let other = root.join("other");
+    std::fs::create_dir_all(&other).unwrap();
+    for args in [
+        &["init", "-q", "-b", "main"][..],
+        &[
+            "-c",
+            "user.name=Test",
+            "-c",
+            "user.email=test@test.com",
+            "commit",
+            "-q",
+            "--allow-empty",
+            "-m",
+            "init",
+        ],
+    ] {
+        std::process::Command::new("git")
+            .args(args)
+            .current_dir(&other)
+            .output()
+            .unwrap();
+    }
+    let mut other_repo = manifest.repos[0].clone();
+    other_repo.name = "other".to_string();
+    other_repo.path = Some("other".to_string());
+    manifest.repos.push(other_repo);
+}
+
 #[test]
 fn test_worktree_add_list_remove_lifecycle() {
     let dir = tempfile::tempdir().unwrap();
     let manifest = setup_worktree_workspace(dir.path());
 
     // Add a worktree set
-    let infos =
-        worktree::add_worktree(dir.path(), &manifest, "feature-x", None, "feature/x").unwrap();
+    let infos = worktree::add_worktree(dir.path(), &manifest, "feature-x", None, "feature/x", None)
+        .unwrap();
     assert_eq!(infos.len(), 1);
     assert!(infos[0].exists);
     assert_eq!(infos[0].branch, "feature/x");
@@ -248,7 +435,7 @@ fn test_worktree_add_list_remove_lifecycle() {
     assert_eq!(head.shorthand().unwrap(), "feature/x");
 
     // Remove the worktree set
-    worktree::remove_worktree(dir.path(), &manifest, "feature-x", false).unwrap();
+    worktree::remove_worktree(dir.path(), &manifest, "feature-x", None, false).unwrap();
 
     // List should be empty
     let sets = worktree::list_worktrees(dir.path(), &manifest).unwrap();
@@ -260,7 +447,893 @@ fn test_worktree_remove_nonexistent_fails() {
     let dir = tempfile::tempdir().unwrap();
     let manifest = setup_worktree_workspace(dir.path());
 
-    assert!(worktree::remove_worktree(dir.path(), &manifest, "nope", false).is_err());
+    assert!(worktree::remove_worktree(dir.path(), &manifest, "nope", None, false).is_err());
+}
+
+#[test]
+fn test_worktree_add_existing_branch_and_base() {
+    let dir = tempfile::tempdir().unwrap();
+    let manifest = setup_worktree_workspace(dir.path());
+    let repo = git2::Repository::open(dir.path().join("my-repo")).unwrap();
+    let first = repo.head().unwrap().peel_to_commit().unwrap();
+    repo.branch("release/1.0", &first, false).unwrap();
+    repo.tag_lightweight("v0.9", first.as_object(), false)
+        .unwrap();
+    std::process::Command::new("git")
+        .args([
+            "-c",
+            "user.name=Test",
+            "-c",
+            "user.email=test@test.com",
+            "commit",
+            "--allow-empty",
+            "-m",
+            "second",
+        ])
+        .current_dir(dir.path().join("my-repo"))
+        .output()
+        .unwrap();
+
+    // An existing branch is checked out as is
+    let infos =
+        worktree::add_worktree(dir.path(), &manifest, "rel", None, "release/1.0", None).unwrap();
+    assert_eq!(infos[0].branch, "release/1.0");
+
+    // A new branch starts at the given ref instead of HEAD
+    let infos = worktree::add_worktree(
+        dir.path(),
+        &manifest,
+        "review",
+        None,
+        "review/old",
+        Some("v0.9"),
+    )
+    .unwrap();
+    assert_eq!(infos[0].branch, "review/old");
+    let wt_repo = git2::Repository::open(&infos[0].path).unwrap();
+    assert_eq!(wt_repo.head().unwrap().target().unwrap(), first.id());
+
+    // An unknown start ref fails, naming the ref
+    let err = worktree::add_worktree(dir.path(), &manifest, "bad", None, "x", Some("nope"))
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("nope"), "{err}");
+
+    // A start ref for an existing branch is refused rather than ignored
+    let err = worktree::add_worktree(
+        dir.path(),
+        &manifest,
+        "again",
+        None,
+        "release/1.0",
+        Some("v0.9"),
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("already exists"), "{err}");
+    assert!(!dir.path().join(".worktrees/again").exists());
+}
+
+#[test]
+fn test_worktree_add_copies_bootstrap_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut manifest = setup_worktree_workspace(dir.path());
+    manifest.repos[0].worktree_copy = vec![
+        ".env".to_string(),
+        "config/local.toml".to_string(),
+        "missing.txt".to_string(),
+        "../outside".to_string(),
+    ];
+    let repo_path = dir.path().join("my-repo");
+    std::fs::write(repo_path.join(".env"), "TOKEN=abc\n").unwrap();
+    std::fs::create_dir_all(repo_path.join("config")).unwrap();
+    std::fs::write(repo_path.join("config/local.toml"), "port = 8080\n").unwrap();
+    std::fs::write(dir.path().join("outside"), "no\n").unwrap();
+
+    let infos =
+        worktree::add_worktree(dir.path(), &manifest, "boot", None, "feature/boot", None).unwrap();
+    let wt_path = &infos[0].path;
+    assert_eq!(
+        std::fs::read_to_string(wt_path.join(".env")).unwrap(),
+        "TOKEN=abc\n"
+    );
+    assert_eq!(
+        std::fs::read_to_string(wt_path.join("config/local.toml")).unwrap(),
+        "port = 8080\n"
+    );
+    assert!(!wt_path.join("missing.txt").exists());
+    assert!(!wt_path.parent().unwrap().join("outside").exists());
+}
+
+#[test]
+fn test_worktree_list_reports_dirty_and_ahead() {
+    let dir = tempfile::tempdir().unwrap();
+    let manifest = setup_worktree_workspace(dir.path());
+    let repo = git2::Repository::open(dir.path().join("my-repo")).unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    repo.branch("develop", &head, false).unwrap();
+
+    let infos =
+        worktree::add_worktree(dir.path(), &manifest, "wip", None, "feature/wip", None).unwrap();
+    assert!(!infos[0].dirty);
+    assert_eq!(infos[0].base.as_deref(), Some("develop"));
+    assert_eq!((infos[0].ahead, infos[0].behind), (0, 0));
+
+    // One commit on the feature, then an unsaved edit
+    let wt_path = &infos[0].path;
+    std::fs::write(wt_path.join("wip.txt"), "wip\n").unwrap();
+    for args in [
+        &["add", "."][..],
+        &[
+            "-c",
+            "user.name=Test",
+            "-c",
+            "user.email=test@test.com",
+            "commit",
+            "-m",
+            "Start wip",
+        ],
+    ] {
+        std::process::Command::new("git")
+            .args(args)
+            .current_dir(wt_path)
+            .output()
+            .unwrap();
+    }
+    std::fs::write(wt_path.join("wip.txt"), "more wip\n").unwrap();
+
+    let sets = worktree::list_worktrees(dir.path(), &manifest).unwrap();
+    let info = &sets[0].worktrees[0];
+    assert!(info.dirty);
+    assert_eq!(info.base.as_deref(), Some("develop"));
+    assert_eq!((info.ahead, info.behind), (1, 0));
+    assert_eq!(info.last_commit_summary.as_deref(), Some("Start wip"));
+}
+
+#[test]
+fn test_worktree_prune_removes_stale_sets() {
+    let dir = tempfile::tempdir().unwrap();
+    let manifest = setup_worktree_workspace(dir.path());
+    let base = dir.path().join(&manifest.worktree.base_dir);
+
+    worktree::add_worktree(dir.path(), &manifest, "gone", None, "feature/gone", None).unwrap();
+    worktree::add_worktree(
+        dir.path(),
+        &manifest,
+        "merged",
+        None,
+        "feature/merged",
+        None,
+    )
+    .unwrap();
+    worktree::add_worktree(dir.path(), &manifest, "live", None, "feature/live", None).unwrap();
+
+    // Directory deleted by hand, branch deleted after a merge
+    std::fs::remove_dir_all(base.join("gone")).unwrap();
+    let repo = git2::Repository::open(dir.path().join("my-repo")).unwrap();
+    repo.find_reference("refs/heads/feature/merged")
+        .unwrap()
+        .delete()
+        .unwrap();
+
+    // Dry run reports without touching anything
+    let pruned = worktree::prune_worktrees(dir.path(), &manifest, true, false).unwrap();
+    assert_eq!(pruned.len(), 2);
+    assert!(base.join("merged").exists());
+    assert_eq!(repo.worktrees().unwrap().len(), 3);
+
+    let pruned = worktree::prune_worktrees(dir.path(), &manifest, false, false).unwrap();
+    let mut reasons: Vec<_> = pruned.iter().map(|p| p.reason.as_str()).collect();
+    reasons.sort();
+    assert_eq!(
+        reasons,
+        [
+            "branch 'feature/merged' was deleted",
+            "worktree directory is missing"
+        ]
+    );
+    assert!(!base.join("merged").exists());
+    assert!(base.join("live").join("my-repo").exists());
+    assert_eq!(repo.worktrees().unwrap().len(), 1);
+
+    let sets = worktree::list_worktrees(dir.path(), &manifest).unwrap();
+    assert_eq!(sets.len(), 1);
+    assert_eq!(sets[0].name, "live");
+
+    // Nothing left to prune
+    assert!(
+        worktree::prune_worktrees(dir.path(), &manifest, false, false)
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_worktree_prune_keeps_dirty_worktrees() {
+    let dir = tempfile::tempdir().unwrap();
+    let manifest = setup_worktree_workspace(dir.path());
+    let base = dir.path().join(&manifest.worktree.base_dir);
+    let repo = git2::Repository::open(dir.path().join("my-repo")).unwrap();
+    for name in ["clean", "untracked", "staged"] {
+        let branch = format!("feature/{name}");
+        worktree::add_worktree(dir.path(), &manifest, name, None, &branch, None).unwrap();
+    }
+    let wt = |name: &str| base.join(name).join("my-repo");
+    std::fs::write(wt("untracked").join("notes.txt"), "wip\n").unwrap();
+    std::fs::write(wt("staged").join("README.md"), "# Staged\n").unwrap();
+    std::process::Command::new("git")
+        .args(["add", "README.md"])
+        .current_dir(wt("staged"))
+        .output()
+        .unwrap();
+    for name in ["clean", "untracked", "staged"] {
+        repo.find_reference(&format!("refs/heads/feature/{name}"))
+            .unwrap()
+            .delete()
+            .unwrap();
+    }
+    // A set directory whose git entry is gone
+    std::fs::create_dir_all(base.join("orphan/my-repo")).unwrap();
+    std::fs::write(base.join("orphan/my-repo/.git"), "gitdir: /nowhere\n").unwrap();
+
+    let pruned = worktree::prune_worktrees(dir.path(), &manifest, false, false).unwrap();
+    assert_eq!(pruned.len(), 4);
+    let kept: Vec<_> = pruned
+        .iter()
+        .filter(|p| p.kept)
+        .map(|p| p.path.parent().unwrap().file_name().unwrap().to_owned())
+        .collect();
+    assert_eq!(kept.len(), 3, "{pruned:?}");
+    assert!(!kept.contains(&"clean".into()));
+    assert!(!base.join("clean").exists());
+    assert!(wt("untracked").join("notes.txt").exists());
+    assert!(wt("staged").join("README.md").exists());
+    assert!(base.join("orphan/my-repo").exists());
+
+    // --force prunes them all
+    let pruned = worktree::prune_worktrees(dir.path(), &manifest, false, true).unwrap();
+    assert_eq!(pruned.len(), 3);
+    assert!(pruned.iter().all(|p| !p.kept));
+    assert!(!base.join("untracked").exists());
+    assert!(!base.join("orphan").exists());
+}
+
+#[test]
+fn test_worktree_exec_runs_in_each_repo() {
+    let dir = tempfile::tempdir().unwrap();
+    let manifest = setup_worktree_workspace(dir.path());
+    worktree::add_worktree(dir.path(), &manifest, "run", None, "feature/run", None).unwrap();
+    let repos: Vec<_> = manifest.repos.iter().collect();
+
+    let cmd = ["git", "rev-parse", "--abbrev-ref", "HEAD"].map(String::from);
+    let results = worktree::exec_in_worktree(dir.path(), &manifest, "run", &repos, &cmd).unwrap();
+    assert_eq!(results.len(), 1);
+    assert!(results[0].success);
+    assert_eq!(results[0].stdout.trim(), "feature/run");
+
+    let cmd = ["git", "rev-parse", "no-such-ref"].map(String::from);
+    let results = worktree::exec_in_worktree(dir.path(), &manifest, "run", &repos, &cmd).unwrap();
+    assert!(!results[0].success);
+    assert_eq!(results[0].code, Some(128));
+
+    assert!(worktree::exec_in_worktree(dir.path(), &manifest, "nope", &repos, &cmd).is_err());
+}
+
+#[test]
+fn test_worktree_code_workspace_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let manifest = setup_worktree_workspace(dir.path());
+    let infos =
+        worktree::add_worktree(dir.path(), &manifest, "ide", None, "feature/ide", None).unwrap();
+
+    let path = worktree::write_code_workspace(dir.path(), &manifest, "ide").unwrap();
+    assert_eq!(path, worktree::code_workspace_path(dir.path(), "ide"));
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    let folders = json["folders"].as_array().unwrap();
+    assert_eq!(folders.len(), 1);
+    assert_eq!(folders[0]["name"], "my-repo");
+    assert_eq!(
+        Path::new(folders[0]["path"].as_str().unwrap()),
+        infos[0].path.canonicalize().unwrap()
+    );
+
+    // Removing the set removes its workspace file
+    worktree::remove_worktree(dir.path(), &manifest, "ide", None, false).unwrap();
+    assert!(!path.exists());
+    assert!(worktree::write_code_workspace(dir.path(), &manifest, "ide").is_err());
+}
+
+#[test]
+fn test_worktree_sync_rebases_onto_base() {
+    let dir = tempfile::tempdir().unwrap();
+    let manifest = setup_worktree_workspace(dir.path());
+    let repo_path = dir.path().join("my-repo");
+    // Rebase and merge commit too; linked worktrees share this config
+    for args in [
+        ["config", "user.name", "Test"],
+        ["config", "user.email", "test@test.com"],
+    ] {
+        std::process::Command::new("git")
+            .args(args)
+            .current_dir(&repo_path)
+            .output()
+            .unwrap();
+    }
+    let commit = |cwd: &Path, file: &str, content: &str, msg: &str| {
+        std::fs::write(cwd.join(file), content).unwrap();
+        for args in [
+            &["add", "."][..],
+            &[
+                "-c",
+                "user.name=Test",
+                "-c",
+                "user.email=test@test.com",
+                "commit",
+                "-m",
+                msg,
+            ],
+        ] {
+            std::process::Command::new("git")
+                .args(args)
+                .current_dir(cwd)
+                .output()
+                .unwrap();
+        }
+    };
+    let git_repo = git2::Repository::open(&repo_path).unwrap();
+    let head = git_repo.head().unwrap().peel_to_commit().unwrap();
+    git_repo.branch("develop", &head, false).unwrap();
+
+    let clean = worktree::add_worktree(dir.path(), &manifest, "clean", None, "feature/clean", None)
+        .unwrap()
+        .remove(0);
+    commit(&clean.path, "clean.txt", "clean\n", "Clean work");
+    let clash = worktree::add_worktree(dir.path(), &manifest, "clash", None, "feature/clash", None)
+        .unwrap()
+        .remove(0);
+    commit(&clash.path, "README.md", "# Mine\n", "Edit readme");
+
+    // develop moves on underneath both
+    std::process::Command::new("git")
+        .args(["checkout", "-q", "develop"])
+        .current_dir(&repo_path)
+        .output()
+        .unwrap();
+    commit(
+        &repo_path,
+        "README.md",
+        "# Theirs\n",
+        "Edit readme on develop",
+    );
+
+    let results =
+        worktree::sync_worktree(dir.path(), &manifest, "clean", SyncMode::Rebase).unwrap();
+    assert!(results[0].success, "{}", results[0].message);
+    assert_eq!(results[0].message, "rebased onto 'develop'");
+    let sets = worktree::list_worktrees(dir.path(), &manifest).unwrap();
+    let info = &sets.iter().find(|s| s.name == "clean").unwrap().worktrees[0];
+    assert_eq!((info.ahead, info.behind), (1, 0));
+
+    let results =
+        worktree::sync_worktree(dir.path(), &manifest, "clean", SyncMode::Rebase).unwrap();
+    assert_eq!(results[0].message, "up to date with 'develop'");
+
+    // A conflict is reported and the worktree left as it was
+    let before = git2::Repository::open(&clash.path)
+        .unwrap()
+        .head()
+        .unwrap()
+        .target();
+    let results = worktree::sync_worktree(dir.path(), &manifest, "clash", SyncMode::Merge).unwrap();
+    assert!(!results[0].success);
+    assert_eq!(results[0].conflicts, ["README.md"]);
+    let wt_repo = git2::Repository::open(&clash.path).unwrap();
+    assert_eq!(wt_repo.head().unwrap().target(), before);
+    assert_eq!(wt_repo.state(), git2::RepositoryState::Clean);
+
+    // Uncommitted changes are never touched
+    std::fs::write(clash.path.join("README.md"), "# Dirty\n").unwrap();
+    let results =
+        worktree::sync_worktree(dir.path(), &manifest, "clash", SyncMode::Rebase).unwrap();
+    assert!(!results[0].success);
+    assert_eq!(results[0].message, "uncommitted changes, skipped");
+}
+
+#[test]
+fn test_worktree_extend_and_remove_single_repo() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut manifest = setup_worktree_workspace(dir.path());
+    add_other_repo(dir.path(), &mut manifest);
+    let only = |name: &str| vec![name.to_string()];
+
+    worktree::add_worktree(
+        dir.path(),
+        &manifest,
+        "grow",
+        Some(&only("my-repo")),
+        "feature/grow",
+        None,
+    )
+    .unwrap();
+    // A second add needs --extend
+    assert!(
+        worktree::add_worktree(
+            dir.path(),
+            &manifest,
+            "grow",
+            Some(&only("other")),
+            "feature/grow",
+            None
+        )
+        .is_err()
+    );
+
+    let infos =
+        worktree::extend_worktree(dir.path(), &manifest, "grow", None, "feature/grow", None)
+            .unwrap();
+    assert_eq!(infos.len(), 1);
+    assert_eq!(infos[0].repo_name, "other");
+    assert_eq!(infos[0].branch, "feature/grow");
+    assert!(
+        worktree::extend_worktree(dir.path(), &manifest, "grow", None, "feature/grow", None)
+            .is_err()
+    );
+
+    // Dropping one repo keeps the set
+    worktree::remove_worktree(dir.path(), &manifest, "grow", Some(&only("my-repo")), false)
+        .unwrap();
+    let sets = worktree::list_worktrees(dir.path(), &manifest).unwrap();
+    let present: Vec<_> = sets[0]
+        .worktrees
+        .iter()
+        .filter(|w| w.exists)
+        .map(|w| w.repo_name.as_str())
+        .collect();
+    assert_eq!(present, ["other"]);
+
+    // Dropping the last one removes it
+    worktree::remove_worktree(dir.path(), &manifest, "grow", Some(&only("other")), false).unwrap();
+    assert!(
+        worktree::list_worktrees(dir.path(), &manifest)
+            .unwrap()
+            .is_empty()
+    );
+    assert!(
+        !dir.path()
+            .join(&manifest.worktree.base_dir)
+            .join("grow")
+            .exists()
+    );
+}
+
+#[test]
+fn test_worktree_archive_before_remove() {
+    let dir = tempfile::tempdir().unwrap();
+    let manifest = setup_worktree_workspace(dir.path());
+    let repo = git2::Repository::open(dir.path().join("my-repo")).unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    repo.branch("develop", &head, false).unwrap();
+
+    let wt = worktree::add_worktree(dir.path(), &manifest, "keep", None, "feature/keep", None)
+        .unwrap()
+        .remove(0)
+        .path;
+    std::fs::write(wt.join("done.txt"), "committed\n").unwrap();
+    for args in [
+        &["add", "."][..],
+        &[
+            "-c",
+            "user.name=Test",
+            "-c",
+            "user.email=test@test.com",
+            "commit",
+            "-m",
+            "Committed work",
+        ],
+    ] {
+        std::process::Command::new("git")
+            .args(args)
+            .current_dir(&wt)
+            .output()
+            .unwrap();
+    }
+    std::fs::write(wt.join("README.md"), "# Edited\n").unwrap();
+    std::fs::create_dir_all(wt.join("notes")).unwrap();
+    std::fs::write(wt.join("notes/todo.txt"), "untracked\n").unwrap();
+
+    let archived = worktree::archive_worktree(dir.path(), &manifest, "keep", None).unwrap();
+    assert_eq!(
+        archived.path,
+        dir.path().join(".smctl/worktree-archive/keep.tar.gz")
+    );
+    let r = &archived.repos[0];
+    assert_eq!(r.branch, "feature/keep");
+    assert_eq!(r.base.as_deref(), Some("develop"));
+    assert_eq!(r.unmerged_commits, 1);
+    assert!(r.uncommitted);
+    assert_eq!(r.untracked_files, 1);
+
+    let listing = std::process::Command::new("tar")
+        .arg("-tzf")
+        .arg(&archived.path)
+        .output()
+        .unwrap();
+    let listing = String::from_utf8_lossy(&listing.stdout);
+    for entry in [
+        "keep/set.json",
+        "keep/my-repo/unmerged.patch",
+        "keep/my-repo/uncommitted.patch",
+        "keep/my-repo/untracked/notes/todo.txt",
+    ] {
+        assert!(listing.lines().any(|l| l == entry), "{entry} in {listing}");
+    }
+
+    // A second archive does not overwrite the first
+    let again = worktree::archive_worktree(dir.path(), &manifest, "keep", None).unwrap();
+    assert_ne!(again.path, archived.path);
+    assert!(archived.path.exists() && again.path.exists());
+    assert!(!dir.path().join(".smctl/worktree-archive/.keep").exists());
+}
+
+#[test]
+fn test_worktree_archive_without_base_branch() {
+    let dir = tempfile::tempdir().unwrap();
+    let manifest = setup_worktree_workspace(dir.path());
+    let git = |cwd: &Path, args: &[&str]| {
+        let output = std::process::Command::new("git")
+            .args(["-c", "user.name=Test", "-c", "user.email=test@test.com"])
+            .args(args)
+            .current_dir(cwd)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{output:?}");
+    };
+    let listing = |archive: &Path| {
+        let output = std::process::Command::new("tar")
+            .arg("-tzf")
+            .arg(archive)
+            .output()
+            .unwrap();
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+
+    // No develop here, so a feature branch is compared with main
+    let wt = worktree::add_worktree(dir.path(), &manifest, "one", None, "feature/one", None)
+        .unwrap()
+        .remove(0)
+        .path;
+    git(&wt, &["commit", "-q", "--allow-empty", "-m", "One"]);
+    let archived = worktree::archive_worktree(dir.path(), &manifest, "one", None).unwrap();
+    assert_eq!(archived.repos[0].base.as_deref(), Some("main"));
+    assert_eq!(archived.repos[0].unmerged_commits, 1);
+    assert!(listing(&archived.path).contains("one/my-repo/unmerged.patch"));
+
+    // With no base branch at all, the whole branch is bundled
+    let repo_path = dir.path().join("my-repo");
+    git(&repo_path, &["checkout", "-q", "--detach"]);
+    git(&repo_path, &["branch", "-q", "-D", "main"]);
+    let archived = worktree::archive_worktree(dir.path(), &manifest, "one", None).unwrap();
+    assert_eq!(archived.repos[0].base, None);
+    assert_eq!(archived.repos[0].unmerged_commits, 2);
+    assert!(listing(&archived.path).contains("one/my-repo/unmerged.bundle"));
+}
+
+#[test]
+fn test_worktree_disk_usage_flags_stale_sets() {
+    fn age(path: &Path, time: std::time::SystemTime) {
+        if path.is_dir() {
+            for entry in std::fs::read_dir(path).unwrap() {
+                age(&entry.unwrap().path(), time);
+            }
+        }
+        std::fs::File::open(path)
+            .unwrap()
+            .set_modified(time)
+            .unwrap();
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    let manifest = setup_worktree_workspace(dir.path());
+    let big = worktree::add_worktree(dir.path(), &manifest, "big", None, "feature/big", None)
+        .unwrap()
+        .remove(0)
+        .path;
+    let old = worktree::add_worktree(dir.path(), &manifest, "old", None, "feature/old", None)
+        .unwrap()
+        .remove(0)
+        .path;
+    std::fs::write(big.join("blob.bin"), vec![0u8; 64 * 1024]).unwrap();
+    let long_ago = std::time::SystemTime::now() - std::time::Duration::from_secs(60 * 24 * 3600);
+    age(&old, long_ago);
+
+    let usage = worktree::disk_usage(dir.path(), &manifest, 30).unwrap();
+    let names: Vec<_> = usage.iter().map(|u| u.name.as_str()).collect();
+    assert_eq!(names, ["big", "old"]);
+    assert!(usage[0].bytes >= 64 * 1024);
+    assert_eq!(usage[0].bytes, usage[0].repos[0].bytes);
+    assert!(!usage[0].stale);
+    assert!(usage[1].stale);
+
+    // A wider window keeps it
+    let usage = worktree::disk_usage(dir.path(), &manifest, 90).unwrap();
+    assert!(usage.iter().all(|u| !u.stale));
+}
+
+#[test]
+fn test_worktree_rename_keeps_git_links() {
+    let dir = tempfile::tempdir().unwrap();
+    let manifest = setup_worktree_workspace(dir.path());
+    worktree::add_worktree(dir.path(), &manifest, "old", None, "feature/old", None).unwrap();
+    worktree::add_worktree(dir.path(), &manifest, "taken", None, "feature/taken", None).unwrap();
+
+    assert!(worktree::rename_worktree(dir.path(), &manifest, "old", "taken", None).is_err());
+    assert!(worktree::rename_worktree(dir.path(), &manifest, "nope", "new", None).is_err());
+
+    let infos = worktree::rename_worktree(dir.path(), &manifest, "old", "new", Some("feature/new"))
+        .unwrap();
+    assert_eq!(infos[0].branch, "feature/new");
+    assert!(!dir.path().join(".worktrees/old").exists());
+
+    // git still knows the worktree, at its new place
+    let repo = git2::Repository::open(dir.path().join("my-repo")).unwrap();
+    let moved = repo
+        .worktrees()
+        .unwrap()
+        .iter()
+        .flatten()
+        .map(|n| repo.find_worktree(n).unwrap())
+        .find(|wt| wt.path().ends_with("new/my-repo"))
+        .unwrap();
+    assert!(moved.validate().is_ok());
+    assert!(
+        repo.find_branch("feature/old", git2::BranchType::Local)
+            .is_err()
+    );
+    let wt_repo = git2::Repository::open(&infos[0].path).unwrap();
+    assert_eq!(wt_repo.head().unwrap().shorthand(), Some("feature/new"));
+
+    let meta = worktree::SetMetadata::load(dir.path(), "new")
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        (meta.name.as_str(), meta.branch.as_str()),
+        ("new", "feature/new")
+    );
+    assert!(
+        worktree::SetMetadata::load(dir.path(), "old")
+            .unwrap()
+            .is_none()
+    );
+    assert!(
+        worktree::prune_worktrees(dir.path(), &manifest, true, false)
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_worktree_rename_is_all_or_nothing() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut manifest = setup_worktree_workspace(dir.path());
+    add_other_repo(dir.path(), &mut manifest);
+    worktree::add_worktree(dir.path(), &manifest, "old", None, "feature/old", None).unwrap();
+    let base = dir.path().join(&manifest.worktree.base_dir);
+    let unchanged = |base: &Path| {
+        assert!(base.join("old/my-repo").exists());
+        assert!(base.join("old/other").exists());
+        assert!(!base.join("new").exists());
+        let wt = git2::Repository::open(base.join("old/my-repo")).unwrap();
+        assert_eq!(wt.head().unwrap().shorthand(), Some("feature/old"));
+    };
+
+    // Checked up front: the new branch name is taken in one repo
+    let other = git2::Repository::open(dir.path().join("other")).unwrap();
+    let head = other.head().unwrap().peel_to_commit().unwrap();
+    other.branch("feature/new", &head, false).unwrap();
+    let err = worktree::rename_worktree(dir.path(), &manifest, "old", "new", Some("feature/new"))
+        .unwrap_err();
+    assert!(err.to_string().contains("already exists"), "{err:#}");
+    unchanged(&base);
+
+    // Failing midway: the locked worktree can't move, so my-repo moves back
+    std::process::Command::new("git")
+        .args(["worktree", "lock"])
+        .arg(base.join("old/other"))
+        .current_dir(dir.path().join("other"))
+        .output()
+        .unwrap();
+    let err =
+        worktree::rename_worktree(dir.path(), &manifest, "old", "new", Some("feature/renamed"))
+            .unwrap_err();
+    assert!(format!("{err:#}").contains("left as it was"), "{err:#}");
+    unchanged(&base);
+    let repo = git2::Repository::open(dir.path().join("my-repo")).unwrap();
+    assert!(
+        repo.find_branch("feature/renamed", git2::BranchType::Local)
+            .is_err()
+    );
+    assert!(
+        worktree::SetMetadata::load(dir.path(), "old")
+            .unwrap()
+            .is_some()
+    );
+}
+
+#[test]
+fn test_worktree_adopt_registers_manual_worktrees() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut manifest = setup_worktree_workspace(dir.path());
+    add_other_repo(dir.path(), &mut manifest);
+    let git = |cwd: &Path, args: &[&str]| {
+        let output = std::process::Command::new("git")
+            .args(args)
+            .current_dir(cwd)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{output:?}");
+    };
+
+    // A worktree made by hand in the base dir is listed, but not adopted
+    git(
+        &dir.path().join("my-repo"),
+        &[
+            "worktree",
+            "add",
+            "-q",
+            "-b",
+            "feature/manual",
+            "../.worktrees/manual/my-repo",
+        ],
+    );
+    let sets = worktree::list_worktrees(dir.path(), &manifest).unwrap();
+    assert_eq!(sets[0].name, "manual");
+    assert!(sets[0].metadata.is_none());
+
+    let set = worktree::adopt_worktree(
+        dir.path(),
+        &manifest,
+        &dir.path().join(".worktrees/manual"),
+        None,
+    )
+    .unwrap();
+    assert_eq!(set.name, "manual");
+    let meta = set.metadata.unwrap();
+    assert_eq!(meta.branch, "feature/manual");
+    assert_eq!(meta.repos, ["my-repo"]);
+    assert!(
+        worktree::adopt_worktree(
+            dir.path(),
+            &manifest,
+            &dir.path().join(".worktrees/manual"),
+            None
+        )
+        .is_err()
+    );
+
+    // One made elsewhere is moved into the set
+    git(
+        &dir.path().join("other"),
+        &[
+            "worktree",
+            "add",
+            "-q",
+            "-b",
+            "feature/manual",
+            "../elsewhere/other-wip",
+        ],
+    );
+    let set = worktree::adopt_worktree(
+        dir.path(),
+        &manifest,
+        &dir.path().join("elsewhere/other-wip"),
+        Some("manual"),
+    )
+    .unwrap();
+    let moved = dir.path().join(".worktrees/manual/other");
+    assert_eq!(set.worktrees[0].path, moved);
+    assert!(!dir.path().join("elsewhere/other-wip").exists());
+    assert!(git2::Repository::open(&moved).unwrap().is_worktree());
+    let meta = worktree::SetMetadata::load(dir.path(), "manual")
+        .unwrap()
+        .unwrap();
+    assert_eq!(meta.repos, ["my-repo", "other"]);
+
+    // Directories that hold no worktree are refused
+    assert!(worktree::adopt_worktree(dir.path(), &manifest, dir.path(), None).is_err());
+}
+
+#[test]
+fn test_worktree_set_metadata() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut manifest = setup_worktree_workspace(dir.path());
+    add_other_repo(dir.path(), &mut manifest);
+    let only = vec!["other".to_string()];
+
+    worktree::add_worktree(
+        dir.path(),
+        &manifest,
+        "meta",
+        Some(&only),
+        "feature/meta",
+        Some("main"),
+    )
+    .unwrap();
+    let meta = worktree::SetMetadata::load(dir.path(), "meta")
+        .unwrap()
+        .unwrap();
+    assert_eq!(meta.branch, "feature/meta");
+    assert_eq!(meta.start.as_deref(), Some("main"));
+    assert_eq!(meta.repos, ["other"]);
+    assert!(meta.created > 0);
+
+    // Extending keeps manifest order; list carries the metadata
+    worktree::extend_worktree(dir.path(), &manifest, "meta", None, "feature/meta", None).unwrap();
+    let sets = worktree::list_worktrees(dir.path(), &manifest).unwrap();
+    let meta = sets[0].metadata.as_ref().unwrap();
+    assert_eq!(meta.repos, ["my-repo", "other"]);
+
+    worktree::remove_worktree(dir.path(), &manifest, "meta", Some(&only), false).unwrap();
+    let meta = worktree::SetMetadata::load(dir.path(), "meta")
+        .unwrap()
+        .unwrap();
+    assert_eq!(meta.repos, ["my-repo"]);
+
+    worktree::remove_worktree(dir.path(), &manifest, "meta", None, false).unwrap();
+    assert!(
+        worktree::SetMetadata::load(dir.path(), "meta")
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[test]
+fn test_worktree_per_repo_base_dir() {
+    let dir = tempfile::tempdir().unwrap();
+    let disk = tempfile::tempdir().unwrap();
+    let mut manifest = setup_worktree_workspace(dir.path());
+    add_other_repo(dir.path(), &mut manifest);
+    manifest.repos[1].worktree_base_dir = Some(disk.path().to_string_lossy().to_string());
+
+    let infos =
+        worktree::add_worktree(dir.path(), &manifest, "big", None, "feature/big", None).unwrap();
+    assert!(infos[0].path.starts_with(dir.path().join(".worktrees")));
+    assert_eq!(infos[1].path, disk.path().join("big").join("other"));
+    assert!(infos.iter().all(|i| i.exists));
+
+    let sets = worktree::list_worktrees(dir.path(), &manifest).unwrap();
+    assert_eq!(sets.len(), 1);
+    assert!(sets[0].worktrees.iter().all(|w| w.exists));
+    assert_eq!(
+        worktree::worktree_path(dir.path(), &manifest, "big").unwrap(),
+        dir.path().join(".worktrees").join("big")
+    );
+
+    // A set of only the relocated repo is found there
+    let only = vec!["other".to_string()];
+    worktree::add_worktree(
+        dir.path(),
+        &manifest,
+        "solo",
+        Some(&only),
+        "feature/solo",
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        worktree::worktree_path(dir.path(), &manifest, "solo").unwrap(),
+        disk.path().join("solo")
+    );
+
+    worktree::remove_worktree(dir.path(), &manifest, "big", None, false).unwrap();
+    worktree::remove_worktree(dir.path(), &manifest, "solo", None, false).unwrap();
+    assert!(!disk.path().join("big").exists());
+    assert!(!disk.path().join("solo").exists());
+    assert!(
+        worktree::list_worktrees(dir.path(), &manifest)
+            .unwrap()
+            .is_empty()
+    );
 }
 
 #[test]
@@ -268,7 +1341,15 @@ fn test_worktree_path() {
     let dir = tempfile::tempdir().unwrap();
     let manifest = setup_worktree_workspace(dir.path());
 
-    worktree::add_worktree(dir.path(), &manifest, "wt-path-test", None, "feature/path").unwrap();
+    worktree::add_worktree(
+        dir.path(),
+        &manifest,
+        "wt-path-test",
+        None,
+        "feature/path",
+        None,
+    )
+    .unwrap();
 
     let path = worktree::worktree_path(dir.path(), &manifest, "wt-path-test").unwrap();
     assert!(path.exists());
@@ -35,6 +35,22 @@ pub fn format_output_with<T: Serialize, F: FnOnce(&T) -> String>(
     }
 }
 
+/// Format a byte count with a binary unit suffix (e.g. `1.5 MiB`).
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
 /// Exit codes for smctl.
 pub mod exit_code {
     pub const SUCCESS: i32 = 0;
@@ -46,6 +62,8 @@ pub mod exit_code {
     pub const BUILD_ERROR: i32 = 6;
     pub const NETWORK_ERROR: i32 = 7;
     pub const DRY_RUN: i32 = 10;
+    /// Interrupted by Ctrl-C (128 + SIGINT, as shells report it).
+    pub const INTERRUPTED: i32 = 130;
 }
 
 // ── Configuration (merged from smctl-config) ────────────────────────
@@ -67,6 +85,20 @@ pub struct UserConfig {
     /// Disable color output
     #[serde(default)]
     pub no_color: bool,
+    /// Notifications sent when a build finishes
+    #[serde(default)]
+    pub notify: NotifyConfig,
+}
+
+/// Build notification sinks (`[notify]` in the user config).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NotifyConfig {
+    /// Show a desktop notification (`notify-send` on Linux, `osascript` on macOS)
+    #[serde(default)]
+    pub desktop: bool,
+    /// POST a JSON summary to this URL; its `text` field suits Slack incoming webhooks
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook: Option<String>,
 }
 
 impl SmctlConfig {
@@ -100,6 +132,8 @@ impl SmctlConfig {
             "user.editor" => self.user.editor.clone(),
             "user.log_level" => self.user.log_level.clone(),
             "user.no_color" => Some(self.user.no_color.to_string()),
+            "user.notify.desktop" => Some(self.user.notify.desktop.to_string()),
+            "user.notify.webhook" => self.user.notify.webhook.clone(),
             _ => None,
         }
     }
@@ -112,6 +146,12 @@ impl SmctlConfig {
             "user.no_color" => {
                 self.user.no_color = value.parse().context("expected true or false")?
             }
+            "user.notify.desktop" => {
+                self.user.notify.desktop = value.parse().context("expected true or false")?
+            }
+            "user.notify.webhook" => {
+                self.user.notify.webhook = (!value.is_empty()).then(|| value.to_string())
+            }
             _ => anyhow::bail!("unknown config key: {key}"),
         }
         Ok(())
@@ -134,6 +174,408 @@ impl SmctlConfig {
     }
 }
 
+// ── Build notifications ─────────────────────────────────────────────
+
+/// Summary of a finished build sent to the configured notification sinks.
+#[derive(Debug, Clone, Serialize)]
+pub struct BuildNotification {
+    pub workspace: String,
+    pub all_passed: bool,
+    pub duration_ms: u64,
+    /// Repos (and test steps) that failed.
+    pub failed: Vec<String>,
+    /// One-line summary, also used as the notification body.
+    pub text: String,
+}
+
+impl BuildNotification {
+    pub fn new(workspace: &str, report: &smctl_build::BuildReport) -> Self {
+        let failed: Vec<String> = report
+            .results
+            .iter()
+            .filter(|r| !r.success && !r.skipped)
+            .map(|r| r.repo_name.clone())
+            .collect();
+        let secs = report.total_duration_ms as f64 / 1000.0;
+        let text = if report.all_passed {
+            format!("{workspace}: build passed in {secs:.1}s")
+        } else {
+            format!(
+                "{workspace}: build FAILED in {secs:.1}s ({})",
+                failed.join(", ")
+            )
+        };
+        Self {
+            workspace: workspace.to_string(),
+            all_passed: report.all_passed,
+            duration_ms: report.total_duration_ms,
+            failed,
+            text,
+        }
+    }
+}
+
+/// Send `notification` to every configured sink. Failures are logged, not
+/// returned: a broken webhook shouldn't change the build's exit code.
+pub async fn send_build_notification(config: &NotifyConfig, notification: &BuildNotification) {
+    if config.desktop
+        && let Err(e) = desktop_notification("smctl build", &notification.text)
+    {
+        tracing::warn!("desktop notification failed: {e:#}");
+    }
+    if let Some(url) = &config.webhook
+        && let Err(e) = post_webhook(url, notification).await
+    {
+        tracing::warn!("build webhook failed: {e:#}");
+    }
+}
+
+fn desktop_notification(title: &str, body: &str) -> Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        let script = format!(
+            "display notification {} with title {}",
+            applescript_string(body),
+            applescript_string(title)
+        );
+        let mut c = std::process::Command::new("osascript");
+        c.args(["-e", &script]);
+        c
+    } else {
+        let mut c = std::process::Command::new("notify-send");
+        c.args([title, body]);
+        c
+    };
+    let status = command.status().context("failed to run desktop notifier")?;
+    if !status.success() {
+        anyhow::bail!("desktop notifier exited with {status}");
+    }
+    Ok(())
+}
+
+fn applescript_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+async fn post_webhook(url: &str, notification: &BuildNotification) -> Result<()> {
+    reqwest::Client::new()
+        .post(url)
+        .json(notification)
+        .timeout(std::time::Duration::from_secs(10))
+        .send()
+        .await
+        .context("failed to reach webhook")?
+        .error_for_status()
+        .context("webhook rejected the notification")?;
+    Ok(())
+}
+
+// ── Spec tracking issues ────────────────────────────────────────────
+
+/// GitHub issues API client for spec tracking issues (`[spec.github]`).
+pub struct GithubIssues {
+    client: reqwest::Client,
+    api_url: String,
+    repo: String,
+    token: String,
+    labels: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct CreatedIssue {
+    number: u64,
+}
+
+impl GithubIssues {
+    /// Build a client from the manifest section; the token is read from
+    /// the configured environment variable.
+    pub fn from_config(config: &smctl_workspace::SpecGithubConfig) -> Result<Self> {
+        Self::from_config_env(config, |name| std::env::var(name).ok())
+    }
+
+    /// [`Self::from_config`], looking the token up with `env`.
+    fn from_config_env(
+        config: &smctl_workspace::SpecGithubConfig,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self> {
+        let token = env(&config.token_env)
+            .filter(|t| !t.is_empty())
+            .with_context(|| format!("{} is not set", config.token_env))?;
+        Ok(Self {
+            client: reqwest::Client::new(),
+            api_url: config.api_url.trim_end_matches('/').to_string(),
+            repo: config.repo.clone(),
+            token,
+            labels: config.labels.clone(),
+        })
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        self.client
+            .request(
+                method,
+                format!("{}/repos/{}/{path}", self.api_url, self.repo),
+            )
+            .bearer_auth(&self.token)
+            .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+            .header(reqwest::header::USER_AGENT, "smctl")
+            .timeout(std::time::Duration::from_secs(10))
+    }
+
+    /// Open an issue and return its number.
+    pub async fn create(&self, title: &str, body: &str) -> Result<u64> {
+        let issue: CreatedIssue = self
+            .request(reqwest::Method::POST, "issues")
+            .json(&serde_json::json!({ "title": title, "body": body, "labels": self.labels }))
+            .send()
+            .await
+            .context("failed to reach GitHub")?
+            .error_for_status()
+            .context("GitHub rejected the new issue")?
+            .json()
+            .await
+            .context("unexpected response from GitHub")?;
+        Ok(issue.number)
+    }
+
+    /// Replace an issue's body.
+    pub async fn update_body(&self, number: u64, body: &str) -> Result<()> {
+        self.patch(number, serde_json::json!({ "body": body }))
+            .await
+    }
+
+    /// Close an issue, updating its body one last time.
+    pub async fn close(&self, number: u64, body: &str) -> Result<()> {
+        self.patch(
+            number,
+            serde_json::json!({ "body": body, "state": "closed" }),
+        )
+        .await
+    }
+
+    async fn patch(&self, number: u64, fields: serde_json::Value) -> Result<()> {
+        self.request(reqwest::Method::PATCH, &format!("issues/{number}"))
+            .json(&fields)
+            .send()
+            .await
+            .context("failed to reach GitHub")?
+            .error_for_status()
+            .with_context(|| format!("GitHub rejected the update to issue #{number}"))?;
+        Ok(())
+    }
+}
+
+// ── Pull requests ───────────────────────────────────────────────────
+
+/// GitHub/GitLab client for `feature finish` in pull request mode
+/// (`[flow] finish = "pull-request"`).
+pub struct PullRequests {
+    client: reqwest::Client,
+    provider: smctl_workspace::PullRequestProvider,
+    api_url: String,
+    token: String,
+}
+
+#[derive(Deserialize)]
+struct CreatedPullRequest {
+    #[serde(alias = "web_url")]
+    html_url: String,
+}
+
+impl PullRequests {
+    /// Build a client from `[flow.pull_request]`; the token is read from
+    /// the configured environment variable.
+    pub fn from_config(config: &smctl_workspace::PullRequestConfig) -> Result<Self> {
+        Self::from_config_env(config, |name| std::env::var(name).ok())
+    }
+
+    /// [`Self::from_config`], looking the token up with `env`.
+    fn from_config_env(
+        config: &smctl_workspace::PullRequestConfig,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self> {
+        let token_env = config.token_env();
+        let token = env(token_env)
+            .filter(|t| !t.is_empty())
+            .with_context(|| format!("{token_env} is not set"))?;
+        Ok(Self {
+            client: reqwest::Client::new(),
+            provider: config.provider,
+            api_url: config.api_url().trim_end_matches('/').to_string(),
+            token,
+        })
+    }
+
+    /// Open a pull (merge) request from `head` into `base` in the repo
+    /// cloned from `repo_url`, returning its web URL.
+    pub async fn open(
+        &self,
+        repo_url: &str,
+        head: &str,
+        base: &str,
+        title: &str,
+        body: &str,
+    ) -> Result<String> {
+        use smctl_workspace::PullRequestProvider;
+
+        let slug = repo_slug(repo_url)
+            .with_context(|| format!("cannot tell the repository path from '{repo_url}'"))?;
+        let request = match self.provider {
+            PullRequestProvider::Github => self
+                .client
+                .post(format!("{}/repos/{slug}/pulls", self.api_url))
+                .bearer_auth(&self.token)
+                .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+                .json(&serde_json::json!({
+                    "title": title, "head": head, "base": base, "body": body,
+                })),
+            PullRequestProvider::Gitlab => self
+                .client
+                .post(format!(
+                    "{}/projects/{}/merge_requests",
+                    self.api_url,
+                    slug.replace('/', "%2F")
+                ))
+                .header("PRIVATE-TOKEN", &self.token)
+                .json(&serde_json::json!({
+                    "title": title, "source_branch": head, "target_branch": base,
+                    "description": body,
+                })),
+        };
+        let created: CreatedPullRequest = request
+            .header(reqwest::header::USER_AGENT, "smctl")
+            .timeout(std::time::Duration::from_secs(10))
+            .send()
+            .await
+            .context("failed to reach the code host")?
+            .error_for_status()
+            .with_context(|| format!("the code host rejected the pull request for {slug}"))?
+            .json()
+            .await
+            .context("unexpected response from the code host")?;
+        Ok(created.html_url)
+    }
+}
+
+/// `owner/name` (or `group/subgroup/name`) path of a repository from its
+/// clone URL: `https://host/owner/name.git`, `ssh://git@host/owner/name` or
+/// `git@host:owner/name.git`.
+pub fn repo_slug(url: &str) -> Option<String> {
+    let path = match url.split_once("://") {
+        Some((_, rest)) => rest.split_once('/')?.1,
+        None => url.split_once(':')?.1,
+    };
+    let path = path.trim_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    path.contains('/').then(|| path.to_string())
+}
+
+// ── Spec import ─────────────────────────────────────────────────────
+
+/// Read a document for `spec new --from`: an `http(s)://` URL is fetched,
+/// anything else is read as a local file.
+pub async fn read_document(source: &str) -> Result<String> {
+    if source.starts_with("http://") || source.starts_with("https://") {
+        reqwest::Client::new()
+            .get(source)
+            .timeout(std::time::Duration::from_secs(30))
+            .send()
+            .await
+            .with_context(|| format!("failed to fetch {source}"))?
+            .error_for_status()
+            .with_context(|| format!("failed to fetch {source}"))?
+            .text()
+            .await
+            .with_context(|| format!("failed to read {source}"))
+    } else {
+        std::fs::read_to_string(source).with_context(|| format!("failed to read {source}"))
+    }
+}
+
+// ── Workspace registry ──────────────────────────────────────────────
+
+/// User-level registry of known workspaces (~/.config/smctl/workspaces.toml).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorkspaceRegistry {
+    /// Workspace selected with `smctl workspace switch`.
+    #[serde(default)]
+    pub current: Option<String>,
+    #[serde(default)]
+    pub workspaces: Vec<RegisteredWorkspace>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisteredWorkspace {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+impl WorkspaceRegistry {
+    /// Default registry path in the user config directory.
+    pub fn default_path() -> Result<PathBuf> {
+        Ok(SmctlConfig::user_config_dir()?.join("workspaces.toml"))
+    }
+
+    /// Load the registry from the default location.
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::default_path()?)
+    }
+
+    /// Load the registry from a file (empty if it doesn't exist).
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path).context("failed to read workspace registry")?;
+        toml::from_str(&content).context("failed to parse workspace registry")
+    }
+
+    /// Save the registry to the default location.
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&Self::default_path()?)
+    }
+
+    /// Save the registry to a file.
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).context("failed to create config directory")?;
+        }
+        let content = toml::to_string_pretty(self).context("failed to serialize registry")?;
+        std::fs::write(path, content).context("failed to write workspace registry")?;
+        Ok(())
+    }
+
+    /// Register a workspace, replacing any entry with the same name.
+    pub fn register(&mut self, name: &str, path: &Path) {
+        self.workspaces.retain(|w| w.name != name);
+        self.workspaces.push(RegisteredWorkspace {
+            name: name.to_string(),
+            path: path.to_path_buf(),
+        });
+    }
+
+    /// Find a registered workspace by name.
+    pub fn find(&self, name: &str) -> Option<&RegisteredWorkspace> {
+        self.workspaces.iter().find(|w| w.name == name)
+    }
+
+    /// Make `name` the current workspace.
+    pub fn switch(&mut self, name: &str) -> Result<&RegisteredWorkspace> {
+        if self.find(name).is_none() {
+            anyhow::bail!("workspace '{name}' is not registered (see `smctl workspace list`)");
+        }
+        self.current = Some(name.to_string());
+        Ok(self.find(name).expect("checked above"))
+    }
+
+    /// Path of the current workspace, if one is selected.
+    pub fn current_path(&self) -> Option<&Path> {
+        self.current
+            .as_deref()
+            .and_then(|name| self.find(name))
+            .map(|w| w.path.as_path())
+    }
+}
+
 /// Resolve XDG-style config directory.
 fn dirs_path() -> PathBuf {
     if let Ok(config) = std::env::var("XDG_CONFIG_HOME") {
@@ -158,6 +600,67 @@ pub fn find_workspace_root(start: &Path) -> Option<PathBuf> {
     }
 }
 
+// ── Shell integration ───────────────────────────────────────────────
+
+/// Shell targeted by `smctl shell-init`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InitShell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl std::str::FromStr for InitShell {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "bash" => Ok(Self::Bash),
+            "zsh" => Ok(Self::Zsh),
+            "fish" => Ok(Self::Fish),
+            other => anyhow::bail!("unsupported shell '{other}' (expected bash, zsh or fish)"),
+        }
+    }
+}
+
+/// Shell code defining `smcd <set>`, which changes into a worktree set via
+/// `smctl worktree cd`, with set names completed from `smctl worktree list
+/// --names`. Meant for `eval "$(smctl shell-init bash)"` in a shell rc file.
+pub fn shell_init(shell: InitShell) -> &'static str {
+    match shell {
+        InitShell::Bash => {
+            r#"smcd() {
+    local dir
+    dir="$(command smctl worktree cd "$@")" && cd "$dir"
+}
+_smcd() {
+    local IFS=$'\n'
+    COMPREPLY=($(compgen -W "$(command smctl worktree list --names 2>/dev/null)" -- "${COMP_WORDS[COMP_CWORD]}"))
+}
+complete -F _smcd smcd
+"#
+        }
+        InitShell::Zsh => {
+            r#"smcd() {
+    local dir
+    dir="$(command smctl worktree cd "$@")" && cd "$dir"
+}
+_smcd() {
+    compadd -- ${(f)"$(command smctl worktree list --names 2>/dev/null)"}
+}
+(( $+functions[compdef] )) && compdef _smcd smcd
+"#
+        }
+        InitShell::Fish => {
+            r#"function smcd --description 'cd into an smctl worktree set'
+    set -l dir (command smctl worktree cd $argv); and cd $dir
+end
+complete -c smcd -f -a '(command smctl worktree list --names 2>/dev/null)'
+"#
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -169,6 +672,127 @@ mod tests {
         assert!(!config.user.no_color);
     }
 
+    #[tokio::test]
+    async fn test_github_issues_create() {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            while !String::from_utf8_lossy(&request).contains("\"labels\"") {
+                let n = stream.read(&mut buf).unwrap();
+                request.extend_from_slice(&buf[..n]);
+            }
+            let body = r#"{"number": 42}"#;
+            write!(
+                stream,
+                "HTTP/1.1 201 Created\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+            String::from_utf8_lossy(&request).to_string()
+        });
+
+        let config = smctl_workspace::SpecGithubConfig {
+            repo: "org/repo".to_string(),
+            token_env: "SMCTL_TEST_GITHUB_TOKEN".to_string(),
+            labels: vec!["spec".to_string()],
+            api_url: format!("http://{addr}/"),
+        };
+        let env = |name: &str| (name == "SMCTL_TEST_GITHUB_TOKEN").then(|| "secret".to_string());
+        let issues = GithubIssues::from_config_env(&config, env).unwrap();
+        assert_eq!(issues.create("Spec: x", "body").await.unwrap(), 42);
+
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /repos/org/repo/issues HTTP/1.1"));
+        assert!(
+            request
+                .to_lowercase()
+                .contains("authorization: bearer secret")
+        );
+
+        let missing = smctl_workspace::SpecGithubConfig {
+            token_env: "SMCTL_TEST_GITHUB_TOKEN_UNSET".to_string(),
+            ..config
+        };
+        assert!(GithubIssues::from_config_env(&missing, env).is_err());
+    }
+
+    #[test]
+    fn test_repo_slug() {
+        for url in [
+            "https://github.com/SmallAIOS/ModelGate.git",
+            "https://github.com/SmallAIOS/ModelGate",
+            "git@github.com:SmallAIOS/ModelGate.git",
+            "ssh://git@github.com/SmallAIOS/ModelGate.git",
+        ] {
+            assert_eq!(
+                repo_slug(url).as_deref(),
+                Some("SmallAIOS/ModelGate"),
+                "{url}"
+            );
+        }
+        assert_eq!(
+            repo_slug("https://gitlab.com/group/sub/repo.git").as_deref(),
+            Some("group/sub/repo")
+        );
+        assert_eq!(repo_slug("/local/path"), None);
+    }
+
+    #[tokio::test]
+    async fn test_pull_request_open_gitlab() {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            while !String::from_utf8_lossy(&request).contains("\"target_branch\"") {
+                let n = stream.read(&mut buf).unwrap();
+                request.extend_from_slice(&buf[..n]);
+            }
+            let body =
+                r#"{"iid": 7, "web_url": "https://gitlab.example/g/app/-/merge_requests/7"}"#;
+            write!(
+                stream,
+                "HTTP/1.1 201 Created\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+            String::from_utf8_lossy(&request).to_string()
+        });
+
+        let config = smctl_workspace::PullRequestConfig {
+            provider: smctl_workspace::PullRequestProvider::Gitlab,
+            token_env: Some("SMCTL_TEST_GITLAB_TOKEN".to_string()),
+            api_url: Some(format!("http://{addr}")),
+            remote: "origin".to_string(),
+        };
+        let env = |name: &str| (name == "SMCTL_TEST_GITLAB_TOKEN").then(|| "secret".to_string());
+        let prs = PullRequests::from_config_env(&config, env).unwrap();
+        let url = prs
+            .open(
+                "git@gitlab.example:g/app.git",
+                "feature/x",
+                "develop",
+                "x",
+                "",
+            )
+            .await
+            .unwrap();
+        assert_eq!(url, "https://gitlab.example/g/app/-/merge_requests/7");
+
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /projects/g%2Fapp/merge_requests HTTP/1.1"));
+        assert!(request.to_lowercase().contains("private-token: secret"));
+        assert!(request.contains("\"source_branch\":\"feature/x\""));
+    }
+
     #[test]
     fn test_get_set_config() {
         let mut config = SmctlConfig::default();
@@ -176,12 +800,70 @@ mod tests {
         assert_eq!(config.get("user.editor"), Some("vim".to_string()));
     }
 
+    #[test]
+    fn test_notify_config_and_message() {
+        let mut config = SmctlConfig::default();
+        config.set("user.notify.desktop", "true").unwrap();
+        config
+            .set("user.notify.webhook", "https://example.com/hook")
+            .unwrap();
+        assert!(config.user.notify.desktop);
+        assert_eq!(
+            config.get("user.notify.webhook").as_deref(),
+            Some("https://example.com/hook")
+        );
+        let parsed: UserConfig = toml::from_str(&config.show()).unwrap();
+        assert!(parsed.notify.desktop);
+
+        let result = |name: &str, success: bool| smctl_build::BuildResult {
+            repo_name: name.to_string(),
+            success,
+            ..Default::default()
+        };
+        let report = smctl_build::BuildReport {
+            build_id: String::new(),
+            results: vec![result("A", true), result("B (test)", false)],
+            total_duration_ms: 12_500,
+            all_passed: false,
+        };
+        let notification = BuildNotification::new("ws", &report);
+        assert_eq!(notification.failed, ["B (test)"]);
+        assert_eq!(notification.text, "ws: build FAILED in 12.5s (B (test))");
+    }
+
     #[test]
     fn test_set_unknown_key() {
         let mut config = SmctlConfig::default();
         assert!(config.set("unknown.key", "value").is_err());
     }
 
+    #[test]
+    fn test_workspace_registry() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("workspaces.toml");
+
+        let mut registry = WorkspaceRegistry::load_from(&path).unwrap();
+        assert!(registry.workspaces.is_empty());
+        registry.register("alpha", Path::new("/tmp/alpha"));
+        registry.register("beta", Path::new("/tmp/beta"));
+        registry.register("alpha", Path::new("/tmp/alpha2"));
+        assert_eq!(registry.workspaces.len(), 2);
+        assert!(registry.switch("nope").is_err());
+        registry.switch("alpha").unwrap();
+        registry.save_to(&path).unwrap();
+
+        let loaded = WorkspaceRegistry::load_from(&path).unwrap();
+        assert_eq!(loaded.current_path(), Some(Path::new("/tmp/alpha2")));
+        assert_eq!(loaded.find("beta").unwrap().path, Path::new("/tmp/beta"));
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(3 * 1024 * 1024), "3.0 MiB");
+    }
+
     #[test]
     fn test_find_workspace_root_none() {
         let dir = tempfile::tempdir().unwrap();
@@ -1,11 +1,14 @@
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
 use std::process;
+use std::sync::Mutex;
 
 use anyhow::{Context, Result};
 use clap::{CommandFactory, Parser, Subcommand};
 use clap_complete::{Shell, generate};
 
-use smctl::{OutputFormat, exit_code, format_output_with};
+use smctl::{OutputFormat, exit_code, format_bytes, format_output_with};
 
 /// smctl — SmallAIOS control
 ///
@@ -15,7 +18,7 @@ use smctl::{OutputFormat, exit_code, format_output_with};
 #[command(name = "smctl", version, about, long_about = None)]
 #[command(propagate_version = true)]
 struct Cli {
-    /// Override workspace root (default: auto-detect from cwd)
+    /// Override workspace root or registered workspace name (default: auto-detect from cwd)
     #[arg(short = 'w', long, global = true, env = "SMCTL_WORKSPACE")]
     workspace: Option<PathBuf>,
 
@@ -74,7 +77,11 @@ enum Commands {
     },
 
     /// Build repos in dependency order
+    #[command(args_conflicts_with_subcommands = true)]
     Build {
+        #[command(subcommand)]
+        command: Option<BuildCommands>,
+
         /// Build a specific repo (and its dependencies)
         repo: Option<String>,
 
@@ -82,6 +89,38 @@ enum Commands {
         #[arg(long)]
         parallel: bool,
 
+        /// Maximum total build_weight of repos built at once (with --parallel; overrides [build] max_parallel)
+        #[arg(short, long, requires = "parallel")]
+        jobs: Option<usize>,
+
+        /// Build profile from [repos.profiles.<name>] (e.g. release)
+        #[arg(long)]
+        profile: Option<String>,
+
+        /// Rerun failed test commands up to N times (overrides [build] test_retries)
+        #[arg(long, value_name = "N", requires = "test")]
+        retries: Option<u32>,
+
+        /// Write a machine-readable report: junit:<path> or json:<path> (repeatable)
+        #[arg(long, value_name = "FORMAT:PATH")]
+        report: Vec<smctl_build::ReportSpec>,
+
+        /// Build only repos changed since REF (default origin/develop) plus their dependents
+        #[arg(long, value_name = "REF", num_args = 0..=1, default_missing_value = "origin/develop", conflicts_with = "repo")]
+        changed: Option<String>,
+
+        /// Keep building independent repos after a failure; dependents of failed repos are skipped
+        #[arg(long)]
+        keep_going: bool,
+
+        /// Watch repo sources and rebuild changed repos plus their dependents
+        #[arg(long)]
+        watch: bool,
+
+        /// Quiet period before a watch rebuild starts, in milliseconds
+        #[arg(long, default_value_t = 500, requires = "watch")]
+        debounce: u64,
+
         /// Run tests after build
         #[arg(long)]
         test: bool,
@@ -94,9 +133,33 @@ enum Commands {
         #[arg(long)]
         verify: bool,
 
-        /// Run Cedar policy analysis only (with --verify)
+        /// Only validate Cedar policies from [repos.cedar]; nothing is built
         #[arg(long)]
         cedar: bool,
+
+        /// Extra arguments appended to each repo's test command (after `--`)
+        #[arg(last = true, value_name = "TEST_ARGS", requires = "test")]
+        test_args: Vec<String>,
+    },
+
+    /// Run per-repo coverage and print a workspace-wide summary
+    Coverage {
+        /// Measure a single repo
+        repo: Option<String>,
+    },
+
+    /// Run per-repo benchmarks and compare against a previous run
+    Bench {
+        /// Benchmark a single repo
+        repo: Option<String>,
+
+        /// Compare against a recorded run (default: the latest one)
+        #[arg(long, value_name = "ID", num_args = 0..=1, default_missing_value = "latest")]
+        compare: Option<String>,
+
+        /// Slowdown in percent that counts as a regression
+        #[arg(long, default_value_t = 5.0, requires = "compare")]
+        threshold: f64,
     },
 
     /// Configuration management
@@ -111,11 +174,21 @@ enum Commands {
         shell: Shell,
     },
 
+    /// Print shell functions for ~/.bashrc etc.: `smcd <set>` changes into a
+    /// worktree set (use: eval "$(smctl shell-init bash)")
+    ShellInit {
+        /// Shell: bash, zsh, or fish
+        shell: smctl::InitShell,
+    },
+
     // --- Convenience aliases ---
     /// Start a feature branch + worktree (alias: flow feature start + worktree add)
     Feat {
         /// Feature name
         name: String,
+        /// Build the new worktrees once created
+        #[arg(long)]
+        build: bool,
     },
 
     /// Finish a feature: remove worktree + merge (alias: worktree remove + flow feature finish)
@@ -162,6 +235,35 @@ enum WorkspaceCommands {
     Status,
     /// Fetch/pull all repos
     Sync,
+    /// Summarize uncommitted and unmerged changes across repos
+    Diff,
+    /// List registered workspaces
+    List,
+    /// Make a registered workspace the current one
+    Switch {
+        /// Registered workspace name
+        name: String,
+    },
+    /// Run clean commands and prune smctl temp dirs
+    Clean {
+        /// Clean a specific repo only
+        repo: Option<String>,
+    },
+    /// Run git maintenance (gc, prune, repack) across repos
+    Gc {
+        /// Use `git gc --aggressive`
+        #[arg(long)]
+        aggressive: bool,
+        /// Expire reflog entries older than this (e.g. "30.days", "now")
+        #[arg(long, value_name = "WHEN")]
+        expire_reflogs: Option<String>,
+    },
+    /// Verify all repos are on the same branch
+    CheckBranches {
+        /// Limit to specific repos (comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        repos: Option<Vec<String>>,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -173,17 +275,105 @@ enum WorktreeCommands {
         /// Limit to specific repos (comma-separated)
         #[arg(long, value_delimiter = ',')]
         repos: Option<Vec<String>>,
+        /// Branch to check out, existing or new (default: feature/<name>)
+        #[arg(long)]
+        branch: Option<String>,
+        /// Ref a new branch starts from (default: HEAD); not allowed for an
+        /// existing branch
+        #[arg(long)]
+        base: Option<String>,
+        /// Add the repos to an existing set instead of creating one
+        #[arg(long)]
+        extend: bool,
+        /// Build the set's repos in their worktrees once created
+        #[arg(long)]
+        build: bool,
     },
     /// List active worktrees
-    List,
+    List {
+        /// Print only set names, one per line (for scripts and completion)
+        #[arg(long)]
+        names: bool,
+    },
     /// Remove a worktree set
     Remove {
         /// Worktree set name
         name: String,
+        /// Only remove these repos from the set (comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        repos: Option<Vec<String>>,
+        /// Save unmerged commits, uncommitted changes and untracked files to
+        /// `.smctl/worktree-archive/<name>.tar.gz` first
+        #[arg(long)]
+        archive: bool,
         /// Force removal even with uncommitted changes
         #[arg(long)]
         force: bool,
     },
+    /// Remove worktrees whose branch was deleted or whose directory or git
+    /// entry is gone, and empty set directories
+    Prune {
+        /// Also prune worktrees with uncommitted or untracked files, and set
+        /// directories git no longer tracks
+        #[arg(long)]
+        force: bool,
+    },
+    /// Run a command in each repo of a worktree set
+    Exec {
+        /// Worktree set name
+        name: String,
+        /// Limit to specific repos (comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        repos: Option<Vec<String>>,
+        /// Run repos in dependency (`depends_on`) order
+        #[arg(long)]
+        ordered: bool,
+        /// Command and arguments to run (after `--`)
+        #[arg(last = true, required = true, value_name = "CMD")]
+        cmd: Vec<String>,
+    },
+    /// Open a worktree set in the configured editor (a multi-root
+    /// workspace for VS Code and its forks, the set directory otherwise)
+    Open {
+        /// Worktree set name
+        name: String,
+    },
+    /// Fetch and rebase (or merge) each worktree of a set onto its base branch
+    Sync {
+        /// Worktree set name
+        name: String,
+        /// Merge the base branch in instead of rebasing (default: `[worktree] sync`)
+        #[arg(long, conflicts_with = "rebase")]
+        merge: bool,
+        /// Rebase onto the base branch (default: `[worktree] sync`)
+        #[arg(long)]
+        rebase: bool,
+    },
+    /// Show disk usage per worktree set and repo, flagging stale sets
+    Du {
+        /// Flag sets with no file changed for this many days
+        #[arg(long, default_value_t = 30)]
+        stale_days: u64,
+    },
+    /// Rename a worktree set, keeping git's worktree bookkeeping intact
+    Rename {
+        /// Current set name
+        old: String,
+        /// New set name
+        new: String,
+        /// Also rename the set's branch to this
+        #[arg(long)]
+        branch: Option<String>,
+    },
+    /// Register worktrees created by hand as a worktree set
+    Adopt {
+        /// A linked worktree, or a directory holding worktrees of workspace
+        /// repos at their local paths
+        path: PathBuf,
+        /// Set name (default: the set directory's name)
+        #[arg(long)]
+        name: Option<String>,
+    },
     /// Print worktree path for shell integration
     Cd {
         /// Worktree set name
@@ -194,7 +384,12 @@ enum WorktreeCommands {
 #[derive(Subcommand, Debug)]
 enum FlowCommands {
     /// Initialize git flow in all repos
-    Init,
+    Init {
+        /// Also push develop to this remote (default: origin) with upstream
+        /// tracking
+        #[arg(long, num_args = 0..=1, default_missing_value = "origin", value_name = "REMOTE")]
+        push: Option<String>,
+    },
     /// Feature branch operations
     Feature {
         #[command(subcommand)]
@@ -210,6 +405,42 @@ enum FlowCommands {
         #[command(subcommand)]
         command: HotfixCommands,
     },
+    /// Bugfix branch operations
+    Bugfix {
+        #[command(subcommand)]
+        command: BugfixCommands,
+    },
+    /// Support branch operations
+    Support {
+        #[command(subcommand)]
+        command: SupportCommands,
+    },
+    /// Resume a finish stopped by a merge conflict, once it is resolved
+    Continue,
+    /// Roll back a finish stopped by a merge conflict
+    Abort,
+    /// Check that a release landed in every repo: tag present and on main,
+    /// release merged into develop
+    VerifyRelease {
+        /// Version string (e.g. "1.0.0")
+        #[arg(value_name = "VERSION")]
+        ver: String,
+    },
+    /// Revert a recorded `release finish`, completed or not; without an id,
+    /// list the recorded operations
+    Undo {
+        /// Operation id, as listed by `smctl flow undo`
+        op_id: Option<String>,
+    },
+    /// Delete feature/bugfix/hotfix branches already merged into develop or main
+    Cleanup {
+        /// Also delete them on this remote (default: origin)
+        #[arg(long, num_args = 0..=1, default_missing_value = "origin", value_name = "REMOTE")]
+        remote: Option<String>,
+        /// Delete without asking for confirmation
+        #[arg(long, short)]
+        yes: bool,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -224,11 +455,76 @@ enum FeatureCommands {
         /// Limit to specific repos
         #[arg(long, value_delimiter = ',')]
         repos: Option<Vec<String>>,
+        /// Run even with uncommitted changes
+        #[arg(long, conflicts_with = "stash")]
+        force: bool,
+        /// Stash uncommitted changes first and restore them afterwards
+        #[arg(long)]
+        stash: bool,
     },
     /// Merge feature into develop
     Finish {
         /// Feature name
         name: String,
+        /// Limit to specific repos
+        #[arg(long, value_delimiter = ',')]
+        repos: Option<Vec<String>>,
+        /// Push the branch and open pull requests instead of merging locally
+        /// (the default with `[flow] finish = "pull-request"`)
+        #[arg(
+            long,
+            conflicts_with_all = ["strategy", "no_rollback", "delete_remote", "force", "stash"]
+        )]
+        pr: bool,
+        /// Merge strategy: no-ff, squash, rebase or ff-only (default: `[flow] merge_strategy`)
+        #[arg(long)]
+        strategy: Option<smctl_workspace::MergeStrategy>,
+        /// Keep the repos already merged when a later repo fails, instead of
+        /// rolling them back
+        #[arg(long)]
+        no_rollback: bool,
+        /// Delete the branch on the remote (default: origin) after it merged
+        #[arg(long, num_args = 0..=1, default_missing_value = "origin", value_name = "REMOTE")]
+        delete_remote: Option<String>,
+        /// Run even with uncommitted changes
+        #[arg(long, conflicts_with = "stash")]
+        force: bool,
+        /// Stash uncommitted changes first and restore them afterwards
+        #[arg(long)]
+        stash: bool,
+    },
+    /// Switch every repo that has the feature to it, tracking the remote
+    /// branch where there is no local one
+    Checkout {
+        /// Feature name
+        name: String,
+        /// Remote to fetch and track
+        #[arg(long, default_value = "origin")]
+        remote: String,
+        /// Run even with uncommitted changes
+        #[arg(long, conflicts_with = "stash")]
+        force: bool,
+        /// Stash uncommitted changes first and restore them afterwards
+        #[arg(long)]
+        stash: bool,
+    },
+    /// Push the feature branch with upstream tracking in every repo that
+    /// has it
+    Publish {
+        /// Feature name
+        name: String,
+        /// Remote to push to
+        #[arg(long, default_value = "origin")]
+        remote: String,
+    },
+    /// Create local branches tracking the remote feature branch, without
+    /// switching to them
+    Track {
+        /// Feature name
+        name: String,
+        /// Remote to fetch and track
+        #[arg(long, default_value = "origin")]
+        remote: String,
     },
     /// List active features
     List,
@@ -244,12 +540,34 @@ enum ReleaseCommands {
         /// Limit to specific repos
         #[arg(long, value_delimiter = ',')]
         repos: Option<Vec<String>>,
+        /// Run even with uncommitted changes
+        #[arg(long, conflicts_with = "stash")]
+        force: bool,
+        /// Stash uncommitted changes first and restore them afterwards
+        #[arg(long)]
+        stash: bool,
     },
     /// Merge release into main + develop, tag
     Finish {
         /// Version string (e.g. "1.0.0")
         #[arg(value_name = "VERSION")]
         ver: String,
+        /// Sign the release tag (GPG or SSH, per git's `gpg.format`)
+        #[arg(long)]
+        sign: bool,
+        /// Merge strategy: no-ff, squash, rebase or ff-only (default: `[flow] merge_strategy`)
+        #[arg(long)]
+        strategy: Option<smctl_workspace::MergeStrategy>,
+        /// Keep the repos already merged when a later repo fails, instead of
+        /// rolling them back
+        #[arg(long)]
+        no_rollback: bool,
+        /// Run even with uncommitted changes
+        #[arg(long, conflicts_with = "stash")]
+        force: bool,
+        /// Stash uncommitted changes first and restore them afterwards
+        #[arg(long)]
+        stash: bool,
     },
     /// List active releases
     List,
@@ -264,50 +582,363 @@ enum HotfixCommands {
         /// Limit to specific repos
         #[arg(long, value_delimiter = ',')]
         repos: Option<Vec<String>>,
+        /// Run even with uncommitted changes
+        #[arg(long, conflicts_with = "stash")]
+        force: bool,
+        /// Stash uncommitted changes first and restore them afterwards
+        #[arg(long)]
+        stash: bool,
     },
     /// Merge hotfix into main + develop
     Finish {
         /// Hotfix name
         name: String,
+        /// Merge strategy: no-ff, squash, rebase or ff-only (default: `[flow] merge_strategy`)
+        #[arg(long)]
+        strategy: Option<smctl_workspace::MergeStrategy>,
+        /// Keep the repos already merged when a later repo fails, instead of
+        /// rolling them back
+        #[arg(long)]
+        no_rollback: bool,
+        /// Delete the branch on the remote (default: origin) after it merged
+        #[arg(long, num_args = 0..=1, default_missing_value = "origin", value_name = "REMOTE")]
+        delete_remote: Option<String>,
+        /// Run even with uncommitted changes
+        #[arg(long, conflicts_with = "stash")]
+        force: bool,
+        /// Stash uncommitted changes first and restore them afterwards
+        #[arg(long)]
+        stash: bool,
     },
     /// List active hotfixes
     List,
 }
 
+#[derive(Subcommand, Debug)]
+enum BugfixCommands {
+    /// Create a bugfix branch from develop
+    Start {
+        /// Bugfix name
+        name: String,
+        /// Limit to specific repos
+        #[arg(long, value_delimiter = ',')]
+        repos: Option<Vec<String>>,
+        /// Run even with uncommitted changes
+        #[arg(long, conflicts_with = "stash")]
+        force: bool,
+        /// Stash uncommitted changes first and restore them afterwards
+        #[arg(long)]
+        stash: bool,
+    },
+    /// Merge bugfix into develop
+    Finish {
+        /// Bugfix name
+        name: String,
+        /// Merge strategy: no-ff, squash, rebase or ff-only (default: `[flow] merge_strategy`)
+        #[arg(long)]
+        strategy: Option<smctl_workspace::MergeStrategy>,
+        /// Keep the repos already merged when a later repo fails, instead of
+        /// rolling them back
+        #[arg(long)]
+        no_rollback: bool,
+        /// Delete the branch on the remote (default: origin) after it merged
+        #[arg(long, num_args = 0..=1, default_missing_value = "origin", value_name = "REMOTE")]
+        delete_remote: Option<String>,
+        /// Run even with uncommitted changes
+        #[arg(long, conflicts_with = "stash")]
+        force: bool,
+        /// Stash uncommitted changes first and restore them afterwards
+        #[arg(long)]
+        stash: bool,
+    },
+    /// List active bugfixes
+    List,
+}
+
+#[derive(Subcommand, Debug)]
+enum SupportCommands {
+    /// Create a long-lived support branch from main
+    Start {
+        /// Support line name (e.g. "1.x")
+        name: String,
+        /// Limit to specific repos
+        #[arg(long, value_delimiter = ',')]
+        repos: Option<Vec<String>>,
+        /// Run even with uncommitted changes
+        #[arg(long, conflicts_with = "stash")]
+        force: bool,
+        /// Stash uncommitted changes first and restore them afterwards
+        #[arg(long)]
+        stash: bool,
+    },
+    /// List support branches
+    List,
+}
+
 #[derive(Subcommand, Debug)]
 enum SpecCommands {
     /// Create a new OpenSpec feature folder
     New {
         /// Spec name
         name: String,
+
+        /// Import an existing design doc (file path or http(s) URL) as the proposal
+        #[arg(long, value_name = "FILE|URL")]
+        from: Option<String>,
+
+        /// Affected workspace repos: a task subsection and Impact row each
+        #[arg(long, value_delimiter = ',', value_name = "REPO,...")]
+        repos: Vec<String>,
     },
     /// Fast-forward: check document completeness
     Ff {
         /// Spec name (default: current)
         name: Option<String>,
     },
-    /// Execute tasks from tasks.md
+    /// List tasks from tasks.md and mark them complete
     Apply {
         /// Spec name (default: current)
         name: Option<String>,
+
+        /// Mark task N complete (numbering as listed; repeatable)
+        #[arg(long = "task", value_name = "N")]
+        tasks: Vec<usize>,
+
+        /// Mark every pending task complete
+        #[arg(long, conflicts_with = "tasks")]
+        all: bool,
+
+        /// Ask about each pending task in turn
+        #[arg(short, long, conflicts_with_all = ["tasks", "all"])]
+        interactive: bool,
+    },
+    /// List, check and add tasks in tasks.md
+    Task {
+        #[command(subcommand)]
+        command: TaskCommands,
+    },
+    /// Scaffold a capability delta (specs/<capability>/spec.md)
+    Delta {
+        /// Spec name
+        name: String,
+        /// Capability the delta changes
+        capability: String,
+    },
+    /// Rename a spec, retitling its documents and updating references
+    Rename {
+        /// Current spec name
+        name: String,
+        /// New spec name
+        new_name: String,
     },
     /// Archive a completed spec
     Archive {
         /// Spec name (default: current)
         name: Option<String>,
+
+        /// Don't merge the spec's deltas into openspec/specs/
+        #[arg(long)]
+        no_promote: bool,
+
+        /// Commit the archive move (and promoted specs) as "Archive spec '<name>'"
+        #[arg(long)]
+        commit: bool,
+    },
+    /// Merge an archived spec's deltas into openspec/specs/<capability>/spec.md
+    Promote {
+        /// Archive folder (`<date>-<name>`) or spec name (latest archive)
+        name: String,
+    },
+    /// Move an archived spec back to changes/
+    Restore {
+        /// Archive folder (`<date>-<name>`) or spec name (latest archive)
+        name: String,
+
+        /// Restore under a different name
+        #[arg(long = "as", value_name = "NAME")]
+        rename_to: Option<String>,
+
+        /// Keep the `<date>-` prefix in the restored folder name
+        #[arg(long, conflicts_with = "rename_to")]
+        keep_date: bool,
+    },
+    /// Open or update the spec's GitHub tracking issue ([spec.github])
+    Sync {
+        /// Spec name
+        name: String,
     },
     /// Check spec completeness
     Validate {
         /// Spec name (default: current)
         name: Option<String>,
     },
+    /// Record a spec's phase in .openspec.yaml
+    SetStatus {
+        /// Spec name
+        name: String,
+        /// new, draft, active or abandoned
+        status: String,
+    },
     /// Show spec progress
     Status {
-        /// Spec name (default: show all)
+        /// Spec name (default: current, else show all)
         name: Option<String>,
     },
     /// List all specs
-    List,
+    List {
+        /// Only specs owned by this person (`me` for your git user.name)
+        #[arg(long)]
+        owner: Option<String>,
+
+        /// Only specs with this label (repeatable; all must match)
+        #[arg(long = "label", value_name = "LABEL")]
+        labels: Vec<String>,
+
+        /// Only specs in this phase (repeatable)
+        #[arg(long = "phase", value_name = "PHASE")]
+        phases: Vec<String>,
+
+        /// Sort order: name, progress (least complete first), or created
+        #[arg(long, default_value = "name")]
+        sort: smctl_spec::SpecSort,
+
+        /// Include archived specs (default)
+        #[arg(long, overrides_with = "no_archived")]
+        archived: bool,
+
+        /// Leave out archived specs
+        #[arg(long, overrides_with = "archived")]
+        no_archived: bool,
+    },
+    /// Show or set a spec's owner, reviewers and labels
+    Meta {
+        /// Spec name
+        name: String,
+
+        /// Owner (`me` for your git user.name)
+        #[arg(long)]
+        owner: Option<String>,
+
+        /// Reviewer (repeatable; replaces the current list)
+        #[arg(long = "reviewer", value_name = "NAME")]
+        reviewers: Vec<String>,
+
+        /// Label (repeatable; replaces the current list)
+        #[arg(long = "label", value_name = "LABEL")]
+        labels: Vec<String>,
+    },
+    /// Requirement → tasks → tests matrix
+    Trace {
+        /// Spec name (default: current)
+        name: Option<String>,
+    },
+    /// Task completion over the git history of tasks.md
+    Burndown {
+        /// Spec name (default: current)
+        name: Option<String>,
+    },
+    /// Workspace-wide spec summary for status updates
+    Report {
+        /// Output format: md, html, or json
+        #[arg(long, default_value = "md")]
+        format: smctl_spec::ReportFormat,
+
+        /// Number of recently archived specs to include
+        #[arg(long, default_value_t = 5)]
+        recent: usize,
+    },
+    /// Search spec documents (active and archived)
+    Search {
+        /// Text to find (case-insensitive)
+        query: String,
+        /// Only search specs in this phase (repeatable)
+        #[arg(long = "phase", value_name = "PHASE")]
+        phases: Vec<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum TaskCommands {
+    /// List tasks with their numbers and sections
+    List {
+        /// Spec name
+        name: String,
+    },
+    /// Mark tasks complete
+    Check {
+        /// Spec name
+        name: String,
+        /// Task numbers, as shown by `spec task list`
+        #[arg(required = true)]
+        numbers: Vec<usize>,
+    },
+    /// Mark tasks pending again
+    Uncheck {
+        /// Spec name
+        name: String,
+        /// Task numbers, as shown by `spec task list`
+        #[arg(required = true)]
+        numbers: Vec<usize>,
+    },
+    /// Add a pending task
+    Add {
+        /// Spec name
+        name: String,
+        /// Task text
+        text: String,
+        /// `##` section to add it under (created if missing; default: after the last task)
+        #[arg(long)]
+        section: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum BuildCommands {
+    /// Print the repo dependency graph with build levels
+    Graph {
+        /// Output format: dot, mermaid, or json
+        #[arg(long, default_value = "dot")]
+        format: smctl_build::GraphFormat,
+
+        /// Annotate repos with durations from the latest recorded build
+        #[arg(long)]
+        durations: bool,
+    },
+    /// List recorded builds (from .smctl/builds/)
+    History {
+        #[command(subcommand)]
+        command: Option<HistoryCommands>,
+
+        /// Number of builds to list
+        #[arg(short = 'n', long, default_value_t = 10)]
+        limit: usize,
+    },
+    /// Explain whether and why a repo would be rebuilt
+    Explain {
+        /// Repo to explain
+        repo: String,
+
+        /// Explain a `build --changed REF` run (default origin/develop) instead of a plain build
+        #[arg(long, value_name = "REF", num_args = 0..=1, default_missing_value = "origin/develop")]
+        changed: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum HistoryCommands {
+    /// Show a recorded build report (`latest` for the most recent)
+    Show {
+        /// Build id
+        id: String,
+    },
+    /// Compare step durations between two recorded builds
+    Compare {
+        /// Earlier build id
+        before: String,
+        /// Later build id (default: latest)
+        #[arg(default_value = "latest")]
+        after: String,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -340,33 +971,427 @@ impl Cli {
     }
 }
 
-fn init_tracing(verbose: u8, quiet: bool) {
-    let level = if quiet {
-        "error"
+/// Build the repos of worktree set `name` in their worktrees, for `--build`
+/// on `worktree add` and `feat`.
+fn build_worktree_set(
+    root: &Path,
+    manifest: &smctl_workspace::WorkspaceManifest,
+    name: &str,
+    fmt: OutputFormat,
+) -> Result<i32> {
+    let set = smctl_workspace::worktree::set_manifest(root, manifest, name)?;
+    let report = smctl_build::build(root, &set, None, false, false)?;
+    println!("{}", format_output_with(&report, fmt, format_build_report));
+    Ok(if report.all_passed {
+        exit_code::SUCCESS
     } else {
-        match verbose {
-            0 => "warn",
-            1 => "info",
-            2 => "debug",
-            _ => "trace",
-        }
-    };
-
-    let env_filter = std::env::var("SMCTL_LOG").unwrap_or_else(|_| level.to_string());
-
-    tracing_subscriber::fmt()
-        .with_env_filter(env_filter)
-        .with_target(false)
-        .init();
+        exit_code::BUILD_ERROR
+    })
 }
 
-#[tokio::main]
-async fn main() {
-    let cli = Cli::parse();
-
-    init_tracing(cli.verbose, cli.quiet);
-
-    let result = run(cli).await;
+/// Editor to launch: user config `editor`, then `$SMCTL_EDITOR`, `$EDITOR`, `vi`.
+fn editor_command(config: &smctl::SmctlConfig) -> String {
+    config
+        .user
+        .editor
+        .clone()
+        .or_else(|| std::env::var("SMCTL_EDITOR").ok())
+        .or_else(|| std::env::var("EDITOR").ok())
+        .unwrap_or_else(|| "vi".to_string())
+}
+
+/// Human-readable summary of a build report.
+fn format_build_report(r: &smctl_build::BuildReport) -> String {
+    let mut lines: Vec<String> = r
+        .results
+        .iter()
+        .map(|br| {
+            let icon = if br.skipped {
+                "-"
+            } else if br.success {
+                "\u{2713}"
+            } else {
+                "\u{2717}"
+            };
+            let mut line = format!("  {} {}", icon, br.repo_name);
+            if br.cancelled {
+                line.push_str(" \u{2014} cancelled");
+            }
+            if br.skipped {
+                line.push_str(&format!(" \u{2014} {}", br.output));
+            }
+            if let Some(level) = br.level {
+                line.push_str(&format!(" (level {level})"));
+            }
+            if let Some(executor) = &br.executor {
+                line.push_str(&format!(" on {executor}"));
+            }
+            if !br.attempts.is_empty() {
+                let attempts: Vec<_> = br.attempts.iter().map(|ms| format!("{ms}ms")).collect();
+                line.push_str(&format!(
+                    " [{} attempts: {}]",
+                    attempts.len(),
+                    attempts.join(", ")
+                ));
+            }
+            for step in &br.steps {
+                let icon = if step.success { "\u{2713}" } else { "\u{2717}" };
+                line.push_str(&format!(
+                    "\n      {} {} ({}ms)",
+                    icon, step.command, step.duration_ms
+                ));
+            }
+            for artifact in &br.artifacts {
+                line.push_str(&format!("\n      \u{2192} {}", artifact.display()));
+            }
+            if !br.success
+                && !br.skipped
+                && let Some(log) = &br.log
+            {
+                line.push_str(&format!("\n      log: {}", log.display()));
+            }
+            line
+        })
+        .collect();
+    if r.was_cancelled() {
+        lines.push(format!("\nbuild cancelled ({}ms)", r.total_duration_ms));
+    } else if r.all_passed {
+        lines.push(format!("\nbuild passed ({}ms)", r.total_duration_ms));
+    } else {
+        lines.push(format!("\nbuild FAILED ({}ms)", r.total_duration_ms));
+    }
+    lines.join("\n")
+}
+
+/// Per-repo spinners for interactive builds, drawn on stderr.
+struct BuildProgress {
+    multi: indicatif::MultiProgress,
+    bars: Mutex<HashMap<String, indicatif::ProgressBar>>,
+}
+
+impl BuildProgress {
+    /// Only shown to a human at a terminal: not with --json, --quiet or piped stdout.
+    fn enabled(fmt: OutputFormat, quiet: bool) -> bool {
+        fmt == OutputFormat::Human && !quiet && std::io::stdout().is_terminal()
+    }
+
+    fn new() -> Self {
+        Self {
+            multi: indicatif::MultiProgress::new(),
+            bars: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn update(&self, repo: &str, state: smctl_build::RepoState) {
+        use smctl_build::RepoState;
+
+        let mut bars = self.bars.lock().unwrap();
+        if state == RepoState::Queued {
+            let bar = self.multi.add(indicatif::ProgressBar::new_spinner());
+            if let Ok(style) = indicatif::ProgressStyle::with_template(
+                "{spinner} {prefix:<24} {msg:<10} {elapsed}",
+            ) {
+                bar.set_style(style);
+            }
+            bar.set_prefix(repo.to_string());
+            bar.set_message("queued");
+            if let Some(old) = bars.insert(repo.to_string(), bar) {
+                old.finish_and_clear();
+            }
+            return;
+        }
+
+        let Some(bar) = bars.get(repo) else {
+            return;
+        };
+        match state {
+            RepoState::Queued => {}
+            RepoState::Building => {
+                bar.reset_elapsed();
+                bar.enable_steady_tick(std::time::Duration::from_millis(100));
+                bar.set_message("building");
+            }
+            RepoState::Testing => bar.set_message("testing"),
+            RepoState::Done => bar.finish_with_message("\u{2713} done"),
+            RepoState::Failed => bar.finish_with_message("\u{2717} failed"),
+            RepoState::Skipped => bar.finish_with_message("- skipped"),
+        }
+    }
+
+    /// Remove the spinners before the final report is printed.
+    fn clear(&self) {
+        for (_, bar) in self.bars.lock().unwrap().drain() {
+            bar.finish_and_clear();
+        }
+        let _ = self.multi.clear();
+    }
+}
+
+fn init_tracing(verbose: u8, quiet: bool) {
+    let level = if quiet {
+        "error"
+    } else {
+        match verbose {
+            0 => "warn",
+            1 => "info",
+            2 => "debug",
+            _ => "trace",
+        }
+    };
+
+    let env_filter = std::env::var("SMCTL_LOG").unwrap_or_else(|_| level.to_string());
+
+    tracing_subscriber::fmt()
+        .with_env_filter(env_filter)
+        .with_target(false)
+        .init();
+}
+
+/// What to do with a spec's GitHub tracking issue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IssueSync {
+    /// Open one if the spec has none yet, otherwise update it.
+    Create,
+    /// Update the task checkboxes of an existing issue.
+    Update,
+    /// Close the issue (after archiving).
+    Close,
+}
+
+/// Keep the tracking issue of the spec at `spec_dir` in step with it. A
+/// no-op unless `[spec.github]` is configured.
+async fn sync_tracking_issue(
+    manifest: &smctl_workspace::WorkspaceManifest,
+    spec_dir: &Path,
+    action: IssueSync,
+) -> Result<Option<u64>> {
+    let Some(config) = &manifest.spec.github else {
+        return Ok(None);
+    };
+    let meta = smctl_spec::read_meta(spec_dir)?.unwrap_or_default();
+    if meta.issue.is_none() && action != IssueSync::Create {
+        return Ok(None);
+    }
+    let issues = smctl::GithubIssues::from_config(config)?;
+    let body = smctl_spec::tracking_issue_body(spec_dir)?;
+    match (meta.issue, action) {
+        (None, _) => {
+            let name = spec_dir
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let number = issues.create(&format!("Spec: {name}"), &body).await?;
+            smctl_spec::set_tracking_issue(spec_dir, number)?;
+            println!("opened tracking issue #{number} in {}", config.repo);
+            Ok(Some(number))
+        }
+        (Some(number), IssueSync::Close) => {
+            issues.close(number, &body).await?;
+            println!("closed tracking issue #{number}");
+            Ok(Some(number))
+        }
+        (Some(number), _) => {
+            issues.update_body(number, &body).await?;
+            Ok(Some(number))
+        }
+    }
+}
+
+/// [`sync_tracking_issue`] after a spec command: failures are logged, since
+/// GitHub being unreachable shouldn't fail local spec edits.
+async fn sync_tracking_issue_or_warn(
+    manifest: &smctl_workspace::WorkspaceManifest,
+    spec_dir: &Path,
+    action: IssueSync,
+) {
+    if let Err(e) = sync_tracking_issue(manifest, spec_dir, action).await {
+        tracing::warn!("could not sync tracking issue: {e:#}");
+    }
+}
+
+/// Resolve the spec named in `command` — a name, a sequential ID or the
+/// slug of a numbered spec — to its directory name.
+fn resolve_spec_refs(command: &mut SpecCommands, openspec_dir: &Path) -> Result<()> {
+    let name = match command {
+        SpecCommands::Ff { name: Some(name) }
+        | SpecCommands::Apply {
+            name: Some(name), ..
+        }
+        | SpecCommands::Validate { name: Some(name) }
+        | SpecCommands::Status { name: Some(name) }
+        | SpecCommands::Archive {
+            name: Some(name), ..
+        }
+        | SpecCommands::Trace { name: Some(name) }
+        | SpecCommands::Burndown { name: Some(name) }
+        | SpecCommands::Sync { name }
+        | SpecCommands::SetStatus { name, .. }
+        | SpecCommands::Meta { name, .. }
+        | SpecCommands::Delta { name, .. }
+        | SpecCommands::Rename { name, .. }
+        | SpecCommands::Restore { name, .. }
+        | SpecCommands::Promote { name }
+        | SpecCommands::Task {
+            command:
+                TaskCommands::List { name }
+                | TaskCommands::Check { name, .. }
+                | TaskCommands::Uncheck { name, .. }
+                | TaskCommands::Add { name, .. },
+        } => name,
+        _ => return Ok(()),
+    };
+    *name = smctl_spec::resolve_name(openspec_dir, name)?;
+    Ok(())
+}
+
+/// `feature finish` in pull request mode: push the feature branch, then open
+/// a pull request into develop in every repo the push succeeded in.
+async fn open_pull_requests(
+    root: &Path,
+    manifest: &smctl_workspace::WorkspaceManifest,
+    name: &str,
+) -> Result<smctl_flow::FlowResult> {
+    let config = manifest.flow.pull_request.clone().unwrap_or_default();
+    let client = smctl::PullRequests::from_config(&config)?;
+    let mut result = smctl_flow::feature_publish(root, manifest, name, &config.remote)?;
+    result.operation = "feature finish (pull request)".to_string();
+    for repo_result in result.repos.iter_mut().filter(|r| r.success) {
+        let Some(repo) = manifest.find_repo(&repo_result.repo_name) else {
+            continue;
+        };
+        let body = format!("Cross-repo feature `{name}`, opened by `smctl flow feature finish`.");
+        match client
+            .open(
+                &repo.url,
+                &result.branch_name,
+                &manifest.flow.develop_branch,
+                name,
+                &body,
+            )
+            .await
+        {
+            Ok(url) => {
+                repo_result.message = format!("opened pull request {url}");
+                repo_result.pull_request = Some(url);
+            }
+            Err(e) => {
+                repo_result.success = false;
+                repo_result.message = format!("{e:#}");
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Strategy used and per-repo failures of a finish operation, for the
+/// one-line human output.
+fn flow_details(result: &smctl_flow::FlowResult) -> String {
+    let mut out = String::new();
+    if let Some(strategy) = result.repos.iter().find_map(|r| r.strategy) {
+        out.push_str(&format!(" ({strategy})"));
+    }
+    for repo in result.repos.iter().filter(|r| !r.success) {
+        out.push_str(&format!("\n  {}: {}", repo.repo_name, repo.message));
+    }
+    out + &hook_failures(result)
+}
+
+/// "finished release 'x'", or how far a finish stopped by a merge conflict
+/// got: "release 'x' stopped (main done, develop pending)".
+fn finish_headline(kind: &str, result: &smctl_flow::FlowResult) -> String {
+    match result.operation.split_once(" (") {
+        Some((_, progress)) => format!("{kind} '{}' stopped ({progress}", result.branch_name),
+        None => format!("finished {kind} '{}'", result.branch_name),
+    }
+}
+
+/// Failed `[flow.hooks]` steps with their output.
+fn hook_failures(result: &smctl_flow::FlowResult) -> String {
+    let mut out = String::new();
+    for hook in result.hooks.iter().filter(|h| !h.success) {
+        out.push_str(&format!("\n  {} hook `{}` failed", hook.hook, hook.command));
+        for line in hook.output.lines() {
+            out.push_str(&format!("\n    {line}"));
+        }
+    }
+    out
+}
+
+/// The `--force` / `--stash` policy for uncommitted changes.
+fn dirty_tree(force: bool, stash: bool) -> smctl_flow::DirtyTree {
+    if force {
+        smctl_flow::DirtyTree::Force
+    } else if stash {
+        smctl_flow::DirtyTree::Stash
+    } else {
+        smctl_flow::DirtyTree::Refuse
+    }
+}
+
+/// A finish that stopped on a merge conflict exits with `GIT_ERROR`.
+fn finish_exit_code(root: &Path) -> Result<i32> {
+    Ok(if smctl_flow::FlowState::load(root)?.is_some() {
+        exit_code::GIT_ERROR
+    } else {
+        exit_code::SUCCESS
+    })
+}
+
+/// One line per capability spec touched by `spec promote`, plus its warnings.
+fn format_promotions(results: &[smctl_spec::PromoteResult]) -> String {
+    let mut lines = Vec::new();
+    for r in results {
+        lines.push(format!(
+            "{} {}: {} added, {} modified, {} removed",
+            if r.created { "created" } else { "updated" },
+            r.path.display(),
+            r.added.len(),
+            r.modified.len(),
+            r.removed.len()
+        ));
+        lines.extend(r.warnings.iter().map(|w| format!("  warning: {w}")));
+    }
+    lines.join("\n")
+}
+
+/// Parse `--phase` values (`draft`, `active`, ...).
+fn parse_phases(phases: &[String]) -> Result<Vec<smctl_spec::SpecPhase>> {
+    phases
+        .iter()
+        .map(|p| {
+            smctl_spec::SpecPhase::from_status(p).with_context(|| format!("unknown phase '{p}'"))
+        })
+        .collect()
+}
+
+/// Resolve `me` to the git `user.name` (falling back to `$USER`); other
+/// names are returned unchanged.
+fn resolve_person(name: &str) -> String {
+    if name != "me" {
+        return name.to_string();
+    }
+    std::process::Command::new("git")
+        .args(["config", "user.name"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .filter(|n| !n.is_empty())
+        .or_else(|| {
+            std::env::var("USER")
+                .or_else(|_| std::env::var("USERNAME"))
+                .ok()
+        })
+        .unwrap_or_else(|| name.to_string())
+}
+
+#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+
+    init_tracing(cli.verbose, cli.quiet);
+
+    let result = run(cli).await;
 
     match result {
         Ok(code) => process::exit(code),
@@ -382,15 +1407,30 @@ async fn run(cli: Cli) -> Result<i32> {
     let dry_run = cli.dry_run;
     let workspace_override = cli.workspace.clone();
 
-    // Helper closure to resolve workspace root
+    // Helper closure to resolve workspace root: explicit path or registered
+    // name, then the enclosing workspace of cwd, then the current workspace.
     let resolve_root = || -> Result<PathBuf> {
         if let Some(ref path) = workspace_override {
+            if !path.exists()
+                && let Some(name) = path.to_str()
+                && let Some(ws) = smctl::WorkspaceRegistry::load()?.find(name)
+            {
+                return Ok(ws.path.clone());
+            }
             return Ok(path.clone());
         }
         let cwd = std::env::current_dir().context("failed to get current directory")?;
-        smctl::find_workspace_root(&cwd).ok_or_else(|| {
-            anyhow::anyhow!("no workspace found (use `smctl workspace init` or set --workspace)")
-        })
+        if let Some(root) = smctl::find_workspace_root(&cwd) {
+            return Ok(root);
+        }
+        smctl::WorkspaceRegistry::load()?
+            .current_path()
+            .map(|p| p.to_path_buf())
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "no workspace found (use `smctl workspace init` or set --workspace)"
+                )
+            })
     };
 
     match cli.command {
@@ -415,6 +1455,15 @@ async fn run(cli: Cli) -> Result<i32> {
                 }
 
                 let manifest = smctl_workspace::init_workspace(&root, &ws_name)?;
+
+                let root = root.canonicalize().unwrap_or(root);
+                let registered = smctl::WorkspaceRegistry::load().and_then(|mut registry| {
+                    registry.register(&manifest.workspace.name, &root);
+                    registry.save()
+                });
+                if let Err(e) = registered {
+                    tracing::warn!("could not register workspace: {e}");
+                }
                 println!(
                     "{}",
                     format_output_with(&manifest, fmt, |m| {
@@ -482,13 +1531,22 @@ async fn run(cli: Cli) -> Result<i32> {
                         ss.iter()
                             .map(|s| {
                                 let state = if s.clean { "clean" } else { "dirty" };
-                                format!(
+                                let mut line = format!(
                                     "  {:<16} {:<16} {} {}",
                                     s.name,
                                     s.branch,
                                     if s.clean { "\u{2713}" } else { "\u{2717}" },
                                     state
-                                )
+                                );
+                                if s.stash_count > 0 {
+                                    line.push_str(&format!("  {} stashed", s.stash_count));
+                                }
+                                if let (Some(sha), Some(summary)) =
+                                    (&s.last_commit_sha, &s.last_commit_summary)
+                                {
+                                    line.push_str(&format!("  {} {}", &sha[..7], summary));
+                                }
+                                line
                             })
                             .collect::<Vec<_>>()
                             .join("\n")
@@ -499,6 +1557,7 @@ async fn run(cli: Cli) -> Result<i32> {
             WorkspaceCommands::Sync => {
                 let root = resolve_root()?;
                 let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
+                let mut results = Vec::new();
 
                 for repo in &manifest.repos {
                     let repo_path = root.join(repo.local_path());
@@ -512,21 +1571,23 @@ async fn run(cli: Cli) -> Result<i32> {
                         continue;
                     }
 
-                    let result = std::process::Command::new("git")
-                        .args(["pull", "--ff-only"])
-                        .current_dir(&repo_path)
-                        .output();
+                    let show_progress = !cli.quiet && fmt == OutputFormat::Human;
+                    let result = smctl_workspace::sync_repo(&root, repo, |p| {
+                        if show_progress && p.total_objects > 0 {
+                            eprint!(
+                                "\r  {} — receiving objects {}/{}",
+                                repo.name, p.received_objects, p.total_objects
+                            );
+                        }
+                    });
+                    if show_progress {
+                        eprint!("\r\x1b[K");
+                    }
 
                     match result {
-                        Ok(output) if output.status.success() => {
-                            println!("  {} — synced", repo.name);
-                        }
-                        Ok(output) => {
-                            let stderr = String::from_utf8_lossy(&output.stderr);
-                            eprintln!("  {} — failed: {}", repo.name, stderr.trim());
-                        }
+                        Ok(r) => results.push(r),
                         Err(e) => {
-                            eprintln!("  {} — error: {}", repo.name, e);
+                            eprintln!("  {} — error: {:#}", repo.name, e);
                         }
                     }
                 }
@@ -534,56 +1595,91 @@ async fn run(cli: Cli) -> Result<i32> {
                 if dry_run {
                     return Ok(exit_code::DRY_RUN);
                 }
+
+                println!(
+                    "{}",
+                    format_output_with(&results, fmt, |rs| {
+                        rs.iter()
+                            .map(|r| match r.status {
+                                smctl_workspace::SyncStatus::UpToDate
+                                | smctl_workspace::SyncStatus::FastForwarded => {
+                                    format!("  {} — synced ({})", r.name, r.message)
+                                }
+                                _ => format!("  {} — skipped: {}", r.name, r.message),
+                            })
+                            .collect::<Vec<_>>()
+                            .join("\n")
+                    })
+                );
                 Ok(exit_code::SUCCESS)
             }
-        },
-
-        Commands::Worktree { command } => match command {
-            WorktreeCommands::Add { name, repos } => {
+            WorkspaceCommands::Diff => {
                 let root = resolve_root()?;
                 let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
-                let branch = format!("{}{}", manifest.flow.feature_prefix, &name);
+                let mut diffs = Vec::new();
 
-                if dry_run {
-                    println!("would create worktree set '{name}' on branch '{branch}'");
-                    return Ok(exit_code::DRY_RUN);
+                for repo in &manifest.repos {
+                    match smctl_workspace::repo_diff(&root, repo) {
+                        Ok(diff) => diffs.push(diff),
+                        Err(e) => {
+                            eprintln!("  {} — error: {}", repo.name, e);
+                        }
+                    }
                 }
 
-                let infos = smctl_workspace::worktree::add_worktree(
-                    &root,
-                    &manifest,
-                    &name,
-                    repos.as_deref(),
-                    &branch,
-                )?;
+                let report = smctl_workspace::DiffReport::from_repos(diffs);
                 println!(
                     "{}",
-                    format_output_with(&infos, fmt, |is| {
-                        format!("created worktree set '{}' ({} repos)", name, is.len())
+                    format_output_with(&report, fmt, |r| {
+                        let stat = |s: &smctl_workspace::DiffStat| {
+                            format!(
+                                "{} files +{} -{}",
+                                s.files_changed, s.insertions, s.deletions
+                            )
+                        };
+                        let mut lines: Vec<String> = r
+                            .repos
+                            .iter()
+                            .map(|d| {
+                                format!(
+                                    "  {:<16} {:<16} uncommitted: {:<20} unmerged: {} commits, {} (vs {})",
+                                    d.name,
+                                    d.branch,
+                                    stat(&d.uncommitted),
+                                    d.unmerged_commits,
+                                    stat(&d.unmerged),
+                                    d.base
+                                )
+                            })
+                            .collect();
+                        lines.push(format!(
+                            "\ntotal: uncommitted {}, unmerged {} commits, {}",
+                            stat(&r.total_uncommitted),
+                            r.total_unmerged_commits,
+                            stat(&r.total_unmerged)
+                        ));
+                        lines.join("\n")
                     })
                 );
                 Ok(exit_code::SUCCESS)
             }
-            WorktreeCommands::List => {
-                let root = resolve_root()?;
-                let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
-                let sets = smctl_workspace::worktree::list_worktrees(&root, &manifest)?;
-
+            WorkspaceCommands::List => {
+                let registry = smctl::WorkspaceRegistry::load()?;
                 println!(
                     "{}",
-                    format_output_with(&sets, fmt, |ss| {
-                        if ss.is_empty() {
-                            "no active worktrees".to_string()
+                    format_output_with(&registry, fmt, |r| {
+                        if r.workspaces.is_empty() {
+                            "no registered workspaces".to_string()
                         } else {
-                            ss.iter()
-                                .map(|s| {
-                                    let repos: Vec<_> = s
-                                        .worktrees
-                                        .iter()
-                                        .filter(|w| w.exists)
-                                        .map(|w| format!("{}@{}", w.repo_name, w.branch))
-                                        .collect();
-                                    format!("  {} — {}", s.name, repos.join(", "))
+                            r.workspaces
+                                .iter()
+                                .map(|w| {
+                                    let marker = if r.current.as_deref() == Some(w.name.as_str()) {
+                                        "*"
+                                    } else {
+                                        " "
+                                    };
+                                    format!("{} {:<24} {}", marker, w.name, w.path.display())
                                 })
                                 .collect::<Vec<_>>()
                                 .join("\n")
@@ -592,112 +1688,927 @@ async fn run(cli: Cli) -> Result<i32> {
                 );
                 Ok(exit_code::SUCCESS)
             }
-            WorktreeCommands::Remove { name, force } => {
-                let root = resolve_root()?;
-                let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
+            WorkspaceCommands::Switch { name } => {
+                let mut registry = smctl::WorkspaceRegistry::load()?;
+                let ws = registry.switch(&name)?.clone();
 
                 if dry_run {
-                    println!("would remove worktree set '{name}'");
+                    println!("would switch to workspace '{name}'");
                     return Ok(exit_code::DRY_RUN);
                 }
 
-                smctl_workspace::worktree::remove_worktree(&root, &manifest, &name, force)?;
-                println!("removed worktree set '{name}'");
+                registry.save()?;
+                println!(
+                    "{}",
+                    format_output_with(&ws, fmt, |w| {
+                        format!("switched to workspace '{}' ({})", w.name, w.path.display())
+                    })
+                );
                 Ok(exit_code::SUCCESS)
             }
-            WorktreeCommands::Cd { name } => {
+            WorkspaceCommands::Clean { repo } => {
                 let root = resolve_root()?;
                 let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
-                let path = smctl_workspace::worktree::worktree_path(&root, &manifest, &name)?;
-                // Print path for shell eval: eval "$(smctl worktree cd <name>)"
-                println!("{}", path.display());
-                Ok(exit_code::SUCCESS)
-            }
-        },
+                let report = smctl_build::clean(&root, &manifest, repo.as_deref(), dry_run)?;
 
-        Commands::Flow { command } => match command {
-            FlowCommands::Init => {
+                println!(
+                    "{}",
+                    format_output_with(&report, fmt, |r| {
+                        let mut lines: Vec<String> = r
+                            .results
+                            .iter()
+                            .map(|c| {
+                                let icon = if c.success { "\u{2713}" } else { "\u{2717}" };
+                                format!(
+                                    "  {} {:<16} {} ({})",
+                                    icon,
+                                    c.repo_name,
+                                    c.message,
+                                    format_bytes(c.reclaimed_bytes)
+                                )
+                            })
+                            .collect();
+                        for d in &r.removed_dirs {
+                            let verb = if r.dry_run { "would remove" } else { "removed" };
+                            lines.push(format!("  {verb} {}", d.display()));
+                        }
+                        let verb = if r.dry_run {
+                            "would reclaim"
+                        } else {
+                            "reclaimed"
+                        };
+                        lines.push(format!(
+                            "\n{verb} {}",
+                            format_bytes(r.total_reclaimed_bytes)
+                        ));
+                        lines.join("\n")
+                    })
+                );
+
+                if dry_run {
+                    Ok(exit_code::DRY_RUN)
+                } else if report.results.iter().all(|c| c.success) {
+                    Ok(exit_code::SUCCESS)
+                } else {
+                    Ok(exit_code::BUILD_ERROR)
+                }
+            }
+            WorkspaceCommands::Gc {
+                aggressive,
+                expire_reflogs,
+            } => {
                 let root = resolve_root()?;
                 let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
 
                 if dry_run {
-                    println!(
-                        "would initialize git flow in {} repos",
-                        manifest.repos.len()
-                    );
+                    for repo in &manifest.repos {
+                        println!("would run git maintenance in {}", repo.name);
+                    }
                     return Ok(exit_code::DRY_RUN);
                 }
 
-                let result = smctl_flow::init(&root, &manifest)?;
+                let opts = smctl_workspace::GcOptions {
+                    aggressive,
+                    expire_reflogs,
+                };
+                let mut results = Vec::new();
+                for repo in &manifest.repos {
+                    match smctl_workspace::gc_repo(&root, repo, &opts) {
+                        Ok(r) => results.push(r),
+                        Err(e) => eprintln!("  {} — error: {}", repo.name, e),
+                    }
+                }
+
                 println!(
                     "{}",
-                    format_output_with(&result, fmt, |r| {
-                        r.repos
-                            .iter()
-                            .map(|rr| {
-                                let icon = if rr.success { "\u{2713}" } else { "\u{2717}" };
-                                format!("  {} {} — {}", icon, rr.repo_name, rr.message)
+                    format_output_with(&results, fmt, |rs| {
+                        rs.iter()
+                            .map(|r| {
+                                let icon = if r.success { "\u{2713}" } else { "\u{2717}" };
+                                format!(
+                                    "  {} {:<16} {} → {}  {}",
+                                    icon,
+                                    r.name,
+                                    format_bytes(r.size_before),
+                                    format_bytes(r.size_after),
+                                    r.message
+                                )
                             })
                             .collect::<Vec<_>>()
                             .join("\n")
                     })
                 );
-                Ok(exit_code::SUCCESS)
-            }
-            FlowCommands::Feature { command } => match command {
-                FeatureCommands::Start {
-                    name,
-                    worktree,
-                    repos,
-                } => {
-                    let root = resolve_root()?;
-                    let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
-
-                    if dry_run {
-                        println!("would start feature '{name}'");
-                        return Ok(exit_code::DRY_RUN);
-                    }
-
-                    let result =
-                        smctl_flow::feature_start(&root, &manifest, &name, repos.as_deref())?;
-                    println!(
-                        "{}",
-                        format_output_with(&result, fmt, |r| {
-                            format!("started feature '{}'", r.branch_name)
-                        })
-                    );
-
-                    if worktree {
-                        let _ = smctl_workspace::worktree::add_worktree(
-                            &root,
-                            &manifest,
-                            &name,
-                            repos.as_deref(),
-                            &result.branch_name,
-                        );
-                        println!("created worktree set '{name}'");
-                    }
 
+                if results.iter().all(|r| r.success) {
                     Ok(exit_code::SUCCESS)
+                } else {
+                    Ok(exit_code::GIT_ERROR)
                 }
-                FeatureCommands::Finish { name } => {
-                    let root = resolve_root()?;
-                    let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
-
-                    if dry_run {
-                        println!("would finish feature '{name}'");
-                        return Ok(exit_code::DRY_RUN);
-                    }
+            }
+            WorkspaceCommands::CheckBranches { repos } => {
+                let root = resolve_root()?;
+                let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
+                let report = smctl_flow::check_branches(&root, &manifest, repos.as_deref())?;
 
-                    let result = smctl_flow::feature_finish(&root, &manifest, &name)?;
+                println!(
+                    "{}",
+                    format_output_with(&report, fmt, |r| {
+                        let expected = r.expected_branch.as_deref().unwrap_or("-");
+                        let mut lines: Vec<String> = r
+                            .repos
+                            .iter()
+                            .map(|b| {
+                                let icon = if b.branch == expected {
+                                    "\u{2713}"
+                                } else {
+                                    "\u{2717}"
+                                };
+                                format!(
+                                    "  {} {:<16} {} ({:?})",
+                                    icon, b.repo_name, b.branch, b.branch_type
+                                )
+                            })
+                            .collect();
+                        if r.consistent {
+                            lines.push(format!("\nall repos on '{expected}'"));
+                        } else {
+                            lines.push(format!(
+                                "\n{} repo(s) diverge from '{expected}': {}",
+                                r.divergent.len(),
+                                r.divergent.join(", ")
+                            ));
+                        }
+                        lines.join("\n")
+                    })
+                );
+
+                if report.consistent {
+                    Ok(exit_code::SUCCESS)
+                } else {
+                    Ok(exit_code::WORKSPACE_ERROR)
+                }
+            }
+        },
+
+        Commands::Worktree { command } => match command {
+            WorktreeCommands::Add {
+                name,
+                repos,
+                branch,
+                base,
+                extend,
+                build,
+            } => {
+                let root = resolve_root()?;
+                let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
+                let branch =
+                    branch.unwrap_or_else(|| format!("{}{}", manifest.flow.feature_prefix, &name));
+
+                if dry_run {
+                    let from = base
+                        .as_deref()
+                        .map(|b| format!(" (new branches from '{b}')"))
+                        .unwrap_or_default();
+                    let verb = if extend { "extend" } else { "create" };
+                    println!("would {verb} worktree set '{name}' on branch '{branch}'{from}");
+                    if build {
+                        println!("would build worktree set '{name}'");
+                    }
+                    return Ok(exit_code::DRY_RUN);
+                }
+
+                let add = if extend {
+                    smctl_workspace::worktree::extend_worktree
+                } else {
+                    smctl_workspace::worktree::add_worktree
+                };
+                let infos = add(
+                    &root,
+                    &manifest,
+                    &name,
+                    repos.as_deref(),
+                    &branch,
+                    base.as_deref(),
+                )?;
+                println!(
+                    "{}",
+                    format_output_with(&infos, fmt, |is| {
+                        let repos: Vec<_> = is.iter().map(|i| i.repo_name.as_str()).collect();
+                        if extend {
+                            format!("added {} to worktree set '{name}'", repos.join(", "))
+                        } else {
+                            format!("created worktree set '{}' ({} repos)", name, is.len())
+                        }
+                    })
+                );
+                if build {
+                    return build_worktree_set(&root, &manifest, &name, fmt);
+                }
+                Ok(exit_code::SUCCESS)
+            }
+            WorktreeCommands::List { names } => {
+                let root = resolve_root()?;
+                let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
+                let sets = smctl_workspace::worktree::list_worktrees(&root, &manifest)?;
+                if names {
+                    for set in &sets {
+                        println!("{}", set.name);
+                    }
+                    return Ok(exit_code::SUCCESS);
+                }
+
+                println!(
+                    "{}",
+                    format_output_with(&sets, fmt, |ss| {
+                        if ss.is_empty() {
+                            "no active worktrees".to_string()
+                        } else {
+                            let mut lines = Vec::new();
+                            for s in ss {
+                                match &s.metadata {
+                                    Some(meta) => {
+                                        let created =
+                                            chrono::DateTime::from_timestamp(meta.created, 0)
+                                                .map(|d| d.format("%Y-%m-%d").to_string())
+                                                .unwrap_or_default();
+                                        lines.push(format!(
+                                            "  {} — {}, created {created}",
+                                            s.name, meta.branch
+                                        ))
+                                    }
+                                    None => lines.push(format!(
+                                        "  {} (not adopted: smctl worktree adopt <dir>)",
+                                        s.name
+                                    )),
+                                }
+                                for w in s.worktrees.iter().filter(|w| w.exists) {
+                                    let mut notes = Vec::new();
+                                    if s.metadata
+                                        .as_ref()
+                                        .is_some_and(|m| !m.repos.contains(&w.repo_name))
+                                    {
+                                        notes.push("not adopted".to_string());
+                                    }
+                                    if w.dirty {
+                                        notes.push("dirty".to_string());
+                                    }
+                                    if let Some(base) = &w.base {
+                                        notes.push(format!(
+                                            "{} ahead, {} behind {base}",
+                                            w.ahead, w.behind
+                                        ));
+                                    }
+                                    let mut line = format!("    {}@{}", w.repo_name, w.branch);
+                                    if !notes.is_empty() {
+                                        line.push_str(&format!(" [{}]", notes.join(", ")));
+                                    }
+                                    if let Some(summary) = &w.last_commit_summary {
+                                        line.push_str(&format!(" — {summary}"));
+                                    }
+                                    lines.push(line);
+                                }
+                            }
+                            lines.join("\n")
+                        }
+                    })
+                );
+                Ok(exit_code::SUCCESS)
+            }
+            WorktreeCommands::Remove {
+                name,
+                repos,
+                archive,
+                force,
+            } => {
+                let root = resolve_root()?;
+                let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
+                let what = match &repos {
+                    Some(repos) => format!("{} from worktree set '{name}'", repos.join(", ")),
+                    None => format!("worktree set '{name}'"),
+                };
+
+                if dry_run {
+                    if archive {
+                        println!("would archive and remove {what}");
+                    } else {
+                        println!("would remove {what}");
+                    }
+                    return Ok(exit_code::DRY_RUN);
+                }
+
+                if archive {
+                    let archived = smctl_workspace::worktree::archive_worktree(
+                        &root,
+                        &manifest,
+                        &name,
+                        repos.as_deref(),
+                    )?;
+                    for r in &archived.repos {
+                        println!(
+                            "  {} ({}) — {} unmerged commit(s), {}, {} untracked file(s)",
+                            r.repo_name,
+                            r.branch,
+                            r.unmerged_commits,
+                            if r.uncommitted {
+                                "uncommitted changes"
+                            } else {
+                                "no uncommitted changes"
+                            },
+                            r.untracked_files
+                        );
+                    }
+                    println!("archived to {}", archived.path.display());
+                }
+
+                smctl_workspace::worktree::remove_worktree(
+                    &root,
+                    &manifest,
+                    &name,
+                    repos.as_deref(),
+                    force,
+                )?;
+                println!("removed {what}");
+                Ok(exit_code::SUCCESS)
+            }
+            WorktreeCommands::Prune { force } => {
+                let root = resolve_root()?;
+                let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
+                let pruned =
+                    smctl_workspace::worktree::prune_worktrees(&root, &manifest, dry_run, force)?;
+
+                println!(
+                    "{}",
+                    format_output_with(&pruned, fmt, |ps| {
+                        if ps.is_empty() {
+                            return "no stale worktrees".to_string();
+                        }
+                        let mut lines: Vec<String> = ps
+                            .iter()
+                            .map(|p| {
+                                let mut line = format!(
+                                    "  {} — {}: {}",
+                                    p.repo_name,
+                                    p.path.display(),
+                                    p.reason
+                                );
+                                if p.kept {
+                                    line.push_str(" (kept: may hold uncommitted work)");
+                                }
+                                line
+                            })
+                            .collect();
+                        let verb = if dry_run { "would prune" } else { "pruned" };
+                        let kept = ps.iter().filter(|p| p.kept).count();
+                        lines.push(format!("\n{verb} {} worktree(s)", ps.len() - kept));
+                        if kept > 0 {
+                            lines.push(format!(
+                                "kept {kept} with uncommitted or unknown changes (use --force)"
+                            ));
+                        }
+                        lines.join("\n")
+                    })
+                );
+                Ok(if dry_run {
+                    exit_code::DRY_RUN
+                } else {
+                    exit_code::SUCCESS
+                })
+            }
+            WorktreeCommands::Exec {
+                name,
+                repos,
+                ordered,
+                cmd,
+            } => {
+                let root = resolve_root()?;
+                let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
+                let order = if ordered {
+                    smctl_build::resolve_build_order(&manifest)?
+                } else {
+                    manifest.repos.iter().collect()
+                };
+                let targets: Vec<_> = order
+                    .into_iter()
+                    .filter(|r| repos.as_ref().is_none_or(|names| names.contains(&r.name)))
+                    .collect();
+
+                if dry_run {
+                    smctl_workspace::worktree::worktree_path(&root, &manifest, &name)?;
+                    let names: Vec<_> = targets
+                        .iter()
+                        .filter(|r| {
+                            smctl_workspace::worktree::repo_worktree_path(
+                                &root, &manifest, r, &name,
+                            )
+                            .exists()
+                        })
+                        .map(|r| r.name.as_str())
+                        .collect();
+                    println!("would run '{}' in {}", cmd.join(" "), names.join(", "));
+                    return Ok(exit_code::DRY_RUN);
+                }
+
+                let results = smctl_workspace::worktree::exec_in_worktree(
+                    &root, &manifest, &name, &targets, &cmd,
+                )?;
+                let failed = results.iter().filter(|r| !r.success).count();
+                println!(
+                    "{}",
+                    format_output_with(&results, fmt, |rs| {
+                        let mut lines = Vec::new();
+                        for r in rs {
+                            if r.success {
+                                lines.push(format!("  \u{2713} {}", r.repo_name));
+                            } else {
+                                let code = r
+                                    .code
+                                    .map(|c| format!("exit {c}"))
+                                    .unwrap_or_else(|| "killed".to_string());
+                                lines.push(format!("  \u{2717} {} — {code}", r.repo_name));
+                            }
+                            for out in [&r.stdout, &r.stderr] {
+                                lines.extend(out.lines().map(|l| format!("    {l}")));
+                            }
+                        }
+                        lines.push(format!(
+                            "\nran in {} worktree(s), {failed} failed",
+                            rs.len()
+                        ));
+                        lines.join("\n")
+                    })
+                );
+                Ok(if failed > 0 {
+                    exit_code::GENERAL_ERROR
+                } else {
+                    exit_code::SUCCESS
+                })
+            }
+            WorktreeCommands::Open { name } => {
+                let root = resolve_root()?;
+                let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
+                let set = smctl_workspace::worktree::worktree_path(&root, &manifest, &name)?;
+                let editor = editor_command(&smctl::SmctlConfig::load_user_config()?);
+                let mut words = editor.split_whitespace();
+                let program = words.next().context("empty editor command")?;
+                let multi_root = matches!(
+                    Path::new(program).file_stem().and_then(|s| s.to_str()),
+                    Some("code" | "code-insiders" | "codium" | "cursor" | "windsurf")
+                );
+
+                if dry_run {
+                    println!("would open worktree set '{name}' in '{editor}'");
+                    return Ok(exit_code::DRY_RUN);
+                }
+
+                let target = if multi_root {
+                    smctl_workspace::worktree::write_code_workspace(&root, &manifest, &name)?
+                } else {
+                    set
+                };
+                let status = std::process::Command::new(program)
+                    .args(words)
+                    .arg(&target)
+                    .status()
+                    .context("failed to open editor")?;
+                Ok(if status.success() {
+                    exit_code::SUCCESS
+                } else {
+                    exit_code::GENERAL_ERROR
+                })
+            }
+            WorktreeCommands::Sync {
+                name,
+                merge,
+                rebase,
+            } => {
+                let root = resolve_root()?;
+                let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
+                let mode = if merge {
+                    smctl_workspace::SyncMode::Merge
+                } else if rebase {
+                    smctl_workspace::SyncMode::Rebase
+                } else {
+                    manifest.worktree.sync
+                };
+
+                if dry_run {
+                    smctl_workspace::worktree::worktree_path(&root, &manifest, &name)?;
+                    let verb = match mode {
+                        smctl_workspace::SyncMode::Rebase => "rebase",
+                        smctl_workspace::SyncMode::Merge => "merge",
+                    };
+                    println!("would fetch and {verb} worktree set '{name}' onto its base branches");
+                    return Ok(exit_code::DRY_RUN);
+                }
+
+                let results =
+                    smctl_workspace::worktree::sync_worktree(&root, &manifest, &name, mode)?;
+                println!(
+                    "{}",
+                    format_output_with(&results, fmt, |rs| {
+                        rs.iter()
+                            .map(|r| {
+                                let icon = if r.success { "\u{2713}" } else { "\u{2717}" };
+                                format!("  {icon} {} ({}) — {}", r.repo_name, r.branch, r.message)
+                            })
+                            .collect::<Vec<_>>()
+                            .join("\n")
+                    })
+                );
+                Ok(if results.iter().all(|r| r.success) {
+                    exit_code::SUCCESS
+                } else {
+                    exit_code::GIT_ERROR
+                })
+            }
+            WorktreeCommands::Du { stale_days } => {
+                let root = resolve_root()?;
+                let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
+                let usage = smctl_workspace::worktree::disk_usage(&root, &manifest, stale_days)?;
+
+                println!(
+                    "{}",
+                    format_output_with(&usage, fmt, |us| {
+                        if us.is_empty() {
+                            return "no active worktrees".to_string();
+                        }
+                        let date = |t: Option<i64>| {
+                            t.and_then(|t| chrono::DateTime::from_timestamp(t, 0))
+                                .map(|d| d.format("%Y-%m-%d").to_string())
+                                .unwrap_or_else(|| "never".to_string())
+                        };
+                        let mut lines = Vec::new();
+                        for u in us {
+                            let stale = if u.stale {
+                                format!(" — stale, untouched for over {stale_days} days")
+                            } else {
+                                String::new()
+                            };
+                            lines.push(format!(
+                                "  {:>10}  {} (last change {}){stale}",
+                                smctl::format_bytes(u.bytes),
+                                u.name,
+                                date(u.last_modified)
+                            ));
+                            for r in &u.repos {
+                                lines.push(format!(
+                                    "  {:>10}    {}",
+                                    smctl::format_bytes(r.bytes),
+                                    r.repo_name
+                                ));
+                            }
+                        }
+                        let total: u64 = us.iter().map(|u| u.bytes).sum();
+                        let stale = us.iter().filter(|u| u.stale).count();
+                        lines.push(format!(
+                            "\n{} in {} set(s), {stale} stale",
+                            smctl::format_bytes(total),
+                            us.len()
+                        ));
+                        lines.join("\n")
+                    })
+                );
+                Ok(exit_code::SUCCESS)
+            }
+            WorktreeCommands::Rename { old, new, branch } => {
+                let root = resolve_root()?;
+                let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
+
+                if dry_run {
+                    smctl_workspace::worktree::worktree_path(&root, &manifest, &old)?;
+                    println!("would rename worktree set '{old}' to '{new}'");
+                    if let Some(branch) = &branch {
+                        println!("would rename its branch to '{branch}'");
+                    }
+                    return Ok(exit_code::DRY_RUN);
+                }
+
+                let infos = smctl_workspace::worktree::rename_worktree(
+                    &root,
+                    &manifest,
+                    &old,
+                    &new,
+                    branch.as_deref(),
+                )?;
+                println!(
+                    "{}",
+                    format_output_with(&infos, fmt, |is| {
+                        format!(
+                            "renamed worktree set '{old}' to '{new}' ({} repos)",
+                            is.len()
+                        )
+                    })
+                );
+                Ok(exit_code::SUCCESS)
+            }
+            WorktreeCommands::Adopt { path, name } => {
+                let root = resolve_root()?;
+                let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
+
+                if dry_run {
+                    println!("would adopt the worktrees at {}", path.display());
+                    return Ok(exit_code::DRY_RUN);
+                }
+
+                let set = smctl_workspace::worktree::adopt_worktree(
+                    &root,
+                    &manifest,
+                    &path,
+                    name.as_deref(),
+                )?;
+                println!(
+                    "{}",
+                    format_output_with(&set, fmt, |s| {
+                        let mut lines = vec![format!(
+                            "adopted {} worktrees into set '{}'",
+                            s.worktrees.len(),
+                            s.name
+                        )];
+                        for w in &s.worktrees {
+                            lines.push(format!(
+                                "  \u{2713} {}@{} at {}",
+                                w.repo_name,
+                                w.branch,
+                                w.path.display()
+                            ));
+                        }
+                        lines.join("\n")
+                    })
+                );
+                Ok(exit_code::SUCCESS)
+            }
+            WorktreeCommands::Cd { name } => {
+                let root = resolve_root()?;
+                let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
+                let path = smctl_workspace::worktree::worktree_path(&root, &manifest, &name)?;
+                // Print path for shell eval: eval "$(smctl worktree cd <name>)"
+                println!("{}", path.display());
+                Ok(exit_code::SUCCESS)
+            }
+        },
+
+        Commands::Flow { command } => match command {
+            FlowCommands::Init { push } => {
+                let root = resolve_root()?;
+                let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
+
+                if dry_run {
+                    println!(
+                        "would initialize git flow in {} repos",
+                        manifest.repos.len()
+                    );
+                    if let Some(remote) = &push {
+                        println!(
+                            "would push '{}' to '{remote}'",
+                            manifest.flow.develop_branch
+                        );
+                    }
+                    return Ok(exit_code::DRY_RUN);
+                }
+
+                let result = smctl_flow::init(&root, &manifest, push.as_deref())?;
+                println!(
+                    "{}",
+                    format_output_with(&result, fmt, |r| {
+                        r.repos
+                            .iter()
+                            .map(|rr| {
+                                let icon = if rr.success { "\u{2713}" } else { "\u{2717}" };
+                                format!("  {} {} — {}", icon, rr.repo_name, rr.message)
+                            })
+                            .collect::<Vec<_>>()
+                            .join("\n")
+                    })
+                );
+                Ok(if result.repos.iter().all(|r| r.success) {
+                    exit_code::SUCCESS
+                } else {
+                    exit_code::GIT_ERROR
+                })
+            }
+            FlowCommands::Feature { command } => match command {
+                FeatureCommands::Start {
+                    name,
+                    worktree,
+                    repos,
+                    force,
+                    stash,
+                } => {
+                    let root = resolve_root()?;
+                    let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
+
+                    if dry_run {
+                        println!("would start feature '{name}'");
+                        return Ok(exit_code::DRY_RUN);
+                    }
+
+                    let result = smctl_flow::feature_start(
+                        &root,
+                        &manifest,
+                        &name,
+                        repos.as_deref(),
+                        dirty_tree(force, stash),
+                    )?;
                     println!(
                         "{}",
                         format_output_with(&result, fmt, |r| {
-                            format!("finished feature '{}'", r.branch_name)
+                            format!("started feature '{}'", r.branch_name)
                         })
                     );
+
+                    if worktree {
+                        let _ = smctl_workspace::worktree::add_worktree(
+                            &root,
+                            &manifest,
+                            &name,
+                            repos.as_deref(),
+                            &result.branch_name,
+                            None,
+                        );
+                        println!("created worktree set '{name}'");
+                    }
+
                     Ok(exit_code::SUCCESS)
                 }
+                FeatureCommands::Finish {
+                    name,
+                    repos,
+                    pr,
+                    strategy,
+                    no_rollback,
+                    delete_remote,
+                    force,
+                    stash,
+                } => {
+                    let root = resolve_root()?;
+                    let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
+                    let pr = pr || manifest.flow.finish == smctl_workspace::FinishMode::PullRequest;
+                    let merge_flags = [
+                        ("--strategy", strategy.is_some()),
+                        ("--no-rollback", no_rollback),
+                        ("--delete-remote", delete_remote.is_some()),
+                        ("--force", force),
+                        ("--stash", stash),
+                    ];
+                    if pr && let Some((flag, _)) = merge_flags.iter().find(|(_, set)| *set) {
+                        anyhow::bail!(
+                            "{flag} only applies to local merges; `[flow] finish = \"pull-request\"` opens pull requests instead"
+                        );
+                    }
+
+                    if dry_run {
+                        if pr {
+                            println!("would open pull requests for feature '{name}'");
+                        } else {
+                            println!("would finish feature '{name}'");
+                        }
+                        return Ok(exit_code::DRY_RUN);
+                    }
+
+                    if pr {
+                        let scoped = manifest.scoped(repos.as_deref());
+                        let result = open_pull_requests(&root, &scoped, &name).await?;
+                        println!(
+                            "{}",
+                            format_output_with(&result, fmt, |r| {
+                                let mut out = format!("pull requests for '{}':", r.branch_name);
+                                for repo in &r.repos {
+                                    out.push_str(&format!(
+                                        "\n  {}: {}",
+                                        repo.repo_name,
+                                        repo.pull_request.as_deref().unwrap_or(&repo.message)
+                                    ));
+                                }
+                                out
+                            })
+                        );
+                        return Ok(if result.repos.iter().all(|r| r.success) {
+                            exit_code::SUCCESS
+                        } else {
+                            exit_code::GIT_ERROR
+                        });
+                    }
+
+                    let options = smctl_flow::FinishOptions {
+                        strategy,
+                        keep_partial: no_rollback,
+                        dirty: dirty_tree(force, stash),
+                        delete_remote,
+                        ..Default::default()
+                    };
+                    let result = smctl_flow::feature_finish(
+                        &root,
+                        &manifest,
+                        &name,
+                        repos.as_deref(),
+                        &options,
+                    )?;
+                    println!(
+                        "{}",
+                        format_output_with(&result, fmt, |r| {
+                            format!("finished feature '{}'{}", r.branch_name, flow_details(r))
+                        })
+                    );
+                    finish_exit_code(&root)
+                }
+                FeatureCommands::Checkout {
+                    name,
+                    remote,
+                    force,
+                    stash,
+                } => {
+                    let root = resolve_root()?;
+                    let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
+
+                    if dry_run {
+                        println!("would check out feature '{name}'");
+                        return Ok(exit_code::DRY_RUN);
+                    }
+
+                    let result = smctl_flow::feature_checkout(
+                        &root,
+                        &manifest,
+                        &name,
+                        &remote,
+                        dirty_tree(force, stash),
+                    )?;
+                    println!(
+                        "{}",
+                        format_output_with(&result, fmt, |r| {
+                            r.repos
+                                .iter()
+                                .map(|rr| {
+                                    let icon = if rr.success { "\u{2713}" } else { "\u{2717}" };
+                                    format!("  {} {} — {}", icon, rr.repo_name, rr.message)
+                                })
+                                .collect::<Vec<_>>()
+                                .join("\n")
+                        })
+                    );
+                    Ok(if result.repos.iter().all(|r| r.success) {
+                        exit_code::SUCCESS
+                    } else {
+                        exit_code::GIT_ERROR
+                    })
+                }
+                FeatureCommands::Publish { name, remote } => {
+                    let root = resolve_root()?;
+                    let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
+
+                    if dry_run {
+                        println!("would publish feature '{name}' to '{remote}'");
+                        return Ok(exit_code::DRY_RUN);
+                    }
+
+                    let result = smctl_flow::feature_publish(&root, &manifest, &name, &remote)?;
+                    println!(
+                        "{}",
+                        format_output_with(&result, fmt, |r| {
+                            r.repos
+                                .iter()
+                                .map(|rr| {
+                                    let icon = if rr.success { "\u{2713}" } else { "\u{2717}" };
+                                    format!("  {} {} — {}", icon, rr.repo_name, rr.message)
+                                })
+                                .collect::<Vec<_>>()
+                                .join("\n")
+                        })
+                    );
+                    Ok(if result.repos.iter().all(|r| r.success) {
+                        exit_code::SUCCESS
+                    } else {
+                        exit_code::GIT_ERROR
+                    })
+                }
+                FeatureCommands::Track { name, remote } => {
+                    let root = resolve_root()?;
+                    let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
+
+                    if dry_run {
+                        println!("would track feature '{name}' from '{remote}'");
+                        return Ok(exit_code::DRY_RUN);
+                    }
+
+                    let result = smctl_flow::feature_track(&root, &manifest, &name, &remote)?;
+                    println!(
+                        "{}",
+                        format_output_with(&result, fmt, |r| {
+                            r.repos
+                                .iter()
+                                .map(|rr| {
+                                    let icon = if rr.success { "\u{2713}" } else { "\u{2717}" };
+                                    format!("  {} {} — {}", icon, rr.repo_name, rr.message)
+                                })
+                                .collect::<Vec<_>>()
+                                .join("\n")
+                        })
+                    );
+                    Ok(if result.repos.iter().all(|r| r.success) {
+                        exit_code::SUCCESS
+                    } else {
+                        exit_code::GIT_ERROR
+                    })
+                }
                 FeatureCommands::List => {
                     let root = resolve_root()?;
                     let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
@@ -719,7 +2630,12 @@ async fn run(cli: Cli) -> Result<i32> {
                 }
             },
             FlowCommands::Release { command } => match command {
-                ReleaseCommands::Start { ver, repos } => {
+                ReleaseCommands::Start {
+                    ver,
+                    repos,
+                    force,
+                    stash,
+                } => {
                     let root = resolve_root()?;
                     let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
 
@@ -728,8 +2644,13 @@ async fn run(cli: Cli) -> Result<i32> {
                         return Ok(exit_code::DRY_RUN);
                     }
 
-                    let result =
-                        smctl_flow::release_start(&root, &manifest, &ver, repos.as_deref())?;
+                    let result = smctl_flow::release_start(
+                        &root,
+                        &manifest,
+                        &ver,
+                        repos.as_deref(),
+                        dirty_tree(force, stash),
+                    )?;
                     println!(
                         "{}",
                         format_output_with(&result, fmt, |r| {
@@ -738,33 +2659,284 @@ async fn run(cli: Cli) -> Result<i32> {
                     );
                     Ok(exit_code::SUCCESS)
                 }
-                ReleaseCommands::Finish { ver } => {
+                ReleaseCommands::Finish {
+                    ver,
+                    sign,
+                    strategy,
+                    no_rollback,
+                    force,
+                    stash,
+                } => {
                     let root = resolve_root()?;
                     let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
+                    let tag = manifest.flow.release_tag(&ver);
 
                     if dry_run {
-                        println!("would finish release '{ver}'");
+                        println!("would finish release '{ver}' and tag it '{tag}'");
                         return Ok(exit_code::DRY_RUN);
                     }
 
-                    let result = smctl_flow::release_finish(&root, &manifest, &ver)?;
+                    let options = smctl_flow::FinishOptions {
+                        strategy,
+                        sign,
+                        keep_partial: no_rollback,
+                        dirty: dirty_tree(force, stash),
+                        ..Default::default()
+                    };
+                    let result = smctl_flow::release_finish(&root, &manifest, &ver, &options)?;
+                    let op_id = smctl_flow::OpJournal::list(&root)?
+                        .into_iter()
+                        .find(|j| j.branch == result.branch_name)
+                        .map(|j| j.id);
                     println!(
                         "{}",
                         format_output_with(&result, fmt, |r| {
-                            format!("finished release '{}'", r.branch_name)
+                            let mut out = finish_headline("release", r);
+                            for repo in r.repos.iter().filter(|repo| !repo.success) {
+                                out.push_str(&format!("\n  {}: {}", repo.repo_name, repo.message));
+                            }
+                            let tagged: Vec<_> = r
+                                .repos
+                                .iter()
+                                .filter(|repo| repo.success && repo.message.starts_with("tagged"))
+                                .map(|repo| repo.repo_name.as_str())
+                                .collect();
+                            if !tagged.is_empty() {
+                                out.push_str(&format!("\ntagged '{tag}' in {}", tagged.join(", ")));
+                            }
+                            if let Some(id) = &op_id {
+                                out.push_str(&format!(
+                                    "\nrecorded as '{id}' (`smctl flow undo {id}` reverts it)"
+                                ));
+                            }
+                            out + &hook_failures(r)
                         })
                     );
-                    Ok(exit_code::SUCCESS)
+                    finish_exit_code(&root)
                 }
                 ReleaseCommands::List => {
                     let root = resolve_root()?;
                     let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
-                    let branches = smctl_flow::release_list(&root, &manifest)?;
+                    let branches = smctl_flow::release_list(&root, &manifest)?;
+                    println!(
+                        "{}",
+                        format_output_with(&branches, fmt, |bs| {
+                            if bs.is_empty() {
+                                "no active releases".to_string()
+                            } else {
+                                bs.iter()
+                                    .map(|b| format!("  {} — {}", b.repo_name, b.branch))
+                                    .collect::<Vec<_>>()
+                                    .join("\n")
+                            }
+                        })
+                    );
+                    Ok(exit_code::SUCCESS)
+                }
+            },
+            FlowCommands::Hotfix { command } => match command {
+                HotfixCommands::Start {
+                    name,
+                    repos,
+                    force,
+                    stash,
+                } => {
+                    let root = resolve_root()?;
+                    let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
+
+                    if dry_run {
+                        println!("would start hotfix '{name}'");
+                        return Ok(exit_code::DRY_RUN);
+                    }
+
+                    let result = smctl_flow::hotfix_start(
+                        &root,
+                        &manifest,
+                        &name,
+                        repos.as_deref(),
+                        dirty_tree(force, stash),
+                    )?;
+                    println!(
+                        "{}",
+                        format_output_with(&result, fmt, |r| {
+                            format!("started hotfix '{}'", r.branch_name)
+                        })
+                    );
+                    Ok(exit_code::SUCCESS)
+                }
+                HotfixCommands::Finish {
+                    name,
+                    strategy,
+                    no_rollback,
+                    delete_remote,
+                    force,
+                    stash,
+                } => {
+                    let root = resolve_root()?;
+                    let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
+
+                    if dry_run {
+                        println!("would finish hotfix '{name}'");
+                        return Ok(exit_code::DRY_RUN);
+                    }
+
+                    let options = smctl_flow::FinishOptions {
+                        strategy,
+                        keep_partial: no_rollback,
+                        dirty: dirty_tree(force, stash),
+                        delete_remote,
+                        ..Default::default()
+                    };
+                    let result = smctl_flow::hotfix_finish(&root, &manifest, &name, &options)?;
+                    println!(
+                        "{}",
+                        format_output_with(&result, fmt, |r| {
+                            format!("{}{}", finish_headline("hotfix", r), flow_details(r))
+                        })
+                    );
+                    finish_exit_code(&root)
+                }
+                HotfixCommands::List => {
+                    let root = resolve_root()?;
+                    let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
+                    let branches = smctl_flow::hotfix_list(&root, &manifest)?;
+                    println!(
+                        "{}",
+                        format_output_with(&branches, fmt, |bs| {
+                            if bs.is_empty() {
+                                "no active hotfixes".to_string()
+                            } else {
+                                bs.iter()
+                                    .map(|b| format!("  {} — {}", b.repo_name, b.branch))
+                                    .collect::<Vec<_>>()
+                                    .join("\n")
+                            }
+                        })
+                    );
+                    Ok(exit_code::SUCCESS)
+                }
+            },
+            FlowCommands::Bugfix { command } => match command {
+                BugfixCommands::Start {
+                    name,
+                    repos,
+                    force,
+                    stash,
+                } => {
+                    let root = resolve_root()?;
+                    let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
+
+                    if dry_run {
+                        println!("would start bugfix '{name}'");
+                        return Ok(exit_code::DRY_RUN);
+                    }
+
+                    let result = smctl_flow::bugfix_start(
+                        &root,
+                        &manifest,
+                        &name,
+                        repos.as_deref(),
+                        dirty_tree(force, stash),
+                    )?;
+                    println!(
+                        "{}",
+                        format_output_with(&result, fmt, |r| {
+                            format!("started bugfix '{}'", r.branch_name)
+                        })
+                    );
+                    Ok(exit_code::SUCCESS)
+                }
+                BugfixCommands::Finish {
+                    name,
+                    strategy,
+                    no_rollback,
+                    delete_remote,
+                    force,
+                    stash,
+                } => {
+                    let root = resolve_root()?;
+                    let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
+
+                    if dry_run {
+                        println!("would finish bugfix '{name}'");
+                        return Ok(exit_code::DRY_RUN);
+                    }
+
+                    let options = smctl_flow::FinishOptions {
+                        strategy,
+                        keep_partial: no_rollback,
+                        dirty: dirty_tree(force, stash),
+                        delete_remote,
+                        ..Default::default()
+                    };
+                    let result = smctl_flow::bugfix_finish(&root, &manifest, &name, &options)?;
+                    println!(
+                        "{}",
+                        format_output_with(&result, fmt, |r| {
+                            format!("finished bugfix '{}'{}", r.branch_name, flow_details(r))
+                        })
+                    );
+                    finish_exit_code(&root)
+                }
+                BugfixCommands::List => {
+                    let root = resolve_root()?;
+                    let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
+                    let branches = smctl_flow::bugfix_list(&root, &manifest)?;
+                    println!(
+                        "{}",
+                        format_output_with(&branches, fmt, |bs| {
+                            if bs.is_empty() {
+                                "no active bugfixes".to_string()
+                            } else {
+                                bs.iter()
+                                    .map(|b| format!("  {} — {}", b.repo_name, b.branch))
+                                    .collect::<Vec<_>>()
+                                    .join("\n")
+                            }
+                        })
+                    );
+                    Ok(exit_code::SUCCESS)
+                }
+            },
+            FlowCommands::Support { command } => match command {
+                SupportCommands::Start {
+                    name,
+                    repos,
+                    force,
+                    stash,
+                } => {
+                    let root = resolve_root()?;
+                    let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
+
+                    if dry_run {
+                        println!("would start support '{name}'");
+                        return Ok(exit_code::DRY_RUN);
+                    }
+
+                    let result = smctl_flow::support_start(
+                        &root,
+                        &manifest,
+                        &name,
+                        repos.as_deref(),
+                        dirty_tree(force, stash),
+                    )?;
+                    println!(
+                        "{}",
+                        format_output_with(&result, fmt, |r| {
+                            format!("started support '{}'", r.branch_name)
+                        })
+                    );
+                    Ok(exit_code::SUCCESS)
+                }
+                SupportCommands::List => {
+                    let root = resolve_root()?;
+                    let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
+                    let branches = smctl_flow::support_list(&root, &manifest)?;
                     println!(
                         "{}",
                         format_output_with(&branches, fmt, |bs| {
                             if bs.is_empty() {
-                                "no active releases".to_string()
+                                "no support branches".to_string()
                             } else {
                                 bs.iter()
                                     .map(|b| format!("  {} — {}", b.repo_name, b.branch))
@@ -776,79 +2948,305 @@ async fn run(cli: Cli) -> Result<i32> {
                     Ok(exit_code::SUCCESS)
                 }
             },
-            FlowCommands::Hotfix { command } => match command {
-                HotfixCommands::Start { name, repos } => {
-                    let root = resolve_root()?;
-                    let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
+            FlowCommands::Continue => {
+                let root = resolve_root()?;
+                let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
 
-                    if dry_run {
-                        println!("would start hotfix '{name}'");
-                        return Ok(exit_code::DRY_RUN);
-                    }
+                if dry_run {
+                    let state = smctl_flow::FlowState::load(&root)?
+                        .context("no flow operation in progress")?;
+                    println!(
+                        "would continue '{}' of '{}' in {}",
+                        state.operation, state.branch, state.conflicted.repo_name
+                    );
+                    return Ok(exit_code::DRY_RUN);
+                }
 
-                    let result =
-                        smctl_flow::hotfix_start(&root, &manifest, &name, repos.as_deref())?;
+                let result = smctl_flow::flow_continue(&root, &manifest)?;
+                println!(
+                    "{}",
+                    format_output_with(&result, fmt, |r| {
+                        format!("{} '{}'{}", r.operation, r.branch_name, flow_details(r))
+                    })
+                );
+                finish_exit_code(&root)
+            }
+            FlowCommands::Abort => {
+                let root = resolve_root()?;
+                let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
+
+                if dry_run {
+                    let state = smctl_flow::FlowState::load(&root)?
+                        .context("no flow operation in progress")?;
                     println!(
-                        "{}",
-                        format_output_with(&result, fmt, |r| {
-                            format!("started hotfix '{}'", r.branch_name)
-                        })
+                        "would abort '{}' of '{}' and reset {} repos",
+                        state.operation,
+                        state.branch,
+                        state.merged.len() + 1
                     );
-                    Ok(exit_code::SUCCESS)
+                    return Ok(exit_code::DRY_RUN);
                 }
-                HotfixCommands::Finish { name } => {
-                    let root = resolve_root()?;
-                    let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
 
-                    if dry_run {
-                        println!("would finish hotfix '{name}'");
-                        return Ok(exit_code::DRY_RUN);
-                    }
+                let result = smctl_flow::flow_abort(&root, &manifest)?;
+                println!(
+                    "{}",
+                    format_output_with(&result, fmt, |r| {
+                        r.repos
+                            .iter()
+                            .map(|rr| {
+                                let icon = if rr.success { "\u{2713}" } else { "\u{2717}" };
+                                format!("  {} {} — {}", icon, rr.repo_name, rr.message)
+                            })
+                            .collect::<Vec<_>>()
+                            .join("\n")
+                    })
+                );
+                Ok(if result.repos.iter().all(|r| r.success) {
+                    exit_code::SUCCESS
+                } else {
+                    exit_code::GIT_ERROR
+                })
+            }
+            FlowCommands::VerifyRelease { ver } => {
+                let root = resolve_root()?;
+                let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
+                let tag = manifest.flow.release_tag(&ver);
+                let checks = smctl_flow::verify_release(&root, &manifest, &ver)?;
+                println!(
+                    "{}",
+                    format_output_with(&checks, fmt, |cs| {
+                        let mut lines = Vec::new();
+                        for check in cs {
+                            if check.problems.is_empty() {
+                                lines.push(format!("  \u{2713} {}", check.repo_name));
+                            }
+                            for problem in &check.problems {
+                                lines.push(format!("  \u{2717} {} — {problem}", check.repo_name));
+                            }
+                        }
+                        let failed = cs.iter().filter(|c| !c.problems.is_empty()).count();
+                        if failed == 0 {
+                            lines.push(format!("\nrelease '{tag}' verified in {} repos", cs.len()));
+                        } else {
+                            lines.push(format!("\nrelease '{tag}' incomplete in {failed} repo(s)"));
+                        }
+                        lines.join("\n")
+                    })
+                );
+                Ok(if checks.iter().all(|c| c.problems.is_empty()) {
+                    exit_code::SUCCESS
+                } else {
+                    exit_code::GIT_ERROR
+                })
+            }
+            FlowCommands::Undo { op_id } => {
+                let root = resolve_root()?;
+                let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
 
-                    let result = smctl_flow::hotfix_finish(&root, &manifest, &name)?;
+                let Some(op_id) = op_id else {
+                    let journals = smctl_flow::OpJournal::list(&root)?;
                     println!(
                         "{}",
-                        format_output_with(&result, fmt, |r| {
-                            format!("finished hotfix '{}'", r.branch_name)
+                        format_output_with(&journals, fmt, |js| {
+                            if js.is_empty() {
+                                "no recorded operations".to_string()
+                            } else {
+                                js.iter()
+                                    .map(|j| {
+                                        format!(
+                                            "  {} — {} '{}' ({})",
+                                            j.id, j.operation, j.branch, j.status
+                                        )
+                                    })
+                                    .collect::<Vec<_>>()
+                                    .join("\n")
+                            }
                         })
                     );
-                    Ok(exit_code::SUCCESS)
+                    return Ok(exit_code::SUCCESS);
+                };
+
+                if dry_run {
+                    let journal = smctl_flow::OpJournal::load(&root, &op_id)?;
+                    println!(
+                        "would undo '{}' of '{}' ({}) in {} repos",
+                        journal.operation,
+                        journal.branch,
+                        journal.status,
+                        journal.repos.len()
+                    );
+                    return Ok(exit_code::DRY_RUN);
                 }
-                HotfixCommands::List => {
-                    let root = resolve_root()?;
-                    let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
-                    let branches = smctl_flow::hotfix_list(&root, &manifest)?;
+
+                let result = smctl_flow::flow_undo(&root, &manifest, &op_id)?;
+                println!(
+                    "{}",
+                    format_output_with(&result, fmt, |r| {
+                        r.repos
+                            .iter()
+                            .map(|rr| {
+                                let icon = if rr.success { "\u{2713}" } else { "\u{2717}" };
+                                format!("  {} {} — {}", icon, rr.repo_name, rr.message)
+                            })
+                            .collect::<Vec<_>>()
+                            .join("\n")
+                    })
+                );
+                Ok(if result.repos.iter().all(|r| r.success) {
+                    exit_code::SUCCESS
+                } else {
+                    exit_code::GIT_ERROR
+                })
+            }
+            FlowCommands::Cleanup { remote, yes } => {
+                let root = resolve_root()?;
+                let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
+                let stale = smctl_flow::stale_branches(&root, &manifest)?;
+                let listing = |branches: &Vec<smctl_flow::StaleBranch>| {
+                    branches
+                        .iter()
+                        .map(|b| {
+                            let date = chrono::DateTime::from_timestamp(b.last_commit, 0)
+                                .map(|d| d.format("%Y-%m-%d").to_string())
+                                .unwrap_or_default();
+                            format!(
+                                "  {} — {} (merged into {}, last commit {date})",
+                                b.repo_name, b.branch, b.merged_into
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                };
+
+                if stale.is_empty() || dry_run {
                     println!(
                         "{}",
-                        format_output_with(&branches, fmt, |bs| {
+                        format_output_with(&stale, fmt, |bs| {
                             if bs.is_empty() {
-                                "no active hotfixes".to_string()
+                                "no merged feature or hotfix branches".to_string()
                             } else {
-                                bs.iter()
-                                    .map(|b| format!("  {} — {}", b.repo_name, b.branch))
-                                    .collect::<Vec<_>>()
-                                    .join("\n")
+                                format!("would delete:\n{}", listing(bs))
                             }
                         })
                     );
-                    Ok(exit_code::SUCCESS)
+                    return Ok(if stale.is_empty() {
+                        exit_code::SUCCESS
+                    } else {
+                        exit_code::DRY_RUN
+                    });
                 }
-            },
+
+                if !yes {
+                    eprintln!("{}", listing(&stale));
+                    let place = match &remote {
+                        Some(remote) => format!("locally and on {remote}"),
+                        None => "locally".to_string(),
+                    };
+                    eprint!("delete {} branches {place}? [y/N] ", stale.len());
+                    let mut answer = String::new();
+                    std::io::stdin()
+                        .read_line(&mut answer)
+                        .context("failed to read answer")?;
+                    if !matches!(answer.trim(), "y" | "Y" | "yes") {
+                        eprintln!("nothing deleted");
+                        return Ok(exit_code::SUCCESS);
+                    }
+                }
+
+                let results =
+                    smctl_flow::delete_branches(&root, &manifest, &stale, remote.as_deref())?;
+                println!(
+                    "{}",
+                    format_output_with(&results, fmt, |rs| {
+                        rs.iter()
+                            .map(|rr| {
+                                let icon = if rr.success { "\u{2713}" } else { "\u{2717}" };
+                                format!("  {} {} — {}", icon, rr.repo_name, rr.message)
+                            })
+                            .collect::<Vec<_>>()
+                            .join("\n")
+                    })
+                );
+                Ok(if results.iter().all(|r| r.success) {
+                    exit_code::SUCCESS
+                } else {
+                    exit_code::GIT_ERROR
+                })
+            }
         },
 
         Commands::Spec { command } => {
             let root = resolve_root()?;
             let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
             let openspec_dir = root.join(&manifest.spec.openspec_dir);
+            // Spec named on the command line, else the one matching the
+            // current worktree set or feature branch.
+            let resolve_spec = |name: Option<String>| -> Result<String> {
+                if let Some(name) = name {
+                    return Ok(name);
+                }
+                let cwd = std::env::current_dir().unwrap_or_else(|_| root.clone());
+                let current = smctl_spec::current_spec(&root, &manifest, &openspec_dir, &cwd)
+                    .context(
+                        "spec name required (no spec matches the current feature branch or worktree)",
+                    )?;
+                tracing::info!("using current spec '{current}'");
+                Ok(current)
+            };
+
+            let mut command = command;
+            resolve_spec_refs(&mut command, &openspec_dir)?;
 
             match command {
-                SpecCommands::New { name } => {
+                SpecCommands::New { name, from, repos } => {
+                    let unknown: Vec<_> = repos
+                        .iter()
+                        .filter(|r| !manifest.repos.iter().any(|m| &m.name == *r))
+                        .map(String::as_str)
+                        .collect();
+                    if !unknown.is_empty() {
+                        let known: Vec<_> =
+                            manifest.repos.iter().map(|r| r.name.as_str()).collect();
+                        anyhow::bail!(
+                            "unknown repo(s) {} (workspace repos: {})",
+                            unknown.join(", "),
+                            known.join(", ")
+                        );
+                    }
                     if dry_run {
-                        println!("would create spec '{name}'");
+                        let name = if manifest.spec.numbered {
+                            let id = smctl_spec::SpecIndex::load(&openspec_dir)?.next_id();
+                            format!("{id:04}-{name}")
+                        } else {
+                            name
+                        };
+                        match &from {
+                            Some(source) => println!("would create spec '{name}' from {source}"),
+                            None => println!("would create spec '{name}'"),
+                        }
+                        if !repos.is_empty() {
+                            println!("  with tasks and Impact rows for {}", repos.join(", "));
+                        }
                         return Ok(exit_code::DRY_RUN);
                     }
 
-                    let info = smctl_spec::new_spec(&openspec_dir, &name)?;
+                    let name = if manifest.spec.numbered {
+                        smctl_spec::assign_id(&openspec_dir, &name)?
+                    } else {
+                        name
+                    };
+                    let info = match &from {
+                        Some(source) => {
+                            let document = smctl::read_document(source).await?;
+                            smctl_spec::import_spec(&openspec_dir, &name, &document)?
+                        }
+                        None => smctl_spec::new_spec(&openspec_dir, &name)?,
+                    };
+                    let info = if repos.is_empty() {
+                        info
+                    } else {
+                        smctl_spec::scaffold_repos(&openspec_dir, &name, &repos)?
+                    };
                     println!(
                         "{}",
                         format_output_with(&info, fmt, |i| {
@@ -861,7 +3259,13 @@ async fn run(cli: Cli) -> Result<i32> {
                         && let Ok(manifest) =
                             smctl_workspace::WorkspaceManifest::load_from_root(&root)
                     {
-                        match smctl_flow::feature_start(&root, &manifest, &name, None) {
+                        match smctl_flow::feature_start(
+                            &root,
+                            &manifest,
+                            &name,
+                            None,
+                            smctl_flow::DirtyTree::default(),
+                        ) {
                             Ok(result) => {
                                 println!("created branch '{}'", result.branch_name);
                             }
@@ -871,15 +3275,37 @@ async fn run(cli: Cli) -> Result<i32> {
                         }
                     }
 
+                    sync_tracking_issue_or_warn(&manifest, &info.path, IssueSync::Create).await;
+
+                    Ok(exit_code::SUCCESS)
+                }
+                SpecCommands::Sync { name } => {
+                    if manifest.spec.github.is_none() {
+                        anyhow::bail!("no [spec.github] section in workspace.toml");
+                    }
+                    let info = smctl_spec::spec_info(&openspec_dir, &name)?;
+                    if dry_run {
+                        println!("would sync tracking issue of spec '{name}'");
+                        return Ok(exit_code::DRY_RUN);
+                    }
+                    let action = if info.phase == smctl_spec::SpecPhase::Archived {
+                        IssueSync::Close
+                    } else {
+                        IssueSync::Create
+                    };
+                    if let Some(number) = sync_tracking_issue(&manifest, &info.path, action).await?
+                    {
+                        println!("synced spec '{name}' to tracking issue #{number}");
+                    }
                     Ok(exit_code::SUCCESS)
                 }
                 SpecCommands::Validate { name } => {
-                    let spec_name = name.context("spec name required")?;
-                    let result = smctl_spec::validate(&openspec_dir, &spec_name)?;
+                    let spec_name = resolve_spec(name)?;
+                    let result = smctl_spec::validate(&openspec_dir, &spec_name, Some(&manifest))?;
                     println!(
                         "{}",
                         format_output_with(&result, fmt, |r| {
-                            if r.valid {
+                            let mut out = if r.valid {
                                 format!("spec '{}' is valid", r.name)
                             } else {
                                 format!(
@@ -891,7 +3317,20 @@ async fn run(cli: Cli) -> Result<i32> {
                                         .collect::<Vec<_>>()
                                         .join("\n")
                                 )
+                            };
+                            for w in &r.warnings {
+                                out.push_str(&format!("\n  warning: {w}"));
+                            }
+                            if !r.deltas.is_empty() {
+                                out.push_str("\ndeltas:");
+                                for d in &r.deltas {
+                                    out.push_str(&format!(
+                                        "\n  {}: {} added, {} modified, {} removed",
+                                        d.capability, d.added, d.modified, d.removed
+                                    ));
+                                }
                             }
+                            out
                         })
                     );
                     if result.valid {
@@ -901,15 +3340,40 @@ async fn run(cli: Cli) -> Result<i32> {
                     }
                 }
                 SpecCommands::Status { name } => {
+                    let name = name.or_else(|| {
+                        let cwd = std::env::current_dir().ok()?;
+                        smctl_spec::current_spec(&root, &manifest, &openspec_dir, &cwd)
+                    });
                     if let Some(name) = name {
                         let info = smctl_spec::spec_info(&openspec_dir, &name)?;
                         println!(
                             "{}",
                             format_output_with(&info, fmt, |i| {
-                                format!(
+                                let mut lines = vec![format!(
                                     "{}: {:?} [{}/{}]",
                                     i.name, i.phase, i.tasks_done, i.tasks_total
-                                )
+                                )];
+                                if let Some(title) = &i.title {
+                                    lines.push(format!("  title: {title}"));
+                                }
+                                if let Some(risk) = &i.risk {
+                                    lines.push(format!("  risk: {risk}"));
+                                }
+                                if !i.requirements.is_empty() {
+                                    lines.push(format!(
+                                        "  requirements: {}",
+                                        i.requirements.join(", ")
+                                    ));
+                                }
+                                for s in &i.sections {
+                                    lines.push(format!(
+                                        "  {:<20} {}/{}",
+                                        s.section.as_deref().unwrap_or("(no section)"),
+                                        s.done,
+                                        s.total
+                                    ));
+                                }
+                                lines.join("\n")
                             })
                         );
                     } else {
@@ -931,43 +3395,403 @@ async fn run(cli: Cli) -> Result<i32> {
                     }
                     Ok(exit_code::SUCCESS)
                 }
-                SpecCommands::List => {
-                    let specs = smctl_spec::list_specs(&openspec_dir)?;
+                SpecCommands::SetStatus { name, status } => {
+                    let phase = smctl_spec::SpecPhase::from_status(&status).with_context(|| {
+                        format!(
+                            "unknown status '{status}' (expected new, draft, active or abandoned)"
+                        )
+                    })?;
+                    if dry_run {
+                        println!("would set status of spec '{name}' to {}", phase.as_status());
+                        return Ok(exit_code::DRY_RUN);
+                    }
+                    let info = smctl_spec::set_status(&openspec_dir, &name, phase)?;
+                    println!(
+                        "{}",
+                        format_output_with(&info, fmt, |i| {
+                            format!("spec '{}' is now {}", i.name, i.phase.as_status())
+                        })
+                    );
+                    Ok(exit_code::SUCCESS)
+                }
+                SpecCommands::Trace { name } => {
+                    let spec_name = resolve_spec(name)?;
+                    let matrix = smctl_spec::trace(&openspec_dir, &spec_name)?;
+                    println!(
+                        "{}",
+                        format_output_with(&matrix, fmt, |m| {
+                            if m.requirements.is_empty() {
+                                return format!("spec '{}' defines no requirements", m.name);
+                            }
+                            let mut out = format!("trace: {}\n", m.name);
+                            for row in &m.requirements {
+                                if row.tasks.is_empty() {
+                                    out.push_str(&format!(
+                                        "  {:<10} no tasks  <- untraced\n",
+                                        row.id
+                                    ));
+                                    continue;
+                                }
+                                let tasks: Vec<_> =
+                                    row.tasks.iter().map(|n| format!("#{n}")).collect();
+                                let mark = if row.done { "\u{2713}" } else { " " };
+                                out.push_str(&format!(
+                                    "  {:<10} {} tasks {}",
+                                    row.id,
+                                    mark,
+                                    tasks.join(", ")
+                                ));
+                                if !row.tests.is_empty() {
+                                    out.push_str(&format!("  tests {}", row.tests.join(", ")));
+                                }
+                                out.push('\n');
+                            }
+                            for (task, id) in &m.unknown {
+                                out.push_str(&format!(
+                                    "  warning: task #{task} references undefined {id}\n"
+                                ));
+                            }
+                            if !m.untraced.is_empty() {
+                                out.push_str(&format!(
+                                    "{} requirement(s) without tasks: {}\n",
+                                    m.untraced.len(),
+                                    m.untraced.join(", ")
+                                ));
+                            }
+                            out.trim_end().to_string()
+                        })
+                    );
+                    Ok(exit_code::SUCCESS)
+                }
+                SpecCommands::Burndown { name } => {
+                    let spec_name = resolve_spec(name)?;
+                    let burndown = smctl_spec::burndown(&openspec_dir, &spec_name)?;
+                    println!(
+                        "{}",
+                        format_output_with(&burndown, fmt, |b| {
+                            let (Some(first), Some(last)) = (b.points.first(), b.points.last())
+                            else {
+                                return format!("spec '{}' has no task history", b.name);
+                            };
+                            format!(
+                                "burndown: {}\n  {}  {} \u{2192} {} remaining ({}/{} done) over {} change(s), {} \u{2192} {}",
+                                b.name,
+                                b.sparkline(),
+                                first.remaining,
+                                last.remaining,
+                                last.done,
+                                last.total,
+                                b.points.len(),
+                                &first.date[..10.min(first.date.len())],
+                                &last.date[..10.min(last.date.len())],
+                            )
+                        })
+                    );
+                    Ok(exit_code::SUCCESS)
+                }
+                SpecCommands::Report { format, recent } => {
+                    let mut report = smctl_spec::report(&openspec_dir, recent)?;
+                    match smctl_flow::feature_list(&root, &manifest) {
+                        Ok(branches) => {
+                            for entry in &mut report.specs {
+                                let branch =
+                                    format!("{}{}", manifest.flow.feature_prefix, entry.name);
+                                entry.branches = branches
+                                    .iter()
+                                    .filter(|b| b.branch == branch)
+                                    .map(|b| format!("{}:{}", b.repo_name, b.branch))
+                                    .collect();
+                            }
+                        }
+                        Err(e) => tracing::warn!("could not list feature branches: {e}"),
+                    }
+
+                    let format = if fmt == OutputFormat::Json {
+                        smctl_spec::ReportFormat::Json
+                    } else {
+                        format
+                    };
+                    match format {
+                        smctl_spec::ReportFormat::Markdown => print!("{}", report.to_markdown()),
+                        smctl_spec::ReportFormat::Html => print!("{}", report.to_html()),
+                        smctl_spec::ReportFormat::Json => {
+                            println!("{}", serde_json::to_string_pretty(&report)?)
+                        }
+                    }
+                    Ok(exit_code::SUCCESS)
+                }
+                SpecCommands::Search { query, phases } => {
+                    let phases = parse_phases(&phases)?;
+                    let hits = smctl_spec::search(&openspec_dir, &query, &phases)?;
+                    println!(
+                        "{}",
+                        format_output_with(&hits, fmt, |hits| {
+                            if hits.is_empty() {
+                                return format!("no matches for '{query}'");
+                            }
+                            hits.iter()
+                                .map(|h| {
+                                    let section = h
+                                        .section
+                                        .as_deref()
+                                        .map_or(String::new(), |s| format!(" [{s}]"));
+                                    format!(
+                                        "{}/{}:{}{}  {}",
+                                        h.spec,
+                                        h.file.display(),
+                                        h.line,
+                                        section,
+                                        h.text
+                                    )
+                                })
+                                .collect::<Vec<_>>()
+                                .join("\n")
+                        })
+                    );
+                    Ok(exit_code::SUCCESS)
+                }
+                SpecCommands::List {
+                    owner,
+                    labels,
+                    phases,
+                    sort,
+                    archived: _,
+                    no_archived,
+                } => {
+                    let filter = smctl_spec::SpecFilter {
+                        phases: parse_phases(&phases)?,
+                        owner: owner.map(|o| resolve_person(&o)),
+                        labels,
+                        exclude_archived: no_archived,
+                        sort,
+                    };
+                    let specs = smctl_spec::list_specs_filtered(&openspec_dir, &filter)?;
+                    println!(
+                        "{}",
+                        format_output_with(&specs, fmt, |ss| {
+                            if ss.is_empty() {
+                                "no specs found".to_string()
+                            } else {
+                                ss.iter()
+                                    .map(|s| {
+                                        let mut line = format!(
+                                            "  {:<24} {:?}  [{}/{}]",
+                                            s.name, s.phase, s.tasks_done, s.tasks_total
+                                        );
+                                        if let Some(owner) = &s.owner {
+                                            line.push_str(&format!("  @{owner}"));
+                                        }
+                                        if !s.labels.is_empty() {
+                                            line.push_str(&format!("  #{}", s.labels.join(" #")));
+                                        }
+                                        line
+                                    })
+                                    .collect::<Vec<_>>()
+                                    .join("\n")
+                            }
+                        })
+                    );
+                    Ok(exit_code::SUCCESS)
+                }
+                SpecCommands::Meta {
+                    name,
+                    owner,
+                    reviewers,
+                    labels,
+                } => {
+                    let owner = owner.map(|o| resolve_person(&o));
+                    let changing = owner.is_some() || !reviewers.is_empty() || !labels.is_empty();
+                    if changing && dry_run {
+                        println!("would update metadata of spec '{name}'");
+                        return Ok(exit_code::DRY_RUN);
+                    }
+                    let info = if changing {
+                        smctl_spec::set_metadata(
+                            &openspec_dir,
+                            &name,
+                            owner.as_deref(),
+                            (!reviewers.is_empty()).then_some(reviewers.as_slice()),
+                            (!labels.is_empty()).then_some(labels.as_slice()),
+                        )?
+                    } else {
+                        smctl_spec::spec_info(&openspec_dir, &name)?
+                    };
+                    println!(
+                        "{}",
+                        format_output_with(&info, fmt, |i| {
+                            format!(
+                                "spec: {}\nowner: {}\nreviewers: {}\nlabels: {}",
+                                i.name,
+                                i.owner.as_deref().unwrap_or("-"),
+                                i.reviewers.join(", "),
+                                i.labels.join(", ")
+                            )
+                        })
+                    );
+                    Ok(exit_code::SUCCESS)
+                }
+                SpecCommands::Task { command } => match command {
+                    TaskCommands::List { name } => {
+                        let tasks_path = smctl_spec::tasks_path(&openspec_dir, &name)?;
+                        let tasks = smctl_spec::read_tasks(&tasks_path)?;
+                        println!(
+                            "{}",
+                            format_output_with(&tasks, fmt, |tasks| {
+                                if tasks.is_empty() {
+                                    return format!("spec '{name}' has no tasks");
+                                }
+                                let mut lines = Vec::new();
+                                let mut section = None;
+                                for task in tasks {
+                                    if task.section != section {
+                                        section = task.section.clone();
+                                        if let Some(s) = &section {
+                                            lines.push(format!("{s}:"));
+                                        }
+                                    }
+                                    let mark = if task.done { "x" } else { " " };
+                                    lines.push(format!(
+                                        "  {:>3}. {}[{mark}] {}",
+                                        task.number,
+                                        "  ".repeat(task.depth),
+                                        task.text
+                                    ));
+                                }
+                                lines.join("\n")
+                            })
+                        );
+                        Ok(exit_code::SUCCESS)
+                    }
+                    TaskCommands::Check {
+                        ref name,
+                        ref numbers,
+                    }
+                    | TaskCommands::Uncheck {
+                        ref name,
+                        ref numbers,
+                    } => {
+                        let done = matches!(command, TaskCommands::Check { .. });
+                        let verb = if done { "checked" } else { "unchecked" };
+                        let tasks_path = smctl_spec::tasks_path(&openspec_dir, name)?;
+                        if dry_run {
+                            println!("would mark task(s) {numbers:?} {verb} in spec '{name}'");
+                            return Ok(exit_code::DRY_RUN);
+                        }
+                        let changed = smctl_spec::set_tasks(&tasks_path, numbers, done)?;
+                        if !changed.is_empty()
+                            && let Some(spec_dir) = tasks_path.parent()
+                        {
+                            sync_tracking_issue_or_warn(&manifest, spec_dir, IssueSync::Update)
+                                .await;
+                        }
+                        println!(
+                            "{}",
+                            format_output_with(&changed, fmt, |changed| {
+                                if changed.is_empty() {
+                                    "no tasks changed".to_string()
+                                } else {
+                                    format!("{verb} task(s) {changed:?} in spec '{name}'")
+                                }
+                            })
+                        );
+                        Ok(exit_code::SUCCESS)
+                    }
+                    TaskCommands::Add {
+                        name,
+                        text,
+                        section,
+                    } => {
+                        let tasks_path = smctl_spec::tasks_path(&openspec_dir, &name)?;
+                        if dry_run {
+                            println!("would add task '{text}' to spec '{name}'");
+                            return Ok(exit_code::DRY_RUN);
+                        }
+                        let task = smctl_spec::add_task(&tasks_path, section.as_deref(), &text)?;
+                        if let Some(spec_dir) = tasks_path.parent() {
+                            sync_tracking_issue_or_warn(&manifest, spec_dir, IssueSync::Update)
+                                .await;
+                        }
+                        println!(
+                            "{}",
+                            format_output_with(&task, fmt, |t| {
+                                format!("added task #{} to spec '{name}': {}", t.number, t.text)
+                            })
+                        );
+                        Ok(exit_code::SUCCESS)
+                    }
+                },
+                SpecCommands::Delta { name, capability } => {
+                    if dry_run {
+                        println!("would create delta '{capability}' in spec '{name}'");
+                        return Ok(exit_code::DRY_RUN);
+                    }
+                    let path = smctl_spec::new_delta(&openspec_dir, &name, &capability)?;
+                    println!("created delta '{capability}' at {}", path.display());
+                    Ok(exit_code::SUCCESS)
+                }
+                SpecCommands::Rename { name, new_name } => {
+                    if dry_run {
+                        println!("would rename spec '{name}' to '{new_name}'");
+                        return Ok(exit_code::DRY_RUN);
+                    }
+                    let result = smctl_spec::rename(&openspec_dir, &name, &new_name)?;
                     println!(
                         "{}",
-                        format_output_with(&specs, fmt, |ss| {
-                            if ss.is_empty() {
-                                "no specs found".to_string()
-                            } else {
-                                ss.iter()
-                                    .map(|s| {
-                                        format!(
-                                            "  {:<24} {:?}  [{}/{}]",
-                                            s.name, s.phase, s.tasks_done, s.tasks_total
-                                        )
-                                    })
-                                    .collect::<Vec<_>>()
-                                    .join("\n")
-                            }
+                        format_output_with(&result, fmt, |r| {
+                            let mut lines = vec![format!(
+                                "renamed spec '{}' to '{}' at {}",
+                                r.from,
+                                r.to,
+                                r.path.display()
+                            )];
+                            lines.extend(r.updated_files.iter().map(|f| {
+                                let f = f.strip_prefix(&openspec_dir).unwrap_or(f);
+                                format!("  updated {}", f.display())
+                            }));
+                            lines.join("\n")
                         })
                     );
                     Ok(exit_code::SUCCESS)
                 }
-                SpecCommands::Archive { name } => {
-                    let spec_name = name.context("spec name required")?;
+                SpecCommands::Archive {
+                    name,
+                    no_promote,
+                    commit,
+                } => {
+                    let spec_name = resolve_spec(name)?;
                     if dry_run {
                         println!("would archive spec '{spec_name}'");
                         return Ok(exit_code::DRY_RUN);
                     }
                     let dest = smctl_spec::archive(&openspec_dir, &spec_name)?;
                     println!("archived spec '{}' to {}", spec_name, dest.display());
+                    if !no_promote {
+                        let archived = dest.file_name().unwrap_or_default().to_string_lossy();
+                        let results = smctl_spec::promote(&openspec_dir, &archived)?;
+                        if !results.is_empty() {
+                            println!("{}", format_promotions(&results));
+                        }
+                    }
+                    if commit {
+                        let oid = smctl_spec::commit_archive(&openspec_dir, &spec_name, &dest)?;
+                        println!("committed {}", &oid[..7.min(oid.len())]);
+                    }
+                    sync_tracking_issue_or_warn(&manifest, &dest, IssueSync::Close).await;
 
-                    // Auto-finish feature branch if workspace is available
+                    // Auto-finish feature branch if workspace is available; in
+                    // pull request mode the merge happens on the code host.
                     if let Ok(root) = resolve_root()
                         && let Ok(manifest) =
                             smctl_workspace::WorkspaceManifest::load_from_root(&root)
+                        && manifest.flow.finish == smctl_workspace::FinishMode::Merge
                     {
-                        match smctl_flow::feature_finish(&root, &manifest, &spec_name) {
+                        match smctl_flow::feature_finish(
+                            &root,
+                            &manifest,
+                            &spec_name,
+                            None,
+                            &smctl_flow::FinishOptions::default(),
+                        ) {
                             Ok(result) => {
                                 println!("merged branch '{}' into develop", result.branch_name);
                             }
@@ -979,99 +3803,389 @@ async fn run(cli: Cli) -> Result<i32> {
 
                     Ok(exit_code::SUCCESS)
                 }
+                SpecCommands::Promote { name } => {
+                    if dry_run {
+                        println!("would promote the deltas of archived spec '{name}'");
+                        return Ok(exit_code::DRY_RUN);
+                    }
+                    let results = smctl_spec::promote(&openspec_dir, &name)?;
+                    println!(
+                        "{}",
+                        format_output_with(&results, fmt, |results| {
+                            if results.is_empty() {
+                                format!("archived spec '{name}' has no deltas")
+                            } else {
+                                format_promotions(results)
+                            }
+                        })
+                    );
+                    Ok(exit_code::SUCCESS)
+                }
+                SpecCommands::Restore {
+                    name,
+                    rename_to,
+                    keep_date,
+                } => {
+                    if dry_run {
+                        println!("would restore archived spec '{name}'");
+                        return Ok(exit_code::DRY_RUN);
+                    }
+                    let result =
+                        smctl_spec::restore(&openspec_dir, &name, rename_to.as_deref(), keep_date)?;
+                    println!(
+                        "{}",
+                        format_output_with(&result, fmt, |r| {
+                            format!(
+                                "restored spec '{}' from archive/{} ({})",
+                                r.name,
+                                r.archived,
+                                r.phase.as_status()
+                            )
+                        })
+                    );
+                    Ok(exit_code::SUCCESS)
+                }
                 SpecCommands::Ff { name } => {
-                    let spec_name = name.context("spec name required")?;
-
-                    // Validate document completeness
-                    let result = smctl_spec::validate(&openspec_dir, &spec_name)?;
-                    let info = smctl_spec::spec_info(&openspec_dir, &spec_name)?;
+                    let spec_name = resolve_spec(name)?;
+                    let report = smctl_spec::fast_forward(&openspec_dir, &spec_name)?;
+                    let status = |present: bool| if present { "ok" } else { "MISSING" };
 
-                    println!("spec: {spec_name}");
-                    println!("phase: {:?}", info.phase);
                     println!(
-                        "documents: proposal={} design={} tasks={}",
-                        if info.has_proposal { "ok" } else { "MISSING" },
-                        if info.has_design { "ok" } else { "MISSING" },
-                        if info.has_tasks { "ok" } else { "MISSING" },
+                        "{}",
+                        format_output_with(&report, fmt, |r| {
+                            let mut lines = vec![
+                                format!("spec: {}", r.name),
+                                format!("phase: {:?}", r.phase),
+                                format!(
+                                    "documents: proposal={} design={} tasks={}",
+                                    status(r.has_proposal),
+                                    status(r.has_design),
+                                    status(r.has_tasks),
+                                ),
+                                format!("tasks: {}/{} complete", r.tasks_done, r.tasks_total),
+                            ];
+                            match &r.next_phase {
+                                Some(smctl_spec::SpecPhase::Archived) => {
+                                    lines.push("validation: PASS".to_string());
+                                    lines.push("ready to archive".to_string());
+                                }
+                                Some(next) => {
+                                    lines.push("validation: PASS".to_string());
+                                    lines.push(format!(
+                                        "ready for {next:?}: {} task(s) remaining",
+                                        r.tasks_total - r.tasks_done
+                                    ));
+                                }
+                                None => {
+                                    lines.push("validation: FAIL".to_string());
+                                    lines.extend(r.issues.iter().map(|i| format!("  - {i}")));
+                                }
+                            }
+                            lines.join("\n")
+                        })
                     );
-                    println!("tasks: {}/{} complete", info.tasks_done, info.tasks_total);
 
-                    if result.valid {
-                        println!("validation: PASS");
-                        if info.tasks_total > 0 && info.tasks_done == info.tasks_total {
-                            println!("ready to archive");
-                        } else {
-                            println!("{} task(s) remaining", info.tasks_total - info.tasks_done);
-                        }
+                    if report.ready {
                         Ok(exit_code::SUCCESS)
                     } else {
-                        println!("validation: FAIL");
-                        for issue in &result.issues {
-                            println!("  - {issue}");
-                        }
-                        Ok(exit_code::GENERAL_ERROR)
+                        Ok(exit_code::SPEC_ERROR)
                     }
                 }
-                SpecCommands::Apply { name } => {
-                    let spec_name = name.context("spec name required")?;
+                SpecCommands::Apply {
+                    name,
+                    tasks,
+                    all,
+                    interactive,
+                } => {
+                    let spec_name = resolve_spec(name)?;
                     let info = smctl_spec::spec_info(&openspec_dir, &spec_name)?;
-
                     if !info.has_tasks {
                         anyhow::bail!("spec '{spec_name}' has no tasks.md");
                     }
-
                     let tasks_path = info.path.join("tasks.md");
-                    let content = std::fs::read_to_string(&tasks_path)?;
-
-                    let mut pending = Vec::new();
-                    let mut done = Vec::new();
-                    for line in content.lines() {
-                        let trimmed = line.trim();
-                        if trimmed.starts_with("- [x]") || trimmed.starts_with("- [X]") {
-                            done.push(
-                                trimmed
-                                    .trim_start_matches("- [x] ")
-                                    .trim_start_matches("- [X] ")
-                                    .to_string(),
-                            );
-                        } else if trimmed.starts_with("- [ ]") {
-                            pending.push(trimmed.trim_start_matches("- [ ] ").to_string());
+                    let pending: Vec<_> = smctl_spec::read_tasks(&tasks_path)?
+                        .into_iter()
+                        .filter(|t| !t.done)
+                        .collect();
+
+                    let to_complete: Vec<usize> = if all {
+                        pending.iter().map(|t| t.number).collect()
+                    } else if interactive {
+                        let mut chosen = Vec::new();
+                        for task in &pending {
+                            eprint!("complete #{} {}? [y/N/q] ", task.number, task.text);
+                            let mut answer = String::new();
+                            std::io::stdin()
+                                .read_line(&mut answer)
+                                .context("failed to read answer")?;
+                            match answer.trim() {
+                                "y" | "Y" | "yes" => chosen.push(task.number),
+                                "q" | "Q" => break,
+                                _ => {}
+                            }
+                        }
+                        chosen
+                    } else {
+                        tasks
+                    };
+
+                    if dry_run {
+                        if to_complete.is_empty() {
+                            println!("would not change tasks.md");
+                        } else {
+                            let numbers: Vec<_> =
+                                to_complete.iter().map(|n| format!("#{n}")).collect();
+                            println!("would mark complete: {}", numbers.join(", "));
                         }
+                        return Ok(exit_code::DRY_RUN);
                     }
 
+                    let report = smctl_spec::apply(&openspec_dir, &spec_name, &to_complete)?;
+                    if !report.newly_completed.is_empty() {
+                        sync_tracking_issue_or_warn(&manifest, &info.path, IssueSync::Update).await;
+                    }
                     println!(
-                        "spec: {spec_name} — {}/{} tasks complete",
-                        done.len(),
-                        done.len() + pending.len()
+                        "{}",
+                        format_output_with(&report, fmt, |r| {
+                            let total = r.completed.len() + r.remaining.len();
+                            let mut lines = vec![format!(
+                                "spec: {} \u{2014} {}/{} tasks complete",
+                                r.name,
+                                r.completed.len(),
+                                total
+                            )];
+                            for n in &r.newly_completed {
+                                if let Some(task) = r.completed.iter().find(|t| t.number == *n) {
+                                    lines.push(format!("  \u{2713} #{} {}", n, task.text));
+                                }
+                            }
+                            if r.remaining.is_empty() {
+                                lines.push(
+                                    "all tasks complete \u{2014} ready for archive".to_string(),
+                                );
+                            } else {
+                                lines.push(format!("\npending ({}):", r.remaining.len()));
+                                for task in &r.remaining {
+                                    let section = task
+                                        .section
+                                        .as_deref()
+                                        .map_or(String::new(), |s| format!(" [{s}]"));
+                                    lines.push(format!(
+                                        "  {}. {}{}",
+                                        task.number, task.text, section
+                                    ));
+                                }
+                            }
+                            lines.join("\n")
+                        })
                     );
 
-                    if !pending.is_empty() {
-                        println!("\npending ({}):", pending.len());
-                        for (i, task) in pending.iter().enumerate() {
-                            println!("  {}. {task}", i + 1);
-                        }
+                    Ok(exit_code::SUCCESS)
+                }
+            }
+        }
+
+        Commands::Build {
+            command: Some(BuildCommands::Graph { format, durations }),
+            ..
+        } => {
+            let root = resolve_root()?;
+            let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
+
+            let last_build = if durations {
+                match smctl_build::load_build(&root, "latest") {
+                    Ok(report) => Some(report),
+                    Err(e) => {
+                        tracing::warn!("no build durations available: {e}");
+                        None
                     }
+                }
+            } else {
+                None
+            };
+            let graph = smctl_build::dependency_graph(&manifest, last_build.as_ref())?;
+
+            let format = if fmt == OutputFormat::Json {
+                smctl_build::GraphFormat::Json
+            } else {
+                format
+            };
+            match format {
+                smctl_build::GraphFormat::Dot => print!("{}", graph.to_dot()),
+                smctl_build::GraphFormat::Mermaid => print!("{}", graph.to_mermaid()),
+                smctl_build::GraphFormat::Json => {
+                    println!("{}", serde_json::to_string_pretty(&graph)?)
+                }
+            }
+            Ok(exit_code::SUCCESS)
+        }
+
+        Commands::Build {
+            command: Some(BuildCommands::Explain { repo, changed }),
+            ..
+        } => {
+            let root = resolve_root()?;
+            let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
 
-                    if pending.is_empty() {
-                        println!("all tasks complete — ready for archive");
+            let explanation = smctl_build::explain(&root, &manifest, &repo, changed.as_deref())?;
+            println!(
+                "{}",
+                format_output_with(&explanation, fmt, |e| {
+                    let short = |h: &Option<String>| {
+                        h.as_deref()
+                            .map_or("-".to_string(), |h| h.chars().take(12).collect())
+                    };
+                    let names: Vec<_> = e.chain.iter().map(|r| r.name.as_str()).collect();
+                    let mut lines = vec![format!("chain: {}", names.join(" \u{2192} "))];
+                    for r in &e.chain {
+                        let mut line = format!(
+                            "  {:<24} inputs {}  last built {}",
+                            r.name,
+                            short(&r.input_hash),
+                            short(&r.last_built_hash)
+                        );
+                        if e.base.is_some() {
+                            line.push_str(&match &r.changed_files {
+                                Some(files) => format!("  {} changed files", files.len()),
+                                None => "  base unresolved".to_string(),
+                            });
+                        }
+                        lines.push(line);
                     }
+                    let mode = match &e.base {
+                        Some(base) => format!("build --changed {base}"),
+                        None => "build".to_string(),
+                    };
+                    let verdict = if e.rebuild { "rebuilds" } else { "skips" };
+                    lines.push(format!(
+                        "\n`smctl {mode}` {verdict} {}: {}",
+                        e.repo_name,
+                        e.reason.describe()
+                    ));
+                    lines.join("\n")
+                })
+            );
+            Ok(exit_code::SUCCESS)
+        }
 
-                    Ok(exit_code::SUCCESS)
+        Commands::Build {
+            command: Some(BuildCommands::History { command, limit }),
+            ..
+        } => {
+            let root = resolve_root()?;
+
+            match command {
+                None => {
+                    let history = smctl_build::list_history(&root, Some(limit))?;
+                    println!(
+                        "{}",
+                        format_output_with(&history, fmt, |h| {
+                            if h.is_empty() {
+                                return "no recorded builds".to_string();
+                            }
+                            h.iter()
+                                .map(|e| {
+                                    let icon = if e.all_passed { "\u{2713}" } else { "\u{2717}" };
+                                    format!(
+                                        "  {} {}  {}ms  {} steps, {} failed",
+                                        icon, e.id, e.total_duration_ms, e.results, e.failures
+                                    )
+                                })
+                                .collect::<Vec<_>>()
+                                .join("\n")
+                        })
+                    );
+                }
+                Some(HistoryCommands::Show { id }) => {
+                    let report = smctl_build::load_build(&root, &id)?;
+                    println!("{}", format_output_with(&report, fmt, format_build_report));
+                }
+                Some(HistoryCommands::Compare { before, after }) => {
+                    let cmp = smctl_build::compare_builds(&root, &before, &after)?;
+                    println!(
+                        "{}",
+                        format_output_with(&cmp, fmt, |c| {
+                            let ms =
+                                |v: Option<u64>| v.map_or("-".to_string(), |v| format!("{v}ms"));
+                            let mut lines: Vec<String> = c
+                                .steps
+                                .iter()
+                                .map(|st| {
+                                    let delta = st
+                                        .delta_ms
+                                        .map_or(String::new(), |d| format!(" ({d:+}ms)"));
+                                    format!(
+                                        "  {:<24} {:>10} → {:<10}{}",
+                                        st.repo_name,
+                                        ms(st.before_ms),
+                                        ms(st.after_ms),
+                                        delta
+                                    )
+                                })
+                                .collect();
+                            lines.push(format!(
+                                "\ntotal: {:+}ms ({} → {})",
+                                c.total_delta_ms, c.before, c.after
+                            ));
+                            lines.join("\n")
+                        })
+                    );
                 }
             }
+            Ok(exit_code::SUCCESS)
         }
 
         Commands::Build {
+            command: None,
             repo,
             parallel,
+            jobs,
+            profile,
+            retries,
+            report: report_specs,
+            changed,
+            keep_going,
+            watch,
+            debounce,
             test,
             clean,
             verify: _,
-            cedar: _,
+            cedar,
+            test_args,
         } => {
             let root = resolve_root()?;
             let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
 
+            // Fast path for policy authors: skip builds and the rest of verification.
+            if cedar {
+                if dry_run {
+                    println!(
+                        "would validate Cedar policies for: {}",
+                        repo.as_deref().unwrap_or("all repos")
+                    );
+                    return Ok(exit_code::DRY_RUN);
+                }
+                let report = smctl_build::verify_cedar(&root, &manifest, repo.as_deref())?;
+                for spec in &report_specs {
+                    report.write_report(spec)?;
+                }
+                println!(
+                    "{}",
+                    format_output_with(&report, fmt, |r| {
+                        if r.results.is_empty() {
+                            "no repos declare Cedar policies ([repos.cedar])".to_string()
+                        } else {
+                            format_build_report(r)
+                        }
+                    })
+                );
+                return Ok(if report.all_passed {
+                    exit_code::SUCCESS
+                } else {
+                    exit_code::BUILD_ERROR
+                });
+            }
+
             if dry_run {
                 let order = smctl_build::resolve_build_order(&manifest)?;
                 let names: Vec<_> = order.iter().map(|r| r.name.as_str()).collect();
@@ -1079,28 +4193,164 @@ async fn run(cli: Cli) -> Result<i32> {
                 return Ok(exit_code::DRY_RUN);
             }
 
-            let report = if parallel {
-                smctl_build::build_parallel(&root, &manifest, repo.as_deref(), test, clean)?
-            } else {
-                smctl_build::build(&root, &manifest, repo.as_deref(), test, clean)?
+            // Ctrl-C kills running commands and reports in-flight repos as
+            // cancelled; a second Ctrl-C exits immediately.
+            let cancel = std::sync::Arc::new(smctl_build::CancelToken::new());
+            {
+                let cancel = cancel.clone();
+                ctrlc::set_handler(move || {
+                    if cancel.is_cancelled() {
+                        process::exit(exit_code::INTERRUPTED);
+                    }
+                    eprintln!("interrupted, stopping build...");
+                    cancel.cancel();
+                })
+                .context("failed to install Ctrl-C handler")?;
+            }
+
+            let progress = BuildProgress::enabled(fmt, cli.quiet).then(BuildProgress::new);
+            let on_progress = |repo: &str, state: smctl_build::RepoState| {
+                if let Some(progress) = &progress {
+                    progress.update(repo, state);
+                }
             };
 
+            let opts = smctl_build::BuildOptions {
+                repo_name: repo.as_deref(),
+                run_tests: test,
+                clean_first: clean,
+                parallel,
+                jobs,
+                profile: profile.as_deref(),
+                retries,
+                only: None,
+                keep_going,
+                changed_since: changed.as_deref(),
+                // Colors end up in the captured output, so keep them out of JSON.
+                color: fmt == OutputFormat::Human
+                    && !cli.no_color
+                    && std::env::var_os("NO_COLOR").is_none(),
+                test_args: &test_args,
+                progress: progress
+                    .is_some()
+                    .then_some(smctl_build::ProgressFn(&on_progress)),
+                cancel: Some(&cancel),
+            };
+            let report = smctl_build::build_with_options(&root, &manifest, &opts);
+            if let Some(progress) = &progress {
+                progress.clear();
+            }
+            let report = report?;
+            for spec in &report_specs {
+                report.write_report(spec)?;
+            }
+            if let Err(e) = smctl_build::record_build(&root, &report) {
+                tracing::warn!("could not record build history: {e}");
+            }
+
+            println!("{}", format_output_with(&report, fmt, format_build_report));
+
+            match smctl::SmctlConfig::load_user_config() {
+                Ok(config) => {
+                    let notification =
+                        smctl::BuildNotification::new(&manifest.workspace.name, &report);
+                    smctl::send_build_notification(&config.user.notify, &notification).await;
+                }
+                Err(e) => tracing::warn!("could not load user config for notifications: {e:#}"),
+            }
+
+            if watch && !cancel.is_cancelled() {
+                eprintln!(
+                    "watching {} repos for changes (Ctrl-C to stop)",
+                    manifest.repos.len()
+                );
+                smctl_build::watch(
+                    &root,
+                    &manifest,
+                    &opts,
+                    std::time::Duration::from_millis(debounce),
+                    |built, report| {
+                        if let Some(progress) = &progress {
+                            progress.clear();
+                        }
+                        if let Err(e) = smctl_build::record_build(&root, report) {
+                            tracing::warn!("could not record build history: {e}");
+                        }
+                        if fmt == OutputFormat::Json {
+                            // One JSON document per line so consumers can stream rebuilds.
+                            if let Ok(line) = serde_json::to_string(report) {
+                                println!("{line}");
+                            }
+                            return;
+                        }
+                        let icon = if report.all_passed {
+                            "\u{2713}"
+                        } else {
+                            "\u{2717}"
+                        };
+                        println!(
+                            "[{}] {} rebuilt {} ({}ms)",
+                            chrono::Local::now().format("%H:%M:%S"),
+                            icon,
+                            built.join(", "),
+                            report.total_duration_ms
+                        );
+                        for failed in report.results.iter().filter(|r| !r.success) {
+                            println!("  \u{2717} {}\n{}", failed.repo_name, failed.output);
+                        }
+                    },
+                )?;
+            }
+
+            if cancel.is_cancelled() {
+                Ok(exit_code::INTERRUPTED)
+            } else if report.all_passed {
+                Ok(exit_code::SUCCESS)
+            } else {
+                Ok(exit_code::BUILD_ERROR)
+            }
+        }
+
+        Commands::Coverage { repo } => {
+            let root = resolve_root()?;
+            let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
+
+            if dry_run {
+                println!(
+                    "would run coverage for: {}",
+                    repo.as_deref().unwrap_or("all repos")
+                );
+                return Ok(exit_code::DRY_RUN);
+            }
+
+            let report = smctl_build::coverage(&root, &manifest, repo.as_deref())?;
             println!(
                 "{}",
                 format_output_with(&report, fmt, |r| {
-                    let mut lines: Vec<String> = r
-                        .results
-                        .iter()
-                        .map(|br| {
-                            let icon = if br.success { "\u{2713}" } else { "\u{2717}" };
-                            format!("  {} {}", icon, br.repo_name)
-                        })
-                        .collect();
-                    if r.all_passed {
-                        lines.push(format!("\nbuild passed ({}ms)", r.total_duration_ms));
-                    } else {
-                        lines.push(format!("\nbuild FAILED ({}ms)", r.total_duration_ms));
+                    let mut lines = vec![format!(
+                        "  {:<24} {:>8} {:>10} {:>8}",
+                        "repo", "lines", "functions", "regions"
+                    )];
+                    for rc in &r.repos {
+                        if rc.success {
+                            lines.push(format!(
+                                "  {:<24} {:>7.1}% {:>9.1}% {:>7.1}%",
+                                rc.repo_name,
+                                rc.lines.percent(),
+                                rc.functions.percent(),
+                                rc.regions.percent()
+                            ));
+                        } else {
+                            lines.push(format!("  {:<24} error: {}", rc.repo_name, rc.message));
+                        }
                     }
+                    lines.push(format!(
+                        "  {:<24} {:>7.1}% {:>9.1}% {:>7.1}%",
+                        "total",
+                        r.lines.percent(),
+                        r.functions.percent(),
+                        r.regions.percent()
+                    ));
                     lines.join("\n")
                 })
             );
@@ -1112,6 +4362,83 @@ async fn run(cli: Cli) -> Result<i32> {
             }
         }
 
+        Commands::Bench {
+            repo,
+            compare,
+            threshold,
+        } => {
+            use smctl_build::bench;
+
+            let root = resolve_root()?;
+            let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
+
+            if dry_run {
+                println!(
+                    "would run benchmarks for: {}",
+                    repo.as_deref().unwrap_or("all repos")
+                );
+                return Ok(exit_code::DRY_RUN);
+            }
+
+            // Load the baseline before this run is recorded, so `latest` means the previous run.
+            let baseline = compare
+                .as_deref()
+                .map(|id| bench::load(&root, id))
+                .transpose()?;
+            let run = bench::run(&root, &manifest, repo.as_deref())?;
+            bench::record(&root, &run)?;
+            let comparison = baseline.map(|b| bench::compare(&b, &run, threshold));
+
+            let output = serde_json::json!({ "run": &run, "comparison": &comparison });
+            println!(
+                "{}",
+                format_output_with(&output, fmt, |_| {
+                    let mut lines = Vec::new();
+                    for rb in &run.repos {
+                        let icon = if rb.success { "\u{2713}" } else { "\u{2717}" };
+                        lines.push(format!(
+                            "  {} {} ({}ms)",
+                            icon, rb.repo_name, rb.duration_ms
+                        ));
+                        if !rb.success {
+                            lines.push(format!("      {}", rb.message));
+                        }
+                        for b in &rb.benchmarks {
+                            lines.push(format!(
+                                "      {:<40} {}",
+                                b.name,
+                                bench::format_nanos(b.nanos)
+                            ));
+                        }
+                    }
+                    if let Some(cmp) = &comparison {
+                        lines.push(format!("\ncompared with {}:", cmp.baseline));
+                        for d in &cmp.deltas {
+                            lines.push(format!(
+                                "  {:<40} {:>+7.1}%{}",
+                                format!("{}/{}", d.repo_name, d.name),
+                                d.change_pct,
+                                if d.regression { "  REGRESSION" } else { "" }
+                            ));
+                        }
+                        lines.push(format!(
+                            "{} regression(s) above {}%",
+                            cmp.regressions, cmp.threshold_pct
+                        ));
+                    }
+                    lines.push(format!("\nrecorded as {}", run.id));
+                    lines.join("\n")
+                })
+            );
+
+            let regressed = comparison.as_ref().is_some_and(|c| c.regressions > 0);
+            if run.repos.iter().all(|r| r.success) && !regressed {
+                Ok(exit_code::SUCCESS)
+            } else {
+                Ok(exit_code::BUILD_ERROR)
+            }
+        }
+
         Commands::Config { command } => {
             let mut config = smctl::SmctlConfig::load_user_config()?;
 
@@ -1137,13 +4464,7 @@ async fn run(cli: Cli) -> Result<i32> {
                     Ok(exit_code::SUCCESS)
                 }
                 ConfigCommands::Edit => {
-                    let editor = config
-                        .user
-                        .editor
-                        .clone()
-                        .or_else(|| std::env::var("SMCTL_EDITOR").ok())
-                        .or_else(|| std::env::var("EDITOR").ok())
-                        .unwrap_or_else(|| "vi".to_string());
+                    let editor = editor_command(&config);
 
                     let path = smctl::SmctlConfig::user_config_path()?;
                     // Ensure config file exists
@@ -1170,20 +4491,47 @@ async fn run(cli: Cli) -> Result<i32> {
             Ok(exit_code::SUCCESS)
         }
 
+        Commands::ShellInit { shell } => {
+            print!("{}", smctl::shell_init(shell));
+            Ok(exit_code::SUCCESS)
+        }
+
         // --- Convenience aliases ---
-        Commands::Feat { name } => {
+        Commands::Feat { name, build } => {
             let root = resolve_root()?;
             let manifest = smctl_workspace::WorkspaceManifest::load_from_root(&root)?;
 
             if dry_run {
                 println!("would start feature '{name}' with worktree");
+                if build {
+                    println!("would build worktree set '{name}'");
+                }
                 return Ok(exit_code::DRY_RUN);
             }
 
-            let result = smctl_flow::feature_start(&root, &manifest, &name, None)?;
+            let result = smctl_flow::feature_start(
+                &root,
+                &manifest,
+                &name,
+                None,
+                smctl_flow::DirtyTree::default(),
+            )?;
             let branch = &result.branch_name;
-            let _ = smctl_workspace::worktree::add_worktree(&root, &manifest, &name, None, branch);
+            let worktree = smctl_workspace::worktree::add_worktree(
+                &root, &manifest, &name, None, branch, None,
+            );
             println!("started feature '{name}' with worktree");
+            if build {
+                if worktree.is_ok() {
+                    return build_worktree_set(&root, &manifest, &name, fmt);
+                }
+                // No worktree set: the checkouts themselves are on the feature branch.
+                let report = smctl_build::build(&root, &manifest, None, false, false)?;
+                println!("{}", format_output_with(&report, fmt, format_build_report));
+                if !report.all_passed {
+                    return Ok(exit_code::BUILD_ERROR);
+                }
+            }
             Ok(exit_code::SUCCESS)
         }
         Commands::Done { name } => {
@@ -1195,8 +4543,23 @@ async fn run(cli: Cli) -> Result<i32> {
                 return Ok(exit_code::DRY_RUN);
             }
 
-            let _ = smctl_workspace::worktree::remove_worktree(&root, &manifest, &name, false);
-            let _result = smctl_flow::feature_finish(&root, &manifest, &name)?;
+            let _ =
+                smctl_workspace::worktree::remove_worktree(&root, &manifest, &name, None, false);
+            if manifest.flow.finish == smctl_workspace::FinishMode::PullRequest {
+                let result = open_pull_requests(&root, &manifest, &name).await?;
+                for repo in &result.repos {
+                    println!("  {}: {}", repo.repo_name, repo.message);
+                }
+                println!("opened pull requests for feature '{name}' and removed worktree");
+                return Ok(exit_code::SUCCESS);
+            }
+            let _result = smctl_flow::feature_finish(
+                &root,
+                &manifest,
+                &name,
+                None,
+                &smctl_flow::FinishOptions::default(),
+            )?;
             println!("finished feature '{name}' and removed worktree");
             Ok(exit_code::SUCCESS)
         }
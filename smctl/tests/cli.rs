@@ -5,14 +5,23 @@ use std::path::Path;
 use assert_cmd::Command;
 use predicates::prelude::*;
 
+/// Keep `workspace init` registrations out of the real user config.
 #[allow(deprecated)]
 fn smctl() -> Command {
-    Command::cargo_bin("smctl").unwrap()
+    let mut cmd = Command::cargo_bin("smctl").unwrap();
+    cmd.env("XDG_CONFIG_HOME", env!("CARGO_TARGET_TMPDIR"));
+    cmd.env_remove("SMCTL_WORKSPACE");
+    cmd
 }
 
 /// Initialize a workspace at the given path with a git repo.
 fn init_workspace_with_git(root: &Path) {
-    let cmds: &[&[&str]] = &[&["git", "init"], &["git", "checkout", "-b", "main"]];
+    let cmds: &[&[&str]] = &[
+        &["git", "init"],
+        &["git", "checkout", "-b", "main"],
+        &["git", "config", "user.name", "Test"],
+        &["git", "config", "user.email", "test@test.com"],
+    ];
     for cmd in cmds {
         std::process::Command::new(cmd[0])
             .args(&cmd[1..])
@@ -206,16 +215,61 @@ fn test_spec_new_validate_archive() {
         .success()
         .stdout(predicate::str::contains("valid"));
 
-    // Archive it
+    // Add a capability delta; validate reports its coverage
+    smctl()
+        .args(["spec", "delta", "test-feature", "auth", "-w"])
+        .arg(dir.path())
+        .assert()
+        .success();
+    smctl()
+        .args(["spec", "validate", "test-feature", "-w"])
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("auth: 1 added"));
+
+    std::fs::write(
+        dir.path()
+            .join("openspec/changes/test-feature/specs/auth/spec.md"),
+        "## ADDED Requirements\n\n### Requirement: Login\n\n#### Scenario: ok\n",
+    )
+    .unwrap();
+
+    // Archive it; the delta is promoted to openspec/specs/
     smctl()
         .args(["spec", "archive", "test-feature", "-w"])
         .arg(dir.path())
         .assert()
         .success()
-        .stdout(predicate::str::contains("archived spec 'test-feature'"));
+        .stdout(predicate::str::contains("archived spec 'test-feature'"))
+        .stdout(predicate::str::contains("auth/spec.md: 1 added"));
+    let capability =
+        std::fs::read_to_string(dir.path().join("openspec/specs/auth/spec.md")).unwrap();
+    assert!(capability.contains("### Requirement: Login"));
+
+    // Promoting again replaces the requirement instead of duplicating it
+    smctl()
+        .args(["spec", "promote", "test-feature", "-w"])
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("already existed; replaced"));
 
     // Original should be gone
     assert!(!dir.path().join("openspec/changes/test-feature").exists());
+
+    // Restore it
+    smctl()
+        .args(["spec", "restore", "test-feature", "-w"])
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("restored spec 'test-feature'"));
+    assert!(
+        dir.path()
+            .join("openspec/changes/test-feature/proposal.md")
+            .exists()
+    );
 }
 
 #[test]
@@ -244,6 +298,19 @@ fn test_spec_ff() {
         .success()
         .stdout(predicate::str::contains("proposal=ok"))
         .stdout(predicate::str::contains("tasks:"));
+
+    // Not ready once a required section is gone
+    std::fs::write(
+        dir.path().join("openspec/changes/ff-test/proposal.md"),
+        "# Proposal\n",
+    )
+    .unwrap();
+    smctl()
+        .args(["spec", "ff", "ff-test", "--json", "-w"])
+        .arg(dir.path())
+        .assert()
+        .code(5)
+        .stdout(predicate::str::contains("\"ready\": false"));
 }
 
 #[test]
@@ -264,6 +331,14 @@ fn test_spec_apply() {
         .assert()
         .success();
 
+    smctl()
+        .args(["spec", "status", "apply-test", "-w"])
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Implementation"))
+        .stdout(predicate::str::contains("0/1"));
+
     // Apply should list tasks
     smctl()
         .args(["spec", "apply", "apply-test", "-w"])
@@ -271,6 +346,178 @@ fn test_spec_apply() {
         .assert()
         .success()
         .stdout(predicate::str::contains("pending"));
+
+    smctl()
+        .args(["spec", "apply", "apply-test", "--task", "1", "-w"])
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\u{2713} #1"));
+
+    smctl()
+        .args(["spec", "apply", "apply-test", "--all", "--json", "-w"])
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"remaining\": []"));
+
+    let tasks =
+        std::fs::read_to_string(dir.path().join("openspec/changes/apply-test/tasks.md")).unwrap();
+    assert!(!tasks.contains("- [ ]"));
+}
+
+#[test]
+fn test_spec_new_from_file() {
+    let dir = tempfile::tempdir().unwrap();
+
+    smctl()
+        .args(["workspace", "init", "--name", "import-ws", "-w"])
+        .arg(dir.path())
+        .assert()
+        .success();
+    let doc = dir.path().join("design-doc.md");
+    std::fs::write(
+        &doc,
+        "# Router cache\n\n## Steps\n\n- [ ] add cache\n- [x] measure\n",
+    )
+    .unwrap();
+
+    smctl()
+        .args(["spec", "new", "imported", "--from"])
+        .arg(&doc)
+        .args(["--json", "-w"])
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"title\": \"Router cache\""))
+        .stdout(predicate::str::contains("\"tasks_total\": 2"));
+
+    smctl()
+        .args(["spec", "new", "missing", "--from", "no-such-doc.md", "-w"])
+        .arg(dir.path())
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_spec_numbered_ids() {
+    let dir = tempfile::tempdir().unwrap();
+
+    smctl()
+        .args(["workspace", "init", "--name", "numbered-ws", "-w"])
+        .arg(dir.path())
+        .assert()
+        .success();
+    let manifest_path = dir.path().join(".smctl/workspace.toml");
+    let manifest = std::fs::read_to_string(&manifest_path).unwrap();
+    std::fs::write(
+        &manifest_path,
+        manifest.replace("numbered = false", "numbered = true"),
+    )
+    .unwrap();
+
+    smctl()
+        .args(["spec", "new", "router-cache", "-w"])
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("created spec '0001-router-cache'"));
+    smctl()
+        .args(["spec", "new", "auth", "--dry-run", "-w"])
+        .arg(dir.path())
+        .assert()
+        .stdout(predicate::str::contains("would create spec '0002-auth'"));
+
+    for reference in ["1", "router-cache"] {
+        smctl()
+            .args(["spec", "status", reference, "-w"])
+            .arg(dir.path())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("0001-router-cache"));
+    }
+    smctl()
+        .args(["spec", "status", "9", "-w"])
+        .arg(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("no spec with ID 9"));
+}
+
+#[test]
+fn test_spec_new_with_repos() {
+    let dir = tempfile::tempdir().unwrap();
+
+    smctl()
+        .args(["workspace", "init", "--name", "repos-ws", "-w"])
+        .arg(dir.path())
+        .assert()
+        .success();
+    let manifest_path = dir.path().join(".smctl/workspace.toml");
+    let manifest = std::fs::read_to_string(&manifest_path).unwrap();
+    std::fs::write(
+        &manifest_path,
+        manifest.replace(
+            "repos = []\n",
+            "[[repos]]\nname = \"api\"\nurl = \"https://example.com/api\"\n\n[[repos]]\nname = \"web\"\nurl = \"https://example.com/web\"\n",
+        ),
+    )
+    .unwrap();
+
+    smctl()
+        .args(["spec", "new", "multi", "--repos", "api,ghost", "-w"])
+        .arg(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown repo(s) ghost"));
+
+    smctl()
+        .args(["spec", "new", "multi", "--repos", "api,web", "-w"])
+        .arg(dir.path())
+        .assert()
+        .success();
+    let tasks =
+        std::fs::read_to_string(dir.path().join("openspec/changes/multi/tasks.md")).unwrap();
+    assert!(tasks.contains("### api") && tasks.contains("### web"));
+    let proposal =
+        std::fs::read_to_string(dir.path().join("openspec/changes/multi/proposal.md")).unwrap();
+    assert!(proposal.contains("| api | |\n| web | |"));
+}
+
+#[test]
+fn test_spec_trace() {
+    let dir = tempfile::tempdir().unwrap();
+
+    smctl()
+        .args(["workspace", "init", "--name", "trace-ws", "-w"])
+        .arg(dir.path())
+        .assert()
+        .success();
+    smctl()
+        .args(["spec", "new", "trace-test", "-w"])
+        .arg(dir.path())
+        .assert()
+        .success();
+
+    let spec_dir = dir.path().join("openspec/changes/trace-test");
+    std::fs::write(
+        spec_dir.join("proposal.md"),
+        "# Proposal\n\n## Why\n\nREQ-1 and REQ-2.\n",
+    )
+    .unwrap();
+    std::fs::write(
+        spec_dir.join("tasks.md"),
+        "## Implementation\n\n- [ ] 1.1 Do REQ-1 `test_req_one`\n",
+    )
+    .unwrap();
+
+    smctl()
+        .args(["spec", "trace", "trace-test", "-w"])
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("test_req_one"))
+        .stdout(predicate::str::contains("REQ-2      no tasks"));
 }
 
 #[test]
@@ -302,6 +549,268 @@ fn test_spec_list() {
         .success()
         .stdout(predicate::str::contains("spec-a"))
         .stdout(predicate::str::contains("spec-b"));
+
+    smctl()
+        .args([
+            "spec",
+            "search",
+            "SPEC-B \u{2014} design",
+            "--phase",
+            "draft",
+            "-w",
+        ])
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("spec-b/design.md:1"));
+
+    smctl()
+        .args(["spec", "report", "-w"])
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("| spec-a | draft |"));
+
+    smctl()
+        .args([
+            "spec", "meta", "spec-a", "--owner", "alice", "--label", "security", "-w",
+        ])
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("owner: alice"));
+
+    smctl()
+        .args([
+            "spec", "list", "--owner", "alice", "--label", "security", "--json", "-w",
+        ])
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "\"labels\": [\n      \"security\"",
+        ))
+        .stdout(predicate::str::contains("spec-b").not());
+
+    smctl()
+        .args([
+            "spec",
+            "list",
+            "--phase",
+            "active",
+            "--sort",
+            "progress",
+            "--no-archived",
+            "-w",
+        ])
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("no specs found"));
+}
+
+#[test]
+fn test_spec_task_commands() {
+    let dir = tempfile::tempdir().unwrap();
+
+    smctl()
+        .args(["workspace", "init", "--name", "task-ws", "-w"])
+        .arg(dir.path())
+        .assert()
+        .success();
+
+    smctl()
+        .args(["spec", "new", "task-test", "-w"])
+        .arg(dir.path())
+        .assert()
+        .success();
+
+    smctl()
+        .args([
+            "spec",
+            "task",
+            "add",
+            "task-test",
+            "write docs",
+            "--section",
+            "Docs",
+            "-w",
+        ])
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("added task #"));
+
+    smctl()
+        .args(["spec", "task", "check", "task-test", "1", "-w"])
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("checked task(s) [1]"));
+
+    smctl()
+        .args(["spec", "task", "list", "task-test", "-w"])
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1. [x]"))
+        .stdout(predicate::str::contains("Docs:"))
+        .stdout(predicate::str::contains("write docs"));
+}
+
+#[test]
+fn test_spec_rename() {
+    let dir = tempfile::tempdir().unwrap();
+
+    smctl()
+        .args(["workspace", "init", "--name", "rename-ws", "-w"])
+        .arg(dir.path())
+        .assert()
+        .success();
+
+    smctl()
+        .args(["spec", "new", "before", "-w"])
+        .arg(dir.path())
+        .assert()
+        .success();
+
+    smctl()
+        .args(["spec", "rename", "before", "after", "-w"])
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("renamed spec 'before' to 'after'"));
+
+    smctl()
+        .args(["spec", "validate", "after", "-w"])
+        .arg(dir.path())
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_spec_set_status() {
+    let dir = tempfile::tempdir().unwrap();
+
+    smctl()
+        .args(["workspace", "init", "--name", "status-ws", "-w"])
+        .arg(dir.path())
+        .assert()
+        .success();
+
+    smctl()
+        .args(["spec", "new", "stale", "-w"])
+        .arg(dir.path())
+        .assert()
+        .success();
+
+    smctl()
+        .args(["spec", "set-status", "stale", "abandoned", "-w"])
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("spec 'stale' is now abandoned"));
+
+    smctl()
+        .args(["spec", "status", "stale", "-w"])
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Abandoned"));
+
+    smctl()
+        .args(["spec", "set-status", "stale", "finished", "-w"])
+        .arg(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown status"));
+}
+
+#[test]
+fn test_spec_sync_requires_github_config() {
+    let dir = tempfile::tempdir().unwrap();
+
+    smctl()
+        .args(["workspace", "init", "--name", "sync-ws", "-w"])
+        .arg(dir.path())
+        .assert()
+        .success();
+
+    smctl()
+        .args(["spec", "new", "synced", "-w"])
+        .arg(dir.path())
+        .assert()
+        .success();
+
+    smctl()
+        .args(["spec", "sync", "synced", "-w"])
+        .arg(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("[spec.github]"));
+}
+
+#[test]
+fn test_spec_defaults_to_current_feature_branch() {
+    let dir = tempfile::tempdir().unwrap();
+    let app = dir.path().join("app");
+    std::fs::create_dir_all(dir.path().join(".smctl")).unwrap();
+    std::fs::create_dir_all(&app).unwrap();
+    std::fs::write(
+        dir.path().join(".smctl/workspace.toml"),
+        r#"
+[workspace]
+name = "current-ws"
+
+[[repos]]
+name = "app"
+url = "https://example.com/app"
+"#,
+    )
+    .unwrap();
+    init_workspace_with_git(&app);
+
+    smctl()
+        .args(["flow", "init", "-w"])
+        .arg(dir.path())
+        .assert()
+        .success();
+
+    smctl()
+        .args(["spec", "new", "router-cache", "-w"])
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "created branch 'feature/router-cache'",
+        ));
+
+    smctl()
+        .current_dir(&app)
+        .args(["spec", "validate"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("spec 'router-cache' is valid"));
+
+    // The openspec dir is outside any git repo in this workspace
+    smctl()
+        .current_dir(&app)
+        .args(["spec", "burndown"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("not in a git repository"));
+
+    std::process::Command::new("git")
+        .args(["checkout", "-q", "main"])
+        .current_dir(&app)
+        .output()
+        .unwrap();
+    smctl()
+        .current_dir(&app)
+        .args(["spec", "ff"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("spec name required"));
 }
 
 // ── Spec duplicate error ─────────────────────────────────────────────
@@ -383,3 +892,221 @@ fn test_completions_bash() {
         .success()
         .stdout(predicate::str::contains("smctl"));
 }
+
+// ── Workspace registry ──────────────────────────────────────────────
+
+#[test]
+fn test_workspace_registry_list_and_switch() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = tempfile::tempdir().unwrap();
+
+    smctl()
+        .env("XDG_CONFIG_HOME", config.path())
+        .args(["workspace", "init", "--name", "reg-ws", "-w"])
+        .arg(dir.path())
+        .assert()
+        .success();
+
+    smctl()
+        .env("XDG_CONFIG_HOME", config.path())
+        .args(["workspace", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("reg-ws"));
+
+    smctl()
+        .env("XDG_CONFIG_HOME", config.path())
+        .args(["workspace", "switch", "missing"])
+        .assert()
+        .failure();
+
+    smctl()
+        .env("XDG_CONFIG_HOME", config.path())
+        .args(["workspace", "switch", "reg-ws"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("switched to workspace 'reg-ws'"));
+
+    // Resolve --workspace by registered name.
+    smctl()
+        .env("XDG_CONFIG_HOME", config.path())
+        .args(["--json", "workspace", "status", "-w", "reg-ws"])
+        .assert()
+        .success();
+
+    // Fall back to the current workspace outside any workspace directory.
+    let elsewhere = tempfile::tempdir().unwrap();
+    smctl()
+        .env("XDG_CONFIG_HOME", config.path())
+        .current_dir(elsewhere.path())
+        .args(["workspace", "status"])
+        .assert()
+        .success();
+}
+
+// ── Build commands ───────────────────────────────────────────────────
+
+#[test]
+fn test_build_records_history() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join(".smctl")).unwrap();
+    std::fs::create_dir_all(dir.path().join("app")).unwrap();
+    std::fs::write(
+        dir.path().join(".smctl/workspace.toml"),
+        r#"
+[workspace]
+name = "build-ws"
+
+[[repos]]
+name = "app"
+url = "https://example.com/app"
+build_cmd = "true"
+"#,
+    )
+    .unwrap();
+
+    smctl()
+        .args(["build", "-w"])
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("build passed"));
+
+    smctl()
+        .args(["build", "history", "-w"])
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1 steps, 0 failed"));
+
+    smctl()
+        .args(["build", "history", "show", "latest", "--json", "-w"])
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"repo_name\": \"app\""));
+}
+
+#[test]
+fn test_worktree_add_build_runs_in_worktree() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join(".smctl")).unwrap();
+    std::fs::create_dir_all(dir.path().join("app")).unwrap();
+    init_workspace_with_git(&dir.path().join("app"));
+    std::fs::write(
+        dir.path().join(".smctl/workspace.toml"),
+        r#"
+[workspace]
+name = "build-ws"
+
+[[repos]]
+name = "app"
+url = "https://example.com/app"
+build_cmd = "touch built.txt"
+"#,
+    )
+    .unwrap();
+
+    smctl()
+        .args(["worktree", "add", "fast", "--build", "-w"])
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("created worktree set 'fast'"))
+        .stdout(predicate::str::contains("build passed"));
+    assert!(dir.path().join(".worktrees/fast/app/built.txt").exists());
+    assert!(!dir.path().join("app/built.txt").exists());
+}
+
+#[test]
+fn test_feature_finish_pr_rejects_merge_flags() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join(".smctl")).unwrap();
+    std::fs::write(
+        dir.path().join(".smctl/workspace.toml"),
+        "[workspace]\nname = \"ws\"\n\n[flow]\nfinish = \"pull-request\"\n",
+    )
+    .unwrap();
+
+    for flag in ["--no-rollback", "--force", "--stash", "--delete-remote"] {
+        smctl()
+            .args(["flow", "feature", "finish", "x", "--pr", flag, "-w"])
+            .arg(dir.path())
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("cannot be used with"));
+        smctl()
+            .args(["flow", "feature", "finish", "x", flag, "-w"])
+            .arg(dir.path())
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(format!(
+                "{flag} only applies to local merges"
+            )));
+    }
+}
+
+#[test]
+fn test_shell_init_smcd() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join(".smctl")).unwrap();
+    std::fs::create_dir_all(dir.path().join("app")).unwrap();
+    init_workspace_with_git(&dir.path().join("app"));
+    std::fs::write(
+        dir.path().join(".smctl/workspace.toml"),
+        "[workspace]\nname = \"ws\"\n\n[[repos]]\nname = \"app\"\nurl = \"x\"\n",
+    )
+    .unwrap();
+    smctl()
+        .args(["worktree", "add", "one", "-w"])
+        .arg(dir.path())
+        .assert()
+        .success();
+    smctl()
+        .args(["worktree", "list", "--names", "-w"])
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout("one\n");
+
+    for shell in ["bash", "zsh", "fish"] {
+        smctl()
+            .args(["shell-init", shell])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("smcd"))
+            .stdout(predicate::str::contains("smctl worktree list --names"));
+    }
+    smctl().args(["shell-init", "tcsh"]).assert().failure();
+
+    // The bash function really changes directory
+    let bin_dir = Path::new(env!("CARGO_BIN_EXE_smctl")).parent().unwrap();
+    let path = format!(
+        "{}:{}",
+        bin_dir.display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+    let output = std::process::Command::new("bash")
+        .args(["-c", "eval \"$(smctl shell-init bash)\"; smcd one && pwd"])
+        .env("PATH", path)
+        .env("XDG_CONFIG_HOME", env!("CARGO_TARGET_TMPDIR"))
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(
+        String::from_utf8_lossy(&output.stdout)
+            .trim()
+            .ends_with(".worktrees/one"),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn test_build_test_args_require_test() {
+    smctl()
+        .args(["build", "--", "--nocapture"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--test"));
+}